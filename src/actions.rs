@@ -0,0 +1,115 @@
+use crate::{
+    config::ReleaseConfig,
+    github::{asset::release_url, tag::Tag},
+    packager::package::Package,
+};
+use anyhow::{Context, Result};
+use once_cell::sync::Lazy;
+use serde_json::json;
+use std::{fs::OpenOptions, io::Write};
+
+/// Whether rr runs in a GitHub Actions workflow, which sets `GITHUB_ACTIONS=true`
+static ENABLED: Lazy<bool> =
+    Lazy::new(|| std::env::var("GITHUB_ACTIONS").is_ok_and(|value| value == "true"));
+
+pub fn enabled() -> bool {
+    *ENABLED
+}
+
+/// Fold the following lines of the job log under `title`, until [`end_group`]
+pub fn group(title: &str) {
+    if enabled() {
+        println!("::group::{}", escape(title));
+    }
+}
+
+pub fn end_group() {
+    if enabled() {
+        println!("::endgroup::");
+    }
+}
+
+/// Annotate the run with the error ending it
+pub fn error(err: &anyhow::Error) {
+    if enabled() {
+        println!("::error title=rr::{}", escape(&format!("{:#}", err)));
+    }
+}
+
+/// Write the release to `$GITHUB_OUTPUT` for the next steps, as `tag`, `url` and `assets`, a
+/// JSON list of `{ name, os, arch, url, sha256 }`, and summarize it in `$GITHUB_STEP_SUMMARY`
+pub fn report(
+    release_config: &ReleaseConfig,
+    tag: &Tag,
+    packages: &[Package],
+    dry_run: bool,
+) -> Result<()> {
+    if !enabled() {
+        return Ok(());
+    }
+
+    let url = release_url(&release_config.owner, &release_config.repo, tag);
+    let outputs = [
+        ("tag", tag.value().to_owned()),
+        ("url", url.to_owned()),
+        ("assets", json!(packages).to_string()),
+        ("dry_run", dry_run.to_string()),
+    ];
+    append(
+        "GITHUB_OUTPUT",
+        &outputs
+            .iter()
+            .map(|(name, value)| format!("{}={}\n", name, value))
+            .collect::<String>(),
+    )?;
+
+    let mut summary = match dry_run {
+        true => format!("## Dry run of {}\n\n", tag.value()),
+        false => format!("## Released [{}]({})\n\n", tag.value(), url),
+    };
+    if !packages.is_empty() {
+        summary.push_str("| Asset | OS | Arch | SHA-256 |\n| --- | --- | --- | --- |\n");
+        for package in packages {
+            let name = match &package.url {
+                Some(url) if !dry_run => format!("[{}]({})", package.name, url),
+                _ => package.name.to_owned(),
+            };
+            summary.push_str(&format!(
+                "| {} | {} | {} | `{}` |\n",
+                name,
+                package
+                    .os
+                    .as_ref()
+                    .map(ToString::to_string)
+                    .unwrap_or_default(),
+                package
+                    .arch
+                    .as_ref()
+                    .map(ToString::to_string)
+                    .unwrap_or_default(),
+                package.sha256
+            ));
+        }
+    }
+    append("GITHUB_STEP_SUMMARY", &summary)
+}
+
+/// Append to the file Actions sets in the `variable` environment variable, if any
+fn append(variable: &str, content: &str) -> Result<()> {
+    let Ok(path) = std::env::var(variable) else {
+        return Ok(());
+    };
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .and_then(|mut file| file.write_all(content.as_bytes()))
+        .with_context(|| format!("failed to write to {} ({})", variable, path))
+}
+
+/// Escape the data of a workflow command, which ends at the first newline
+fn escape(data: &str) -> String {
+    data.replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
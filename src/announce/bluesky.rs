@@ -0,0 +1,146 @@
+use super::{render, shorten, Announcement};
+use crate::{config::BlueskyConfig, http::HttpClient};
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::env;
+use time::{format_description::well_known::Rfc3339, OffsetDateTime};
+
+const APP_PASSWORD_VAR: &str = "BLUESKY_APP_PASSWORD";
+/// Longest post Bluesky accepts, in graphemes
+const MAX_LENGTH: usize = 300;
+const POST_COLLECTION: &str = "app.bsky.feed.post";
+
+pub const DEFAULT_MESSAGE: &str = "{{ name }} {{ version }} is out!
+{{ #if highlights }}
+
+{{ #each highlights }}
+- {{ description }}
+{{ /each }}
+{{ /if }}";
+
+/// Record of an `app.bsky.feed.post`
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Post {
+    #[serde(rename = "$type")]
+    kind: &'static str,
+    text: String,
+    /// Links aren't detected from the text, they are marked with their byte range
+    facets: Vec<Facet>,
+    embed: Embed,
+    created_at: String,
+}
+
+#[derive(Debug, Serialize)]
+struct Facet {
+    index: ByteSlice,
+    features: Vec<serde_json::Value>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ByteSlice {
+    byte_start: usize,
+    byte_end: usize,
+}
+
+/// Link card shown under the text
+#[derive(Debug, Serialize)]
+struct Embed {
+    #[serde(rename = "$type")]
+    kind: &'static str,
+    external: External,
+}
+
+#[derive(Debug, Serialize)]
+struct External {
+    uri: String,
+    title: String,
+    description: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Session {
+    access_jwt: String,
+    did: String,
+}
+
+/// Rendered message followed by a link to the release, shortened to fit in a post
+pub fn post(config: &BlueskyConfig, announcement: &Announcement) -> Result<Post> {
+    let message = render(config.message.as_deref(), DEFAULT_MESSAGE, announcement)?;
+
+    let room = MAX_LENGTH.saturating_sub(announcement.url.chars().count() + 2);
+    let message = shorten(&message, room, |text| text.chars().count());
+    let text = format!("{}\n\n{}", message, announcement.url);
+    let byte_start = text.len() - announcement.url.len();
+
+    Ok(Post {
+        kind: POST_COLLECTION,
+        facets: vec![Facet {
+            index: ByteSlice {
+                byte_start,
+                byte_end: text.len(),
+            },
+            features: vec![json!({
+                "$type": "app.bsky.richtext.facet#link",
+                "uri": announcement.url,
+            })],
+        }],
+        text,
+        embed: Embed {
+            kind: "app.bsky.embed.external",
+            external: External {
+                uri: announcement.url.to_owned(),
+                title: format!("{} {}", announcement.name, announcement.version),
+                description: format!("Release notes of {}", announcement.tag),
+            },
+        },
+        created_at: OffsetDateTime::now_utc().format(&Rfc3339)?,
+    })
+}
+
+/// Log in with the app password and create `post` in the account's repository
+pub async fn create(config: &BlueskyConfig, post: &Post) -> Result<()> {
+    let password = env::var(APP_PASSWORD_VAR)
+        .with_context(|| format!("{} is required to post to Bluesky", APP_PASSWORD_VAR))?;
+    let service = config.service.trim_end_matches('/');
+
+    let response = HttpClient::new()
+        .post(format!("{}/xrpc/com.atproto.server.createSession", service))
+        .json(&json!({ "identifier": config.identifier, "password": password }))
+        .send()
+        .await?;
+    let status = response.status();
+    if !status.is_success() {
+        bail!(
+            "failed to log in as {}: {} {}",
+            config.identifier,
+            status,
+            response.text().await?
+        );
+    }
+    let session = response.json::<Session>().await?;
+
+    let response = HttpClient::new()
+        .post(format!("{}/xrpc/com.atproto.repo.createRecord", service))
+        .bearer_auth(session.access_jwt)
+        .json(&json!({
+            "repo": session.did,
+            "collection": POST_COLLECTION,
+            "record": post,
+        }))
+        .send()
+        .await?;
+    let status = response.status();
+    if !status.is_success() {
+        bail!(
+            "{} answered {}: {}",
+            service,
+            status,
+            response.text().await?
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,128 @@
+use super::{render, Announcement};
+use crate::{config::DiscordConfig, http::HttpClient};
+use anyhow::{bail, Context, Result};
+use serde::Serialize;
+use std::env;
+
+const WEBHOOK_URL_VAR: &str = "DISCORD_WEBHOOK_URL";
+/// Length of the notes shown in the embed, well under the 4096 characters Discord accepts
+const EXCERPT_LENGTH: usize = 1000;
+/// Longest value of an embed field
+const FIELD_LENGTH: usize = 1024;
+
+#[derive(Debug, Serialize)]
+pub struct Payload {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    content: Option<String>,
+    embeds: Vec<Embed>,
+}
+
+#[derive(Debug, Serialize)]
+struct Embed {
+    title: String,
+    url: String,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    description: String,
+    fields: Vec<Field>,
+}
+
+#[derive(Debug, Serialize)]
+struct Field {
+    name: String,
+    value: String,
+}
+
+/// Message with an embed linking to the release, with an excerpt of its notes and its assets
+pub fn payload(config: &DiscordConfig, announcement: &Announcement) -> Result<Payload> {
+    let content = match &config.message {
+        Some(message) => Some(render(Some(message), "", announcement)?),
+        None => None,
+    };
+
+    let mut fields = vec![];
+    if !announcement.downloads.is_empty() {
+        let links = announcement
+            .downloads
+            .iter()
+            .map(|download| format!("[{}]({})", download.name, download.url))
+            .collect::<Vec<_>>();
+        fields.push(Field {
+            name: "Downloads".to_owned(),
+            value: fit(&links, FIELD_LENGTH),
+        });
+    }
+
+    Ok(Payload {
+        content,
+        embeds: vec![Embed {
+            title: format!("{} {}", announcement.name, announcement.version),
+            url: announcement.url.to_owned(),
+            description: excerpt(&announcement.changelog, EXCERPT_LENGTH),
+            fields,
+        }],
+    })
+}
+
+pub async fn post(config: &DiscordConfig, payload: &Payload) -> Result<()> {
+    let url = config
+        .webhook_url
+        .to_owned()
+        .or_else(|| env::var(WEBHOOK_URL_VAR).ok())
+        .with_context(|| format!("set announce.discord.webhook_url or {}", WEBHOOK_URL_VAR))?;
+
+    let response = HttpClient::new().post(url).json(payload).send().await?;
+
+    let status = response.status();
+    if !status.is_success() {
+        bail!("webhook answered {}: {}", status, response.text().await?);
+    }
+    Ok(())
+}
+
+/// The first lines of `notes` fitting in `limit` characters
+fn excerpt(notes: &str, limit: usize) -> String {
+    let notes = notes.trim();
+    if notes.chars().count() <= limit {
+        return notes.to_owned();
+    }
+
+    let mut excerpt = String::new();
+    for line in notes.lines() {
+        // keep room for the ellipsis
+        if excerpt.chars().count() + line.chars().count() + 2 > limit {
+            break;
+        }
+        excerpt.push_str(line);
+        excerpt.push('\n');
+    }
+    // a single line longer than the limit is cut
+    if excerpt.is_empty() {
+        excerpt = notes.chars().take(limit - 1).collect();
+    }
+    format!("{}…", excerpt.trim_end())
+}
+
+/// As many `lines` as fit in `limit` bytes, followed by a count of the others
+fn fit(lines: &[String], limit: usize) -> String {
+    let mut value = String::new();
+    for (i, line) in lines.iter().enumerate() {
+        let more = format!("and {} more", lines.len() - i);
+        // room for the count, unless this is the last line
+        let reserved = if i + 1 < lines.len() {
+            more.len() + 1
+        } else {
+            0
+        };
+        let fits = value.len() + line.len() + 1 + reserved <= limit;
+
+        if !value.is_empty() {
+            value.push('\n');
+        }
+        if !fits {
+            value.push_str(&more);
+            break;
+        }
+        value.push_str(line);
+    }
+    value
+}
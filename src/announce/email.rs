@@ -0,0 +1,91 @@
+use super::{render, Announcement};
+use crate::config::{EmailConfig, SmtpTls};
+use anyhow::{Context, Result};
+use lettre::{
+    message::{header::ContentType, Mailbox},
+    transport::smtp::authentication::Credentials,
+    AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor,
+};
+use serde::Serialize;
+use std::env;
+
+const HOST_VAR: &str = "SMTP_HOST";
+const PORT_VAR: &str = "SMTP_PORT";
+const USERNAME_VAR: &str = "SMTP_USERNAME";
+const PASSWORD_VAR: &str = "SMTP_PASSWORD";
+
+pub const DEFAULT_SUBJECT: &str = "[ANN] {{ name }} {{ version }}";
+
+pub const DEFAULT_MESSAGE: &str = "{{ name }} {{ version }} has been released.
+
+{{ changelog }}
+
+Release: {{ url }}
+{{ #if downloads }}
+
+Downloads:
+{{ #each downloads }}
+- {{ name }}: {{ url }}
+{{ /each }}
+{{ /if }}";
+
+/// Rendered email, recorded as is in a dry run
+#[derive(Debug, Serialize)]
+pub struct Mail {
+    from: String,
+    to: Vec<String>,
+    subject: String,
+    body: String,
+}
+
+pub fn mail(config: &EmailConfig, announcement: &Announcement) -> Result<Mail> {
+    Ok(Mail {
+        from: config.from.to_owned(),
+        to: config.to.to_owned(),
+        subject: render(config.subject.as_deref(), DEFAULT_SUBJECT, announcement)?,
+        body: render(config.message.as_deref(), DEFAULT_MESSAGE, announcement)?,
+    })
+}
+
+/// Send `mail` through the SMTP server, logging in when credentials are set
+pub async fn send(config: &EmailConfig, mail: &Mail) -> Result<()> {
+    let mut message = Message::builder()
+        .from(mailbox(&mail.from)?)
+        .subject(&mail.subject)
+        // mailing lists tend to reject messages without one
+        .message_id(None)
+        .header(ContentType::TEXT_PLAIN);
+    for to in &mail.to {
+        message = message.to(mailbox(to)?);
+    }
+    let message = message.body(mail.body.to_owned())?;
+
+    let host = config
+        .host
+        .to_owned()
+        .or_else(|| env::var(HOST_VAR).ok())
+        .with_context(|| format!("set announce.email.host or {}", HOST_VAR))?;
+    let mut transport = match config.tls {
+        SmtpTls::StartTls => AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&host)?,
+        SmtpTls::Tls => AsyncSmtpTransport::<Tokio1Executor>::relay(&host)?,
+        SmtpTls::None => AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(&host),
+    };
+    if let Ok(port) = env::var(PORT_VAR) {
+        let port = port
+            .parse()
+            .with_context(|| format!("{} must be a port number, got {}", PORT_VAR, port))?;
+        transport = transport.port(port);
+    }
+    if let (Ok(username), Ok(password)) = (env::var(USERNAME_VAR), env::var(PASSWORD_VAR)) {
+        transport = transport.credentials(Credentials::new(username, password));
+    }
+
+    transport.build().send(message).await?;
+    Ok(())
+}
+
+fn mailbox(address: &str) -> Result<Mailbox> {
+    address
+        .parse()
+        .with_context(|| format!("invalid email address {}", address))
+}
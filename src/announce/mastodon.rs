@@ -0,0 +1,89 @@
+use super::{render, shorten, Announcement};
+use crate::{config::MastodonConfig, http::HttpClient};
+use anyhow::{bail, Context, Result};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use std::env;
+
+const ACCESS_TOKEN_VAR: &str = "MASTODON_ACCESS_TOKEN";
+/// Length Mastodon counts for any URL
+const URL_LENGTH: usize = 23;
+
+pub const DEFAULT_MESSAGE: &str = "{{ name }} {{ version }} is out!
+{{ #if highlights }}
+
+{{ #each highlights }}
+- {{ description }}
+{{ /each }}
+{{ /if }}";
+
+static URL: Lazy<Regex> = Lazy::new(|| Regex::new(r"https?://\S+").unwrap());
+
+/// Rendered message followed by the release URL and the hashtags, with the message shortened to
+/// fit the length limit of the instance
+pub fn status(config: &MastodonConfig, announcement: &Announcement) -> Result<String> {
+    let message = render(config.message.as_deref(), DEFAULT_MESSAGE, announcement)?;
+
+    let mut suffix = String::new();
+    if config.include_url {
+        suffix.push_str("\n\n");
+        suffix.push_str(&announcement.url);
+    }
+    if !config.hashtags.is_empty() {
+        let hashtags = config
+            .hashtags
+            .iter()
+            .map(|hashtag| format!("#{}", hashtag.trim_start_matches('#')))
+            .collect::<Vec<_>>();
+        suffix.push_str("\n\n");
+        suffix.push_str(&hashtags.join(" "));
+    }
+
+    let room = config.max_length.saturating_sub(length(&suffix));
+    if room == 0 {
+        bail!(
+            "the release URL and hashtags don't fit in {} characters",
+            config.max_length
+        );
+    }
+    Ok(format!("{}{}", shorten(&message, room, length), suffix))
+}
+
+pub async fn post(config: &MastodonConfig, status: &str) -> Result<()> {
+    let token = env::var(ACCESS_TOKEN_VAR)
+        .with_context(|| format!("{} is required to post to Mastodon", ACCESS_TOKEN_VAR))?;
+
+    let response = HttpClient::new()
+        .post(format!(
+            "{}/api/v1/statuses",
+            config.instance.trim_end_matches('/')
+        ))
+        .bearer_auth(token)
+        // a retried request can't post the same status twice
+        .header("Idempotency-Key", hex::encode(Sha256::digest(status)))
+        .json(&json!({ "status": status }))
+        .send()
+        .await?;
+
+    let status = response.status();
+    if !status.is_success() {
+        bail!(
+            "{} answered {}: {}",
+            config.instance,
+            status,
+            response.text().await?
+        );
+    }
+    Ok(())
+}
+
+/// Length of `text` as Mastodon counts it
+fn length(text: &str) -> usize {
+    let urls = URL.find_iter(text);
+    let (count, chars) = urls.fold((0, 0), |(count, chars), url| {
+        (count + 1, chars + url.as_str().chars().count())
+    });
+    text.chars().count() - chars + count * URL_LENGTH
+}
@@ -0,0 +1,55 @@
+use crate::{config::MatrixConfig, http::HttpClient};
+use anyhow::{bail, Context, Result};
+use reqwest::Url;
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use std::env;
+
+const ACCESS_TOKEN_VAR: &str = "MATRIX_ACCESS_TOKEN";
+
+pub const DEFAULT_MESSAGE: &str = "{{ name }} {{ version }} is out: {{ url }}
+{{ #each highlights }}
+- {{ description }}
+{{ /each }}";
+
+/// Send `message` to the room as the account of the access token
+pub async fn post(config: &MatrixConfig, message: &str) -> Result<()> {
+    let token = env::var(ACCESS_TOKEN_VAR)
+        .with_context(|| format!("{} is required to post to Matrix", ACCESS_TOKEN_VAR))?;
+
+    // the homeserver drops a message sent again with the same transaction id
+    let transaction = hex::encode(Sha256::digest(format!("{}\n{}", config.room_id, message)));
+    let mut url = Url::parse(&config.homeserver)
+        .with_context(|| format!("invalid homeserver {}", config.homeserver))?;
+    url.path_segments_mut()
+        .map_err(|_| anyhow::anyhow!("invalid homeserver {}", config.homeserver))?
+        .pop_if_empty()
+        .extend([
+            "_matrix",
+            "client",
+            "v3",
+            "rooms",
+            &config.room_id,
+            "send",
+            "m.room.message",
+            &transaction,
+        ]);
+
+    let response = HttpClient::new()
+        .put(url)
+        .bearer_auth(token)
+        .json(&json!({ "msgtype": config.msgtype, "body": message }))
+        .send()
+        .await?;
+
+    let status = response.status();
+    if !status.is_success() {
+        bail!(
+            "{} answered {}: {}",
+            config.homeserver,
+            status,
+            response.text().await?
+        );
+    }
+    Ok(())
+}
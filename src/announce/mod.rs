@@ -0,0 +1,235 @@
+pub mod bluesky;
+pub mod discord;
+pub mod email;
+pub mod mastodon;
+pub mod matrix;
+pub mod slack;
+pub mod teams;
+pub mod telegram;
+pub mod webhook;
+
+use crate::{
+    changelog::{Changelog, Entry},
+    config::{AnnounceConfig, ReleaseConfig},
+    dry_run,
+    github::{
+        asset::release_url,
+        tag::{Tag, VersionComponents},
+    },
+    packager::package::Package,
+    template,
+};
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::{future::Future, path::Path};
+
+/// Values available to the announcement templates
+#[derive(Debug, Serialize)]
+pub struct Announcement {
+    pub name: String,
+    pub version: String,
+    #[serde(flatten)]
+    pub components: VersionComponents,
+    pub tag: String,
+    /// Page of the release on GitHub
+    pub url: String,
+    /// Release notes
+    pub changelog: String,
+    /// Breaking changes and features of the changelog
+    pub highlights: Vec<Entry>,
+    pub downloads: Vec<Download>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Download {
+    pub name: String,
+    pub url: String,
+}
+
+impl Announcement {
+    pub fn new(
+        release_info: &ReleaseConfig,
+        tag: &Tag,
+        notes: Option<&str>,
+        changelog: Option<&Changelog>,
+        packages: &[Package],
+    ) -> Self {
+        let highlights = changelog
+            .map(|changelog| {
+                changelog
+                    .sections
+                    .iter()
+                    .flat_map(|section| &section.entries)
+                    .filter(|entry| entry.breaking || entry.kind == "feat")
+                    .cloned()
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Announcement {
+            name: release_info.repo.to_owned(),
+            version: tag.version().to_owned(),
+            components: tag.components(),
+            tag: tag.value().to_owned(),
+            url: release_url(&release_info.owner, &release_info.repo, tag),
+            changelog: notes.unwrap_or_default().to_owned(),
+            highlights,
+            downloads: packages
+                .iter()
+                .filter_map(|package| {
+                    package.url.as_ref().map(|url| Download {
+                        name: package.name.to_owned(),
+                        url: url.to_owned(),
+                    })
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Post the announcement to every configured channel. The release is already out by then, so
+/// failures are reported without failing the run.
+pub async fn announce(
+    config: &AnnounceConfig,
+    announcement: &Announcement,
+    dry_run: bool,
+    output_path: &Path,
+) -> Result<()> {
+    if let Some(slack) = config.slack.as_ref().filter(|slack| slack.toggle.enabled) {
+        let message = render(
+            slack.message.as_deref(),
+            slack::DEFAULT_MESSAGE,
+            announcement,
+        )
+        .context("failed to render the Slack message")?;
+        let post = slack::post(slack, &message);
+        publish("Slack", &message, post, dry_run, output_path).await?;
+    }
+
+    if let Some(discord) = config
+        .discord
+        .as_ref()
+        .filter(|discord| discord.toggle.enabled)
+    {
+        let payload = discord::payload(discord, announcement)
+            .context("failed to render the Discord message")?;
+        let post = discord::post(discord, &payload);
+        publish("Discord", &payload, post, dry_run, output_path).await?;
+    }
+
+    if let Some(mastodon) = config
+        .mastodon
+        .as_ref()
+        .filter(|mastodon| mastodon.toggle.enabled)
+    {
+        let status = mastodon::status(mastodon, announcement)
+            .context("failed to render the Mastodon status")?;
+        let post = mastodon::post(mastodon, &status);
+        publish("Mastodon", &status, post, dry_run, output_path).await?;
+    }
+
+    if let Some(bluesky) = config
+        .bluesky
+        .as_ref()
+        .filter(|bluesky| bluesky.toggle.enabled)
+    {
+        let post =
+            bluesky::post(bluesky, announcement).context("failed to render the Bluesky post")?;
+        let create = bluesky::create(bluesky, &post);
+        publish("Bluesky", &post, create, dry_run, output_path).await?;
+    }
+
+    if let Some(telegram) = config
+        .telegram
+        .as_ref()
+        .filter(|telegram| telegram.toggle.enabled)
+    {
+        let message = telegram::message(telegram, announcement)
+            .context("failed to render the Telegram message")?;
+        let post = telegram::post(telegram, &message);
+        publish("Telegram", &message, post, dry_run, output_path).await?;
+    }
+
+    if let Some(teams) = config.teams.as_ref().filter(|teams| teams.toggle.enabled) {
+        let payload =
+            teams::payload(teams, announcement).context("failed to render the Teams card")?;
+        let post = teams::post(teams, &payload);
+        publish("Teams", &payload, post, dry_run, output_path).await?;
+    }
+
+    if let Some(matrix) = config
+        .matrix
+        .as_ref()
+        .filter(|matrix| matrix.toggle.enabled)
+    {
+        let message = render(
+            matrix.message.as_deref(),
+            matrix::DEFAULT_MESSAGE,
+            announcement,
+        )
+        .context("failed to render the Matrix message")?;
+        let post = matrix::post(matrix, &message);
+        publish("Matrix", &message, post, dry_run, output_path).await?;
+    }
+
+    if let Some(email) = config.email.as_ref().filter(|email| email.toggle.enabled) {
+        let mail = email::mail(email, announcement).context("failed to render the email")?;
+        let send = email::send(email, &mail);
+        publish("Email", &mail, send, dry_run, output_path).await?;
+    }
+
+    Ok(())
+}
+
+/// Run `post`, or record `payload` in a dry run
+async fn publish<T, F>(
+    service: &str,
+    payload: &T,
+    post: F,
+    dry_run: bool,
+    output_path: &Path,
+) -> Result<()>
+where
+    T: Serialize,
+    F: Future<Output = Result<()>>,
+{
+    if dry_run {
+        return dry_run::record(
+            output_path,
+            &format!("announce-{}", service.to_lowercase()),
+            &format!("announce the release on {}", service),
+            payload,
+        );
+    }
+
+    match post.await {
+        Ok(()) => log::info!("Announced the release on {}", service),
+        Err(err) => log::error!("Failed to announce the release on {}: {:#}", service, err),
+    }
+    Ok(())
+}
+
+fn render(template: Option<&str>, default: &str, announcement: &Announcement) -> Result<String> {
+    let rendered = template::render(template.unwrap_or(default), announcement)?;
+    Ok(rendered.trim().to_owned())
+}
+
+/// `text` cut at the last word fitting in `room` characters as counted by `length`
+fn shorten(text: &str, room: usize, length: fn(&str) -> usize) -> String {
+    if length(text) <= room {
+        return text.to_owned();
+    }
+
+    let mut shortened = String::new();
+    for word in text.split_inclusive(char::is_whitespace) {
+        // keep room for the ellipsis
+        if length(format!("{}{}", shortened, word).trim_end()) + 1 > room {
+            break;
+        }
+        shortened.push_str(word);
+    }
+    if shortened.trim().is_empty() {
+        shortened = text.chars().take(room.saturating_sub(1)).collect();
+    }
+    format!("{}…", shortened.trim_end())
+}
@@ -0,0 +1,81 @@
+use crate::{config::SlackConfig, http::HttpClient};
+use anyhow::{bail, Context, Result};
+use serde::Deserialize;
+use serde_json::json;
+use std::env;
+
+const WEBHOOK_URL_VAR: &str = "SLACK_WEBHOOK_URL";
+const BOT_TOKEN_VAR: &str = "SLACK_BOT_TOKEN";
+const POST_MESSAGE_URL: &str = "https://slack.com/api/chat.postMessage";
+
+pub const DEFAULT_MESSAGE: &str = "*{{ name }} {{ version }}* is out! <{{ url }}|Release notes>
+{{ #each highlights }}
+• {{ description }}
+{{ /each }}
+{{ #if downloads }}
+Downloads: {{ #each downloads }}<{{ url }}|{{ name }}>{{ #unless @last }}, {{ /unless }}{{ /each }}
+{{ /if }}";
+
+#[derive(Deserialize)]
+struct PostMessageResponse {
+    ok: bool,
+    error: Option<String>,
+}
+
+/// Post `message` to the configured channel with the bot token, or else to the webhook
+pub async fn post(config: &SlackConfig, message: &str) -> Result<()> {
+    match &config.channel {
+        Some(channel) => post_message(channel, message).await,
+        None => {
+            let url = config
+                .webhook_url
+                .to_owned()
+                .or_else(|| env::var(WEBHOOK_URL_VAR).ok())
+                .with_context(|| {
+                    format!(
+                        "set announce.slack.webhook_url, {} or announce.slack.channel",
+                        WEBHOOK_URL_VAR
+                    )
+                })?;
+            post_webhook(&url, message).await
+        }
+    }
+}
+
+async fn post_webhook(url: &str, message: &str) -> Result<()> {
+    let response = HttpClient::new()
+        .post(url)
+        .json(&json!({ "text": message }))
+        .send()
+        .await?;
+
+    let status = response.status();
+    if !status.is_success() {
+        bail!("webhook answered {}: {}", status, response.text().await?);
+    }
+    Ok(())
+}
+
+async fn post_message(channel: &str, message: &str) -> Result<()> {
+    let token = env::var(BOT_TOKEN_VAR)
+        .with_context(|| format!("{} is required to post to a channel", BOT_TOKEN_VAR))?;
+
+    // the API answers 200 to most errors, the outcome is in the body
+    let response = HttpClient::new()
+        .post(POST_MESSAGE_URL)
+        .bearer_auth(token)
+        .json(&json!({ "channel": channel, "text": message }))
+        .send()
+        .await?
+        .error_for_status()?
+        .json::<PostMessageResponse>()
+        .await?;
+
+    if !response.ok {
+        bail!(
+            "chat.postMessage failed: {}",
+            response.error.unwrap_or_default()
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,85 @@
+use super::{render, Announcement};
+use crate::{config::TeamsConfig, http::HttpClient};
+use anyhow::{bail, Context, Result};
+use serde_json::{json, Value};
+use std::env;
+
+const WEBHOOK_URL_VAR: &str = "TEAMS_WEBHOOK_URL";
+const ADAPTIVE_CARD: &str = "application/vnd.microsoft.card.adaptive";
+/// Assets linked on the card, the others are left to the release page
+const MAX_DOWNLOADS: usize = 10;
+
+pub const DEFAULT_MESSAGE: &str = "{{ #each highlights }}
+- {{ description }}
+{{ /each }}";
+
+/// Message holding an Adaptive Card with the rendered text, the assets and a button to the
+/// release
+pub fn payload(config: &TeamsConfig, announcement: &Announcement) -> Result<Value> {
+    let message = render(config.message.as_deref(), DEFAULT_MESSAGE, announcement)?;
+
+    let mut body = vec![json!({
+        "type": "TextBlock",
+        "text": format!("{} {} is out", announcement.name, announcement.version),
+        "size": "Large",
+        "weight": "Bolder",
+        "wrap": true,
+    })];
+    if !message.is_empty() {
+        body.push(json!({ "type": "TextBlock", "text": message, "wrap": true }));
+    }
+    if !announcement.downloads.is_empty() {
+        let mut links = announcement
+            .downloads
+            .iter()
+            .take(MAX_DOWNLOADS)
+            .map(|download| format!("- [{}]({})", download.name, download.url))
+            .collect::<Vec<_>>();
+        if announcement.downloads.len() > MAX_DOWNLOADS {
+            links.push(format!(
+                "- and {} more",
+                announcement.downloads.len() - MAX_DOWNLOADS
+            ));
+        }
+        body.push(json!({
+            "type": "TextBlock",
+            "text": links.join("\n"),
+            "wrap": true,
+            "separator": true,
+        }));
+    }
+
+    Ok(json!({
+        "type": "message",
+        "attachments": [{
+            "contentType": ADAPTIVE_CARD,
+            "content": {
+                "$schema": "http://adaptivecards.io/schemas/adaptive-card.json",
+                "type": "AdaptiveCard",
+                "version": "1.4",
+                "body": body,
+                "actions": [{
+                    "type": "Action.OpenUrl",
+                    "title": "Release notes",
+                    "url": announcement.url,
+                }],
+            },
+        }],
+    }))
+}
+
+pub async fn post(config: &TeamsConfig, payload: &Value) -> Result<()> {
+    let url = config
+        .webhook_url
+        .to_owned()
+        .or_else(|| env::var(WEBHOOK_URL_VAR).ok())
+        .with_context(|| format!("set announce.teams.webhook_url or {}", WEBHOOK_URL_VAR))?;
+
+    let response = HttpClient::new().post(url).json(payload).send().await?;
+
+    let status = response.status();
+    if !status.is_success() {
+        bail!("webhook answered {}: {}", status, response.text().await?);
+    }
+    Ok(())
+}
@@ -0,0 +1,82 @@
+use super::{render, shorten, Announcement};
+use crate::{config::TelegramConfig, http::HttpClient};
+use anyhow::{bail, Context, Result};
+use serde::Deserialize;
+use serde_json::json;
+use std::env;
+
+const BOT_TOKEN_VAR: &str = "TELEGRAM_BOT_TOKEN";
+const API_URL: &str = "https://api.telegram.org";
+/// Longest message the Bot API accepts
+const MAX_LENGTH: usize = 4096;
+
+pub const DEFAULT_MESSAGE: &str = "*{{ name }} {{ version }}* is out! [Release notes]({{ url }})
+{{ #each highlights }}
+• {{ description }}
+{{ /each }}";
+
+#[derive(Deserialize)]
+struct SendMessageResponse {
+    ok: bool,
+    description: Option<String>,
+}
+
+/// Rendered message, shortened to fit in a Telegram message
+pub fn message(config: &TelegramConfig, announcement: &Announcement) -> Result<String> {
+    let message = render(config.message.as_deref(), DEFAULT_MESSAGE, announcement)?;
+    Ok(shorten(&message, MAX_LENGTH, |text| text.chars().count()))
+}
+
+/// Send `message` to the chat with the bot token. A message whose formatting Telegram rejects,
+/// e.g. because a highlight contains a stray `_`, is sent again as plain text.
+pub async fn post(config: &TelegramConfig, message: &str) -> Result<()> {
+    let token = env::var(BOT_TOKEN_VAR)
+        .with_context(|| format!("{} is required to post to Telegram", BOT_TOKEN_VAR))?;
+
+    let response = send_message(&token, config, message, Some(&config.parse_mode)).await?;
+    if response.ok {
+        return Ok(());
+    }
+
+    let error = response.description.unwrap_or_default();
+    if !error.contains("can't parse entities") {
+        bail!("sendMessage failed: {}", error);
+    }
+    log::warn!(
+        "Telegram could not parse the message as {}, sending it as plain text: {}",
+        config.parse_mode,
+        error
+    );
+    let response = send_message(&token, config, message, None).await?;
+    if !response.ok {
+        bail!(
+            "sendMessage failed: {}",
+            response.description.unwrap_or_default()
+        );
+    }
+    Ok(())
+}
+
+async fn send_message(
+    token: &str,
+    config: &TelegramConfig,
+    message: &str,
+    parse_mode: Option<&str>,
+) -> Result<SendMessageResponse> {
+    // errors are answered with a 4xx status and the reason in the body
+    let response = HttpClient::new()
+        .post(format!("{}/bot{}/sendMessage", API_URL, token))
+        .json(&json!({
+            "chat_id": config.chat_id,
+            "text": message,
+            "parse_mode": parse_mode,
+        }))
+        .send()
+        .await
+        // the token is part of the URL
+        .map_err(reqwest::Error::without_url)?
+        .json::<SendMessageResponse>()
+        .await
+        .map_err(reqwest::Error::without_url)?;
+    Ok(response)
+}
@@ -0,0 +1,124 @@
+use super::Announcement;
+use crate::{
+    config::{WebhookConfig, WebhookEvent},
+    dry_run,
+    http::HttpClient,
+    template,
+};
+use anyhow::{bail, Context, Result};
+use hmac::{Hmac, Mac};
+use serde::Serialize;
+use sha2::Sha256;
+use std::{collections::HashMap, env, path::Path};
+
+/// Values available to the payload template
+#[derive(Debug, Serialize)]
+struct Event<'a> {
+    event: WebhookEvent,
+    #[serde(flatten)]
+    announcement: &'a Announcement,
+}
+
+/// Request a dry run would have sent
+#[derive(Debug, Serialize)]
+struct Request<'a> {
+    url: &'a str,
+    headers: &'a HashMap<String, String>,
+    body: serde_json::Value,
+}
+
+/// Send `event` to the webhooks subscribed to it. Like the other announcements, failures are
+/// reported without failing the run.
+pub async fn notify(
+    webhooks: &[WebhookConfig],
+    event: WebhookEvent,
+    announcement: &Announcement,
+    dry_run: bool,
+    output_path: &Path,
+) -> Result<()> {
+    let subscribed = webhooks
+        .iter()
+        .enumerate()
+        .filter(|(_, webhook)| webhook.enabled && webhook.events.contains(&event));
+
+    for (i, webhook) in subscribed {
+        let body = body(webhook, event, announcement)
+            .with_context(|| format!("failed to render the payload of {}", webhook.url))?;
+
+        if dry_run {
+            dry_run::record(
+                output_path,
+                &format!("webhook-{}-{}", event.as_str(), i),
+                &format!("send {} to {}", event.as_str(), webhook.url),
+                &Request {
+                    url: &webhook.url,
+                    headers: &webhook.headers,
+                    body: serde_json::from_str(&body)?,
+                },
+            )?;
+            continue;
+        }
+
+        match send(webhook, body).await {
+            Ok(()) => log::info!("Sent {} to {}", event.as_str(), webhook.url),
+            Err(err) => log::error!(
+                "Failed to send {} to {}: {:#}",
+                event.as_str(),
+                webhook.url,
+                err
+            ),
+        }
+    }
+    Ok(())
+}
+
+/// The rendered payload, or else the event with the announcement values
+fn body(
+    webhook: &WebhookConfig,
+    event: WebhookEvent,
+    announcement: &Announcement,
+) -> Result<String> {
+    let event = Event {
+        event,
+        announcement,
+    };
+    match &webhook.payload {
+        Some(payload) => {
+            let body = template::render(payload, &event)?;
+            serde_json::from_str::<serde_json::Value>(&body).context("the payload isn't JSON")?;
+            Ok(body)
+        }
+        None => Ok(serde_json::to_string(&event)?),
+    }
+}
+
+async fn send(webhook: &WebhookConfig, body: String) -> Result<()> {
+    let mut request = HttpClient::new()
+        .post(&webhook.url)
+        .header(reqwest::header::CONTENT_TYPE, "application/json");
+    for (name, value) in &webhook.headers {
+        request = request.header(name, value);
+    }
+    if let Some(secret_env) = &webhook.secret_env {
+        let secret = env::var(secret_env)
+            .with_context(|| format!("{} is required to sign the payload", secret_env))?;
+        request = request.header(&webhook.signature_header, signature(&secret, &body)?);
+    }
+
+    let response = request.body(body).send().await?;
+    let status = response.status();
+    if !status.is_success() {
+        bail!("webhook answered {}: {}", status, response.text().await?);
+    }
+    Ok(())
+}
+
+/// `sha256=` followed by the HMAC-SHA256 of `body`, as GitHub signs its webhooks
+fn signature(secret: &str, body: &str) -> Result<String> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes())?;
+    mac.update(body.as_bytes());
+    Ok(format!(
+        "sha256={}",
+        hex::encode(mac.finalize().into_bytes())
+    ))
+}
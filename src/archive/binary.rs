@@ -0,0 +1,26 @@
+use super::{ArchiveEntry, Archiver};
+use anyhow::{bail, Result};
+use std::{fs, path::Path};
+
+/// Publishes the binary as is, without any archive around it
+pub struct BinaryArchiver;
+
+impl Archiver for BinaryArchiver {
+    fn archive(&self, entries: &[ArchiveEntry], output: &Path) -> Result<()> {
+        let Some((binary, files)) = entries.split_first() else {
+            bail!("no binary to publish")
+        };
+
+        if !files.is_empty() {
+            log::warn!(
+                "{} extra files are ignored for raw binary {}",
+                files.len(),
+                output.display()
+            );
+        }
+
+        fs::copy(&binary.path, output)?;
+
+        Ok(())
+    }
+}
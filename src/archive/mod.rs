@@ -0,0 +1,67 @@
+pub mod binary;
+pub mod tar;
+pub mod zip;
+
+use anyhow::{Context, Result};
+use std::path::{Path, PathBuf};
+
+/// A file to put in an archive
+#[derive(Debug, Clone)]
+pub struct ArchiveEntry {
+    /// Path of the file inside the archive
+    pub name: String,
+    /// Path of the file on disk
+    pub path: PathBuf,
+}
+
+impl ArchiveEntry {
+    pub fn new<S, P>(name: S, path: P) -> Self
+    where
+        S: Into<String>,
+        P: AsRef<Path>,
+    {
+        ArchiveEntry {
+            name: name.into(),
+            path: path.as_ref().to_path_buf(),
+        }
+    }
+}
+
+/// Resolve glob patterns relative to `base`, keeping the relative paths as archive names
+pub fn resolve_files(base: &Path, patterns: &[String]) -> Result<Vec<ArchiveEntry>> {
+    let mut entries: Vec<ArchiveEntry> = vec![];
+
+    for pattern in patterns {
+        let full_pattern = base.join(pattern);
+        let paths = glob::glob(&full_pattern.to_string_lossy())
+            .with_context(|| format!("invalid file pattern: {}", pattern))?;
+
+        let mut matched = false;
+        for path in paths {
+            let path = path?;
+            if !path.is_file() {
+                continue;
+            }
+            matched = true;
+
+            let name = path
+                .strip_prefix(base)
+                .unwrap_or(&path)
+                .to_string_lossy()
+                .replace('\\', "/");
+            if !entries.iter().any(|entry| entry.name == name) {
+                entries.push(ArchiveEntry::new(name, &path));
+            }
+        }
+
+        if !matched {
+            log::warn!("no files matched pattern: {}", pattern);
+        }
+    }
+
+    Ok(entries)
+}
+
+pub trait Archiver {
+    fn archive(&self, entries: &[ArchiveEntry], output: &Path) -> Result<()>;
+}
@@ -0,0 +1,52 @@
+use super::{ArchiveEntry, Archiver};
+use crate::build::compression::Compression;
+use anyhow::{bail, Result};
+use std::{fs::File, io::Write, path::Path};
+use tar::Builder;
+
+pub struct TarArchiver {
+    compression: Compression,
+}
+
+impl TarArchiver {
+    pub fn new(compression: Compression) -> Self {
+        TarArchiver { compression }
+    }
+}
+
+impl Archiver for TarArchiver {
+    fn archive(&self, entries: &[ArchiveEntry], output: &Path) -> Result<()> {
+        let file = File::create(output)?;
+
+        match self.compression {
+            Compression::TarGz => {
+                let encoder = flate2::write::GzEncoder::new(file, flate2::Compression::default());
+                append(entries, encoder)?.finish()?;
+            }
+            Compression::TarXz => {
+                let encoder = xz2::write::XzEncoder::new(file, 6);
+                append(entries, encoder)?.finish()?;
+            }
+            Compression::TarZst => {
+                let encoder = zstd::Encoder::new(file, zstd::DEFAULT_COMPRESSION_LEVEL)?;
+                append(entries, encoder)?.finish()?;
+            }
+            Compression::Zip | Compression::Binary => {
+                bail!("{:?} is not a tar format", self.compression)
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn append<W>(entries: &[ArchiveEntry], writer: W) -> Result<W>
+where
+    W: Write,
+{
+    let mut archive = Builder::new(writer);
+    for entry in entries {
+        archive.append_path_with_name(&entry.path, &entry.name)?;
+    }
+    Ok(archive.into_inner()?)
+}
@@ -0,0 +1,40 @@
+use super::{ArchiveEntry, Archiver};
+use anyhow::Result;
+use std::{
+    fs::{self, File},
+    io,
+    path::Path,
+};
+use zip::{write::FileOptions, CompressionMethod, ZipWriter};
+
+pub struct ZipArchiver;
+
+impl Archiver for ZipArchiver {
+    fn archive(&self, entries: &[ArchiveEntry], output: &Path) -> Result<()> {
+        let mut archive = ZipWriter::new(File::create(output)?);
+
+        for entry in entries {
+            let options = FileOptions::default()
+                .compression_method(CompressionMethod::Deflated)
+                .unix_permissions(permissions(&entry.path)?);
+            archive.start_file(entry.name.as_str(), options)?;
+            io::copy(&mut File::open(&entry.path)?, &mut archive)?;
+        }
+
+        archive.finish()?;
+
+        Ok(())
+    }
+}
+
+#[cfg(unix)]
+fn permissions(path: &Path) -> Result<u32> {
+    use std::os::unix::fs::PermissionsExt;
+    Ok(fs::metadata(path)?.permissions().mode())
+}
+
+#[cfg(not(unix))]
+fn permissions(path: &Path) -> Result<u32> {
+    let _ = fs::metadata(path)?;
+    Ok(0o755)
+}
@@ -0,0 +1,149 @@
+use crate::{config::BrewAudit, render::PLACEHOLDER_SHA256};
+use anyhow::{bail, Context, Result};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::{fs, path::Path};
+use tokio::process::Command;
+
+/// Longest `desc` accepted by `brew audit`
+const MAX_DESCRIPTION_LENGTH: usize = 80;
+
+static CLASS: Lazy<Regex> = Lazy::new(|| Regex::new(r"^class (\S+) < Formula\s*$").unwrap());
+static CONSTANT: Lazy<Regex> = Lazy::new(|| Regex::new(r"^[A-Z][A-Za-z0-9]*$").unwrap());
+static STRING: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"^\s*(desc|homepage|url|sha256) "([^"]*)""#).unwrap());
+static ENTITY: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"&(quot|amp|lt|gt|#x[0-9A-Fa-f]+|#[0-9]+);").unwrap());
+/// Keywords opening a block when they start a line, rather than modifying a statement
+static OPENER: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^\s*(class|def|module|if|unless|case|while|until|begin)\b|\bdo(\s*\|[^|]*\|)?\s*$")
+        .unwrap()
+});
+static END: Lazy<Regex> = Lazy::new(|| Regex::new(r"^\s*end\s*$").unwrap());
+
+/// Check the formula written at `path` with `audit`, returning its content, as fixed by
+/// `brew style` if it ran
+pub async fn audit(audit: BrewAudit, path: &Path, formula: String) -> Result<String> {
+    match audit {
+        BrewAudit::Off => Ok(formula),
+        BrewAudit::Builtin => {
+            let problems = lint(&formula);
+            if problems.is_empty() {
+                return Ok(formula);
+            }
+            for problem in &problems {
+                log::error!("{}: {}", path.display(), problem);
+            }
+            bail!(
+                "found {} problem(s) in the formula {}",
+                problems.len(),
+                path.display()
+            )
+        }
+        BrewAudit::Brew => {
+            log::info!("Running brew style on {}", path.display());
+            let output = Command::new("brew")
+                .args(["style", "--fix"])
+                .arg(path)
+                .output()
+                .await
+                .context("failed to run brew, is Homebrew installed?")?;
+            if !output.status.success() {
+                bail!(
+                    "brew style found problems in {}:\n{}",
+                    path.display(),
+                    String::from_utf8_lossy(&output.stdout).trim()
+                );
+            }
+            fs::read_to_string(path).with_context(|| format!("failed to read {}", path.display()))
+        }
+    }
+}
+
+/// Mistakes `brew audit` and `brew style` would report, or that break the formula outright
+pub fn lint(formula: &str) -> Vec<String> {
+    let mut problems = vec![];
+
+    match formula.lines().find_map(|line| CLASS.captures(line)) {
+        Some(class) if !CONSTANT.is_match(&class[1]) => problems.push(format!(
+            "class name {} is not a valid Ruby constant",
+            &class[1]
+        )),
+        Some(_) => {}
+        None => problems.push("no class inheriting from Formula".to_owned()),
+    }
+
+    let values = formula
+        .lines()
+        .filter_map(|line| STRING.captures(line))
+        .map(|captures| (captures[1].to_owned(), captures[2].to_owned()))
+        .collect::<Vec<_>>();
+    let value = |name: &str| values.iter().find(|(key, _)| key == name).map(|(_, v)| v);
+
+    match value("desc") {
+        Some(desc) => {
+            if desc.len() > MAX_DESCRIPTION_LENGTH {
+                problems.push(format!(
+                    "desc is {} characters long, at most {} are allowed",
+                    desc.len(),
+                    MAX_DESCRIPTION_LENGTH
+                ));
+            }
+            if desc.starts_with("A ") || desc.starts_with("An ") {
+                problems.push("desc should not start with an article".to_owned());
+            }
+            if desc.ends_with('.') {
+                problems.push("desc should not end with a period".to_owned());
+            }
+        }
+        None => problems.push("missing desc".to_owned()),
+    }
+    if value("homepage").is_none() {
+        problems.push("missing homepage".to_owned());
+    }
+    if value("url").is_none() {
+        problems.push("missing url".to_owned());
+    }
+    for (key, value) in &values {
+        match key.as_str() {
+            "homepage" | "url" if value.starts_with("http://") => {
+                problems.push(format!("{} {} should use https", key, value))
+            }
+            "sha256" if value == PLACEHOLDER_SHA256 => {
+                problems.push("sha256 is a placeholder".to_owned())
+            }
+            "sha256" if value.len() != 64 || !value.chars().all(|c| c.is_ascii_hexdigit()) => {
+                problems.push(format!("sha256 {} is not a SHA-256 checksum", value))
+            }
+            _ => {}
+        }
+    }
+
+    for (i, line) in formula.lines().enumerate() {
+        if ENTITY.is_match(line) {
+            problems.push(format!(
+                "line {} has HTML-escaped text: {}",
+                i + 1,
+                line.trim()
+            ));
+        }
+    }
+
+    let code = formula
+        .lines()
+        .filter(|line| !line.trim_start().starts_with('#'));
+    let (opened, ended) = code.fold((0, 0), |(opened, ended), line| {
+        (
+            opened + OPENER.is_match(line) as usize,
+            ended + END.is_match(line) as usize,
+        )
+    });
+    if opened != ended {
+        problems.push(format!(
+            "{} blocks are opened but {} are closed with end",
+            opened, ended
+        ));
+    }
+
+    problems
+}
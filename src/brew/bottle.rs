@@ -0,0 +1,167 @@
+use crate::{
+    archive, checksum,
+    config::{BrewBottlesConfig, ReleaseConfig},
+    dry_run,
+    github::{
+        asset::{download_url, Asset},
+        github_client,
+        tag::Tag,
+    },
+};
+use anyhow::{bail, Context, Result};
+use itertools::Itertools;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::path::{Path, PathBuf};
+
+/// Cellars a bottle can be poured into without a path, see `brew bottle`
+const CELLAR_SYMBOLS: [&str; 2] = ["any", "any_skip_relocation"];
+
+/// Bottle tag and rebuild of a file written by `brew bottle`, e.g.
+/// `app--1.0.0.arm64_sonoma.bottle.tar.gz` or `app--1.0.0.x86_64_linux.bottle.1.tar.gz`
+static BOTTLE_FILE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"\.([a-z0-9_]+)\.bottle(?:\.(\d+))?\.tar\.gz$").unwrap());
+
+/// `bottle do` block of the formula
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Bottles {
+    pub root_url: String,
+    /// Ruby value of the `cellar`, e.g. `:any_skip_relocation`
+    pub cellar: String,
+    pub rebuild: u32,
+    pub files: Vec<Bottle>,
+    /// Whether the bottles are uploaded to the release, rather than hosted at `root_url`
+    #[serde(skip)]
+    pub upload: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Bottle {
+    /// Bottle tag, e.g. `arm64_sonoma`
+    pub tag: String,
+    pub sha256: String,
+    /// Name Homebrew downloads the bottle as from `root_url`
+    #[serde(skip)]
+    pub name: String,
+    #[serde(skip)]
+    pub path: PathBuf,
+}
+
+impl Bottles {
+    /// Bottles of the formula `name` matched by the `files` patterns in `base`
+    pub fn resolve(
+        config: BrewBottlesConfig,
+        base: &Path,
+        name: &str,
+        release_config: &ReleaseConfig,
+        version: &Tag,
+    ) -> Result<Bottles> {
+        let mut files = vec![];
+        let mut rebuilds = vec![];
+        for entry in archive::resolve_files(base, &config.files)? {
+            let Some(captures) = BOTTLE_FILE.captures(&entry.name) else {
+                bail!(
+                    "{} is not a bottle, expected a <name>--<version>.<tag>.bottle.tar.gz file",
+                    entry.name
+                );
+            };
+            let tag = captures[1].to_owned();
+            let rebuild = match captures.get(2) {
+                Some(rebuild) => rebuild.as_str().parse()?,
+                None => 0,
+            };
+            if files.iter().any(|bottle: &Bottle| bottle.tag == tag) {
+                bail!("found several bottles for {}", tag);
+            }
+            rebuilds.push(rebuild);
+            files.push(Bottle {
+                sha256: checksum::create(&entry.name, &entry.path)
+                    .with_context(|| format!("failed to hash the bottle {}", entry.name))?,
+                name: format!("{}-{}{}", name, version.version(), &captures[0]),
+                path: entry.path,
+                tag,
+            });
+        }
+
+        let rebuild = match rebuilds.into_iter().unique().collect_vec()[..] {
+            [] | [0] => 0,
+            [rebuild] => rebuild,
+            _ => bail!("the bottles of {} were built with different rebuilds", name),
+        };
+        let cellar = match CELLAR_SYMBOLS.contains(&config.cellar.as_str()) {
+            true => format!(":{}", config.cellar),
+            false => format!("\"{}\"", config.cellar),
+        };
+        files.sort_by(|a, b| a.tag.cmp(&b.tag));
+
+        Ok(Bottles {
+            upload: config.root_url.is_none(),
+            root_url: config.root_url.unwrap_or_else(|| {
+                download_url(&release_config.owner, &release_config.repo, version, "")
+                    .trim_end_matches('/')
+                    .to_owned()
+            }),
+            cellar,
+            rebuild,
+            files,
+        })
+    }
+
+    /// Upload the bottles to the release of `version`, replacing the ones of a previous run
+    pub async fn upload(
+        &self,
+        release_config: &ReleaseConfig,
+        version: &Tag,
+        dry_run: bool,
+        output_path: &Path,
+    ) -> Result<()> {
+        if !self.upload {
+            return Ok(());
+        }
+        if dry_run {
+            let files: Vec<_> = self
+                .files
+                .iter()
+                .map(|bottle| json!({ "name": bottle.name, "path": bottle.path }))
+                .collect();
+            return dry_run::record(
+                output_path,
+                "bottles",
+                &format!(
+                    "upload {} bottles to the release {} on {}/{}",
+                    files.len(),
+                    version.value(),
+                    release_config.owner,
+                    release_config.repo
+                ),
+                &files,
+            );
+        }
+
+        let release = github_client::instance()
+            .repo(&release_config.owner, &release_config.repo)
+            .releases()
+            .get_by_tag(version)
+            .await
+            .with_context(|| {
+                format!(
+                    "no release to upload the bottles to for {}",
+                    version.value()
+                )
+            })?
+            .with_upload_config(release_config.upload.to_owned())
+            .replacing_assets(true);
+        for bottle in &self.files {
+            log::info!("Uploading bottle {}", bottle.name);
+            release
+                .upload_asset(
+                    &Asset::new(bottle.name.to_owned(), bottle.path.to_owned()),
+                    version,
+                )
+                .await?;
+        }
+        Ok(())
+    }
+}
@@ -1,4 +1,122 @@
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
+/// Install block of the formula, either the script alone or a table adding the shell completions
+/// and man pages
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Install(pub String);
+#[serde(try_from = "InstallValue")]
+pub struct Install {
+    pub script: String,
+    pub completions: Option<GeneratedCompletions>,
+    pub bash_completion: Option<String>,
+    pub zsh_completion: Option<String>,
+    pub fish_completion: Option<String>,
+    pub man_pages: Vec<ManPage>,
+}
+
+/// Completions printed by the binary, installed with `generate_completions_from_executable`
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct GeneratedCompletions {
+    /// Binary of the formula printing the completions
+    pub executable: String,
+    /// Arguments before the shell, e.g. `["completions"]` runs `app completions bash`
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// Shells to generate the completions of, all the shells Homebrew knows by default
+    #[serde(default)]
+    pub shells: Vec<Shell>,
+    /// How the shell is passed to the binary, e.g. `flag` for `--shell=bash` or `clap` for the
+    /// `COMPLETE` environment variable of `clap_complete`
+    pub shell_parameter_format: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+    Pwsh,
+}
+
+/// Man page shipped in the archive, installed in the section of its extension
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ManPage {
+    pub path: String,
+    pub section: String,
+}
+
+impl TryFrom<String> for ManPage {
+    type Error = String;
+
+    fn try_from(path: String) -> Result<Self, Self::Error> {
+        let name = path.strip_suffix(".gz").unwrap_or(&path);
+        match name.rsplit_once('.') {
+            Some((_, section))
+                if section.starts_with(|c: char| c.is_ascii_digit()) && !section.contains('/') =>
+            {
+                Ok(ManPage {
+                    section: section[..1].to_owned(),
+                    path,
+                })
+            }
+            _ => Err(format!(
+                "invalid man page `{}`, expected its section as extension, e.g. app.1",
+                path
+            )),
+        }
+    }
+}
+
+/// Accepts both `install = 'bin.install "app"'` and a `[brew.install]` table
+#[derive(Deserialize, JsonSchema)]
+#[serde(untagged)]
+pub enum InstallValue {
+    Script(String),
+    Table {
+        script: String,
+        completions: Option<GeneratedCompletions>,
+        /// Pre-built completions in the archive
+        bash_completion: Option<String>,
+        zsh_completion: Option<String>,
+        fish_completion: Option<String>,
+        /// Pre-built man pages in the archive, e.g. `man/app.1`
+        #[serde(default)]
+        man_pages: Vec<String>,
+    },
+}
+
+impl TryFrom<InstallValue> for Install {
+    type Error = String;
+
+    fn try_from(value: InstallValue) -> Result<Self, Self::Error> {
+        Ok(match value {
+            InstallValue::Script(script) => Install {
+                script,
+                completions: None,
+                bash_completion: None,
+                zsh_completion: None,
+                fish_completion: None,
+                man_pages: vec![],
+            },
+            InstallValue::Table {
+                script,
+                completions,
+                bash_completion,
+                zsh_completion,
+                fish_completion,
+                man_pages,
+            } => Install {
+                script,
+                completions,
+                bash_completion,
+                zsh_completion,
+                fish_completion,
+                man_pages: man_pages
+                    .into_iter()
+                    .map(ManPage::try_from)
+                    .collect::<Result<_, _>>()?,
+            },
+        })
+    }
+}
@@ -1,51 +1,124 @@
+pub mod audit;
+pub mod bottle;
 pub mod install;
-pub mod package;
 pub mod repository;
 pub mod target;
 
 use self::{
+    bottle::Bottles,
     install::Install,
-    package::Package,
     repository::Repository,
-    target::{MultiTarget, SingleTarget, Target, Targets},
+    target::{MultiTarget, Platform, SingleTarget, Target, Targets},
 };
 use crate::{
     build::{arch::Arch, committer::Committer},
-    config::{BrewConfig, CommitterConfig, PullRequestConfig, ReleaseConfig},
-    git,
-    github::{builder::BuilderExecutor, github_client, tag::Tag},
-    http,
+    config::{
+        BrewAudit, BrewConfig, BrewConflict, BrewDependency, BrewKegOnly, BrewLivecheckConfig,
+        BrewServiceConfig, CommitterConfig, PullRequestConfig, ReleaseConfig,
+    },
+    github::{
+        github_client,
+        tag::{Tag, VersionComponents},
+    },
+    packager::{self, package::Package, Packager},
     template::{handlebars, Template},
 };
 use anyhow::{Context, Result};
 use itertools::Itertools;
 use serde::{Deserialize, Serialize};
 use sha2::Digest;
-use std::{fs, path::PathBuf};
+use std::{fs, path::Path};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize)]
 pub struct Brew {
+    /// Ruby class of the formula, e.g. `MyCoolTool`
     pub name: String,
+    /// Name the formula is installed with and its file is named after, e.g. `my-cool-tool`
+    pub formula_name: String,
     pub description: String,
     pub homepage: String,
     pub license: String,
     pub head: String,
     pub test: String,
     pub caveats: String,
+    pub macos_caveats: String,
+    pub linux_caveats: String,
+    pub keg_only: Option<BrewKegOnly>,
+    pub conflicts_with: Vec<BrewConflict>,
+    pub service: Option<BrewServiceConfig>,
+    pub private: bool,
+    #[serde(skip)]
+    pub audit: BrewAudit,
+    pub livecheck: Option<Livecheck>,
+    pub bottles: Option<Bottles>,
+    pub source: Option<Source>,
+    pub dependencies: Vec<BrewDependency>,
     pub commit_message: String,
     pub commit_author: Option<CommitterConfig>,
-    pub install_info: Install,
+    /// Script of the install block
+    pub install_info: String,
+    pub install: Install,
     pub repository: Repository,
     #[serde(flatten)]
-    #[serde(rename(serialize = "version"))]
     pub tag: Tag,
+    #[serde(flatten)]
+    pub components: VersionComponents,
     pub pull_request: Option<PullRequestConfig>,
     pub targets: Targets,
     pub path: Option<String>,
+    pub formula_path: Option<String>,
+    #[serde(skip)]
+    pub template_path: Option<String>,
     pub url: String,
     pub hash: String,
 }
 
+impl Packager for Brew {
+    fn render(&self) -> Result<String> {
+        serialize_brew(self)
+    }
+
+    fn repository(&self) -> (&str, &str) {
+        (&self.repository.owner, &self.repository.name)
+    }
+
+    fn branch(&self) -> &str {
+        &self.head
+    }
+
+    fn path(&self) -> String {
+        self.file_path()
+    }
+
+    fn commit_message(&self) -> String {
+        self.commit_message
+            .replace("{{version}}", self.tag.version())
+    }
+
+    fn committer(&self) -> Option<Committer> {
+        self.commit_author.to_owned().map(Committer::from)
+    }
+
+    fn pull_request(&self) -> Option<&PullRequestConfig> {
+        self.pull_request.as_ref()
+    }
+
+    async fn check(&self, path: &Path, formula: String) -> Result<String> {
+        audit::audit(self.audit, path, formula).await
+    }
+
+    /// Create the tap, on the base branch of the pull requests if there are any
+    async fn prepare(&self) -> Result<()> {
+        let branch = match &self.pull_request {
+            Some(pull_request) => &pull_request.base,
+            None => &self.head,
+        };
+        self.repository
+            .create_if_missing(branch, &self.formula_name)
+            .await
+    }
+}
+
 impl Brew {
     pub async fn new(
         brew: BrewConfig,
@@ -53,39 +126,132 @@ impl Brew {
         version: Tag,
         packages: Vec<Package>,
     ) -> Result<Brew> {
-        let url = format!(
-            "https://github.com/{}/{}/archive/refs/tags/v{}.tar.gz",
-            release_config.owner, release_config.repo, version.name
-        );
+        let repo = github_client::instance().repo(&release_config.owner, &release_config.repo);
         let hash = {
             let mut hasher = sha2::Sha256::new();
-            let bytes = http::HttpClient::new()
-                .get(&url)
-                .send()
-                .await?
-                .bytes()
-                .await?;
-            hasher.update(&bytes);
+            hasher.update(repo.archive(&version).await?);
             format!("{:x}", hasher.finalize())
         };
-        Ok(Brew {
-            name: captalize(brew.name),
+        Ok(Brew::with_hash(
+            brew,
+            &release_config,
+            version,
+            packages,
+            hash,
+        ))
+    }
+
+    /// Formula of `version`, given the sha256 of its source archive
+    pub fn with_hash(
+        brew: BrewConfig,
+        release_config: &ReleaseConfig,
+        version: Tag,
+        packages: Vec<Package>,
+        hash: String,
+    ) -> Brew {
+        let repo = github_client::instance().repo(&release_config.owner, &release_config.repo);
+        let url = repo.archive_url(&version);
+        let targets = Targets::from(packages);
+        let source = brew.source.map(|source| Source {
+            url: repo.git_url(),
+            branch: source
+                .branch
+                .unwrap_or_else(|| release_config.target_branch.to_owned()),
+            path: source.path,
+            fallback: match source.fallback {
+                true => targets.missing_platforms(),
+                false => vec![],
+            },
+        });
+        Brew {
+            name: class_name(&brew.name),
+            formula_name: brew.name,
             description: brew.description,
             homepage: brew.homepage,
-            install_info: brew.install,
+            install_info: brew.install.script.to_owned(),
+            install: brew.install,
             repository: brew.repository,
+            components: version.components(),
+            livecheck: Livecheck::new(brew.livecheck, &version),
+            bottles: None,
             tag: version,
-            targets: Targets::from(packages),
+            targets,
+            source,
             license: brew.license,
             head: brew.head,
             test: brew.test,
             caveats: brew.caveats,
+            macos_caveats: brew.macos_caveats,
+            linux_caveats: brew.linux_caveats,
+            keg_only: brew.keg_only,
+            conflicts_with: brew.conflicts_with,
+            service: brew.service,
+            private: brew.private,
+            audit: brew.audit,
+            dependencies: brew.dependencies,
             commit_message: brew.commit_message,
             commit_author: brew.commit_author,
             pull_request: brew.pull_request,
             path: brew.path,
+            formula_path: brew.formula_path,
+            template_path: brew.template_path,
             url,
             hash,
+        }
+    }
+
+    pub fn with_bottles(mut self, bottles: Option<Bottles>) -> Brew {
+        self.bottles = bottles;
+        self
+    }
+
+    /// Path of the formula in the tap
+    pub fn file_path(&self) -> String {
+        formula_path(
+            &self.formula_name,
+            self.path.as_deref(),
+            self.formula_path.as_deref(),
+        )
+    }
+}
+
+/// `head` spec of the formula, and the platforms built from the source archive
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Source {
+    pub url: String,
+    pub branch: String,
+    pub path: String,
+    pub fallback: Vec<Platform>,
+}
+
+/// `livecheck` block of the formula
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Livecheck {
+    /// `url` of the formula checked, e.g. `stable`
+    pub symbol: Option<String>,
+    pub url: Option<String>,
+    pub regex: String,
+    pub strategy: Option<String>,
+}
+
+impl Livecheck {
+    fn new(config: BrewLivecheckConfig, version: &Tag) -> Option<Livecheck> {
+        if !config.enabled {
+            return None;
+        }
+        let (symbol, url) = match config.url.as_str() {
+            "stable" | "head" | "homepage" | "url" => (Some(config.url), None),
+            _ => (None, Some(config.url)),
+        };
+        // the tags of the release, e.g. `^cli-v?(\d+(?:\.\d+)+)$` for the prefix `cli-v`
+        let regex = config
+            .regex
+            .unwrap_or_else(|| format!(r"^{}v?(\d+(?:\.\d+)+)$", regex::escape(version.scope())));
+        Some(Livecheck {
+            symbol,
+            url,
+            regex: regex.replace(r"\/", "/").replace('/', r"\/"),
+            strategy: config.strategy,
         })
     }
 }
@@ -101,163 +267,129 @@ pub async fn release(
     brew_config: BrewConfig,
     release_config: ReleaseConfig,
     packages: Vec<Package>,
-    template: Template,
-    base: PathBuf,
+    tag: Tag,
     dry_run: bool,
-    output_path: &PathBuf,
+    base: &Path,
+    output_path: &Path,
 ) -> Result<String> {
-    let brew = Brew::new(
-        brew_config,
-        release_config,
-        git::get_current_tag(&base)?,
-        packages,
-    )
-    .await?;
-
-    log::debug!("Rendering Formula template {}", template.to_string());
-    let data = serialize_brew(&brew, template)?;
-
-    write_file(output_path.join(format!("{}.rb", brew.name)), &data)?;
-
-    if !dry_run {
-        if brew.pull_request.is_some() {
-            log::debug!("Creating pull request");
-            push_formula(brew).await?;
-        } else {
-            log::debug!("Committing file to head branch");
-            github_client::instance()
-                .repo(&brew.repository.owner, &brew.repository.name)
-                .branch(&brew.head)
-                .upsert_file()
-                .path(if brew.path.is_some() {
-                    format!("{}/{}.rb", brew.path.unwrap(), brew.name)
-                } else {
-                    format!("{}.rb", brew.name)
-                })
-                .message(brew.commit_message.replace("{{version}}", &brew.tag.name))
-                .content(&data)
-                .execute()
-                .await
-                .context("error uploading file to main branch")?;
-        }
-    } else {
-        log::debug!("Dry run, not pushing to github or creating pull request");
+    let bottles = brew_config
+        .bottles
+        .to_owned()
+        .map(|bottles| Bottles::resolve(bottles, base, &brew_config.name, &release_config, &tag))
+        .transpose()?;
+    if let Some(bottles) = &bottles {
+        bottles
+            .upload(&release_config, &tag, dry_run, output_path)
+            .await?;
     }
+    let brew = Brew::new(brew_config, release_config, tag, packages)
+        .await?
+        .with_bottles(bottles);
 
-    Ok(data)
+    packager::publish(&brew, dry_run, output_path).await
 }
 
-fn serialize_brew<T>(data: &T, template: Template) -> Result<String>
-where
-    T: Serialize,
-{
+/// Render the formula from the user template, or else the built-in template matching its targets
+pub fn serialize_brew(brew: &Brew) -> Result<String> {
     let hb = handlebars()?;
-    let rendered = hb.render(&template.to_string(), data)?;
-    Ok(rendered)
-}
+    let template = Template::from(&brew.targets);
 
-fn write_file<S>(path: PathBuf, data: S) -> Result<()>
-where
-    S: Into<String>,
-{
-    fs::write(path, data.into())?;
-    Ok(())
+    let rendered = match &brew.template_path {
+        Some(path) => {
+            log::debug!("Rendering Formula template {}", path);
+            let content = fs::read_to_string(path)
+                .with_context(|| format!("failed to read formula template {}", path))?;
+            hb.render_template(&content, brew)?
+        }
+        None => {
+            log::debug!("Rendering Formula template {}", template.to_string());
+            hb.render(&template.to_string(), brew)?
+        }
+    };
+    Ok(rendered)
 }
 
-fn captalize(mut string: String) -> String {
-    format!("{}{string}", string.remove(0).to_uppercase())
+/// Ruby class of the formula `name`, derived like Homebrew's `Formulary.class_s`: `my-cool-tool`
+/// is `MyCoolTool`, `foo++` is `Fooxx` and `openssl@3` is `OpensslAT3`
+pub fn class_name(name: &str) -> String {
+    let mut class = String::new();
+    let mut chars = name.chars().peekable();
+    if let Some(first) = chars.next() {
+        class.extend(first.to_uppercase());
+    }
+    while let Some(c) = chars.next() {
+        match (c, chars.peek()) {
+            ('-' | '_' | '.' | ' ', Some(next)) if next.is_ascii_alphanumeric() => {
+                class.push(next.to_ascii_uppercase());
+                chars.next();
+            }
+            ('+', _) => class.push('x'),
+            ('@', Some(next)) if next.is_ascii_digit() && !class.is_empty() => class.push_str("AT"),
+            _ => class.extend(c.to_lowercase()),
+        }
+    }
+    class
 }
 
-async fn push_formula(brew: Brew) -> Result<()> {
-    let pull_request = brew.pull_request.unwrap();
-
-    let committer: Committer = brew.commit_author.map(Committer::from).unwrap_or_default();
-
-    let repo_handler =
-        github_client::instance().repo(&brew.repository.owner, &brew.repository.name);
-
-    log::debug!("Creating branch");
-    let sha = repo_handler
-        .branch(&pull_request.base)
-        .get_commit_sha()
-        .await
-        .context("error getting the base branch commit sha")?;
-
-    repo_handler
-        .branches()
-        .create()
-        .branch(&pull_request.head)
-        .sha(sha.sha)
-        .execute()
-        .await
-        .context("error creating the branch")?;
-
-    let content = fs::read_to_string(format!("{}.rb", brew.name))?;
-
-    log::debug!("Updating formula");
-    repo_handler
-        .branch(&pull_request.head)
-        .upsert_file()
-        .path(format!("{}.rb", brew.name))
-        .message(brew.commit_message.replace("{{version}}", &brew.tag.name))
-        .content(content)
-        .committer(&committer)
-        .execute()
-        .await
-        .context("error uploading file to head branch")?;
-
-    log::debug!("Creating pull request");
-    repo_handler
-        .pull_request()
-        .create()
-        .assignees(pull_request.assignees.unwrap_or_default())
-        .base(pull_request.base)
-        .head(&pull_request.head)
-        .body(pull_request.body.unwrap_or_default())
-        .labels(pull_request.labels.unwrap_or_default())
-        .title(pull_request.title.unwrap_or_default())
-        .committer(&committer)
-        .execute()
-        .await
-        .context("error creating pull request")?;
-
-    Ok(())
+/// Path of the formula file inside the tap repository, `file` or else `<name>.rb` in `path`
+pub fn formula_path(name: &str, path: Option<&str>, file: Option<&str>) -> String {
+    if let Some(file) = file {
+        file.trim_start_matches('/').to_owned()
+    } else if let Some(path) = path {
+        format!("{}/{}.rb", path, name)
+    } else {
+        format!("{}.rb", name)
+    }
 }
 
 impl From<Vec<Package>> for Targets {
-    fn from(value: Vec<Package>) -> Targets {
-        let v: Vec<Target> = if value.is_empty() {
-            vec![]
-        } else if value[0].arch.is_none() && value[0].os.is_none() {
-            let target = vec![Target::Single(SingleTarget {
-                url: value[0].url.clone().unwrap_or_default(),
-                hash: value[0].sha256.clone(),
-            })];
-            target
-        } else {
-            let group = value
-                .iter()
-                .cloned()
-                .group_by(|p| p.os.to_owned())
-                .into_iter()
-                .map(|g| MultiTarget {
-                    os: g.0.unwrap(),
-                    archs: g
-                        .1
-                        .map(|p| BrewArch {
-                            arch: p.arch.to_owned().unwrap(),
+    fn from(mut value: Vec<Package>) -> Targets {
+        if value.iter().all(|p| p.os.is_none() && p.arch.is_none()) {
+            return Targets(
+                value
+                    .first()
+                    .map(|p| {
+                        Target::Single(SingleTarget {
                             url: p.url.clone().unwrap_or_default(),
                             hash: p.sha256.clone(),
                         })
+                    })
+                    .into_iter()
+                    .collect(),
+            );
+        }
+
+        // group_by only groups consecutive packages
+        value.sort_by(|a, b| a.os.cmp(&b.os));
+        let targets = value
+            .into_iter()
+            .filter_map(|p| match (p.os.to_owned(), p.arch.to_owned()) {
+                (Some(os), Some(arch)) => Some((os, arch, p)),
+                _ => {
+                    log::warn!(
+                        "Leaving {} out of the formula, it has no OS or arch",
+                        p.name
+                    );
+                    None
+                }
+            })
+            .group_by(|(os, _, _)| os.to_owned())
+            .into_iter()
+            .map(|(os, packages)| {
+                Target::Multi(MultiTarget {
+                    os,
+                    archs: packages
+                        .map(|(_, arch, p)| BrewArch {
+                            arch,
+                            url: p.url.unwrap_or_default(),
+                            hash: p.sha256,
+                        })
                         .collect(),
                 })
-                .map(Target::Multi)
-                .collect();
-
-            group
-        };
+            })
+            .collect();
 
-        Targets(v)
+        Targets(targets)
     }
 }
 
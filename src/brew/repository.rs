@@ -1,19 +1,78 @@
+use crate::github::{builder::BuilderExecutor, github_client};
+use anyhow::{Context, Result};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use std::fmt::Display;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Repository {
     pub owner: String,
     pub name: String,
+    /// Create the tap, with a README and a `Formula` directory, when it doesn't exist
+    #[serde(default)]
+    pub create: bool,
 }
 
 impl Repository {
     pub fn url(&self) -> String {
         format!("https://github.com/{}/{}", self.owner, self.name)
     }
+
+    /// Create the tap if it's missing and `create` is set, with `branch` for the formulas
+    pub async fn create_if_missing(&self, branch: &str, formula: &str) -> Result<()> {
+        let repo = github_client::instance().repo(&self.owner, &self.name);
+        if !self.create || repo.exists().await? {
+            return Ok(());
+        }
+
+        log::info!("Creating the tap {}/{}", self.owner, self.name);
+        let default_branch = repo
+            .create(&format!("Homebrew formulas of {}", self.owner))
+            .await?;
+        repo.branch(&default_branch)
+            .commit_files()
+            .file("README.md", self.readme(formula))
+            .file("Formula/.gitkeep", "")
+            .message("initialize the tap")
+            .execute()
+            .await
+            .context("error initializing the tap")?;
+
+        if branch != default_branch {
+            let sha = repo.branch(&default_branch).get_commit_sha().await?;
+            repo.branches()
+                .create()
+                .branch(branch)
+                .sha(sha.sha)
+                .execute()
+                .await?;
+        }
+        Ok(())
+    }
+
+    fn readme(&self, formula: &str) -> String {
+        // `brew tap` finds `homebrew-` repositories without their URL
+        let install = match self.name.strip_prefix("homebrew-") {
+            Some(tap) => format!("brew install {}/{}/{}", self.owner, tap, formula),
+            None => format!(
+                "brew tap {}/{} {}\nbrew install {}/{}/{}",
+                self.owner,
+                self.name,
+                self.url(),
+                self.owner,
+                self.name,
+                formula
+            ),
+        };
+        format!(
+            "# {}/{}\n\nHomebrew formulas of {}.\n\n## Usage\n\n```sh\n{}\n```\n",
+            self.owner, self.name, self.owner, install
+        )
+    }
 }
 
-impl ToString for Repository {
-    fn to_string(&self) -> String {
-        self.url()
+impl Display for Repository {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.url())
     }
 }
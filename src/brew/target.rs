@@ -1,10 +1,52 @@
 use super::BrewArch;
-use crate::build::os::Os;
+use crate::build::{arch::Arch, os::Os};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Targets(pub Vec<Target>);
 
+impl Targets {
+    /// Whether the packages were built for specific OSes and architectures, rather than being a
+    /// single archive
+    pub fn is_multi_target(&self) -> bool {
+        self.0
+            .iter()
+            .any(|target| matches!(target, Target::Multi(_)))
+    }
+
+    /// Platforms of Homebrew without a binary, when the packages target specific platforms
+    pub fn missing_platforms(&self) -> Vec<Platform> {
+        if !self.is_multi_target() {
+            return vec![];
+        }
+        let mut missing = vec![];
+        for (os, os_name) in [(Os::AppleDarwin, "macos"), (Os::UnknownLinuxGnu, "linux")] {
+            for (arch, arch_name) in [(Arch::Amd64, "intel"), (Arch::Arm64, "arm")] {
+                let built = self.0.iter().any(|target| match target {
+                    Target::Multi(multi) => {
+                        multi.os == os && multi.archs.iter().any(|a| a.arch == arch)
+                    }
+                    Target::Single(_) => false,
+                });
+                if !built {
+                    missing.push(Platform {
+                        os: os_name.to_owned(),
+                        arch: arch_name.to_owned(),
+                    });
+                }
+            }
+        }
+        missing
+    }
+}
+
+/// OS and arch as named by the `on_<os>` and `on_<arch>` blocks of a formula
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Platform {
+    pub os: String,
+    pub arch: String,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MultiTarget {
     pub os: Os,
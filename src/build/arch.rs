@@ -1,6 +1,8 @@
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use std::fmt::Display;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
 #[serde(rename_all = "lowercase")]
 pub enum Arch {
     Amd64,
@@ -25,12 +27,12 @@ impl From<String> for Arch {
     }
 }
 
-impl ToString for Arch {
-    fn to_string(&self) -> String {
+impl Display for Arch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            Arch::Amd64 => "x86_64".to_string(),
-            Arch::Arm => "arm".to_string(),
-            Arch::Arm64 => "aarch64".to_string(),
+            Arch::Amd64 => write!(f, "x86_64"),
+            Arch::Arm => write!(f, "arm"),
+            Arch::Arm64 => write!(f, "aarch64"),
         }
     }
 }
@@ -0,0 +1,189 @@
+use super::{arch::Arch, os::Os, Build};
+use crate::{archive::ArchiveEntry, brew::install::Install, template};
+use anyhow::{bail, Context, Result};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+use tokio::process::Command;
+
+/// Directory of the output where the completions are generated
+const OUTPUT_DIR: &str = "completions";
+
+fn default_args() -> Vec<String> {
+    vec!["completions".to_owned(), "{{ shell }}".to_owned()]
+}
+
+fn default_shells() -> Vec<CompletionShell> {
+    vec![
+        CompletionShell::Bash,
+        CompletionShell::Zsh,
+        CompletionShell::Fish,
+        CompletionShell::Powershell,
+    ]
+}
+
+fn default_dir() -> String {
+    "completions".to_owned()
+}
+
+/// Shell completions printed by the built binary, bundled into the archives
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct Completions {
+    /// Arguments printing the completions of `{{ shell }}`, e.g. `app completions bash`
+    #[serde(default = "default_args")]
+    pub args: Vec<String>,
+    #[serde(default = "default_shells")]
+    pub shells: Vec<CompletionShell>,
+    /// Directory of the completions in the archives
+    #[serde(default = "default_dir")]
+    pub dir: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum CompletionShell {
+    Bash,
+    Zsh,
+    Fish,
+    #[serde(alias = "pwsh")]
+    Powershell,
+}
+
+impl CompletionShell {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CompletionShell::Bash => "bash",
+            CompletionShell::Zsh => "zsh",
+            CompletionShell::Fish => "fish",
+            CompletionShell::Powershell => "powershell",
+        }
+    }
+
+    /// Conventional file name of the completions of `binary`, e.g. `_app` for zsh
+    pub fn file_name(&self, binary: &str) -> String {
+        match self {
+            CompletionShell::Bash => format!("{}.bash", binary),
+            CompletionShell::Zsh => format!("_{}", binary),
+            CompletionShell::Fish => format!("{}.fish", binary),
+            CompletionShell::Powershell => format!("_{}.ps1", binary),
+        }
+    }
+}
+
+impl Completions {
+    /// Path of the completions of `shell` in the archives
+    pub fn archive_path(&self, shell: CompletionShell, binary: &str) -> String {
+        format!(
+            "{}/{}",
+            self.dir.trim_end_matches('/'),
+            shell.file_name(binary)
+        )
+    }
+
+    /// Archive entries of the completions generated in `output_path`
+    pub fn entries(&self, binary: &str, output_path: &Path) -> Result<Vec<ArchiveEntry>> {
+        self.shells
+            .iter()
+            .map(|shell| {
+                let path = generated_path(*shell, binary, output_path);
+                if !path.is_file() {
+                    bail!(
+                        "missing {} completions at {}, they are generated by the build stage",
+                        shell.as_str(),
+                        path.display()
+                    );
+                }
+                Ok(ArchiveEntry::new(self.archive_path(*shell, binary), path))
+            })
+            .collect()
+    }
+
+    /// Install the bundled completions with the formula, unless the install block already sets
+    /// them. Homebrew has no directory for the PowerShell ones
+    pub fn install(&self, install: &mut Install, binary: &str) {
+        for shell in &self.shells {
+            let field = match shell {
+                CompletionShell::Bash => &mut install.bash_completion,
+                CompletionShell::Zsh => &mut install.zsh_completion,
+                CompletionShell::Fish => &mut install.fish_completion,
+                CompletionShell::Powershell => continue,
+            };
+            if field.is_none() {
+                *field = Some(self.archive_path(*shell, binary));
+            }
+        }
+    }
+}
+
+/// Run the binary built for this host to write the completions of every shell to `output_path`
+pub async fn generate(build_info: &Build, base: &Path, output_path: &Path) -> Result<()> {
+    let Some(completions) = &build_info.completions else {
+        return Ok(());
+    };
+    let (arch, os) = (Arch::current(), Os::current());
+    if !build_info
+        .targets()
+        .contains(&(arch.to_owned(), os.to_owned()))
+    {
+        bail!(
+            "the completions are printed by the binary, but none of the targets runs on {}-{}",
+            arch,
+            os
+        );
+    }
+    let binary = build_info.binary_path(base, &arch, &os);
+    let binary = fs::canonicalize(&binary)
+        .with_context(|| format!("{} is missing, build it first", binary.display()))?;
+
+    let dir = output_path.join(OUTPUT_DIR);
+    fs::create_dir_all(&dir).with_context(|| format!("failed to create {}", dir.display()))?;
+    for shell in &completions.shells {
+        let args = completions
+            .args
+            .iter()
+            .map(|arg| template::render(arg, &json!({ "shell": shell.as_str() })))
+            .collect::<Result<Vec<_>>>()?;
+        log::info!(
+            "Generating the {} completions with {} {}",
+            shell.as_str(),
+            build_info.binary,
+            args.join(" ")
+        );
+
+        let output = Command::new(&binary)
+            .args(&args)
+            .current_dir(base)
+            .output()
+            .await
+            .with_context(|| format!("failed to run {}", binary.display()))?;
+        if !output.status.success() {
+            bail!(
+                "{} {} failed ({}): {}",
+                build_info.binary,
+                args.join(" "),
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+        if output.stdout.is_empty() {
+            bail!(
+                "{} {} printed no completions",
+                build_info.binary,
+                args.join(" ")
+            );
+        }
+
+        let path = generated_path(*shell, &build_info.binary, output_path);
+        fs::write(&path, &output.stdout)
+            .with_context(|| format!("failed to write {}", path.display()))?;
+    }
+    Ok(())
+}
+
+fn generated_path(shell: CompletionShell, binary: &str, output_path: &Path) -> PathBuf {
+    output_path.join(OUTPUT_DIR).join(shell.file_name(binary))
+}
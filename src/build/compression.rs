@@ -1,15 +1,58 @@
+use super::os::Os;
+use crate::archive::{binary::BinaryArchiver, tar::TarArchiver, zip::ZipArchiver, Archiver};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
 pub enum Compression {
     #[default]
+    #[serde(rename = "tar.gz")]
     TarGz,
+    #[serde(rename = "tar.xz")]
+    TarXz,
+    #[serde(rename = "tar.zst")]
+    TarZst,
+    #[serde(rename = "zip")]
+    Zip,
+    /// Raw binary, without archive
+    #[serde(rename = "binary")]
+    Binary,
 }
 
 impl Compression {
     pub fn extension(&self) -> &str {
         match self {
             Compression::TarGz => "tar.gz",
+            Compression::TarXz => "tar.xz",
+            Compression::TarZst => "tar.zst",
+            Compression::Zip => "zip",
+            Compression::Binary => "",
+        }
+    }
+
+    /// File extension of the published file for an OS, empty if there is none
+    pub fn extension_for(&self, os: &Os) -> &str {
+        match (self, os) {
+            (Compression::Binary, Os::PcWindowsMsvc) => "exe",
+            _ => self.extension(),
+        }
+    }
+
+    /// Archive format used for an OS when none is configured
+    pub fn default_for(os: &Os) -> Self {
+        match os {
+            Os::PcWindowsMsvc => Compression::Zip,
+            _ => Compression::TarGz,
+        }
+    }
+
+    pub fn archiver(&self) -> Box<dyn Archiver> {
+        match self {
+            Compression::TarGz | Compression::TarXz | Compression::TarZst => {
+                Box::new(TarArchiver::new(self.to_owned()))
+            }
+            Compression::Zip => Box::new(ZipArchiver),
+            Compression::Binary => Box::new(BinaryArchiver),
         }
     }
 }
@@ -1,28 +1,78 @@
 pub mod arch;
 pub mod committer;
+pub mod completions;
 pub mod compression;
 pub mod os;
 
-use std::path::PathBuf;
+use std::{
+    path::{Path, PathBuf},
+    time::Instant,
+};
 
-use self::compression::Compression;
-use anyhow::Result;
+use self::{completions::Completions, compression::Compression};
+use crate::{
+    archive::{self, ArchiveEntry},
+    github::tag::{Tag, VersionComponents},
+    logger, progress, template,
+};
+use anyhow::{bail, Context, Result};
 use arch::Arch;
+use log::Level;
 use os::Os;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use serde_json::json;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+const SINGLE_TARGET_NAME_TEMPLATE: &str = "{{ binary }}_{{ tag }}{{ #if ext }}.{{ ext }}{{ /if }}";
+const MULTI_TARGET_NAME_TEMPLATE: &str =
+    "{{ binary }}_{{ tag }}_{{ arch }}_{{ os }}{{ #if ext }}.{{ ext }}{{ /if }}";
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Build {
     pub arch: Option<Vec<Arch>>,
     pub os: Option<Vec<Os>>,
     pub binary: String,
+    /// Archive format for every target, defaults to zip on Windows and tar.gz elsewhere
+    #[serde(alias = "format")]
+    pub compression: Option<Compression>,
+    /// Extra files to include in the archives, as globs relative to the project root
     #[serde(default)]
-    pub compression: Compression,
+    pub files: Vec<String>,
+    /// Handlebars template for the archive file names
+    pub name_template: Option<String>,
+    /// Archive format overrides for specific targets
+    #[serde(default)]
+    pub archives: Vec<ArchiveOverride>,
     #[serde(default)]
     pub tool: Tool,
+    /// Shell completions generated with the built binary and bundled into the archives
+    pub completions: Option<Completions>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ArchiveOverride {
+    pub os: Option<Os>,
+    pub arch: Option<Arch>,
+    #[serde(alias = "format")]
+    pub compression: Compression,
+}
+
+/// Values available to `name_template`
+#[derive(Debug, Serialize)]
+pub struct ArchiveName<'name> {
+    pub project: &'name str,
+    pub binary: &'name str,
+    pub tag: &'name str,
+    pub version: &'name str,
+    #[serde(flatten)]
+    pub components: VersionComponents,
+    pub os: String,
+    pub arch: String,
+    pub target: String,
+    pub ext: &'name str,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, JsonSchema)]
 pub enum Tool {
     #[serde(rename = "cargo")]
     #[default]
@@ -51,46 +101,135 @@ impl Build {
             false
         }
     }
+
+    /// Archive format to use for a target
+    pub fn compression_for(&self, arch: &Arch, os: &Os) -> Compression {
+        self.archives
+            .iter()
+            .find(|archive| {
+                archive.os.as_ref().is_none_or(|o| o == os)
+                    && archive.arch.as_ref().is_none_or(|a| a == arch)
+            })
+            .map(|archive| archive.compression.to_owned())
+            .or_else(|| self.compression.to_owned())
+            .unwrap_or_else(|| Compression::default_for(os))
+    }
+
+    /// File name of the archive for a target
+    pub fn archive_name(&self, tag: &Tag, arch: &Arch, os: &Os) -> Result<String> {
+        let template = match &self.name_template {
+            Some(template) => template.as_str(),
+            None if self.is_multi_target() => MULTI_TARGET_NAME_TEMPLATE,
+            None => SINGLE_TARGET_NAME_TEMPLATE,
+        };
+
+        let compression = self.compression_for(arch, os);
+        let data = ArchiveName {
+            project: &self.binary,
+            binary: &self.binary,
+            tag: tag.value(),
+            version: tag.version(),
+            components: tag.components(),
+            os: os.to_string(),
+            arch: arch.to_string(),
+            target: os_arch_to_toolchain(os, arch),
+            ext: compression.extension_for(os),
+        };
+
+        template::render(template, &data)
+            .with_context(|| format!("failed to render name template: {}", template))
+    }
+
+    /// Every (arch, os) pair this build produces
+    pub fn targets(&self) -> Vec<(Arch, Os)> {
+        if self.is_multi_target() {
+            let archs = self.arch.to_owned().unwrap_or_default();
+            let oss = self.os.to_owned().unwrap_or_default();
+            archs
+                .iter()
+                .flat_map(|arch| oss.iter().map(|os| (arch.to_owned(), os.to_owned())))
+                .collect()
+        } else {
+            vec![(Arch::current(), Os::current())]
+        }
+    }
+
+    /// Toolchains of the targets that can't be built here, `rustup` listing the installed ones.
+    /// `cross` builds in containers, so it only needs to be installed
+    pub async fn missing_toolchains(&self) -> Result<Vec<String>> {
+        let toolchains = self
+            .targets()
+            .into_iter()
+            .map(|(arch, os)| os_arch_to_toolchain(&os, &arch));
+        match self.tool {
+            Tool::Cargo => {
+                let output = tokio::process::Command::new("rustup")
+                    .args(["target", "list", "--installed"])
+                    .output()
+                    .await
+                    .context("failed to run rustup")?;
+                let installed = String::from_utf8_lossy(&output.stdout);
+                Ok(toolchains
+                    .filter(|toolchain| !installed.lines().any(|line| line.trim() == toolchain))
+                    .collect())
+            }
+            Tool::Cross => {
+                tokio::process::Command::new("cross")
+                    .arg("--version")
+                    .output()
+                    .await
+                    .context("cross is not installed")?;
+                Ok(vec![])
+            }
+        }
+    }
+
+    /// Files bundled with the binary in the archives: the `files` patterns and the generated
+    /// completions
+    pub fn archive_files(&self, base: &Path, output_path: &Path) -> Result<Vec<ArchiveEntry>> {
+        let mut entries = archive::resolve_files(base, &self.files)?;
+        if let Some(completions) = &self.completions {
+            entries.extend(completions.entries(&self.binary, output_path)?);
+        }
+        Ok(entries)
+    }
+
+    /// Location of the compiled binary for a target
+    pub fn binary_path(&self, base: &Path, arch: &Arch, os: &Os) -> PathBuf {
+        let binary = os.binary_name(&self.binary);
+        if self.is_multi_target() {
+            base.join(format!("target/{}-{}/release/{}", arch, os, binary))
+        } else {
+            base.join(format!("target/release/{}", binary))
+        }
+    }
 }
 
-pub async fn build(build_info: &Build, path: PathBuf, dry_run: bool) -> Result<()> {
+pub async fn build(build_info: &Build, path: PathBuf) -> Result<()> {
     if build_info.is_multi_target() {
         log::info!("Building for multiple targets");
         for arch in build_info.arch.as_ref().unwrap_or(&vec![]) {
             for os in build_info.os.as_ref().unwrap_or(&vec![]) {
                 log::debug!("Building for arch: {:?} and os: {:?}", arch, os);
-                build_target(&build_info, &path, &arch, &os, dry_run).await?;
+                build_target(build_info, &path, arch, os).await?;
             }
         }
     } else {
         log::info!("Building for single target");
-        build_target(
-            &build_info,
-            &path,
-            &Arch::current(),
-            &Os::current(),
-            dry_run,
-        )
-        .await?;
+        build_target(build_info, &path, &Arch::current(), &Os::current()).await?;
     }
 
     Ok(())
 }
 
-async fn build_target(
-    build_info: &Build,
-    path: &PathBuf,
-    arch: &Arch,
-    os: &Os,
-    dry_run: bool,
-) -> Result<()> {
+async fn build_target(build_info: &Build, path: &PathBuf, arch: &Arch, os: &Os) -> Result<()> {
     let toolchain = os_arch_to_toolchain(os, arch);
     let mut cmd = match &build_info.tool {
         Tool::Cargo => {
             let mut cmd = tokio::process::Command::new("cargo");
             cmd.arg("build");
             cmd.arg("--release");
-            cmd.arg("--target").arg(toolchain);
+            cmd.arg("--target").arg(&toolchain);
             cmd.current_dir(path);
             cmd
         }
@@ -98,17 +237,25 @@ async fn build_target(
             let mut cmd = tokio::process::Command::new("cross");
             cmd.arg("build");
             cmd.arg("--release");
-            cmd.arg("--target").arg(toolchain);
+            cmd.arg("--target").arg(&toolchain);
             cmd.current_dir(path);
             cmd
         }
     };
 
-    if dry_run {
-        log::info!("Would run: {:?}", cmd);
-    } else {
-        cmd.status().await?;
+    let task = progress::task(format!("Building {}", toolchain));
+    let start = Instant::now();
+    let status = cmd.status().await?;
+    if !status.success() {
+        bail!("failed to build {} ({})", toolchain, status);
     }
+    let duration = start.elapsed();
+    task.finish();
+    logger::event(
+        Level::Info,
+        json!({ "target": toolchain, "duration_ms": logger::millis(duration) }),
+        &format!("Built {} in {:.1?}", toolchain, duration),
+    );
 
     Ok(())
 }
@@ -124,6 +271,7 @@ fn os_arch_to_toolchain(os: &Os, arch: &Arch) -> String {
         match os {
             Os::UnknownLinuxGnu => "unknown-linux-gnu",
             Os::AppleDarwin => "apple-darwin",
+            Os::PcWindowsMsvc => "pc-windows-msvc",
         },
     )
 }
@@ -1,17 +1,29 @@
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use std::fmt::Display;
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, JsonSchema)]
 pub enum Os {
     #[serde(rename(deserialize = "darwin"))]
     AppleDarwin,
     #[serde(rename(deserialize = "linux"))]
     UnknownLinuxGnu,
+    #[serde(rename(deserialize = "windows"))]
+    PcWindowsMsvc,
 }
 
 impl Os {
     pub fn current() -> Self {
         std::env::consts::OS.to_string().into()
     }
+
+    /// Name of the compiled binary on this OS
+    pub fn binary_name(&self, binary: &str) -> String {
+        match self {
+            Os::PcWindowsMsvc => format!("{}.exe", binary),
+            _ => binary.to_string(),
+        }
+    }
 }
 
 impl From<String> for Os {
@@ -19,16 +31,18 @@ impl From<String> for Os {
         match value.to_lowercase().as_str() {
             "apple-darwin" | "darwin" | "macos" => Os::AppleDarwin,
             "unknown-linux-gnu" | "linux" => Os::UnknownLinuxGnu,
+            "pc-windows-msvc" | "windows" => Os::PcWindowsMsvc,
             _ => panic!("Unknown arch"),
         }
     }
 }
 
-impl ToString for Os {
-    fn to_string(&self) -> String {
+impl Display for Os {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            Os::AppleDarwin => "apple-darwin".to_string(),
-            Os::UnknownLinuxGnu => "unknown-linux-gnu".to_string(),
+            Os::AppleDarwin => write!(f, "apple-darwin"),
+            Os::UnknownLinuxGnu => write!(f, "unknown-linux-gnu"),
+            Os::PcWindowsMsvc => write!(f, "pc-windows-msvc"),
         }
     }
 }
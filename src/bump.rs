@@ -0,0 +1,220 @@
+use crate::{
+    changelog::{self, Changelog},
+    cli::{BumpLevel, Opts},
+    config::{ChangelogConfig, ChangelogFileConfig, ReleaserConfig},
+    git,
+    github::tag::Tag,
+};
+use anyhow::{bail, Context, Result};
+use semver::{BuildMetadata, Prerelease, Version};
+use std::fs;
+
+const MANIFEST_FILE: &str = "Cargo.toml";
+const LOCK_FILE: &str = "Cargo.lock";
+const COMMIT_MESSAGE: &str = "chore: release {{version}}";
+const RC_PRERELEASE: &str = "rc";
+
+/// Bump the package version, commit it with the lockfile and the changelog, then tag it
+pub async fn bump(
+    config: &ReleaserConfig,
+    opts: &Opts,
+    level: BumpLevel,
+    update_changelog: bool,
+    push: bool,
+) -> Result<()> {
+    let manifest = fs::read_to_string(opts.path.join(MANIFEST_FILE))
+        .with_context(|| format!("failed to read {}", MANIFEST_FILE))?;
+    let (name, current) = package(&manifest)?;
+    let next = next_version(&current, level)?;
+    log::info!("Bumping {} from {} to {}", name, current, next);
+
+    let changed = git::uncommitted_files(&opts.path)?;
+    if !changed.is_empty() && !opts.allow_dirty {
+        bail!(
+            "refusing to bump with uncommitted changes in {}; use --allow-dirty to bump anyway",
+            changed.join(", ")
+        );
+    }
+
+    if opts.dry_run {
+        log::info!("Would commit, tag and push {}", next);
+        return Ok(());
+    }
+
+    // rendered first so that nothing is written when one fails
+    let bumped = set_manifest_version(&manifest, &current, &next)?;
+    let changelog = update_changelog
+        .then(|| render_changelog(config, opts, &next))
+        .transpose()?;
+
+    let mut files = vec![MANIFEST_FILE];
+    fs::write(opts.path.join(MANIFEST_FILE), bumped)?;
+
+    let lock_path = opts.path.join(LOCK_FILE);
+    if let Ok(lock) = fs::read_to_string(&lock_path) {
+        match set_lock_version(&lock, &name, &current, &next) {
+            Some(lock) => {
+                fs::write(&lock_path, lock)?;
+                files.push(LOCK_FILE);
+            }
+            None => log::warn!("{} is not in {}, not updating it", name, LOCK_FILE),
+        }
+    }
+
+    if let Some((changelog, changelog_config, file)) = &changelog {
+        if changelog::file::write(changelog, changelog_config, file, &opts.path)? {
+            files.push(&file.path);
+        }
+    }
+
+    git::commit(
+        &opts.path,
+        &files,
+        &COMMIT_MESSAGE.replace("{{version}}", &next.to_string()),
+    )?;
+    if push {
+        git::push_branch(&opts.path, &config.tag.remote)?;
+    }
+    git::create_tag(
+        &opts.path,
+        Some(&next.to_string()),
+        &config.tag,
+        push,
+        false,
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Changelog of the new version with the config of its file
+fn render_changelog<'a>(
+    config: &'a ReleaserConfig,
+    opts: &Opts,
+    next: &Version,
+) -> Result<(Changelog, &'a ChangelogConfig, &'a ChangelogFileConfig)> {
+    let changelog_config = config
+        .release
+        .changelog
+        .as_ref()
+        .context("--changelog needs release.changelog to be configured")?;
+    let file = changelog_config
+        .file
+        .as_ref()
+        .context("--changelog needs release.changelog.file to be configured")?;
+
+    // the new tag doesn't exist yet, its commits are the ones up to HEAD
+    let tag = Tag::with_prefix(format!("{}{}", config.tag.prefix, next), &config.tag.prefix);
+    let changelog = changelog::generate(changelog_config, &tag, &opts.path)?;
+
+    Ok((changelog, changelog_config, file))
+}
+
+/// Name and version of the `[package]` of a manifest
+fn package(manifest: &str) -> Result<(String, Version)> {
+    let manifest: toml::Value = manifest
+        .parse()
+        .with_context(|| format!("failed to parse {}", MANIFEST_FILE))?;
+    let package = manifest
+        .get("package")
+        .with_context(|| format!("no [package] in {}", MANIFEST_FILE))?;
+
+    let name = package
+        .get("name")
+        .and_then(toml::Value::as_str)
+        .with_context(|| format!("no package name in {}", MANIFEST_FILE))?;
+    let version = package
+        .get("version")
+        .and_then(toml::Value::as_str)
+        .with_context(|| {
+            format!(
+                "no package version in {}, inherited versions can't be bumped",
+                MANIFEST_FILE
+            )
+        })?;
+
+    Ok((name.to_owned(), Version::parse(version)?))
+}
+
+/// Bump like npm does: a prerelease is released by the level it was cut for
+fn next_version(current: &Version, level: BumpLevel) -> Result<Version> {
+    let mut next = current.clone();
+    let is_pre = !current.pre.is_empty();
+    next.pre = Prerelease::EMPTY;
+    next.build = BuildMetadata::EMPTY;
+
+    match level {
+        BumpLevel::Major if is_pre && current.minor == 0 && current.patch == 0 => {}
+        BumpLevel::Major => {
+            next.major += 1;
+            next.minor = 0;
+            next.patch = 0;
+        }
+        BumpLevel::Minor if is_pre && current.patch == 0 => {}
+        BumpLevel::Minor => {
+            next.minor += 1;
+            next.patch = 0;
+        }
+        BumpLevel::Patch if is_pre => {}
+        BumpLevel::Patch => next.patch += 1,
+        BumpLevel::Rc => {
+            let candidate = current
+                .pre
+                .as_str()
+                .strip_prefix(RC_PRERELEASE)
+                .map(|rest| rest.trim_start_matches('.'))
+                .and_then(|number| number.parse::<u64>().ok());
+            match candidate {
+                Some(number) => {
+                    next.pre = Prerelease::new(&format!("{}.{}", RC_PRERELEASE, number + 1))?
+                }
+                None => {
+                    if !is_pre {
+                        next.patch += 1;
+                    }
+                    next.pre = Prerelease::new(&format!("{}.1", RC_PRERELEASE))?;
+                }
+            }
+        }
+    }
+
+    Ok(next)
+}
+
+/// Replace the version of the `[package]` table, leaving the rest of the manifest untouched
+fn set_manifest_version(manifest: &str, current: &Version, next: &Version) -> Result<String> {
+    let quoted = format!("\"{}\"", current);
+    let mut in_package = false;
+    let mut replaced = false;
+
+    let mut result = String::with_capacity(manifest.len());
+    for line in manifest.split_inclusive('\n') {
+        let trimmed = line.trim();
+        if trimmed.starts_with('[') {
+            in_package = trimmed == "[package]";
+        }
+
+        if in_package && !replaced && trimmed.starts_with("version") && trimmed.contains(&quoted) {
+            result.push_str(&line.replacen(&quoted, &format!("\"{}\"", next), 1));
+            replaced = true;
+        } else {
+            result.push_str(line);
+        }
+    }
+
+    if !replaced {
+        bail!(
+            "version {} not found in the [package] of {}",
+            current,
+            MANIFEST_FILE
+        );
+    }
+    Ok(result)
+}
+
+/// Lockfile with the version of the `name` package replaced, if it is there
+fn set_lock_version(lock: &str, name: &str, current: &Version, next: &Version) -> Option<String> {
+    let entry = |version: &Version| format!("name = \"{}\"\nversion = \"{}\"", name, version);
+    lock.contains(&entry(current))
+        .then(|| lock.replacen(&entry(current), &entry(next), 1))
+}
@@ -0,0 +1,55 @@
+use super::Changelog;
+use crate::{config::ReleaseConfig, git::Commit, github::github_client};
+use anyhow::Result;
+use serde::Serialize;
+use std::collections::HashSet;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Contributor {
+    pub name: String,
+    #[serde(skip)]
+    pub email: String,
+    /// GitHub login, when it could be resolved
+    pub login: Option<String>,
+    /// Oldest commit of the contributor in this release
+    pub sha: String,
+    pub first_time: bool,
+}
+
+/// Unique commit authors in order of their first contribution
+pub fn collect(commits: &[Commit], known: &HashSet<String>) -> Vec<Contributor> {
+    let mut contributors: Vec<Contributor> = vec![];
+    for commit in commits.iter().rev() {
+        if contributors.iter().any(|c| c.email == commit.email) {
+            continue;
+        }
+        contributors.push(Contributor {
+            name: commit.author.to_owned(),
+            email: commit.email.to_owned(),
+            login: None,
+            sha: commit.sha.to_owned(),
+            first_time: !known.contains(&commit.email),
+        });
+    }
+    contributors
+}
+
+/// Look up the GitHub login of every contributor through the commits API
+pub async fn resolve_logins(changelog: &mut Changelog, release_info: &ReleaseConfig) -> Result<()> {
+    let repo = github_client::instance().repo(&release_info.owner, &release_info.repo);
+
+    let mut contributors = changelog.contributors.to_owned();
+    for contributor in contributors.iter_mut() {
+        match repo.commit_author(&contributor.sha).await {
+            Ok(login) => contributor.login = login,
+            Err(err) => log::warn!(
+                "failed to resolve the GitHub login of {}: {:#}",
+                contributor.name,
+                err
+            ),
+        }
+    }
+    changelog.set_contributors(contributors);
+
+    Ok(())
+}
@@ -0,0 +1,42 @@
+/// A commit message following https://www.conventionalcommits.org
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConventionalCommit {
+    pub kind: String,
+    pub scope: Option<String>,
+    pub breaking: bool,
+    pub description: String,
+}
+
+impl ConventionalCommit {
+    pub fn parse(message: &str) -> Option<Self> {
+        let mut lines = message.lines();
+        let summary = lines.next()?;
+        let (header, description) = summary.split_once(": ")?;
+
+        let (header, bang) = match header.strip_suffix('!') {
+            Some(header) => (header, true),
+            None => (header, false),
+        };
+
+        let (kind, scope) = match header.split_once('(') {
+            Some((kind, scope)) => (kind, Some(scope.strip_suffix(')')?.to_string())),
+            None => (header, None),
+        };
+
+        if kind.is_empty() || !kind.chars().all(|c| c.is_ascii_alphabetic()) {
+            return None;
+        }
+
+        let breaking = bang
+            || lines.any(|line| {
+                line.starts_with("BREAKING CHANGE:") || line.starts_with("BREAKING-CHANGE:")
+            });
+
+        Some(ConventionalCommit {
+            kind: kind.to_lowercase(),
+            scope,
+            breaking,
+            description: description.trim().to_string(),
+        })
+    }
+}
@@ -0,0 +1,152 @@
+use super::Changelog;
+use crate::{
+    build::committer::Committer,
+    config::{ChangelogConfig, ChangelogFileConfig, ReleaseConfig},
+    dry_run::{self, FileChange},
+    github::{self, builder::BuilderExecutor, github_client},
+};
+use anyhow::{Context, Result};
+use std::{fs, path::Path};
+
+const HEADER: &str = "# Changelog
+
+All notable changes to this project will be documented in this file.
+
+The format is based on [Keep a Changelog](https://keepachangelog.com/en/1.1.0/),
+and this project adheres to [Semantic Versioning](https://semver.org/spec/v2.0.0.html).
+";
+const UNRELEASED: &str = "## [Unreleased]";
+
+/// Prepend the release to the changelog file and commit it back to the repository
+pub async fn update(
+    changelog: &Changelog,
+    config: &ChangelogConfig,
+    file: &ChangelogFileConfig,
+    release_config: &ReleaseConfig,
+    base: &Path,
+    dry_run: bool,
+    output_path: &Path,
+) -> Result<()> {
+    let (existing, content) = render(changelog, config, file, base)?;
+
+    let file_name = Path::new(&file.path)
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| file.path.to_owned());
+    fs::write(output_path.join(file_name), &content)?;
+
+    if content == existing {
+        log::info!("{} already contains {}", file.path, changelog.version);
+        return Ok(());
+    }
+
+    let message = file
+        .commit_message
+        .replace("{{version}}", &changelog.version);
+    let branch = file
+        .branch
+        .as_deref()
+        .unwrap_or(&release_config.target_branch);
+
+    if dry_run {
+        return dry_run::record_file_change(
+            output_path,
+            &FileChange {
+                owner: &release_config.owner,
+                repo: &release_config.repo,
+                branch,
+                path: &file.path,
+                message: &message,
+                content: &content,
+                pull_request: file.pull_request.as_ref(),
+            },
+        );
+    }
+
+    let committer = file.commit_author.to_owned().map(Committer::from);
+
+    if let Some(pull_request) = &file.pull_request {
+        github::push_with_pull_request(
+            &release_config.owner,
+            &release_config.repo,
+            &file.path,
+            &content,
+            &message,
+            committer.as_ref(),
+            pull_request.to_owned(),
+        )
+        .await?;
+    } else {
+        let mut commit = github_client::instance()
+            .repo(&release_config.owner, &release_config.repo)
+            .branch(branch)
+            .commit_files()
+            .file(&file.path, content)
+            .message(message);
+        if let Some(committer) = &committer {
+            commit = commit.committer(committer);
+        }
+        commit
+            .execute()
+            .await
+            .with_context(|| format!("error uploading {}", file.path))?;
+    }
+    Ok(())
+}
+
+/// Add the release to the changelog file of the working tree, returns whether it changed
+pub fn write(
+    changelog: &Changelog,
+    config: &ChangelogConfig,
+    file: &ChangelogFileConfig,
+    base: &Path,
+) -> Result<bool> {
+    let (existing, content) = render(changelog, config, file, base)?;
+    if content == existing {
+        return Ok(false);
+    }
+
+    fs::write(base.join(&file.path), content)
+        .with_context(|| format!("failed to write {}", file.path))?;
+    Ok(true)
+}
+
+/// Current content of the changelog file, and the one with the release added
+fn render(
+    changelog: &Changelog,
+    config: &ChangelogConfig,
+    file: &ChangelogFileConfig,
+    base: &Path,
+) -> Result<(String, String)> {
+    let existing = fs::read_to_string(base.join(&file.path)).unwrap_or_default();
+    let section = changelog.render_file(config)?;
+    let content = prepend(&existing, &section, &changelog.version);
+
+    Ok((existing, content))
+}
+
+/// Insert `section` above the latest released version, keeping the `Unreleased` section on top
+fn prepend(existing: &str, section: &str, version: &str) -> String {
+    if existing.trim().is_empty() {
+        return format!("{}\n{}\n\n{}\n", HEADER, UNRELEASED, section);
+    }
+
+    if existing.contains(&format!("## [{}]", version)) {
+        return existing.to_string();
+    }
+
+    let mut offset = 0;
+    for line in existing.split_inclusive('\n') {
+        if line.starts_with("## [") && !line.starts_with(UNRELEASED) {
+            return format!(
+                "{}{}\n\n{}",
+                &existing[..offset],
+                section,
+                &existing[offset..]
+            );
+        }
+        offset += line.len();
+    }
+
+    format!("{}\n\n{}\n", existing.trim_end(), section)
+}
@@ -0,0 +1,241 @@
+pub mod contributors;
+pub mod conventional;
+pub mod file;
+
+use self::{contributors::Contributor, conventional::ConventionalCommit};
+use crate::{
+    cli::Opts,
+    config::{ChangelogConfig, ReleaserConfig},
+    git::{self, Commit, Trailer},
+    github::tag::{Tag, VersionComponents},
+    template::handlebars,
+};
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+use time::OffsetDateTime;
+
+const CHANGELOG_TEMPLATE: &str = "changelog";
+const CHANGELOG_FILE_TEMPLATE: &str = "changelog_file";
+
+#[derive(Debug, Serialize)]
+pub struct Changelog {
+    pub version: String,
+    #[serde(flatten)]
+    pub components: VersionComponents,
+    pub previous: Option<String>,
+    pub date: String,
+    /// No release precedes this one
+    pub first_release: bool,
+    pub sections: Vec<Section>,
+    /// Entries grouped by keep-a-changelog change types
+    pub changes: Vec<Section>,
+    pub contributors: Vec<Contributor>,
+    /// Contributors whose first commit is part of this release
+    pub new_contributors: Vec<Contributor>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Section {
+    pub title: String,
+    pub entries: Vec<Entry>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Entry {
+    pub kind: String,
+    pub scope: Option<String>,
+    pub description: String,
+    pub breaking: bool,
+    pub sha: String,
+    pub short_sha: String,
+    pub author: String,
+    pub trailers: Vec<Trailer>,
+}
+
+impl Changelog {
+    pub fn new(
+        config: &ChangelogConfig,
+        tag: &Tag,
+        previous: Option<&Tag>,
+        commits: &[Commit],
+    ) -> Self {
+        let entries: Vec<Entry> = commits
+            .iter()
+            .filter_map(|commit| match ConventionalCommit::parse(&commit.message) {
+                Some(parsed) => Some(Entry::new(commit, parsed)),
+                None if config.include_other => Some(Entry::other(commit)),
+                None => None,
+            })
+            .collect();
+
+        let section = |title: &str, filter: &dyn Fn(&Entry) -> bool| Section {
+            title: title.to_string(),
+            entries: entries.iter().filter(|e| filter(e)).cloned().collect(),
+        };
+
+        let changes = vec![
+            section("Added", &|e| e.kind == "feat"),
+            section("Changed", &|e| !["feat", "fix"].contains(&e.kind.as_str())),
+            section("Fixed", &|e| e.kind == "fix"),
+        ]
+        .into_iter()
+        .filter(|section| !section.entries.is_empty())
+        .collect();
+
+        let sections = vec![
+            section("Breaking Changes", &|e| e.breaking),
+            section("Features", &|e| !e.breaking && e.kind == "feat"),
+            section("Bug Fixes", &|e| !e.breaking && e.kind == "fix"),
+            section("Performance", &|e| !e.breaking && e.kind == "perf"),
+            section("Other", &|e| {
+                !e.breaking && !["feat", "fix", "perf"].contains(&e.kind.as_str())
+            }),
+        ]
+        .into_iter()
+        .filter(|section| !section.entries.is_empty())
+        .collect();
+
+        Changelog {
+            version: tag.version().to_string(),
+            components: tag.components(),
+            previous: previous.map(|tag| tag.version().to_string()),
+            first_release: previous.is_none(),
+            date: OffsetDateTime::now_utc().date().to_string(),
+            sections,
+            changes,
+            contributors: vec![],
+            new_contributors: vec![],
+        }
+    }
+
+    pub fn set_contributors(&mut self, contributors: Vec<Contributor>) {
+        self.new_contributors = contributors
+            .iter()
+            .filter(|contributor| contributor.first_time)
+            .cloned()
+            .collect();
+        self.contributors = contributors;
+    }
+
+    pub fn render(&self, config: &ChangelogConfig) -> Result<String> {
+        let mut hb = handlebars()?;
+        if let Some(template) = &config.template {
+            let content = fs::read_to_string(template)
+                .with_context(|| format!("failed to read changelog template {}", template))?;
+            hb.register_template_string(CHANGELOG_TEMPLATE, content)?;
+        }
+
+        let rendered = hb.render(CHANGELOG_TEMPLATE, self)?;
+        Ok(rendered.trim().to_string())
+    }
+
+    /// Render the section added to the changelog file
+    pub fn render_file(&self, config: &ChangelogConfig) -> Result<String> {
+        let hb = handlebars()?;
+        let template = config.file.as_ref().and_then(|file| file.template.as_ref());
+
+        let rendered = match template {
+            Some(template) => {
+                let content = fs::read_to_string(template).with_context(|| {
+                    format!("failed to read changelog file template {}", template)
+                })?;
+                hb.render_template(&content, self)?
+            }
+            None => hb.render(CHANGELOG_FILE_TEMPLATE, self)?,
+        };
+        Ok(rendered.trim().to_string())
+    }
+}
+
+impl Entry {
+    fn new(commit: &Commit, parsed: ConventionalCommit) -> Self {
+        Entry {
+            kind: parsed.kind,
+            scope: parsed.scope,
+            description: parsed.description,
+            breaking: parsed.breaking,
+            sha: commit.sha.to_owned(),
+            short_sha: commit.short_sha().to_string(),
+            author: commit.author.to_owned(),
+            trailers: commit.trailers.to_owned(),
+        }
+    }
+
+    fn other(commit: &Commit) -> Self {
+        Entry {
+            kind: String::new(),
+            scope: None,
+            description: commit.summary().to_string(),
+            breaking: false,
+            sha: commit.sha.to_owned(),
+            short_sha: commit.short_sha().to_string(),
+            author: commit.author.to_owned(),
+            trailers: commit.trailers.to_owned(),
+        }
+    }
+}
+
+/// Collect the commits between the previous tag and `tag`
+pub fn generate(config: &ChangelogConfig, tag: &Tag, base: &PathBuf) -> Result<Changelog> {
+    let previous = git::get_previous_tag(base, tag)?;
+    generate_between(config, previous.as_ref(), tag, base)
+}
+
+/// Collect the commits of `tag` since `from`, or the whole history without it
+pub fn generate_between(
+    config: &ChangelogConfig,
+    from: Option<&Tag>,
+    tag: &Tag,
+    base: &PathBuf,
+) -> Result<Changelog> {
+    let commits = git::commits_between(base, from, tag, &config.paths)?;
+    if from.is_none() {
+        log::info!("No previous release, the changelog covers the whole history");
+    }
+
+    let mut changelog = Changelog::new(config, tag, from, &commits);
+    if config.contributors {
+        // everyone is new in a first release, nobody is singled out
+        let known = match from {
+            Some(from) => git::authors_until(base, from)?,
+            None => commits.iter().map(|c| c.email.to_owned()).collect(),
+        };
+        changelog.set_contributors(contributors::collect(&commits, &known));
+    }
+
+    Ok(changelog)
+}
+
+/// Print the changelog of `to`, or else of HEAD, since `from`, or else the previous tag, without
+/// releasing anything, or write it to `file`
+pub fn print(
+    config: &ReleaserConfig,
+    opts: &Opts,
+    from: Option<&str>,
+    to: Option<&str>,
+    file: Option<&Path>,
+) -> Result<()> {
+    let prefix = &config.tag.prefix;
+    let to = match to.or(opts.tag.as_deref()) {
+        Some(to) => Tag::with_prefix(to, prefix),
+        None => git::get_snapshot_tag(&opts.path, prefix)?,
+    };
+    let from = match from {
+        Some(from) => Some(Tag::with_prefix(from, prefix)),
+        None => git::get_previous_tag(&opts.path, &to)?,
+    };
+    let changelog_config = config.release.changelog.to_owned().unwrap_or_default();
+
+    let rendered = generate_between(&changelog_config, from.as_ref(), &to, &opts.path)?
+        .render(&changelog_config)?;
+    match file {
+        Some(file) => fs::write(file, rendered)
+            .with_context(|| format!("failed to write {}", file.display()))?,
+        None => println!("{}", rendered),
+    }
+    Ok(())
+}
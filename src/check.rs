@@ -0,0 +1,418 @@
+use crate::{
+    brew::formula_path,
+    config::{BodySource, CloudsmithFormat, ReleaserConfig, WindowsSignTool},
+    github::{auth, github_client},
+    upload,
+};
+use anyhow::{bail, Result};
+use itertools::Itertools;
+use lettre::message::Mailbox;
+use regex::Regex;
+use std::{collections::HashSet, fs, iter};
+
+/// Validate the config and the GitHub access without releasing anything
+pub async fn check(config: &ReleaserConfig, offline: bool) -> Result<()> {
+    let mut problems = validate(config);
+
+    if offline {
+        log::info!("Skipping the GitHub checks");
+    } else {
+        problems.extend(check_access(config).await);
+    }
+
+    if problems.is_empty() {
+        log::info!("Configuration is valid");
+        return Ok(());
+    }
+
+    for problem in &problems {
+        log::error!("{}", problem);
+    }
+    bail!("found {} problem(s) in the configuration", problems.len())
+}
+
+/// Problems of the config that don't need GitHub to be found
+pub fn validate(config: &ReleaserConfig) -> Vec<String> {
+    let mut problems = vec![];
+
+    if config.build.binary.trim().is_empty() {
+        problems.push("build.binary must not be empty".to_string());
+    }
+    if config.build.arch.is_some() != config.build.os.is_some() {
+        problems
+            .push("build.arch and build.os must be set together for multi-target builds".into());
+    }
+    if let Some(completions) = &config.build.completions {
+        if completions.args.is_empty() {
+            problems.push("build.completions.args must not be empty".to_string());
+        }
+        if completions.shells.is_empty() {
+            problems.push("build.completions.shells must not be empty".to_string());
+        }
+    }
+
+    check_repository(
+        "release",
+        &config.release.owner,
+        &config.release.repo,
+        &mut problems,
+    );
+    if config.release.target_branch.trim().is_empty() {
+        problems.push("release.target_branch must not be empty".to_string());
+    }
+    if let Err(err) = Regex::new(&config.tag.pattern) {
+        problems.push(format!(
+            "tag.pattern is not a valid regular expression: {}",
+            err
+        ));
+    }
+
+    let mut templates = vec![
+        ("build.name_template", config.build.name_template.to_owned()),
+        ("release.name", config.release.name.to_owned()),
+        ("release.body", config.release.body.to_owned()),
+    ];
+    if let Some(changelog) = &config.release.changelog {
+        templates.push((
+            "release.changelog.template",
+            read_template(&changelog.template, &mut problems),
+        ));
+        if let Some(file) = &changelog.file {
+            templates.push((
+                "release.changelog.file.template",
+                read_template(&file.template, &mut problems),
+            ));
+            templates.push((
+                "release.changelog.file.commit_message",
+                Some(file.commit_message.to_owned()),
+            ));
+        }
+        if changelog.contributors && config.release.body_source == BodySource::Github {
+            problems.push(
+                "release.changelog.contributors has no effect with release.body_source: github"
+                    .into(),
+            );
+        }
+    }
+    if let Some(slack) = config.announce.as_ref().and_then(|a| a.slack.as_ref()) {
+        templates.push(("announce.slack.message", slack.message.to_owned()));
+    }
+    if let Some(discord) = config.announce.as_ref().and_then(|a| a.discord.as_ref()) {
+        templates.push(("announce.discord.message", discord.message.to_owned()));
+    }
+    if let Some(mastodon) = config.announce.as_ref().and_then(|a| a.mastodon.as_ref()) {
+        templates.push(("announce.mastodon.message", mastodon.message.to_owned()));
+    }
+    if let Some(bluesky) = config.announce.as_ref().and_then(|a| a.bluesky.as_ref()) {
+        templates.push(("announce.bluesky.message", bluesky.message.to_owned()));
+    }
+    if let Some(telegram) = config.announce.as_ref().and_then(|a| a.telegram.as_ref()) {
+        templates.push(("announce.telegram.message", telegram.message.to_owned()));
+    }
+    if let Some(teams) = config.announce.as_ref().and_then(|a| a.teams.as_ref()) {
+        templates.push(("announce.teams.message", teams.message.to_owned()));
+    }
+    if let Some(matrix) = config.announce.as_ref().and_then(|a| a.matrix.as_ref()) {
+        templates.push(("announce.matrix.message", matrix.message.to_owned()));
+    }
+    if let Some(email) = config.announce.as_ref().and_then(|a| a.email.as_ref()) {
+        templates.push(("announce.email.subject", email.subject.to_owned()));
+        templates.push(("announce.email.message", email.message.to_owned()));
+    }
+    for (field, template) in templates {
+        if let Some(Err(err)) = template.map(|t| handlebars::Template::compile(&t)) {
+            problems.push(format!("{} is not a valid template: {}", field, err));
+        }
+    }
+    for (i, brew) in config.brew.iter().enumerate() {
+        let template = read_template(&brew.template_path, &mut problems);
+        if let Some(Err(err)) = template.map(|t| handlebars::Template::compile(&t)) {
+            problems.push(format!(
+                "{}.template_path is not a valid template: {}",
+                brew_field(config, i),
+                err
+            ));
+        }
+    }
+    for (i, webhook) in config.announce.iter().flat_map(|a| &a.webhooks).enumerate() {
+        if let Some(Err(err)) = webhook
+            .payload
+            .as_deref()
+            .map(handlebars::Template::compile)
+        {
+            problems.push(format!(
+                "announce.webhooks[{}].payload is not a valid template: {}",
+                i, err
+            ));
+        }
+        if webhook.events.is_empty() {
+            problems.push(format!("announce.webhooks[{}].events is empty", i));
+        }
+    }
+    for (i, s3) in config.storage.iter().flat_map(|u| &u.s3).enumerate() {
+        if s3.bucket.trim().is_empty() {
+            problems.push(format!("storage.s3[{}].bucket must not be empty", i));
+        }
+        if let Err(err) = handlebars::Template::compile(&s3.prefix) {
+            problems.push(format!(
+                "storage.s3[{}].prefix is not a valid template: {}",
+                i, err
+            ));
+        }
+    }
+    for (i, target) in config.storage.iter().flat_map(|u| &u.targets).enumerate() {
+        if let Err(err) = handlebars::Template::compile(&target.url) {
+            problems.push(format!(
+                "storage.targets[{}].url is not a valid template: {}",
+                i, err
+            ));
+        } else if let Err(err) = upload::check_url(&target.url) {
+            problems.push(format!("storage.targets[{}].url: {}", i, err));
+        }
+    }
+    for (i, cloudsmith) in config
+        .storage
+        .iter()
+        .flat_map(|u| &u.cloudsmith)
+        .enumerate()
+    {
+        if cloudsmith.format != CloudsmithFormat::Raw {
+            if cloudsmith.distribution.is_none() {
+                problems.push(format!(
+                    "storage.cloudsmith[{}].distribution is required for {} packages",
+                    i,
+                    cloudsmith.format.as_str()
+                ));
+            }
+            if cloudsmith.files.is_empty() {
+                problems.push(format!(
+                    "storage.cloudsmith[{}].files is required for {} packages",
+                    i,
+                    cloudsmith.format.as_str()
+                ));
+            }
+        }
+    }
+    for (i, gemfury) in config.storage.iter().flat_map(|u| &u.gemfury).enumerate() {
+        if gemfury.files.is_empty() {
+            problems.push(format!("storage.gemfury[{}].files is empty", i));
+        }
+    }
+    let repositories = config.storage.iter().flat_map(|u| {
+        let apt = u.apt.iter().map(|apt| ("apt", &apt.files, &apt.repository));
+        let yum = u.yum.iter().map(|yum| ("yum", &yum.files, &yum.repository));
+        apt.chain(yum)
+    });
+    for (kind, files, repository) in repositories {
+        if files.is_empty() {
+            problems.push(format!("storage.{}.files is empty", kind));
+        }
+        match (&repository.branch, &repository.target) {
+            (Some(_), Some(_)) => {
+                problems.push(format!("storage.{} has both a branch and a target", kind))
+            }
+            (None, None) => problems.push(format!("storage.{} needs a branch or a target", kind)),
+            (None, Some(target)) => {
+                if let Err(err) = handlebars::Template::compile(&target.url) {
+                    problems.push(format!(
+                        "storage.{}.target.url is not a valid template: {}",
+                        kind, err
+                    ));
+                } else if let Err(err) = upload::check_url(&target.url) {
+                    problems.push(format!("storage.{}.target.url: {}", kind, err));
+                }
+            }
+            (Some(_), None) => {}
+        }
+    }
+    if let Some(email) = config.announce.as_ref().and_then(|a| a.email.as_ref()) {
+        if email.to.is_empty() {
+            problems.push("announce.email.to is empty".to_string());
+        }
+        for address in iter::once(&email.from).chain(&email.to) {
+            if address.parse::<Mailbox>().is_err() {
+                problems.push(format!(
+                    "announce.email: {} is not a valid email address",
+                    address
+                ));
+            }
+        }
+    }
+
+    let mut formulas = HashSet::new();
+    for (i, brew) in config.brew.iter().enumerate() {
+        let field = brew_field(config, i);
+        check_repository(
+            &format!("{}.repository", field),
+            &brew.repository.owner,
+            &brew.repository.name,
+            &mut problems,
+        );
+        if brew.name.trim().is_empty() {
+            problems.push(format!("{}.name must not be empty", field));
+        }
+        if let Some(path) = &brew.formula_path {
+            if !path.ends_with(".rb") {
+                problems.push(format!(
+                    "{}.formula_path: {} is not a .rb file",
+                    field, path
+                ));
+            }
+        }
+        if brew
+            .service
+            .as_ref()
+            .is_some_and(|service| service.run.is_empty())
+        {
+            problems.push(format!("{}.service.run must name the binary to run", field));
+        }
+        if brew
+            .bottles
+            .as_ref()
+            .is_some_and(|bottles| bottles.files.is_empty())
+        {
+            problems.push(format!("{}.bottles.files must match the bottles", field));
+        }
+        let path = formula_path(
+            &brew.name,
+            brew.path.as_deref(),
+            brew.formula_path.as_deref(),
+        );
+        let tap = format!("{}/{}", brew.repository.owner, brew.repository.name);
+        if !formulas.insert((tap.to_owned(), path.to_owned())) {
+            problems.push(format!(
+                "{}: another formula is already written to {} in {}",
+                field, path, tap
+            ));
+        }
+    }
+
+    for (i, plugin) in config.plugins.iter().enumerate() {
+        if plugin.command.is_empty() {
+            problems.push(format!("plugins[{}].command must name a program", i));
+        }
+        if plugin.hooks.is_empty() {
+            problems.push(format!("plugins[{}].hooks is empty", i));
+        }
+    }
+
+    if let Some(sign) = config.windows.as_ref().and_then(|w| w.sign.as_ref()) {
+        match sign.tool {
+            WindowsSignTool::AzureSignTool if sign.key_vault.is_none() => {
+                problems.push("windows.sign.key_vault is required with azuresigntool".into())
+            }
+            WindowsSignTool::Signtool | WindowsSignTool::Osslsigncode
+                if sign.certificate.is_none() =>
+            {
+                problems.push(
+                    "windows.sign.certificate is required with signtool and osslsigncode".into(),
+                )
+            }
+            _ => {}
+        }
+    }
+
+    if let Some(notarize) = config.macos.as_ref().and_then(|m| m.notarize.as_ref()) {
+        match (&notarize.key, &notarize.apple_id) {
+            (Some(_), Some(_)) => problems.push(
+                "macos.notarize.key and macos.notarize.apple_id are mutually exclusive".into(),
+            ),
+            (Some(_), None) if notarize.key_id.is_none() || notarize.issuer.is_none() => problems
+                .push("macos.notarize.key_id and issuer are required with an API key".into()),
+            (None, Some(_)) if notarize.team_id.is_none() || notarize.password_env.is_none() => {
+                problems.push(
+                    "macos.notarize.team_id and password_env are required with an Apple ID".into(),
+                )
+            }
+            (None, None) => {
+                problems.push("macos.notarize needs either an API key or an Apple ID".to_string())
+            }
+            _ => {}
+        }
+    }
+
+    problems
+}
+
+/// Field of the `i`-th formula, `brew` when there is a single one
+fn brew_field(config: &ReleaserConfig, i: usize) -> String {
+    if config.brew.len() > 1 {
+        format!("brew[{}]", i)
+    } else {
+        "brew".to_owned()
+    }
+}
+
+fn check_repository(field: &str, owner: &str, repo: &str, problems: &mut Vec<String>) {
+    let valid = |value: &str| {
+        !value.is_empty()
+            && value
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || "-_.".contains(c))
+    };
+    if !valid(owner) || !valid(repo) {
+        problems.push(format!(
+            "{} must name a GitHub repository as separate owner and name, got `{}/{}`",
+            field, owner, repo
+        ));
+    }
+}
+
+fn read_template(path: &Option<String>, problems: &mut Vec<String>) -> Option<String> {
+    let path = path.as_ref()?;
+    match fs::read_to_string(path) {
+        Ok(content) => Some(content),
+        Err(err) => {
+            problems.push(format!("cannot read template {}: {}", path, err));
+            None
+        }
+    }
+}
+
+/// Problems of the token with the release and tap repositories
+pub async fn check_access(config: &ReleaserConfig) -> Vec<String> {
+    if let Err(err) = auth::token().await {
+        return vec![format!("{:#}", err)];
+    }
+
+    let mut repositories = vec![(&config.release.owner, &config.release.repo, false)];
+    for brew in &config.brew {
+        repositories.push((
+            &brew.repository.owner,
+            &brew.repository.name,
+            brew.repository.create,
+        ));
+    }
+
+    let mut problems = vec![];
+    for (owner, repo, create) in repositories.into_iter().unique() {
+        let handler = github_client::instance().repo(owner, repo);
+        // a tap created by the release doesn't need to exist yet
+        if create && !handler.exists().await.unwrap_or(true) {
+            continue;
+        }
+        match handler.access().await {
+            Ok(access) => {
+                if !access.scopes.is_empty()
+                    && !access
+                        .scopes
+                        .iter()
+                        .any(|s| s == "repo" || s == "public_repo")
+                {
+                    problems.push(format!(
+                        "the GitHub token needs the repo or public_repo scope, it has: {}",
+                        access.scopes.join(", ")
+                    ));
+                }
+                if access.push == Some(false) {
+                    problems.push(format!(
+                        "the GitHub token cannot push to {}/{}",
+                        owner, repo
+                    ));
+                }
+            }
+            Err(err) => problems.push(format!("cannot access {}/{}: {:#}", owner, repo, err)),
+        }
+    }
+    problems
+}
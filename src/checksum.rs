@@ -1,3 +1,4 @@
+use crate::progress;
 use anyhow::Result;
 use sha2::{Digest, Sha256};
 use std::{fs::File, io, path::Path};
@@ -8,6 +9,7 @@ where
 {
     let path = path.as_ref();
     log::info!("creating checksum for: {}: {}", binary_name, path.display());
+    let task = progress::task(format!("Hashing {}", binary_name));
 
     let mut file = File::open(path)?;
 
@@ -16,6 +18,7 @@ where
     let hash = hasher.finalize();
 
     let encoded = hex::encode(hash);
+    task.finish();
 
     Ok(encoded)
 }
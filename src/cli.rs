@@ -1,19 +1,272 @@
 use std::path::PathBuf;
 
-use clap::Parser;
+use anyhow::{bail, Result};
+use clap::{Parser, Subcommand, ValueEnum};
 #[derive(Parser, Debug)]
 #[clap(about, author, version, name = "rust-releaser")]
 pub struct Opts {
     /// Path to the project
     #[clap(default_value = ".")]
     pub path: PathBuf,
-    /// Path to the config file
-    #[clap(short, long, default_value = "releaser.toml")]
-    pub config: String,
-    /// Dry run (do not upload anything)
+    /// Path to the config file, looked up in the project when omitted
+    #[clap(short, long)]
+    pub config: Option<String>,
+    /// Profile of the config to merge over the base values
+    #[clap(long)]
+    pub profile: Option<String>,
+    /// Build and render everything, writing what would be published to `<output>/dry-run`
     #[clap(short, long)]
     pub dry_run: bool,
+    /// Release this tag instead of the latest one
+    #[clap(long, env = "RELEASER_TAG")]
+    pub tag: Option<String>,
+    /// Tag HEAD with this version, or the Cargo.toml one, push the tag and release it
+    #[clap(long, value_name = "VERSION", num_args = 0..=1, conflicts_with_all = ["tag", "snapshot"])]
+    pub create_tag: Option<Option<String>>,
+    /// Build a snapshot of HEAD without publishing, versioned after the latest tag when untagged
+    #[clap(long, conflicts_with = "tag")]
+    pub snapshot: bool,
+    /// Only warn about uncommitted changes and unpushed commits instead of refusing to release
+    #[clap(long)]
+    pub allow_dirty: bool,
+    /// Create the release as a draft
+    #[clap(long)]
+    pub draft: bool,
+    /// Stages to skip
+    #[clap(long, value_enum, value_delimiter = ',', conflicts_with = "only")]
+    pub skip: Vec<Stage>,
+    /// Only run these stages
+    #[clap(long, value_enum, value_delimiter = ',')]
+    pub only: Vec<Stage>,
+    /// Log debug messages
+    #[clap(short, long)]
+    pub verbose: bool,
+    /// Format of the logs, `json` writing one event per line to stderr for CI systems
+    #[clap(long, value_enum, default_value_t = LogFormat::Text)]
+    pub log_format: LogFormat,
+    /// Format of the error ending a failed run, `json` printing its kind and exit code to stderr
+    #[clap(long, value_enum, default_value_t = ErrorFormat::Text)]
+    pub error_format: ErrorFormat,
     /// Output directory for temporary files
     #[clap(short, long, default_value = ".")]
     pub output: PathBuf,
+    #[clap(subcommand)]
+    pub command: Option<Commands>,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Commands {
+    /// Write a starter config file from Cargo.toml and the git remote
+    Init {
+        /// Accept the detected defaults without asking
+        #[clap(short, long)]
+        yes: bool,
+        /// Overwrite an existing config file
+        #[clap(short, long)]
+        force: bool,
+    },
+    /// Print the JSON Schema of the config file
+    Schema,
+    /// Validate the config and the GitHub access without releasing
+    Check {
+        /// Skip the checks that call the GitHub API
+        #[clap(long)]
+        offline: bool,
+    },
+    /// Check the git repository, toolchains, templates and GitHub access, and print a report
+    Doctor {
+        /// Skip the checks that call the GitHub API
+        #[clap(long)]
+        offline: bool,
+    },
+    /// Verify the assets, checksums and formula of a published release
+    Verify {
+        /// Tag of the release to verify
+        tag: String,
+    },
+    /// Bump the version in Cargo.toml, then commit, tag and push it
+    Bump {
+        level: BumpLevel,
+        /// Add the new version to the changelog file
+        #[clap(long)]
+        changelog: bool,
+        /// Commit and tag without pushing
+        #[clap(long)]
+        no_push: bool,
+    },
+    /// Only build and sign the binaries, e.g. in the jobs of a CI matrix
+    Build {
+        /// Only build for these OSes of the config
+        #[clap(long, value_delimiter = ',', value_parser = ["darwin", "linux", "windows"])]
+        os: Vec<String>,
+        /// Only build for these architectures of the config
+        #[clap(long, value_delimiter = ',', value_parser = ["amd64", "arm", "arm64"])]
+        arch: Vec<String>,
+    },
+    /// Archive the built binaries and write their checksums to the output directory, without
+    /// publishing
+    Package,
+    /// Create the release from binaries already built, then mirror and prune it
+    Release,
+    /// Update the formulas, publish to crates.io and announce a release made by `release`
+    Publish,
+    /// Print the changelog between two tags, without releasing anything
+    Changelog {
+        /// Tag to start after, defaults to the one preceding `--to`
+        #[clap(long)]
+        from: Option<String>,
+        /// Tag to end at, defaults to HEAD
+        #[clap(long)]
+        to: Option<String>,
+        /// Write the changelog to this file instead of stdout
+        #[clap(long)]
+        file: Option<PathBuf>,
+    },
+    /// Render a template to preview it, without building or calling GitHub
+    Render {
+        target: RenderTarget,
+        /// JSON file of the packages to render with, e.g.
+        /// `[{"name": "app.tar.gz", "os": "linux", "arch": "amd64", "url": "...", "sha256": "..."}]`
+        #[clap(long)]
+        data: Option<PathBuf>,
+        /// Write the output to this file instead of stdout
+        #[clap(long)]
+        file: Option<PathBuf>,
+    },
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RenderTarget {
+    /// Homebrew formula
+    Brew,
+    /// Release notes generated from the changelog
+    Notes,
+    /// Section added to the changelog file
+    ChangelogFile,
+    /// Release name and body
+    Release,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorFormat {
+    Text,
+    Json,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogFormat {
+    Text,
+    Json,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BumpLevel {
+    Major,
+    Minor,
+    Patch,
+    /// Next release candidate, e.g. `1.2.0-rc.2` after `1.2.0-rc.1` and `1.2.1-rc.1` after `1.2.0`
+    Rc,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stage {
+    /// Compile the binaries
+    Build,
+    /// Sign the Windows and macOS binaries
+    Sign,
+    /// Generate the SBOM
+    Sbom,
+    /// Generate the changelog and update the changelog file
+    Changelog,
+    /// Archive the binaries and create the GitHub release
+    Release,
+    /// Mirror the release to S3 or a repository
+    Upload,
+    /// Delete the old releases
+    Prune,
+    /// Update the Homebrew formulas
+    Brew,
+    /// Publish the crate to crates.io
+    CratesIo,
+    /// Announce the release and send the webhooks
+    Announce,
+}
+
+impl Stage {
+    /// Whether the stage works on the packages of the release, which an earlier run saved when
+    /// the release stage doesn't run
+    pub fn needs_release(&self) -> bool {
+        matches!(self, Stage::Upload | Stage::Brew | Stage::Announce)
+    }
+
+    /// Name of the stage on the command line, e.g. `crates-io`
+    pub fn name(&self) -> String {
+        self.to_possible_value()
+            .map(|value| value.get_name().to_owned())
+            .unwrap_or_default()
+    }
+}
+
+impl Commands {
+    /// Stages of the subcommands splitting a release, `None` for the other subcommands
+    pub fn stages(&self) -> Option<&'static [Stage]> {
+        match self {
+            Commands::Build { .. } => Some(&[Stage::Build, Stage::Sign]),
+            Commands::Package => Some(&[Stage::Sbom, Stage::Release]),
+            Commands::Release => Some(&[
+                Stage::Sbom,
+                Stage::Changelog,
+                Stage::Release,
+                Stage::Upload,
+                Stage::Prune,
+            ]),
+            Commands::Publish => Some(&[Stage::Brew, Stage::CratesIo, Stage::Announce]),
+            _ => None,
+        }
+    }
+}
+
+impl Opts {
+    /// Check that `--only` selects stages of the subcommand
+    pub fn validate(&self) -> Result<()> {
+        let Some(command) = &self.command else {
+            return Ok(());
+        };
+        if let Some(stages) = command.stages() {
+            if let Some(stage) = self.only.iter().find(|stage| !stages.contains(stage)) {
+                bail!(
+                    "--only {} selects nothing, the stage is not part of this command",
+                    stage.name()
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether a stage that needs the packages runs without the release stage
+    pub fn reuses_release(&self) -> bool {
+        !self.runs(Stage::Release)
+            && Stage::value_variants()
+                .iter()
+                .any(|stage| stage.needs_release() && self.runs(*stage))
+    }
+
+    /// Whether `stage` runs according to the subcommand, `--skip` and `--only`
+    pub fn runs(&self, stage: Stage) -> bool {
+        if let Some(stages) = self.command.as_ref().and_then(Commands::stages) {
+            if !stages.contains(&stage) {
+                return false;
+            }
+        }
+        // snapshots are never published, so the stages that only act on GitHub don't apply
+        if self.snapshot
+            && matches!(
+                stage,
+                Stage::Upload | Stage::Prune | Stage::Brew | Stage::CratesIo | Stage::Announce
+            )
+        {
+            return false;
+        }
+        !self.skip.contains(&stage) && (self.only.is_empty() || self.only.contains(&stage))
+    }
 }
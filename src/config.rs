@@ -1,38 +1,431 @@
 use crate::{
-    brew::{install::Install, repository::Repository},
+    brew::{
+        install::{Install, InstallValue},
+        repository::Repository,
+    },
     build::Build,
+    sbom::SbomFormat,
 };
-use anyhow::Result;
-use config::Config;
+use anyhow::{bail, Context, Result};
+use config::{Config, ConfigError, FileFormat, Map, Source, Value, ValueKind};
+use itertools::Itertools;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+/// Config file names looked up in the project when `--config` is not given, by precedence
+const CONFIG_FILE_NAMES: [&str; 2] = ["releaser", "rustreleaser"];
+/// Config file extensions by precedence
+const CONFIG_FILE_EXTENSIONS: [&str; 7] = ["toml", "yaml", "yml", "json", "json5", "ron", "ini"];
+pub const DEFAULT_CONFIG_FILE: &str = "releaser.toml";
+/// Table of Cargo.toml holding the config when there is no config file, or merged under it
+const CARGO_METADATA_KEY: &str = "rustreleaser";
+/// Section of the shell commands run around the stages, left to the shell to interpolate
+const HOOKS_KEY: &str = "hooks";
 const MAIN_BRANCH_NAME: &str = "main";
 const BREW_DEFAULT_COMMIT_MESSAGE: &str = "update formula";
+const BREW_LIVECHECK_DEFAULT_URL: &str = "stable";
+const BREW_SOURCE_DEFAULT_PATH: &str = ".";
+const BREW_BOTTLES_DEFAULT_CELLAR: &str = "any_skip_relocation";
+/// Reasons of `keg_only` rendered as symbols rather than strings
+const BREW_KEG_ONLY_REASONS: [&str; 3] = [
+    "provided_by_macos",
+    "shadowed_by_macos",
+    "versioned_formula",
+];
 
 const PR_DEFAULT_BASE_BRANCH_NAME: &str = MAIN_BRANCH_NAME;
 const PR_DEFAULT_HEAD_BRANCH_NAME: &str = "bumps-formula-version";
+const CHANGELOG_DEFAULT_PATH: &str = "CHANGELOG.md";
+const CHANGELOG_DEFAULT_COMMIT_MESSAGE: &str = "update changelog for {{version}}";
+const SBOM_DEFAULT_LOCKFILE: &str = "Cargo.lock";
+const WINDOWS_DEFAULT_TIMESTAMP_URL: &str = "http://timestamp.digicert.com";
+const UPLOAD_DEFAULT_RETRIES: u32 = 3;
+const UPLOAD_DEFAULT_BACKOFF_MS: u64 = 1000;
+const UPLOAD_DEFAULT_MAX_BACKOFF_MS: u64 = 30000;
+const HTTP_DEFAULT_CONNECT_TIMEOUT_SECS: u64 = 10;
+const HTTP_DEFAULT_TIMEOUT_SECS: u64 = 60;
+const HTTP_DEFAULT_TRANSFER_TIMEOUT_SECS: u64 = 60 * 60;
+const HTTP_DEFAULT_TCP_KEEPALIVE_SECS: u64 = 60;
+const HTTP_DEFAULT_POOL_IDLE_TIMEOUT_SECS: u64 = 90;
+const MASTODON_DEFAULT_MAX_LENGTH: usize = 500;
+const BLUESKY_DEFAULT_SERVICE: &str = "https://bsky.social";
+const TELEGRAM_DEFAULT_PARSE_MODE: &str = "Markdown";
+const MATRIX_DEFAULT_MSGTYPE: &str = "m.text";
+const WEBHOOK_DEFAULT_SIGNATURE_HEADER: &str = "X-Signature-256";
+const S3_DEFAULT_PREFIX: &str = "{{ name }}/{{ version }}";
+const APT_DEFAULT_SUITE: &str = "stable";
+const APT_DEFAULT_COMPONENT: &str = "main";
+const PROVENANCE_DEFAULT_BUILDER_ID: &str = "https://github.com/cestef/rustreleaser";
+const TAG_DEFAULT_PREFIX: &str = "v";
+const TAG_DEFAULT_MESSAGE: &str = "Release {{version}}";
+const TAG_DEFAULT_REMOTE: &str = "origin";
+const TAG_DEFAULT_PATTERN: &str = r"^v?\d+\.\d+\.\d+(-[0-9A-Za-z.-]+)?$";
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ReleaserConfig {
     pub build: Build,
-    pub brew: Option<BrewConfig>,
+    /// Homebrew formulas, a `[brew]` section or several `[[brew]]` ones, e.g. one per crate of a
+    /// workspace
+    #[serde(default, deserialize_with = "one_or_many")]
+    #[schemars(with = "Option<OneOrMany<BrewConfig>>")]
+    pub brew: Vec<BrewConfig>,
     pub release: ReleaseConfig,
+    #[serde(alias = "cratesio")]
     pub crates_io: Option<CratesIoConfig>,
+    pub sbom: Option<SbomConfig>,
+    /// Object storages the release archives are mirrored to
+    pub storage: Option<StorageConfig>,
+    /// Messages posted once the release is published
+    pub announce: Option<AnnounceConfig>,
+    pub windows: Option<WindowsConfig>,
+    pub macos: Option<MacosConfig>,
+    /// Tags created with `--create-tag`
+    #[serde(default)]
+    pub tag: TagConfig,
+    /// Access to the GitHub API
+    #[serde(default)]
+    pub github: GithubConfig,
+    /// Proxy, certificates, timeouts and connection reuse of the HTTP client
+    #[serde(default)]
+    pub http: HttpConfig,
+    /// External commands called at hook points of the release, `[[plugins]]` tables
+    #[serde(default)]
+    pub plugins: Vec<PluginConfig>,
+    /// Shell commands run before and after the stages
+    #[serde(default)]
+    pub hooks: HooksConfig,
+    /// Named sets of values merged over the config with `--profile`
+    #[serde(default, skip_serializing)]
+    #[allow(dead_code)] // merged by `load`, declared for the schema
+    pub profiles: HashMap<String, serde_json::Value>,
+    /// Path of a config merged under this one, relative to this file
+    #[serde(default, skip_serializing)]
+    #[allow(dead_code)] // merged by `load`, declared for the schema
+    pub extends: Option<String>,
+}
+
+/// Accepts both a single table and an array of tables
+#[derive(Deserialize, JsonSchema)]
+#[serde(untagged)]
+#[allow(dead_code)] // deserialized by `one_or_many`, declared for the schema
+enum OneOrMany<T> {
+    One(T),
+    Many(Vec<T>),
+}
+
+/// Deserialize through a JSON value rather than [`OneOrMany`], which would hide the errors of
+/// the tables behind a non-matching variant
+fn one_or_many<'de, D, T>(deserializer: D) -> Result<Vec<T>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    T: serde::de::DeserializeOwned,
+{
+    use serde::de::Error;
+
+    match serde_json::Value::deserialize(deserializer)? {
+        serde_json::Value::Array(values) => values
+            .into_iter()
+            .map(|value| serde_json::from_value(value).map_err(D::Error::custom))
+            .collect(),
+        value => Ok(vec![
+            serde_json::from_value(value).map_err(D::Error::custom)?
+        ]),
+    }
+}
+
+/// Values of a config file, with the environment variables interpolated, or of a profile,
+/// layered between the config file and the environment
+#[derive(Debug, Clone)]
+struct Values(Map<String, Value>);
+
+impl Values {
+    fn parse(content: &str, format: FileFormat) -> Result<Self> {
+        let mut values = config::File::from_str(content, format).collect()?;
+        for (key, value) in &mut values {
+            interpolate_value(key, value)?;
+        }
+        Ok(Values(values))
+    }
+}
+
+impl Source for Values {
+    fn clone_into_box(&self) -> Box<dyn Source + Send + Sync> {
+        Box::new(self.clone())
+    }
+
+    fn collect(&self) -> Result<Map<String, Value>, ConfigError> {
+        Ok(self.0.clone())
+    }
 }
 
 impl ReleaserConfig {
-    pub async fn load(path: String) -> Result<Self> {
-        let config = Config::builder()
-            .add_source(config::File::with_name(&path))
-            .add_source(config::Environment::with_prefix("RELEASER"))
+    /// Load the config file at `path`, merged over `[package.metadata.rustreleaser]` of the
+    /// project Cargo.toml when present
+    pub async fn load(base: &Path, path: Option<String>, profile: Option<&str>) -> Result<Self> {
+        let mut files = vec![];
+        if let Some(values) = cargo_metadata(base)? {
+            files.extend(with_extended(values, base, &mut vec![])?);
+        }
+        if let Some(path) = path {
+            files.extend(read_file(Path::new(&path), &mut vec![])?);
+        }
+        if files.is_empty() {
+            bail!(
+                "no releaser config found in {}, add a config file or [package.metadata.{}] to Cargo.toml",
+                base.display(),
+                CARGO_METADATA_KEY
+            );
+        }
+
+        let mut builder = files.iter().fold(Config::builder(), |builder, file| {
+            builder.add_source(file.clone())
+        });
+        if let Some(profile) = profile {
+            let profiles = builder
+                .build_cloned()?
+                .get_table("profiles")
+                .unwrap_or_default();
+            let values = profiles
+                .get(profile)
+                .cloned()
+                .with_context(|| {
+                    format!(
+                        "profile {} not found, available profiles: {}",
+                        profile,
+                        profiles.keys().sorted().join(", ")
+                    )
+                })?
+                .into_table()
+                .with_context(|| format!("profile {} must be a table", profile))?;
+            log::info!("Using profile {}", profile);
+            builder = builder.add_source(Values(values));
+        }
+
+        let mut config: ReleaserConfig = builder
+            // `RELEASER__RELEASE__OWNER` for `release.owner`, leaving `RELEASER_TAG` and the other
+            // variables of the CLI alone
+            .add_source(config::Environment::with_prefix("RELEASER").separator("__"))
             .build()?
             .try_deserialize()?;
 
+        if let Some(completions) = &config.build.completions {
+            for brew in &mut config.brew {
+                completions.install(&mut brew.install, &config.build.binary);
+            }
+        }
+
         Ok(config)
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// JSON Schema of the config, derived from the serde definitions
+pub fn schema() -> Result<String> {
+    let schema = schemars::schema_for!(ReleaserConfig);
+    Ok(serde_json::to_string_pretty(&schema)?)
+}
+
+/// Find the config file of the project following the documented precedence
+pub fn find(base: &Path) -> Option<String> {
+    let mut found = CONFIG_FILE_NAMES.iter().flat_map(|name| {
+        CONFIG_FILE_EXTENSIONS
+            .iter()
+            .map(move |ext| base.join(format!("{}.{}", name, ext)))
+    });
+
+    let path = found.find(|path| path.is_file())?;
+
+    let ignored: Vec<String> = found
+        .filter(|path| path.is_file())
+        .map(|path| path.display().to_string())
+        .collect();
+    if !ignored.is_empty() {
+        log::warn!("Using {}, ignoring {}", path.display(), ignored.join(", "));
+    }
+
+    Some(path.display().to_string())
+}
+
+/// Read a config file, preceded by the files it extends
+fn read_file(path: &Path, chain: &mut Vec<PathBuf>) -> Result<Vec<Values>> {
+    let (path, format) = resolve_file(path)?;
+    let canonical = path
+        .canonicalize()
+        .with_context(|| format!("failed to read {}", path.display()))?;
+    if chain.contains(&canonical) {
+        bail!("{} extends itself", path.display());
+    }
+
+    let content =
+        fs::read_to_string(&path).with_context(|| format!("failed to read {}", path.display()))?;
+    let file = Values::parse(&content, format)
+        .with_context(|| format!("failed to read {}", path.display()))?;
+
+    chain.push(canonical);
+    let dir = path.parent().unwrap_or(Path::new("."));
+    let files = with_extended(file, dir, chain)
+        .with_context(|| format!("failed to load the config extended by {}", path.display()))?;
+    chain.pop();
+
+    Ok(files)
+}
+
+/// The files extended by `file`, resolved from `dir` and deep-merged in order, then `file`
+fn with_extended(file: Values, dir: &Path, chain: &mut Vec<PathBuf>) -> Result<Vec<Values>> {
+    let extends = Config::builder()
+        .add_source(file.clone())
+        .build()?
+        .get_string("extends")
+        .ok();
+
+    let mut files = match extends {
+        Some(extends) => read_file(&dir.join(extends), chain)?,
+        None => vec![],
+    };
+    files.push(file);
+
+    Ok(files)
+}
+
+/// `[package.metadata.rustreleaser]` of the project Cargo.toml, interpolated
+fn cargo_metadata(base: &Path) -> Result<Option<Values>> {
+    let manifest_path = base.join("Cargo.toml");
+    let Ok(manifest) = fs::read_to_string(&manifest_path) else {
+        return Ok(None);
+    };
+    let manifest: toml::Value = manifest
+        .parse()
+        .with_context(|| format!("failed to parse {}", manifest_path.display()))?;
+
+    let Some(metadata) = manifest
+        .get("package")
+        .and_then(|package| package.get("metadata"))
+        .and_then(|metadata| metadata.get(CARGO_METADATA_KEY))
+    else {
+        return Ok(None);
+    };
+
+    log::debug!(
+        "Using [package.metadata.{}] of {}",
+        CARGO_METADATA_KEY,
+        manifest_path.display()
+    );
+    let values = Values::parse(&toml::to_string(metadata)?, FileFormat::Toml)
+        .with_context(|| format!("failed to read {}", manifest_path.display()))?;
+
+    Ok(Some(values))
+}
+
+/// Find the config file, trying the supported extensions when `path` has none
+fn resolve_file(path: &Path) -> Result<(PathBuf, FileFormat)> {
+    let format = |path: &Path| match path.extension()?.to_str()? {
+        "toml" => Some(FileFormat::Toml),
+        "json" => Some(FileFormat::Json),
+        "yaml" | "yml" => Some(FileFormat::Yaml),
+        "ini" => Some(FileFormat::Ini),
+        "ron" => Some(FileFormat::Ron),
+        "json5" => Some(FileFormat::Json5),
+        _ => None,
+    };
+
+    if path.is_file() {
+        let format = format(path)
+            .with_context(|| format!("unsupported config format: {}", path.display()))?;
+        return Ok((path.to_path_buf(), format));
+    }
+
+    CONFIG_FILE_EXTENSIONS
+        .iter()
+        .map(|ext| PathBuf::from(format!("{}.{}", path.display(), ext)))
+        .find(|candidate| candidate.is_file())
+        .and_then(|candidate| format(&candidate).map(|format| (candidate, format)))
+        .with_context(|| format!("config file {} not found", path.display()))
+}
+
+/// Interpolate the strings of `value`, found at `key`. Comments and the syntax of the file are
+/// never touched, and the values are inserted as is, whatever quotes or newlines they contain
+fn interpolate_value(key: &str, value: &mut Value) -> Result<()> {
+    // shell commands, whose `${VERSION}` is expanded by the shell from the hook variables
+    if key == HOOKS_KEY || key.ends_with(&format!(".{}", HOOKS_KEY)) {
+        return Ok(());
+    }
+    match &mut value.kind {
+        ValueKind::String(string) => {
+            *string =
+                interpolate(string).with_context(|| format!("failed to interpolate {}", key))?;
+        }
+        ValueKind::Table(table) => {
+            for (name, value) in table {
+                interpolate_value(&format!("{}.{}", key, name), value)?;
+            }
+        }
+        ValueKind::Array(array) => {
+            for (index, value) in array.iter_mut().enumerate() {
+                interpolate_value(&format!("{}[{}]", key, index), value)?;
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Replace `${VAR}` and `${VAR:-default}` with environment variables, `$${` escapes
+fn interpolate(content: &str) -> Result<String> {
+    let mut result = String::with_capacity(content.len());
+    let mut rest = content;
+
+    while let Some(start) = rest.find('$') {
+        result.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        if let Some(escaped) = rest.strip_prefix("$${") {
+            result.push_str("${");
+            rest = escaped;
+        } else if let Some(expression) = rest.strip_prefix("${") {
+            let end = expression.find('}').with_context(|| {
+                format!(
+                    "unclosed `${{` in `{}`",
+                    rest.lines().next().unwrap_or_default()
+                )
+            })?;
+            let (name, default) = match expression[..end].split_once(":-") {
+                Some((name, default)) => (name, Some(default)),
+                None => (&expression[..end], None),
+            };
+
+            // like the shell, `:-` also replaces empty values
+            let value = env::var(name.trim())
+                .ok()
+                .filter(|value| default.is_none() || !value.is_empty());
+            match (value, default) {
+                (Some(value), _) => result.push_str(&value),
+                (None, Some(default)) => result.push_str(default),
+                (None, None) => bail!("environment variable {} is not set", name.trim()),
+            }
+            rest = &expression[end + 1..];
+        } else {
+            result.push('$');
+            rest = &rest[1..];
+        }
+    }
+    result.push_str(rest);
+
+    Ok(result)
+}
+
+/// Default of the flags that are on unless disabled, e.g. `enabled`
+fn default_true() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct CratesIoConfig {
+    /// Packages to publish, in any order; defaults to the publishable packages of the workspace
+    #[serde(default)]
     pub packages: Vec<String>,
     pub registry: Option<String>,
     pub index: Option<String>,
@@ -40,13 +433,554 @@ pub struct CratesIoConfig {
     pub no_verify: Option<bool>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct StorageConfig {
+    #[serde(default)]
+    pub s3: Vec<S3Config>,
+    /// Storages given by URL, the backend being picked by the scheme
+    #[serde(default)]
+    pub targets: Vec<StorageTargetConfig>,
+    #[serde(default)]
+    pub cloudsmith: Vec<CloudsmithConfig>,
+    #[serde(default)]
+    pub gemfury: Vec<GemfuryConfig>,
+    pub apt: Option<AptConfig>,
+    pub yum: Option<YumConfig>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct AptConfig {
+    /// Glob patterns of the deb packages, relative to the project, e.g. `target/debian/*.deb`
+    pub files: Vec<String>,
+    #[serde(default = "AptConfig::default_suite")]
+    pub suite: String,
+    #[serde(default = "AptConfig::default_component")]
+    pub component: String,
+    /// `Origin` and `Label` of the Release file, the release owner by default
+    pub origin: Option<String>,
+    pub label: Option<String>,
+    #[serde(flatten)]
+    pub repository: PackageRepositoryConfig,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct YumConfig {
+    /// Glob patterns of the rpm packages, relative to the project, e.g.
+    /// `target/generate-rpm/*.rpm`
+    pub files: Vec<String>,
+    #[serde(flatten)]
+    pub repository: PackageRepositoryConfig,
+}
+
+/// Where an apt or yum repository is published
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct PackageRepositoryConfig {
+    /// Branch of the release repository the repository is committed to, e.g. `gh-pages`
+    pub branch: Option<String>,
+    /// Storage the repository is uploaded to instead, as in `storage.targets`
+    pub target: Option<StorageTargetConfig>,
+    /// Public URL of the repository uploaded to `target`, read to keep the packages of the
+    /// previous releases in the metadata
+    pub url: Option<String>,
+    /// Sign the metadata with gpg
+    #[serde(default = "default_true")]
+    pub sign: bool,
+    /// Key signing the metadata, exported as `key.asc`; gpg's default key otherwise
+    pub signing_key: Option<String>,
+}
+
+impl AptConfig {
+    fn default_suite() -> String {
+        APT_DEFAULT_SUITE.to_owned()
+    }
+
+    fn default_component() -> String {
+        APT_DEFAULT_COMPONENT.to_owned()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct CloudsmithConfig {
+    /// Account or organization owning the repository; the API key is read from
+    /// `CLOUDSMITH_API_KEY`
+    pub owner: String,
+    pub repository: String,
+    #[serde(default)]
+    pub format: CloudsmithFormat,
+    /// `<distribution>/<release>` of deb and rpm packages, e.g. `ubuntu/jammy` or
+    /// `any-distro/any-version`
+    pub distribution: Option<String>,
+    /// Glob patterns of the packages, relative to the project; defaults to the release archives
+    /// and checksums for `raw`
+    #[serde(default)]
+    pub files: Vec<String>,
+    /// Replace a package already uploaded with the same version
+    #[serde(default)]
+    pub republish: bool,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum CloudsmithFormat {
+    #[default]
+    Raw,
+    Deb,
+    Rpm,
+}
+
+impl CloudsmithFormat {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CloudsmithFormat::Raw => "raw",
+            CloudsmithFormat::Deb => "deb",
+            CloudsmithFormat::Rpm => "rpm",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct GemfuryConfig {
+    /// Account pushed to; the push token is read from `FURY_PUSH_TOKEN`
+    pub account: String,
+    /// Glob patterns of the packages, relative to the project, e.g. `target/debian/*.deb`
+    pub files: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct StorageTargetConfig {
+    /// Handlebars template of `s3://<bucket>/<prefix>`, `gs://<bucket>/<prefix>`,
+    /// `az://<account>/<container>/<prefix>`, or an `https://` URL the files are PUT under
+    pub url: String,
+    /// Canned ACL on S3, predefined ACL on GCS, e.g. `public-read` or `publicRead`
+    pub acl: Option<String>,
+    /// Basic authentication user of HTTP targets
+    pub username: Option<String>,
+    /// Environment variable holding the password of `username`
+    pub password_env: Option<String>,
+    /// Environment variable holding a bearer token for HTTP targets
+    pub token_env: Option<String>,
+    /// Headers added to the requests of HTTP targets
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct S3Config {
+    pub bucket: String,
+    /// Handlebars template of the key prefix of the files
+    #[serde(default = "S3Config::default_prefix")]
+    pub prefix: String,
+    /// Defaults to the region of the AWS config, `auto` for R2
+    pub region: Option<String>,
+    /// Endpoint of an S3-compatible storage, e.g. R2 or MinIO
+    pub endpoint: Option<String>,
+    /// Address buckets in the path instead of the host name, as MinIO expects
+    #[serde(default)]
+    pub force_path_style: bool,
+    /// Canned ACL of the files, e.g. `public-read`
+    pub acl: Option<String>,
+    /// AWS profile of the credentials, instead of the default chain
+    pub profile: Option<String>,
+}
+
+impl S3Config {
+    fn default_prefix() -> String {
+        S3_DEFAULT_PREFIX.to_owned()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SbomConfig {
+    #[serde(default = "SbomConfig::default_formats")]
+    pub formats: Vec<SbomFormat>,
+    #[serde(default = "SbomConfig::default_lockfile")]
+    pub lockfile: String,
+}
+
+impl SbomConfig {
+    fn default_formats() -> Vec<SbomFormat> {
+        vec![SbomFormat::CycloneDx]
+    }
+
+    fn default_lockfile() -> String {
+        SBOM_DEFAULT_LOCKFILE.to_owned()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct AnnounceConfig {
+    pub slack: Option<SlackConfig>,
+    pub discord: Option<DiscordConfig>,
+    pub mastodon: Option<MastodonConfig>,
+    pub bluesky: Option<BlueskyConfig>,
+    pub telegram: Option<TelegramConfig>,
+    pub teams: Option<TeamsConfig>,
+    pub matrix: Option<MatrixConfig>,
+    pub email: Option<EmailConfig>,
+    /// Endpoints notified as the release progresses
+    #[serde(default)]
+    pub webhooks: Vec<WebhookConfig>,
+}
+
+/// `enabled` flag of every announcer
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct AnnounceToggle {
+    /// Set to `false` to keep the section without announcing, e.g. in a profile
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SlackConfig {
+    #[serde(flatten)]
+    pub toggle: AnnounceToggle,
+    /// Incoming webhook receiving the message; defaults to `SLACK_WEBHOOK_URL`
+    pub webhook_url: Option<String>,
+    /// Channel to post to with the bot token in `SLACK_BOT_TOKEN`, instead of a webhook
+    pub channel: Option<String>,
+    /// Handlebars template of the message, in Slack `mrkdwn`
+    pub message: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct DiscordConfig {
+    #[serde(flatten)]
+    pub toggle: AnnounceToggle,
+    /// Webhook receiving the message; defaults to `DISCORD_WEBHOOK_URL`
+    pub webhook_url: Option<String>,
+    /// Handlebars template of a text posted above the embed, e.g. to mention a role
+    pub message: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct MastodonConfig {
+    #[serde(flatten)]
+    pub toggle: AnnounceToggle,
+    /// Base URL of the instance, e.g. `https://mastodon.social`; the access token is read from
+    /// `MASTODON_ACCESS_TOKEN`
+    pub instance: String,
+    /// Handlebars template of the status, shortened to fit `max_length`
+    pub message: Option<String>,
+    /// Hashtags appended to the status, with or without the leading `#`
+    #[serde(default)]
+    pub hashtags: Vec<String>,
+    /// Append the URL of the release to the status
+    #[serde(default = "default_true")]
+    pub include_url: bool,
+    /// Character limit of the instance
+    #[serde(default = "MastodonConfig::default_max_length")]
+    pub max_length: usize,
+}
+
+impl MastodonConfig {
+    fn default_max_length() -> usize {
+        MASTODON_DEFAULT_MAX_LENGTH
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct BlueskyConfig {
+    #[serde(flatten)]
+    pub toggle: AnnounceToggle,
+    /// Handle or DID of the account; the app password is read from `BLUESKY_APP_PASSWORD`
+    pub identifier: String,
+    /// PDS hosting the account
+    #[serde(default = "BlueskyConfig::default_service")]
+    pub service: String,
+    /// Handlebars template of the post, shortened to fit the 300 characters Bluesky accepts
+    pub message: Option<String>,
+}
+
+impl BlueskyConfig {
+    fn default_service() -> String {
+        BLUESKY_DEFAULT_SERVICE.to_owned()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct TelegramConfig {
+    #[serde(flatten)]
+    pub toggle: AnnounceToggle,
+    /// Chat receiving the message, e.g. `@my_channel` or a numeric id; the bot token is read
+    /// from `TELEGRAM_BOT_TOKEN`
+    pub chat_id: String,
+    /// Handlebars template of the message, formatted with `parse_mode`
+    pub message: Option<String>,
+    /// `Markdown`, `MarkdownV2` or `HTML`
+    #[serde(default = "TelegramConfig::default_parse_mode")]
+    pub parse_mode: String,
+}
+
+impl TelegramConfig {
+    fn default_parse_mode() -> String {
+        TELEGRAM_DEFAULT_PARSE_MODE.to_owned()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct TeamsConfig {
+    #[serde(flatten)]
+    pub toggle: AnnounceToggle,
+    /// Incoming webhook or workflow receiving the card; defaults to `TEAMS_WEBHOOK_URL`
+    pub webhook_url: Option<String>,
+    /// Handlebars template of the card text, in Markdown
+    pub message: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct MatrixConfig {
+    #[serde(flatten)]
+    pub toggle: AnnounceToggle,
+    /// Base URL of the homeserver, e.g. `https://matrix.org`; the access token is read from
+    /// `MATRIX_ACCESS_TOKEN`
+    pub homeserver: String,
+    /// Room ID, e.g. `!abcdef:matrix.org`, joined by the account of the token
+    pub room_id: String,
+    /// Handlebars template of the message
+    pub message: Option<String>,
+    /// `m.text`, or `m.notice` for a message bots don't react to
+    #[serde(default = "MatrixConfig::default_msgtype")]
+    pub msgtype: String,
+}
+
+impl MatrixConfig {
+    fn default_msgtype() -> String {
+        MATRIX_DEFAULT_MSGTYPE.to_owned()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct EmailConfig {
+    #[serde(flatten)]
+    pub toggle: AnnounceToggle,
+    /// Sender, e.g. `Releases <releases@example.com>`
+    pub from: String,
+    /// Recipients, e.g. a mailing list
+    pub to: Vec<String>,
+    /// Handlebars template of the subject
+    pub subject: Option<String>,
+    /// Handlebars template of the plain text body
+    pub message: Option<String>,
+    /// SMTP server; defaults to `SMTP_HOST`. The port is read from `SMTP_PORT` and the
+    /// credentials from `SMTP_USERNAME` and `SMTP_PASSWORD`.
+    pub host: Option<String>,
+    #[serde(default)]
+    pub tls: SmtpTls,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum SmtpTls {
+    /// Upgrade the connection with STARTTLS, on port 587 by default
+    #[default]
+    StartTls,
+    /// Connect over TLS, on port 465 by default
+    Tls,
+    /// Plain text, e.g. for a relay on localhost, on port 25 by default
+    None,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct WebhookConfig {
+    /// Set to `false` to keep the webhook without calling it, e.g. in a profile
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    pub url: String,
+    /// Milestones sent to the webhook, all of them by default
+    #[serde(default = "WebhookConfig::default_events")]
+    pub events: Vec<WebhookEvent>,
+    /// Handlebars template of the JSON body; defaults to the event and the announcement values
+    pub payload: Option<String>,
+    /// Headers added to the request
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+    /// Environment variable holding the key of an HMAC-SHA256 signature of the body
+    pub secret_env: Option<String>,
+    /// Header carrying the signature, as `sha256=<hex>`
+    #[serde(default = "WebhookConfig::default_signature_header")]
+    pub signature_header: String,
+}
+
+impl WebhookConfig {
+    fn default_events() -> Vec<WebhookEvent> {
+        vec![
+            WebhookEvent::ReleaseCreated,
+            WebhookEvent::AssetsUploaded,
+            WebhookEvent::FormulaUpdated,
+        ]
+    }
+
+    fn default_signature_header() -> String {
+        WEBHOOK_DEFAULT_SIGNATURE_HEADER.to_owned()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookEvent {
+    ReleaseCreated,
+    AssetsUploaded,
+    FormulaUpdated,
+}
+
+impl WebhookEvent {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WebhookEvent::ReleaseCreated => "release_created",
+            WebhookEvent::AssetsUploaded => "assets_uploaded",
+            WebhookEvent::FormulaUpdated => "formula_updated",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct HooksConfig {
+    /// Commands run before each stage, e.g. `release = ["make docs"]`
+    #[serde(default)]
+    pub before: StageHooks,
+    /// Commands run once each stage succeeded
+    #[serde(default)]
+    pub after: StageHooks,
+}
+
+/// Shell commands of each stage, as templates of `version`, `tag`, `release_url` and `assets`
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct StageHooks {
+    pub build: Vec<String>,
+    pub sign: Vec<String>,
+    pub sbom: Vec<String>,
+    pub changelog: Vec<String>,
+    pub release: Vec<String>,
+    pub upload: Vec<String>,
+    pub prune: Vec<String>,
+    pub brew: Vec<String>,
+    #[serde(alias = "crates-io", alias = "cratesio")]
+    pub crates_io: Vec<String>,
+    pub announce: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct PluginConfig {
+    /// Name of the plugin in the logs
+    pub name: String,
+    /// Program and arguments run from the project, e.g. `["./scripts/notarize.sh", "--wait"]`
+    pub command: Vec<String>,
+    /// Hook points the plugin is called at, all of them by default
+    #[serde(default = "PluginConfig::default_hooks")]
+    pub hooks: Vec<PluginHook>,
+}
+
+impl PluginConfig {
+    fn default_hooks() -> Vec<PluginHook> {
+        vec![
+            PluginHook::PreRelease,
+            PluginHook::PostUpload,
+            PluginHook::PostBrew,
+        ]
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum PluginHook {
+    /// Before the release is created, when plugins can add assets to it
+    PreRelease,
+    /// Once the assets are uploaded and mirrored
+    PostUpload,
+    /// Once the formulas are published
+    PostBrew,
+}
+
+impl PluginHook {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PluginHook::PreRelease => "pre-release",
+            PluginHook::PostUpload => "post-upload",
+            PluginHook::PostBrew => "post-brew",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct WindowsConfig {
+    pub sign: Option<WindowsSignConfig>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct WindowsSignConfig {
+    #[serde(default)]
+    pub tool: WindowsSignTool,
+    /// Path to a PKCS#12 (.pfx) certificate
+    pub certificate: Option<String>,
+    /// Name of the environment variable holding the certificate password
+    pub password_env: Option<String>,
+    pub key_vault: Option<KeyVaultConfig>,
+    pub description: Option<String>,
+    #[serde(default = "WindowsSignConfig::default_timestamp_url")]
+    pub timestamp_url: String,
+}
+
+impl WindowsSignConfig {
+    fn default_timestamp_url() -> String {
+        WINDOWS_DEFAULT_TIMESTAMP_URL.to_owned()
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum WindowsSignTool {
+    #[default]
+    Signtool,
+    Osslsigncode,
+    AzureSignTool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct KeyVaultConfig {
+    pub url: String,
+    pub certificate: String,
+    pub client_id: String,
+    pub tenant_id: String,
+    /// Name of the environment variable holding the client secret
+    pub client_secret_env: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct MacosConfig {
+    pub sign: Option<MacosSignConfig>,
+    pub notarize: Option<NotarizeConfig>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct MacosSignConfig {
+    /// Developer ID Application identity, e.g. "Developer ID Application: Name (TEAMID)"
+    pub identity: String,
+    pub entitlements: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct NotarizeConfig {
+    /// Path to an App Store Connect API key (.p8)
+    pub key: Option<String>,
+    pub key_id: Option<String>,
+    pub issuer: Option<String>,
+    pub apple_id: Option<String>,
+    pub team_id: Option<String>,
+    /// Name of the environment variable holding the app-specific password
+    pub password_env: Option<String>,
+    #[serde(default)]
+    pub staple: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct BrewConfig {
     pub name: String,
     #[serde(default)]
     pub description: String,
     #[serde(default)]
     pub homepage: String,
+    #[schemars(with = "InstallValue")]
     pub install: Install,
     #[serde(default)]
     pub license: String,
@@ -56,12 +990,56 @@ pub struct BrewConfig {
     pub test: String,
     #[serde(default)]
     pub caveats: String,
+    /// Caveats only shown on macOS, after `caveats`
+    #[serde(default)]
+    pub macos_caveats: String,
+    /// Caveats only shown on Linux, after `caveats`
+    #[serde(default)]
+    pub linux_caveats: String,
+    /// Why the formula is not linked into the prefix, a reason or one of `provided_by_macos`,
+    /// `shadowed_by_macos` and `versioned_formula`
+    #[schemars(with = "Option<String>")]
+    pub keg_only: Option<BrewKegOnly>,
+    /// Formulas installing the same files, `"foo"` or
+    /// `{ name = "foo", because = "both install a foo binary" }`
+    #[serde(default)]
+    #[schemars(with = "Vec<BrewConflictValue>")]
+    pub conflicts_with: Vec<BrewConflict>,
+    /// Download the assets of a private repository through the GitHub API, with the token of
+    /// `HOMEBREW_GITHUB_API_TOKEN`
+    #[serde(default)]
+    pub private: bool,
+    /// Check the rendered formula before committing it, `true` for the built-in checks or
+    /// `"brew"` for `brew style`
+    #[serde(default)]
+    pub audit: BrewAudit,
+    /// `head` spec building the repository with cargo, for `brew install --HEAD`
+    pub source: Option<BrewSourceConfig>,
+    /// `livecheck` block finding the new versions of the formula
+    #[serde(default)]
+    pub livecheck: BrewLivecheckConfig,
+    /// `bottle do` block of the bottles built by `brew bottle`, uploaded to the release
+    pub bottles: Option<BrewBottlesConfig>,
+    /// `service do` block running the binary as a daemon with `brew services`
+    pub service: Option<BrewServiceConfig>,
+    /// Formulas needed by the binary, `"openssl@3"` or
+    /// `{ name = "pkgconf", type = "build", os = "linux" }`
+    #[serde(default)]
+    #[schemars(with = "Vec<BrewDependencyValue>")]
+    pub dependencies: Vec<BrewDependency>,
     #[serde(default = "BrewConfig::default_commit_message")]
     pub commit_message: String,
     pub commit_author: Option<CommitterConfig>,
     pub pull_request: Option<PullRequestConfig>,
     pub repository: Repository,
+    /// Directory of the tap the formula is written to, e.g. `Formula`
     pub path: Option<String>,
+    /// Path of the formula file in the tap, e.g. `Formula/app.rb`, instead of `<name>.rb` in
+    /// `path`
+    pub formula_path: Option<String>,
+    /// Path to a handlebars template replacing the built-in formula, which can still include
+    /// the built-in `single_target` and `multi_target` templates as partials
+    pub template_path: Option<String>,
 }
 
 impl BrewConfig {
@@ -75,12 +1053,267 @@ impl BrewConfig {
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(from = "BrewDependencyValue")]
+pub struct BrewDependency {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub kind: Option<BrewDependencyKind>,
+    /// Only depend on the formula on this OS
+    pub os: Option<BrewOs>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum BrewDependencyKind {
+    Build,
+    Test,
+    Optional,
+    Recommended,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum BrewOs {
+    Macos,
+    Linux,
+}
+
+/// Accepts both `"openssl@3"` and `{ name = "openssl@3", type = "build" }`
+#[derive(Deserialize, JsonSchema)]
+#[serde(untagged)]
+enum BrewDependencyValue {
+    Name(String),
+    Table {
+        name: String,
+        #[serde(rename = "type")]
+        kind: Option<BrewDependencyKind>,
+        os: Option<BrewOs>,
+    },
+}
+
+impl From<BrewDependencyValue> for BrewDependency {
+    fn from(value: BrewDependencyValue) -> Self {
+        match value {
+            BrewDependencyValue::Name(name) => BrewDependency {
+                name,
+                kind: None,
+                os: None,
+            },
+            BrewDependencyValue::Table { name, kind, os } => BrewDependency { name, kind, os },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase", try_from = "BrewAuditValue")]
+pub enum BrewAudit {
+    #[default]
+    Off,
+    /// Common mistakes checked without Ruby
+    Builtin,
+    /// `brew style --fix`, failing on the offenses it cannot fix
+    Brew,
+}
+
+/// Accepts both `audit = true` and `audit = "brew"`
+#[derive(Deserialize, JsonSchema)]
+#[serde(untagged)]
+enum BrewAuditValue {
+    Bool(bool),
+    String(String),
+}
+
+impl TryFrom<BrewAuditValue> for BrewAudit {
+    type Error = String;
+
+    fn try_from(value: BrewAuditValue) -> Result<Self, Self::Error> {
+        match value {
+            BrewAuditValue::Bool(true) => Ok(BrewAudit::Builtin),
+            BrewAuditValue::Bool(false) => Ok(BrewAudit::Off),
+            BrewAuditValue::String(value) => match value.as_str() {
+                "true" | "builtin" => Ok(BrewAudit::Builtin),
+                "false" | "off" => Ok(BrewAudit::Off),
+                "brew" => Ok(BrewAudit::Brew),
+                _ => Err(format!(
+                    "invalid audit value `{}`, expected true, false, builtin or brew",
+                    value
+                )),
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct BrewSourceConfig {
+    /// Branch of the head spec, `release.target_branch` by default
+    pub branch: Option<String>,
+    /// Directory of the crate installed by `cargo install`, relative to the repository
+    #[serde(default = "BrewSourceConfig::default_path")]
+    pub path: String,
+    /// Build the source archive of the release on the platforms without a binary
+    #[serde(default)]
+    pub fallback: bool,
+}
+
+impl BrewSourceConfig {
+    fn default_path() -> String {
+        BREW_SOURCE_DEFAULT_PATH.to_owned()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct BrewBottlesConfig {
+    /// Patterns of the bottles built on each OS, e.g. `bottles/*.bottle.tar.gz`
+    pub files: Vec<String>,
+    /// URL the bottles are downloaded from, the release by default
+    pub root_url: Option<String>,
+    /// Cellar the bottles were built for, `any`, `any_skip_relocation` or a path
+    #[serde(default = "BrewBottlesConfig::default_cellar")]
+    pub cellar: String,
+}
+
+impl BrewBottlesConfig {
+    fn default_cellar() -> String {
+        BREW_BOTTLES_DEFAULT_CELLAR.to_owned()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct BrewLivecheckConfig {
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// `stable`, `head`, `homepage` or `url` of the formula, or a URL to check
+    #[serde(default = "BrewLivecheckConfig::default_url")]
+    pub url: String,
+    /// Regular expression capturing the version, by default matching the tags of the release
+    pub regex: Option<String>,
+    /// Livecheck strategy, e.g. `github_latest`
+    pub strategy: Option<String>,
+}
+
+impl BrewLivecheckConfig {
+    fn default_url() -> String {
+        BREW_LIVECHECK_DEFAULT_URL.to_owned()
+    }
+}
+
+impl Default for BrewLivecheckConfig {
+    fn default() -> Self {
+        BrewLivecheckConfig {
+            enabled: default_true(),
+            url: BrewLivecheckConfig::default_url(),
+            regex: None,
+            strategy: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct BrewServiceConfig {
+    /// Binary of the formula and its arguments, e.g. `["app", "serve"]`
+    pub run: Vec<String>,
+    /// Restart the service when it exits
+    #[serde(default)]
+    pub keep_alive: bool,
+    /// Log of the standard output, relative to the Homebrew `var` directory
+    pub log_path: Option<String>,
+    /// Log of the standard error, relative to the Homebrew `var` directory
+    pub error_log_path: Option<String>,
+    /// Working directory, relative to the Homebrew `var` directory
+    pub working_dir: Option<String>,
+    /// Environment variables of the service, as `KEY=value`
+    #[serde(default)]
+    #[schemars(with = "Vec<String>")]
+    pub environment: Vec<BrewServiceVariable>,
+}
+
+/// `KEY=value` environment variable, kept as a list since the keys of tables are lowercased
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(try_from = "String")]
+pub struct BrewServiceVariable {
+    pub name: String,
+    pub value: String,
+}
+
+impl TryFrom<String> for BrewServiceVariable {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        match value.split_once('=') {
+            Some((name, value)) if !name.trim().is_empty() => Ok(BrewServiceVariable {
+                name: name.trim().to_owned(),
+                value: value.to_owned(),
+            }),
+            _ => Err(format!(
+                "invalid environment variable `{}`, expected KEY=value",
+                value
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(from = "BrewConflictValue")]
+pub struct BrewConflict {
+    pub name: String,
+    pub because: Option<String>,
+}
+
+/// Accepts both `"foo"` and `{ name = "foo", because = "both install a foo binary" }`
+#[derive(Deserialize, JsonSchema)]
+#[serde(untagged)]
+enum BrewConflictValue {
+    Name(String),
+    Table {
+        name: String,
+        because: Option<String>,
+    },
+}
+
+impl From<BrewConflictValue> for BrewConflict {
+    fn from(value: BrewConflictValue) -> Self {
+        match value {
+            BrewConflictValue::Name(name) => BrewConflict {
+                name,
+                because: None,
+            },
+            BrewConflictValue::Table { name, because } => BrewConflict { name, because },
+        }
+    }
+}
+
+/// Reason of `keg_only`, either one of the reasons Homebrew knows or a free-form one
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(from = "String")]
+pub struct BrewKegOnly {
+    pub symbol: Option<String>,
+    pub reason: Option<String>,
+}
+
+impl From<String> for BrewKegOnly {
+    fn from(value: String) -> Self {
+        let symbol = value.trim_start_matches(':');
+        if BREW_KEG_ONLY_REASONS.contains(&symbol) {
+            BrewKegOnly {
+                symbol: Some(symbol.to_owned()),
+                reason: None,
+            }
+        } else {
+            BrewKegOnly {
+                symbol: None,
+                reason: Some(value),
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct CommitterConfig {
     pub email: String,
     pub name: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct PullRequestConfig {
     pub title: Option<String>,
     pub body: Option<String>,
@@ -104,14 +1337,358 @@ impl PullRequestConfig {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ReleaseConfig {
     pub owner: String,
     pub repo: String,
     pub target_branch: String,
-    #[serde(default)]
-    pub prerelease: bool,
+    /// Handlebars template for the release title
+    pub name: Option<String>,
+    /// Overrides the prerelease detection from the tag
+    pub prerelease: Option<bool>,
     #[serde(default)]
     pub draft: bool,
+    /// Whether the release is marked as the latest one, GitHub decides when unset
+    #[schemars(with = "Option<MakeLatestValue>")]
+    pub make_latest: Option<MakeLatest>,
+    /// Open a discussion for the release in this category
+    pub discussion_category_name: Option<String>,
+    /// Handlebars template for the release body, defaults to the changelog
     pub body: Option<String>,
+    /// Where the notes rendered as `{{ changelog }}` come from
+    #[serde(default)]
+    pub body_source: BodySource,
+    /// Glob patterns of additional files to upload to the release
+    #[serde(default)]
+    pub extra_assets: Vec<String>,
+    /// What to do when a release already exists for the tag
+    #[serde(default)]
+    pub existing: ExistingRelease,
+    /// What to delete when the release, the uploads or the formulas fail
+    #[serde(default)]
+    pub rollback: Rollback,
+    #[serde(default)]
+    pub upload: UploadConfig,
+    /// Clean up prereleases superseded by a stable release
+    pub prune: Option<PruneConfig>,
+    pub provenance: Option<ProvenanceConfig>,
+    pub changelog: Option<ChangelogConfig>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase", try_from = "MakeLatestValue")]
+pub enum MakeLatest {
+    True,
+    False,
+    /// Latest by creation date and semantic version
+    Legacy,
+}
+
+/// Accepts both `make_latest: false` and `make_latest: "legacy"`
+#[derive(Deserialize, JsonSchema)]
+#[serde(untagged)]
+enum MakeLatestValue {
+    Bool(bool),
+    String(String),
+}
+
+impl TryFrom<MakeLatestValue> for MakeLatest {
+    type Error = String;
+
+    fn try_from(value: MakeLatestValue) -> Result<Self, Self::Error> {
+        match value {
+            MakeLatestValue::Bool(true) => Ok(MakeLatest::True),
+            MakeLatestValue::Bool(false) => Ok(MakeLatest::False),
+            MakeLatestValue::String(value) => match value.as_str() {
+                "true" => Ok(MakeLatest::True),
+                "false" => Ok(MakeLatest::False),
+                "legacy" => Ok(MakeLatest::Legacy),
+                _ => Err(format!(
+                    "invalid make_latest value `{}`, expected true, false or legacy",
+                    value
+                )),
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum BodySource {
+    /// Conventional commits changelog, when `release.changelog` is set
+    #[default]
+    Changelog,
+    /// Notes generated by GitHub from merged pull requests
+    Github,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct PruneConfig {
+    #[serde(default)]
+    pub action: PruneAction,
+    /// Also prune drafts of the same version
+    #[serde(default)]
+    pub drafts: bool,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum PruneAction {
+    #[default]
+    Delete,
+    /// Keep the release but point to the stable one in its name and body
+    Mark,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct UploadConfig {
+    /// Number of retries after a failed asset upload
+    #[serde(default = "UploadConfig::default_retries")]
+    pub retries: u32,
+    /// Delay before the first retry in milliseconds, doubled after each attempt
+    #[serde(default = "UploadConfig::default_backoff")]
+    pub backoff: u64,
+    #[serde(default = "UploadConfig::default_max_backoff")]
+    pub max_backoff: u64,
+}
+
+impl UploadConfig {
+    fn default_retries() -> u32 {
+        UPLOAD_DEFAULT_RETRIES
+    }
+
+    fn default_backoff() -> u64 {
+        UPLOAD_DEFAULT_BACKOFF_MS
+    }
+
+    fn default_max_backoff() -> u64 {
+        UPLOAD_DEFAULT_MAX_BACKOFF_MS
+    }
+}
+
+impl Default for UploadConfig {
+    fn default() -> Self {
+        UploadConfig {
+            retries: UploadConfig::default_retries(),
+            backoff: UploadConfig::default_backoff(),
+            max_backoff: UploadConfig::default_max_backoff(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ExistingRelease {
+    /// Abort the release
+    Fail,
+    /// Upload to the existing release without touching it
+    #[default]
+    Keep,
+    /// Update the name and body, and replace assets with the same name
+    Replace,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Rollback {
+    /// Leave everything published so the run can be resumed
+    #[default]
+    None,
+    /// Delete the release created by the run
+    Release,
+    /// Also delete the tag and the pull request branches created by the run
+    All,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct ChangelogConfig {
+    /// Path to a handlebars template replacing the built-in one
+    pub template: Option<String>,
+    /// Keep commits that don't follow the conventional commits format
+    #[serde(default)]
+    pub include_other: bool,
+    /// Thank the authors of the release, resolved to their GitHub logins
+    #[serde(default)]
+    pub contributors: bool,
+    /// Only keep the commits touching these paths, e.g. the crate of a monorepo
+    #[serde(default)]
+    pub paths: Vec<String>,
+    /// Keep a changelog file up to date in the repository
+    pub file: Option<ChangelogFileConfig>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ChangelogFileConfig {
+    #[serde(default = "ChangelogFileConfig::default_path")]
+    pub path: String,
+    /// Template for the section added for each release
+    pub template: Option<String>,
+    /// Branch to commit to, defaults to the release target branch
+    pub branch: Option<String>,
+    #[serde(default = "ChangelogFileConfig::default_commit_message")]
+    pub commit_message: String,
+    pub commit_author: Option<CommitterConfig>,
+    pub pull_request: Option<PullRequestConfig>,
+}
+
+impl ChangelogFileConfig {
+    fn default_path() -> String {
+        CHANGELOG_DEFAULT_PATH.to_owned()
+    }
+
+    fn default_commit_message() -> String {
+        CHANGELOG_DEFAULT_COMMIT_MESSAGE.to_owned()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ProvenanceConfig {
+    #[serde(default = "ProvenanceConfig::default_builder_id")]
+    pub builder_id: String,
+}
+
+impl ProvenanceConfig {
+    fn default_builder_id() -> String {
+        PROVENANCE_DEFAULT_BUILDER_ID.to_owned()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct TagConfig {
+    /// Prepended to the version to name the tag, e.g. `cli-v` to release one crate of a
+    /// monorepo; only the tags with this prefix, the `v` being optional, are released
+    #[serde(default = "TagConfig::default_prefix")]
+    pub prefix: String,
+    /// Message of the annotated tag, `{{version}}` is replaced by the version
+    #[serde(default = "TagConfig::default_message")]
+    pub message: String,
+    /// Remote the tag is pushed to
+    #[serde(default = "TagConfig::default_remote")]
+    pub remote: String,
+    /// Regular expression released tags must match
+    #[serde(default = "TagConfig::default_pattern")]
+    pub pattern: String,
+    /// Sign created tags with GPG
+    #[serde(default)]
+    pub sign: bool,
+    /// Key signing the tags, defaults to the `user.signingkey` of git
+    pub signing_key: Option<String>,
+    /// Refuse to release tags without a valid signature
+    #[serde(default)]
+    pub verify: bool,
+}
+
+impl TagConfig {
+    fn default_prefix() -> String {
+        TAG_DEFAULT_PREFIX.to_owned()
+    }
+
+    fn default_message() -> String {
+        TAG_DEFAULT_MESSAGE.to_owned()
+    }
+
+    fn default_remote() -> String {
+        TAG_DEFAULT_REMOTE.to_owned()
+    }
+
+    fn default_pattern() -> String {
+        TAG_DEFAULT_PATTERN.to_owned()
+    }
+}
+
+impl Default for TagConfig {
+    fn default() -> Self {
+        TagConfig {
+            prefix: TagConfig::default_prefix(),
+            message: TagConfig::default_message(),
+            remote: TagConfig::default_remote(),
+            pattern: TagConfig::default_pattern(),
+            sign: false,
+            signing_key: None,
+            verify: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct GithubConfig {
+    /// File holding the token, used when no token is found in the environment or `gh`
+    pub token_file: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct HttpConfig {
+    /// Proxy of all requests, e.g. `http://proxy.internal:3128`; defaults to `HTTPS_PROXY`,
+    /// hosts in `NO_PROXY` are reached directly either way
+    pub proxy: Option<String>,
+    /// PEM file of CA certificates trusted besides the system ones, e.g. those of a TLS
+    /// inspecting proxy; defaults to `SSL_CERT_FILE`
+    pub ca_bundle: Option<String>,
+    /// Seconds allowed to open a connection
+    #[serde(default = "HttpConfig::default_connect_timeout")]
+    pub connect_timeout: u64,
+    /// Seconds allowed for an API request, from sending it to reading the whole response
+    #[serde(default = "HttpConfig::default_timeout")]
+    pub timeout: u64,
+    /// Seconds allowed for an asset upload or download, which replaces `timeout` for them
+    #[serde(default = "HttpConfig::default_transfer_timeout")]
+    pub transfer_timeout: u64,
+    /// Seconds between TCP keep-alive probes of open connections, 0 to disable them
+    #[serde(default = "HttpConfig::default_tcp_keepalive")]
+    pub tcp_keepalive: u64,
+    /// Seconds an idle connection is kept for reuse by later requests
+    #[serde(default = "HttpConfig::default_pool_idle_timeout")]
+    pub pool_idle_timeout: u64,
+    /// Idle connections kept per host, unlimited by default
+    pub pool_max_idle_per_host: Option<usize>,
+    /// Negotiate HTTP/2 with the servers supporting it instead of always using HTTP/1.1
+    #[serde(default)]
+    pub http2: bool,
+    /// Revalidate the GitHub data fetched by previous runs with its ETag instead of fetching it
+    /// again
+    #[serde(default = "default_true")]
+    pub cache: bool,
+    /// Directory of the cached responses; defaults to `rustreleaser/http` in the user cache
+    /// directory
+    pub cache_dir: Option<String>,
+}
+
+impl HttpConfig {
+    fn default_connect_timeout() -> u64 {
+        HTTP_DEFAULT_CONNECT_TIMEOUT_SECS
+    }
+
+    fn default_timeout() -> u64 {
+        HTTP_DEFAULT_TIMEOUT_SECS
+    }
+
+    fn default_transfer_timeout() -> u64 {
+        HTTP_DEFAULT_TRANSFER_TIMEOUT_SECS
+    }
+
+    fn default_tcp_keepalive() -> u64 {
+        HTTP_DEFAULT_TCP_KEEPALIVE_SECS
+    }
+
+    fn default_pool_idle_timeout() -> u64 {
+        HTTP_DEFAULT_POOL_IDLE_TIMEOUT_SECS
+    }
+}
+
+impl Default for HttpConfig {
+    fn default() -> Self {
+        HttpConfig {
+            proxy: None,
+            ca_bundle: None,
+            connect_timeout: HttpConfig::default_connect_timeout(),
+            timeout: HttpConfig::default_timeout(),
+            transfer_timeout: HttpConfig::default_transfer_timeout(),
+            tcp_keepalive: HttpConfig::default_tcp_keepalive(),
+            pool_idle_timeout: HttpConfig::default_pool_idle_timeout(),
+            pool_max_idle_per_host: None,
+            http2: false,
+            cache: default_true(),
+            cache_dir: None,
+        }
+    }
 }
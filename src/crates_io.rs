@@ -0,0 +1,187 @@
+use crate::{config::CratesIoConfig, github::tag::Tag};
+use anyhow::{bail, Context, Result};
+use itertools::Itertools;
+use serde::Deserialize;
+use std::{collections::HashMap, path::Path};
+use tokio::process::Command;
+
+/// Output of `cargo metadata --no-deps`
+#[derive(Deserialize)]
+struct Metadata {
+    packages: Vec<Package>,
+    workspace_members: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct Package {
+    id: String,
+    name: String,
+    version: String,
+    /// Registries the package may be published to, empty with `publish = false`
+    publish: Option<Vec<String>>,
+    dependencies: Vec<Dependency>,
+}
+
+#[derive(Deserialize)]
+struct Dependency {
+    name: String,
+    kind: Option<String>,
+    /// Set for the dependencies on other packages of the workspace
+    path: Option<String>,
+}
+
+/// Publish the configured packages, or else every publishable package of the workspace, after
+/// the packages they depend on. In a dry run, `cargo publish --dry-run` packages and verifies them
+/// without uploading.
+pub async fn publish(config: &CratesIoConfig, tag: &Tag, path: &Path, dry_run: bool) -> Result<()> {
+    let metadata = metadata(path).await?;
+    let packages = order(select(config, &metadata)?)?;
+
+    let mismatches = packages
+        .iter()
+        .filter(|package| package.version != tag.version())
+        .map(|package| format!("{} {}", package.name, package.version))
+        .collect::<Vec<_>>();
+    if !mismatches.is_empty() {
+        bail!(
+            "the version of {} doesn't match the tag {}",
+            mismatches.join(", "),
+            tag.value()
+        );
+    }
+
+    // the packages depending on others of the workspace only resolve once those are on the
+    // registry, unless cargo packages them together
+    if dry_run {
+        log::info!("Checking that the packages can be published");
+        return cargo_publish(config, path, &packages, true).await;
+    }
+    for package in packages {
+        log::info!("Publishing {} to crates.io", package.name);
+        cargo_publish(config, path, &[package], false).await?;
+    }
+    Ok(())
+}
+
+async fn cargo_publish(
+    config: &CratesIoConfig,
+    path: &Path,
+    packages: &[&Package],
+    dry_run: bool,
+) -> Result<()> {
+    let mut cmd = Command::new("cargo");
+    cmd.arg("publish").current_dir(path);
+    if config.allow_dirty.unwrap_or(false) {
+        cmd.arg("--allow-dirty");
+    }
+    if config.no_verify.unwrap_or(false) {
+        cmd.arg("--no-verify");
+    }
+    if let Some(registry) = &config.registry {
+        cmd.arg("--registry").arg(registry);
+    }
+    if let Some(index) = &config.index {
+        cmd.arg("--index").arg(index);
+    }
+    if dry_run {
+        cmd.arg("--dry-run");
+    }
+    for package in packages {
+        cmd.arg("--package").arg(&package.name);
+    }
+
+    log::debug!("Running {:?}", cmd.as_std());
+    let status = cmd.status().await?;
+    if !status.success() {
+        bail!(
+            "cargo publish failed for {} with {}",
+            packages.iter().map(|package| &package.name).join(", "),
+            status
+        );
+    }
+    Ok(())
+}
+
+async fn metadata(path: &Path) -> Result<Metadata> {
+    let output = Command::new("cargo")
+        .args(["metadata", "--format-version", "1", "--no-deps"])
+        .current_dir(path)
+        .output()
+        .await
+        .context("failed to run cargo metadata")?;
+    if !output.status.success() {
+        bail!(
+            "cargo metadata failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+    serde_json::from_slice(&output.stdout).context("failed to parse the output of cargo metadata")
+}
+
+/// The configured packages, or else the workspace members not marked `publish = false`
+fn select<'a>(config: &CratesIoConfig, metadata: &'a Metadata) -> Result<Vec<&'a Package>> {
+    let members = metadata
+        .packages
+        .iter()
+        .filter(|package| metadata.workspace_members.contains(&package.id))
+        .collect::<Vec<_>>();
+
+    if config.packages.is_empty() {
+        return Ok(members
+            .into_iter()
+            .filter(|package| {
+                package
+                    .publish
+                    .as_ref()
+                    .is_none_or(|registries| !registries.is_empty())
+            })
+            .collect());
+    }
+
+    config
+        .packages
+        .iter()
+        .map(|name| {
+            members
+                .iter()
+                .find(|package| &package.name == name)
+                .copied()
+                .with_context(|| {
+                    format!("crates_io.packages: no package {} in the workspace", name)
+                })
+        })
+        .collect()
+}
+
+/// `packages` with the ones they depend on first, as crates.io needs the dependencies to exist
+fn order(packages: Vec<&Package>) -> Result<Vec<&Package>> {
+    let by_name = packages
+        .iter()
+        .map(|package| (package.name.as_str(), *package))
+        .collect::<HashMap<_, _>>();
+
+    let mut ordered: Vec<&Package> = Vec::with_capacity(packages.len());
+    let mut remaining = packages;
+    while !remaining.is_empty() {
+        // dev-dependencies are removed when publishing and may form cycles
+        let (ready, blocked): (Vec<_>, Vec<_>) = remaining.into_iter().partition(|package| {
+            package
+                .dependencies
+                .iter()
+                .filter(|dependency| {
+                    dependency.path.is_some() && dependency.kind.as_deref() != Some("dev")
+                })
+                .filter_map(|dependency| by_name.get(dependency.name.as_str()))
+                .all(|dependency| ordered.iter().any(|done| done.id == dependency.id))
+        });
+        if ready.is_empty() {
+            bail!(
+                "the dependencies of {} form a cycle",
+                blocked.iter().map(|package| &package.name).join(", ")
+            );
+        }
+        ordered.extend(ready);
+        remaining = blocked;
+    }
+    Ok(ordered)
+}
@@ -0,0 +1,140 @@
+use crate::{
+    check,
+    cli::{Opts, RenderTarget},
+    config::ReleaserConfig,
+    git,
+    github::auth,
+    render,
+};
+use anyhow::{bail, Result};
+use clap::ValueEnum;
+
+/// Outcome of a check of the environment
+enum Outcome {
+    Pass(String),
+    Fail(String),
+    Skip(String),
+}
+
+/// Check that everything a release needs is in place, from the git repository to the GitHub
+/// access, and print a report of what passed and what failed
+pub async fn doctor(config: &ReleaserConfig, opts: &Opts, offline: bool) -> Result<()> {
+    let mut report = vec![];
+
+    report.push((
+        "git repository",
+        match git::get_head_commit(&opts.path) {
+            Ok(sha) => Outcome::Pass(format!("HEAD at {}", &sha[..7.min(sha.len())])),
+            Err(err) => Outcome::Fail(format!("{:#}", err)),
+        },
+    ));
+    report.push((
+        "tag",
+        match git::get_current_tag(&opts.path, &config.tag.prefix) {
+            Ok(tag) => Outcome::Pass(format!("latest tag {}", tag.value())),
+            Err(err) => Outcome::Fail(format!(
+                "{:#}, create one with `rr bump` or `rr --create-tag`",
+                err
+            )),
+        },
+    ));
+
+    let problems = check::validate(config);
+    report.push((
+        "config",
+        match problems.is_empty() {
+            true => Outcome::Pass("valid".to_owned()),
+            false => Outcome::Fail(problems.join("; ")),
+        },
+    ));
+    report.push(("templates", templates(config, opts)));
+
+    report.push((
+        "toolchains",
+        match config.build.missing_toolchains().await {
+            Ok(missing) if missing.is_empty() => Outcome::Pass(format!(
+                "{} target(s) installed",
+                config.build.targets().len()
+            )),
+            Ok(missing) => Outcome::Fail(format!(
+                "missing {}, install them with `rustup target add`",
+                missing.join(", ")
+            )),
+            Err(err) => Outcome::Fail(format!("{:#}", err)),
+        },
+    ));
+
+    if offline {
+        report.push(("github token", Outcome::Skip("offline".to_owned())));
+        report.push(("github access", Outcome::Skip("offline".to_owned())));
+    } else {
+        report.push((
+            "github token",
+            match auth::token().await {
+                Ok(_) => Outcome::Pass("found".to_owned()),
+                Err(err) => Outcome::Fail(format!("{:#}", err)),
+            },
+        ));
+        let problems = check::check_access(config).await;
+        report.push((
+            "github access",
+            match problems.is_empty() {
+                true => Outcome::Pass("can push to the release and tap repositories".to_owned()),
+                false => Outcome::Fail(problems.join("; ")),
+            },
+        ));
+    }
+
+    let width = report.iter().map(|(name, _)| name.len()).max().unwrap_or(0);
+    let mut failed = 0;
+    for (name, outcome) in &report {
+        let (status, detail) = match outcome {
+            Outcome::Pass(detail) => ("pass", detail),
+            Outcome::Fail(detail) => {
+                failed += 1;
+                ("FAIL", detail)
+            }
+            Outcome::Skip(detail) => ("skip", detail),
+        };
+        println!("[{}] {:<width$}  {}", status, name, detail, width = width);
+    }
+
+    if failed > 0 {
+        bail!("{} of {} check(s) failed", failed, report.len());
+    }
+    Ok(())
+}
+
+/// Render the templates a release of the current tag uses
+fn templates(config: &ReleaserConfig, opts: &Opts) -> Outcome {
+    let mut targets = vec![RenderTarget::Release];
+    if config.release.changelog.is_some() {
+        targets.push(RenderTarget::Notes);
+    }
+    if config
+        .release
+        .changelog
+        .as_ref()
+        .is_some_and(|changelog| changelog.file.is_some())
+    {
+        targets.push(RenderTarget::ChangelogFile);
+    }
+    if !config.brew.is_empty() {
+        targets.push(RenderTarget::Brew);
+    }
+
+    let mut failures = vec![];
+    for target in &targets {
+        if let Err(err) = render::rendered(config, opts, *target, None) {
+            let name = target
+                .to_possible_value()
+                .map(|value| value.get_name().to_owned())
+                .unwrap_or_default();
+            failures.push(format!("{}: {:#}", name, err));
+        }
+    }
+    match failures.is_empty() {
+        true => Outcome::Pass(format!("{} template(s) rendered", targets.len())),
+        false => Outcome::Fail(failures.join("; ")),
+    }
+}
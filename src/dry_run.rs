@@ -0,0 +1,45 @@
+use crate::config::PullRequestConfig;
+use anyhow::Result;
+use serde::Serialize;
+use std::{fs, path::Path};
+
+/// Directory of the output path collecting what a dry run would have sent to GitHub
+const DRY_RUN_DIR: &str = "dry-run";
+
+/// A file a dry run would have committed, directly or through a pull request
+#[derive(Debug, Serialize)]
+pub struct FileChange<'a> {
+    pub owner: &'a str,
+    pub repo: &'a str,
+    pub branch: &'a str,
+    pub path: &'a str,
+    pub message: &'a str,
+    pub content: &'a str,
+    pub pull_request: Option<&'a PullRequestConfig>,
+}
+
+/// Write the payload of a skipped remote call to the dry run directory
+pub fn record<T: Serialize>(output_path: &Path, name: &str, what: &str, payload: &T) -> Result<()> {
+    let dir = output_path.join(DRY_RUN_DIR);
+    fs::create_dir_all(&dir)?;
+
+    let path = dir.join(format!("{}.json", name));
+    fs::write(&path, serde_json::to_string_pretty(payload)?)?;
+
+    log::info!("Would {}, see {}", what, path.display());
+    Ok(())
+}
+
+pub fn record_file_change(output_path: &Path, change: &FileChange) -> Result<()> {
+    let what = match change.pull_request {
+        Some(pull_request) => format!(
+            "open a pull request updating {} on {}/{}:{}",
+            change.path, change.owner, change.repo, pull_request.head
+        ),
+        None => format!(
+            "commit {} to {}/{}:{}",
+            change.path, change.owner, change.repo, change.branch
+        ),
+    };
+    record(output_path, &change.path.replace('/', "_"), &what, change)
+}
@@ -0,0 +1,75 @@
+use crate::{cli::ErrorFormat, http::error_response::ErrorResponse};
+use serde_json::json;
+use std::process::ExitCode;
+use thiserror::Error;
+
+/// Exit code of the failures that don't fall in a [`Failure`] class
+const DEFAULT_EXIT_CODE: u8 = 1;
+
+/// Class of the failure ending a run, which CI can branch on through the exit code. Errors are
+/// marked with it as context where they happen, e.g. `.context(Failure::Build)`
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Failure {
+    #[error("invalid configuration")]
+    Config,
+    #[error("build failed")]
+    Build,
+    #[error("upload failed")]
+    Upload,
+    #[error("GitHub API error")]
+    Forge,
+}
+
+impl Failure {
+    /// Class of `err`: the outermost failure it was marked with, or a failed GitHub API call,
+    /// read through the macros or with `error_for_status`
+    pub fn of(err: &anyhow::Error) -> Option<Failure> {
+        err.downcast_ref::<Failure>()
+            .copied()
+            .or_else(|| err.downcast_ref::<ErrorResponse>().map(|_| Failure::Forge))
+            .or_else(|| {
+                err.downcast_ref::<reqwest::Error>()
+                    .and_then(reqwest::Error::status)
+                    .map(|_| Failure::Forge)
+            })
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Failure::Config => "config",
+            Failure::Build => "build",
+            Failure::Upload => "upload",
+            Failure::Forge => "forge",
+        }
+    }
+
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            Failure::Config => 2,
+            Failure::Build => 3,
+            Failure::Upload => 4,
+            Failure::Forge => 5,
+        }
+    }
+}
+
+/// Print the error ending the run in `format` to stderr and exit with the code of its class
+pub fn report(err: &anyhow::Error, format: ErrorFormat) -> ExitCode {
+    let failure = Failure::of(err);
+    let code = failure.map_or(DEFAULT_EXIT_CODE, |failure| failure.exit_code());
+    match format {
+        ErrorFormat::Text => eprintln!("Error: {:?}", err),
+        ErrorFormat::Json => eprintln!(
+            "{}",
+            json!({
+                "error": {
+                    "kind": failure.map_or("other", |failure| failure.name()),
+                    "exit_code": code,
+                    "message": err.to_string(),
+                    "causes": err.chain().skip(1).map(ToString::to_string).collect::<Vec<_>>(),
+                }
+            })
+        ),
+    }
+    ExitCode::from(code)
+}
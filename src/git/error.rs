@@ -0,0 +1,33 @@
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum GitError {
+    #[error(transparent)]
+    Git(#[from] git2::Error),
+    #[error("invalid version: {0}")]
+    Version(#[from] semver::Error),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error("no tags found")]
+    NoTags,
+    #[error("tag {0} not found")]
+    TagNotFound(String),
+    #[error("tag {0} already exists on another commit")]
+    TagExists(String),
+    #[error("HEAD is not on a branch")]
+    DetachedHead,
+    #[error("tag {0} is not annotated")]
+    NotAnnotated(String),
+    #[error("failed to read the package version of {path}: {message}")]
+    Manifest { path: String, message: String },
+    #[error("failed to push {refspec} to {remote}: {message}")]
+    Push {
+        remote: String,
+        refspec: String,
+        message: String,
+    },
+    #[error("failed to sign tag {tag}: {message}")]
+    Sign { tag: String, message: String },
+    #[error("tag {tag} has no valid signature: {message}")]
+    Signature { tag: String, message: String },
+}
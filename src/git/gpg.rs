@@ -0,0 +1,74 @@
+use super::error::GitError;
+use std::{env, fs, process::Stdio};
+use tokio::{io::AsyncWriteExt, process::Command};
+
+const SIGNATURE_HEADER: &str = "-----BEGIN PGP SIGNATURE-----";
+
+/// Armored detached signature of `payload`
+pub async fn sign(
+    program: &str,
+    key: Option<&str>,
+    tag: &str,
+    payload: &str,
+) -> Result<String, GitError> {
+    let mut cmd = Command::new(program);
+    cmd.arg("--detach-sign").arg("--armor");
+    if let Some(key) = key {
+        cmd.arg("--local-user").arg(key);
+    }
+
+    let output = run(cmd, payload).await?;
+    if !output.status.success() {
+        return Err(GitError::Sign {
+            tag: tag.to_owned(),
+            message: String::from_utf8_lossy(&output.stderr).trim().to_owned(),
+        });
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Check the signature appended to the content of a tag object
+pub async fn verify(program: &str, tag: &str, content: &str) -> Result<(), GitError> {
+    let Some(start) = content.find(SIGNATURE_HEADER) else {
+        return Err(GitError::Signature {
+            tag: tag.to_owned(),
+            message: "the tag is not signed".to_owned(),
+        });
+    };
+    let (payload, signature) = content.split_at(start);
+
+    // gpg reads the detached signature from a file and the signed data from stdin
+    let signature_path =
+        env::temp_dir().join(format!("rustreleaser-{}.sig", tag.replace('/', "-")));
+    fs::write(&signature_path, signature)?;
+
+    let mut cmd = Command::new(program);
+    cmd.arg("--verify").arg(&signature_path).arg("-");
+    let output = run(cmd, payload).await;
+    fs::remove_file(&signature_path)?;
+
+    let output = output?;
+    if !output.status.success() {
+        return Err(GitError::Signature {
+            tag: tag.to_owned(),
+            message: String::from_utf8_lossy(&output.stderr).trim().to_owned(),
+        });
+    }
+
+    Ok(())
+}
+
+async fn run(mut cmd: Command, input: &str) -> Result<std::process::Output, GitError> {
+    let mut child = cmd
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+
+    let mut stdin = child.stdin.take().expect("stdin is piped");
+    stdin.write_all(input.as_bytes()).await?;
+    drop(stdin);
+
+    Ok(child.wait_with_output().await?)
+}
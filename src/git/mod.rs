@@ -1,27 +1,562 @@
-use std::path::PathBuf;
+pub mod error;
+mod gpg;
 
-use crate::github::tag::Tag;
-use anyhow::{bail, Result};
-use git2::Repository;
+use std::{
+    collections::HashSet,
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+use self::error::GitError;
+use crate::{
+    config::TagConfig,
+    github::tag::{self, Tag},
+};
+use git2::{
+    Config, Cred, CredentialType, DiffOptions, ObjectType, PushOptions, RemoteCallbacks,
+    Repository, Signature, Sort, StatusOptions,
+};
 use itertools::Itertools;
 use log::debug;
-use semver::Version;
+use semver::{BuildMetadata, Prerelease, Version};
+use serde::Serialize;
+
+type Result<T> = std::result::Result<T, GitError>;
+
+/// Pre-release identifier of the versions synthesized for untagged snapshots
+const SNAPSHOT_PRERELEASE: &str = "next";
+/// Uncommitted files listed when refusing to release a dirty tree
+const DIRTY_FILES_SHOWN: usize = 5;
+/// Credentials tried before giving up on a push, libgit2 asks again after each rejection
+const PUSH_CREDENTIAL_ATTEMPTS: usize = 3;
+const GPG_DEFAULT_PROGRAM: &str = "gpg";
+
+/// A commit of the repository history
+#[derive(Debug, Clone)]
+pub struct Commit {
+    pub sha: String,
+    pub author: String,
+    pub email: String,
+    pub message: String,
+    pub trailers: Vec<Trailer>,
+}
+
+/// A `Key: value` line closing a commit message, e.g. `Co-authored-by` or `Closes`
+#[derive(Debug, Clone, Serialize)]
+pub struct Trailer {
+    pub key: String,
+    pub value: String,
+}
+
+impl Commit {
+    pub fn short_sha(&self) -> &str {
+        &self.sha[..7.min(self.sha.len())]
+    }
 
-pub fn get_current_tag(base: &PathBuf) -> Result<Tag> {
+    /// First line of the message
+    pub fn summary(&self) -> &str {
+        self.message.lines().next().unwrap_or_default()
+    }
+}
+
+pub fn get_head_commit(base: &PathBuf) -> Result<String> {
     let repo = Repository::open(base)?;
+    let commit = repo.head()?.peel_to_commit()?;
 
-    let binding = repo
-        .tag_names(None)?
-        .into_iter()
-        .filter_map(|t| t.map(|t| t.to_string().trim_start_matches("v").to_string()))
-        .filter_map(|t| Version::parse(&t).ok())
-        .sorted_by(|a, b| a.cmp(b));
-    let tag = match binding.last() {
-        Some(tag) => tag,
-        None => bail!(anyhow::anyhow!("No tags found")),
+    debug!("head commit: {}", commit.id());
+
+    Ok(commit.id().to_string())
+}
+
+/// Modified and untracked files of the working tree
+pub fn uncommitted_files(base: &PathBuf) -> Result<Vec<String>> {
+    let repo = Repository::open(base)?;
+
+    let mut options = StatusOptions::new();
+    options.include_untracked(true).include_ignored(false);
+    let files = repo
+        .statuses(Some(&mut options))?
+        .iter()
+        .filter_map(|entry| entry.path().map(str::to_owned))
+        .collect();
+
+    Ok(files)
+}
+
+/// Commit `paths`, relative to `base`, on the current branch
+pub fn commit(base: &PathBuf, paths: &[&str], message: &str) -> Result<()> {
+    let repo = Repository::open(base)?;
+    let workdir = repo
+        .workdir()
+        .ok_or_else(|| git2::Error::from_str("bare repositories can't be committed to"))?
+        .canonicalize()?;
+
+    let mut index = repo.index()?;
+    for path in paths {
+        let path = base.join(path).canonicalize()?;
+        let relative = path
+            .strip_prefix(&workdir)
+            .map_err(|_| git2::Error::from_str("path outside of the repository"))?;
+        index.add_path(relative)?;
+    }
+    index.write()?;
+
+    let tree = repo.find_tree(index.write_tree()?)?;
+    let signature = repo.signature()?;
+    let parent = repo.head()?.peel_to_commit()?;
+    let oid = repo.commit(
+        Some("HEAD"),
+        &signature,
+        &signature,
+        message,
+        &tree,
+        &[&parent],
+    )?;
+
+    debug!("committed {}", oid);
+    Ok(())
+}
+
+/// Push the current branch to `remote`
+pub fn push_branch(base: &PathBuf, remote: &str) -> Result<()> {
+    let repo = Repository::open(base)?;
+    let head = repo.head()?;
+    let branch = match head.name() {
+        Some(name) if head.is_branch() => name.to_owned(),
+        _ => return Err(GitError::DetachedHead),
+    };
+
+    log::info!("Pushing {} to {}", branch, remote);
+    push(&repo, remote, &branch)
+}
+
+/// What makes HEAD differ from what `remote` has: uncommitted changes and unpushed commits
+pub fn unpublished_changes(base: &PathBuf, remote: &str) -> Result<Vec<String>> {
+    let repo = Repository::open(base)?;
+    let mut problems = vec![];
+
+    let changed = uncommitted_files(base)?;
+    if !changed.is_empty() {
+        let mut files = changed.iter().take(DIRTY_FILES_SHOWN).join(", ");
+        if changed.len() > DIRTY_FILES_SHOWN {
+            files.push_str(&format!(" and {} more", changed.len() - DIRTY_FILES_SHOWN));
+        }
+        problems.push(format!("uncommitted changes in {}", files));
+    }
+
+    let head = repo.head()?.peel_to_commit()?.id();
+    let mut branches = repo
+        .references_glob(&format!("refs/remotes/{}/*", remote))?
+        .filter_map(|reference| reference.ok()?.resolve().ok()?.target())
+        .peekable();
+    if branches.peek().is_none() {
+        log::warn!(
+            "No branch of {} is known, skipping the unpushed commits check",
+            remote
+        );
+        return Ok(problems);
+    }
+    let mut pushed = false;
+    for branch in branches {
+        if branch == head || repo.graph_descendant_of(branch, head)? {
+            pushed = true;
+            break;
+        }
+    }
+    if !pushed {
+        problems.push(format!(
+            "commits not pushed to {} (HEAD {})",
+            remote,
+            &head.to_string()[..7]
+        ));
+    }
+
+    Ok(problems)
+}
+
+/// Latest tag named with `prefix`
+pub fn get_current_tag(base: &PathBuf, prefix: &str) -> Result<Tag> {
+    let repo = Repository::open(base)?;
+    let scope = tag::scope(prefix);
+
+    let tag = match semver_tags(&repo, scope)?.pop() {
+        Some((_, name)) => name,
+        None => return Err(GitError::NoTags),
     };
 
     debug!("tag: {}", tag);
 
-    Ok(Tag::new(tag.to_string()))
+    Ok(Tag::with_scope(tag, scope))
+}
+
+/// Tag of HEAD, or the next patch of the latest tag marked as a snapshot, e.g. `v0.4.3-next+gabc1234`
+pub fn get_snapshot_tag(base: &PathBuf, prefix: &str) -> Result<Tag> {
+    let repo = Repository::open(base)?;
+    let head = repo.head()?.peel_to_commit()?.id();
+    let scope = tag::scope(prefix);
+
+    let mut tags = semver_tags(&repo, scope)?;
+    for (_, name) in tags.iter().rev() {
+        if resolve_tag_name(&repo, name)? == head {
+            debug!("HEAD is tagged {}", name);
+            return Ok(Tag::with_scope(name, scope));
+        }
+    }
+
+    let mut version = tags
+        .pop()
+        .map(|(version, _)| version)
+        .unwrap_or_else(|| Version::new(0, 0, 0));
+    version.patch += 1;
+    version.pre = Prerelease::new(SNAPSHOT_PRERELEASE)?;
+    version.build = BuildMetadata::new(&format!("g{}", &head.to_string()[..7]))?;
+
+    debug!("snapshot tag: {}{}", prefix, version);
+
+    Ok(Tag::with_scope(format!("{}{}", prefix, version), scope))
+}
+
+/// Create an annotated tag of HEAD for `version`, or the Cargo.toml version, and push it
+/// unless `push` is false. Also returns whether the tag was created, HEAD may already have it
+pub async fn create_tag(
+    base: &PathBuf,
+    version: Option<&str>,
+    config: &TagConfig,
+    push: bool,
+    dry_run: bool,
+) -> Result<(Tag, bool)> {
+    let version = match version {
+        Some(version) => Version::parse(version.trim_start_matches('v'))?,
+        None => package_version(base)?,
+    };
+    let name = format!("{}{}", config.prefix, version);
+    let message = config.message.replace("{{version}}", &version.to_string());
+
+    let repo = Repository::open(base)?;
+    let head = repo.head()?.peel_to_commit()?;
+    if let Ok(existing) = repo.revparse_single(&format!("refs/tags/{}", name)) {
+        if existing.peel_to_commit()?.id() != head.id() {
+            return Err(GitError::TagExists(name));
+        }
+        log::info!("HEAD is already tagged {}", name);
+        return Ok((Tag::with_prefix(name, &config.prefix), false));
+    }
+
+    if dry_run {
+        log::info!("Would create tag {} and push it to {}", name, config.remote);
+        return Ok((Tag::with_prefix(name, &config.prefix), false));
+    }
+
+    let tagger = repo.signature()?;
+    if config.sign {
+        log::info!("Creating signed tag {}", name);
+        let repo_config = repo.config()?;
+        let key = config
+            .signing_key
+            .to_owned()
+            .or_else(|| repo_config.get_string("user.signingkey").ok());
+        let payload = format!(
+            "object {}\ntype commit\ntag {}\ntagger {}\n\n{}\n",
+            head.id(),
+            name,
+            format_signature(&tagger),
+            message.trim_end()
+        );
+        let signature =
+            gpg::sign(&gpg_program(&repo_config), key.as_deref(), &name, &payload).await?;
+
+        // libgit2 can't sign tags, the signed object is written as git would
+        let oid = repo.odb()?.write(
+            ObjectType::Tag,
+            format!("{}{}", payload, signature).as_bytes(),
+        )?;
+        repo.reference(&format!("refs/tags/{}", name), oid, false, &message)?;
+    } else {
+        log::info!("Creating tag {}", name);
+        repo.tag(&name, head.as_object(), &tagger, &message, false)?;
+    }
+
+    if push {
+        log::info!("Pushing tag {} to {}", name, config.remote);
+        self::push(&repo, &config.remote, &format!("refs/tags/{}", name))?;
+    }
+
+    Ok((Tag::with_prefix(name, &config.prefix), true))
+}
+
+/// Check the GPG signature of `tag`
+pub async fn verify_tag(base: &PathBuf, tag: &Tag) -> Result<()> {
+    let repo = Repository::open(base)?;
+    let version = Version::parse(tag.version())?;
+    let name = semver_tags(&repo, tag.scope())?
+        .into_iter()
+        .find(|(v, _)| v == &version)
+        .map(|(_, name)| name)
+        .ok_or_else(|| GitError::TagNotFound(tag.value().to_owned()))?;
+
+    let object = repo.revparse_single(&format!("refs/tags/{}", name))?;
+    if object.kind() != Some(ObjectType::Tag) {
+        return Err(GitError::NotAnnotated(name));
+    }
+    let odb = repo.odb()?;
+    let content = odb.read(object.id())?;
+
+    let program = gpg_program(&repo.config()?);
+    gpg::verify(&program, &name, &String::from_utf8_lossy(content.data())).await?;
+
+    log::info!("Verified the signature of tag {}", name);
+    Ok(())
+}
+
+/// Push `refspec` to `remote`, authenticating like git does in CI and on workstations
+fn push(repo: &Repository, remote: &str, refspec: &str) -> Result<()> {
+    let config = repo.config()?;
+    let mut rejection = None;
+    let mut attempts = 0;
+
+    let mut callbacks = RemoteCallbacks::new();
+    callbacks.credentials(|url, username, allowed| {
+        attempts += 1;
+        if attempts > PUSH_CREDENTIAL_ATTEMPTS {
+            return Err(git2::Error::from_str("authentication failed"));
+        }
+        credentials(&config, url, username, allowed)
+    });
+    callbacks.push_update_reference(|_, status| {
+        rejection = status.map(str::to_owned);
+        Ok(())
+    });
+
+    let mut options = PushOptions::new();
+    options.remote_callbacks(callbacks);
+    let result = repo
+        .find_remote(remote)?
+        .push(&[refspec], Some(&mut options));
+    drop(options);
+
+    let error = |message: String| GitError::Push {
+        remote: remote.to_owned(),
+        refspec: refspec.to_owned(),
+        message,
+    };
+    result.map_err(|err| error(err.message().to_owned()))?;
+    match rejection {
+        Some(message) => Err(error(message)),
+        None => Ok(()),
+    }
+}
+
+/// `GITHUB_TOKEN` for https remotes, the git credential helpers otherwise, and the SSH agent
+fn credentials(
+    config: &Config,
+    url: &str,
+    username: Option<&str>,
+    allowed: CredentialType,
+) -> std::result::Result<Cred, git2::Error> {
+    if allowed.contains(CredentialType::SSH_KEY) {
+        return Cred::ssh_key_from_agent(username.unwrap_or("git"));
+    }
+    if allowed.contains(CredentialType::USER_PASS_PLAINTEXT) {
+        if let Ok(token) = env::var("GITHUB_TOKEN") {
+            return Cred::userpass_plaintext("x-access-token", &token);
+        }
+        return Cred::credential_helper(config, url, username);
+    }
+    Cred::default()
+}
+
+fn gpg_program(config: &Config) -> String {
+    config
+        .get_string("gpg.program")
+        .unwrap_or_else(|_| GPG_DEFAULT_PROGRAM.to_owned())
+}
+
+/// Signature as written in git objects, e.g. `Name <email> 1700000000 +0100`
+fn format_signature(signature: &Signature) -> String {
+    let when = signature.when();
+    let offset = when.offset_minutes();
+    format!(
+        "{} <{}> {} {}{:02}{:02}",
+        signature.name().unwrap_or_default(),
+        signature.email().unwrap_or_default(),
+        when.seconds(),
+        if offset < 0 { '-' } else { '+' },
+        offset.abs() / 60,
+        offset.abs() % 60
+    )
+}
+
+/// Version of the package in the Cargo.toml of `base`
+fn package_version(base: &Path) -> Result<Version> {
+    let manifest_path = base.join("Cargo.toml");
+    let error = |message: String| GitError::Manifest {
+        path: manifest_path.display().to_string(),
+        message,
+    };
+
+    let manifest: toml::Value = fs::read_to_string(&manifest_path)
+        .map_err(|err| error(err.to_string()))?
+        .parse()
+        .map_err(|err: toml::de::Error| error(err.to_string()))?;
+    let version = manifest
+        .get("package")
+        .and_then(|package| package.get("version"))
+        .and_then(toml::Value::as_str)
+        .ok_or_else(|| error("no package version".to_owned()))?;
+
+    Ok(Version::parse(version)?)
+}
+
+/// Latest semver tag older than `tag`
+pub fn get_previous_tag(base: &PathBuf, tag: &Tag) -> Result<Option<Tag>> {
+    let repo = Repository::open(base)?;
+    let current = Version::parse(tag.version())?;
+
+    let previous = semver_tags(&repo, tag.scope())?
+        .into_iter()
+        .rfind(|(version, _)| version < &current)
+        .map(|(_, name)| Tag::with_scope(name, tag.scope()));
+
+    debug!("previous tag: {:?}", previous);
+
+    Ok(previous)
+}
+
+/// Commits reachable from `to` but not from `from`, newest first, touching `paths` when not empty
+pub fn commits_between(
+    base: &PathBuf,
+    from: Option<&Tag>,
+    to: &Tag,
+    paths: &[String],
+) -> Result<Vec<Commit>> {
+    let repo = Repository::open(base)?;
+
+    let mut revwalk = repo.revwalk()?;
+    revwalk.set_sorting(Sort::TOPOLOGICAL | Sort::TIME)?;
+    revwalk.push(resolve_tag(&repo, to)?)?;
+    if let Some(from) = from {
+        revwalk.hide(resolve_tag(&repo, from)?)?;
+    }
+
+    let mut commits = vec![];
+    for oid in revwalk {
+        let commit = repo.find_commit(oid?)?;
+        if !paths.is_empty() && !touches(&repo, &commit, paths)? {
+            continue;
+        }
+
+        let author = commit.author();
+        let message = commit.message().unwrap_or_default().trim().to_string();
+        commits.push(Commit {
+            sha: commit.id().to_string(),
+            author: author.name().unwrap_or_default().to_string(),
+            email: author.email().unwrap_or_default().to_string(),
+            trailers: trailers(&message)?,
+            message,
+        });
+    }
+
+    debug!(
+        "{} commits between {:?} and {}",
+        commits.len(),
+        from,
+        to.value()
+    );
+
+    Ok(commits)
+}
+
+fn trailers(message: &str) -> Result<Vec<Trailer>> {
+    Ok(git2::message_trailers_strs(message)?
+        .iter()
+        .map(|(key, value)| Trailer {
+            key: key.to_owned(),
+            value: value.to_owned(),
+        })
+        .collect())
+}
+
+/// Whether `commit` changes a file under one of `paths`, compared to its first parent
+fn touches(repo: &Repository, commit: &git2::Commit, paths: &[String]) -> Result<bool> {
+    let parent = match commit.parent(0) {
+        Ok(parent) => Some(parent.tree()?),
+        Err(_) => None,
+    };
+
+    let mut options = DiffOptions::new();
+    for path in paths {
+        options.pathspec(path);
+    }
+    let diff =
+        repo.diff_tree_to_tree(parent.as_ref(), Some(&commit.tree()?), Some(&mut options))?;
+
+    Ok(diff.stats()?.files_changed() > 0)
+}
+
+/// Emails of everyone who authored a commit reachable from `tag`
+pub fn authors_until(base: &PathBuf, tag: &Tag) -> Result<HashSet<String>> {
+    let repo = Repository::open(base)?;
+
+    let mut revwalk = repo.revwalk()?;
+    revwalk.push(resolve_tag(&repo, tag)?)?;
+
+    let mut authors = HashSet::new();
+    for oid in revwalk {
+        let commit = repo.find_commit(oid?)?;
+        authors.insert(commit.author().email().unwrap_or_default().to_string());
+    }
+
+    Ok(authors)
+}
+
+/// Tags of `scope` that parse as semver, with or without a `v` prefix, oldest first
+fn semver_tags(repo: &Repository, scope: &str) -> Result<Vec<(Version, String)>> {
+    let tags = repo
+        .tag_names(None)?
+        .into_iter()
+        .flatten()
+        .filter_map(|name| {
+            let version = name.strip_prefix(scope)?;
+            Version::parse(version.strip_prefix('v').unwrap_or(version))
+                .ok()
+                .map(|version| (version, name.to_string()))
+        })
+        .sorted_by(|a, b| a.0.cmp(&b.0))
+        .collect();
+
+    Ok(tags)
+}
+
+fn resolve_tag(repo: &Repository, tag: &Tag) -> Result<git2::Oid> {
+    let version = Version::parse(tag.version())?;
+    let name = semver_tags(repo, tag.scope())?
+        .into_iter()
+        .find(|(v, _)| v == &version)
+        .map(|(_, name)| name);
+
+    match name {
+        Some(name) => resolve_tag_name(repo, &name),
+        // snapshots and tags not created yet by a dry run are released from HEAD
+        None if is_unreleased(repo, tag.scope(), &version)? => {
+            debug!("{} is not tagged yet, using HEAD", tag.value());
+            Ok(repo.head()?.peel_to_commit()?.id())
+        }
+        None => resolve_tag_name(repo, tag.value()),
+    }
+}
+
+/// Whether `version` is newer than every tag of `scope`
+fn is_unreleased(repo: &Repository, scope: &str, version: &Version) -> Result<bool> {
+    Ok(semver_tags(repo, scope)?
+        .last()
+        .is_none_or(|(latest, _)| latest < version))
+}
+
+fn resolve_tag_name(repo: &Repository, name: &str) -> Result<git2::Oid> {
+    let commit = repo
+        .revparse_single(&format!("refs/tags/{}", name))
+        .map_err(|_| GitError::TagNotFound(name.to_owned()))?
+        .peel_to_commit()?;
+
+    Ok(commit.id())
 }
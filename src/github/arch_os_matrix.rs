@@ -1,5 +1,5 @@
 use super::asset::Asset;
-use crate::build::{arch::Arch, compression::Compression, os::Os};
+use crate::build::{arch::Arch, os::Os};
 
 #[derive(Debug, Clone)]
 pub struct ArchOsMatrixEntry<'matrix> {
@@ -10,21 +10,7 @@ pub struct ArchOsMatrixEntry<'matrix> {
 }
 
 impl<'matrix> ArchOsMatrixEntry<'matrix> {
-    pub fn new(
-        arch: &'matrix Arch,
-        os: &'matrix Os,
-        name: String,
-        tag: &'matrix str,
-        compression: &'matrix Compression,
-    ) -> Self {
-        let name = format!(
-            "{}_{}_{}_{}.{}",
-            name,
-            tag,
-            arch.to_string(),
-            os.to_string(),
-            compression.extension()
-        );
+    pub fn new(arch: &'matrix Arch, os: &'matrix Os, name: String) -> Self {
         Self {
             arch,
             os,
@@ -1,5 +1,29 @@
+use super::{github_client, tag::Tag};
 use std::path::PathBuf;
 
+/// Public download URL of a release asset
+pub fn download_url(owner: &str, repo: &str, tag: &Tag, name: &str) -> String {
+    format!(
+        "{}/{}/{}/releases/download/{}/{}",
+        github_client::instance().web_url(),
+        owner,
+        repo,
+        tag.value(),
+        name
+    )
+}
+
+/// Public page of a release
+pub fn release_url(owner: &str, repo: &str, tag: &Tag) -> String {
+    format!(
+        "{}/{}/{}/releases/tag/{}",
+        github_client::instance().web_url(),
+        owner,
+        repo,
+        tag.value()
+    )
+}
+
 #[derive(Debug, Clone)]
 pub struct Asset {
     pub name: String,
@@ -0,0 +1,256 @@
+use super::{
+    github_client,
+    response::installation_response::{Installation, InstallationToken},
+};
+use crate::{config::GithubConfig, http::RateLimited};
+use anyhow::{anyhow, bail, Context, Result};
+use jsonwebtoken::{Algorithm, EncodingKey, Header};
+use log::debug;
+use once_cell::sync::{Lazy, OnceCell};
+use reqwest::header::{ACCEPT, USER_AGENT};
+use serde::Serialize;
+use std::{env, fmt, fs, io::ErrorKind};
+use time::{format_description::well_known::Rfc3339, Duration, OffsetDateTime};
+use tokio::{process::Command, sync::Mutex};
+
+const APP_ID_VAR: &str = "GITHUB_APP_ID";
+/// PEM encoded private key of the app, or the path of a file holding it
+const APP_PRIVATE_KEY_VAR: &str = "GITHUB_APP_PRIVATE_KEY";
+/// Only needed when the app is installed on several accounts
+const APP_INSTALLATION_ID_VAR: &str = "GITHUB_APP_INSTALLATION_ID";
+
+/// GitHub refuses app JWTs living longer than 10 minutes
+const JWT_LIFETIME: Duration = Duration::minutes(9);
+/// Covers clock drift between this machine and GitHub
+const JWT_BACKDATE: Duration = Duration::seconds(60);
+/// Installation tokens are minted again this long before they expire
+const TOKEN_REFRESH_MARGIN: Duration = Duration::minutes(5);
+
+/// Where a token is looked up when no GitHub App is configured, in this order
+const TOKEN_SOURCES: [TokenSource; 4] = [
+    TokenSource::Env("GITHUB_TOKEN"),
+    TokenSource::Env("GH_TOKEN"),
+    TokenSource::GhCli,
+    TokenSource::File,
+];
+
+static CONFIG: OnceCell<GithubConfig> = OnceCell::new();
+static TOKEN: tokio::sync::OnceCell<String> = tokio::sync::OnceCell::const_new();
+static INSTALLATION_TOKEN: Lazy<Mutex<Option<CachedToken>>> = Lazy::new(|| Mutex::new(None));
+
+enum TokenSource {
+    Env(&'static str),
+    GhCli,
+    File,
+}
+
+impl TokenSource {
+    /// The token, or why this source has none
+    async fn lookup(&self) -> std::result::Result<String, String> {
+        let token = match self {
+            TokenSource::Env(var) => env::var(var).map_err(|_| "not set".to_owned())?,
+            TokenSource::GhCli => {
+                let output = Command::new("gh")
+                    .args(["auth", "token"])
+                    .output()
+                    .await
+                    .map_err(|err| match err.kind() {
+                        ErrorKind::NotFound => "gh is not installed".to_owned(),
+                        _ => err.to_string(),
+                    })?;
+                if !output.status.success() {
+                    return Err(String::from_utf8_lossy(&output.stderr).trim().to_owned());
+                }
+                String::from_utf8_lossy(&output.stdout).into_owned()
+            }
+            TokenSource::File => {
+                let path = CONFIG
+                    .get()
+                    .and_then(|config| config.token_file.as_ref())
+                    .ok_or_else(|| "`github.token_file` is not set".to_owned())?;
+                fs::read_to_string(path).map_err(|err| format!("{}: {}", path, err))?
+            }
+        };
+
+        match token.trim() {
+            "" => Err("empty".to_owned()),
+            token => Ok(token.to_owned()),
+        }
+    }
+}
+
+impl fmt::Display for TokenSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TokenSource::Env(var) => write!(f, "{}", var),
+            TokenSource::GhCli => write!(f, "`gh auth token`"),
+            TokenSource::File => write!(f, "the token file"),
+        }
+    }
+}
+
+struct CachedToken {
+    token: String,
+    expires_at: OffsetDateTime,
+}
+
+#[derive(Serialize)]
+struct Claims {
+    iat: i64,
+    exp: i64,
+    iss: String,
+}
+
+/// Set the config the token sources read, before any request
+pub fn init(config: &GithubConfig) {
+    let _ = CONFIG.set(config.clone());
+}
+
+/// Use `token` instead of looking one up, leaving the environment of the other tests alone
+#[cfg(test)]
+pub(super) fn set_test_token(token: &str) {
+    // the tests all use the same token
+    let _ = TOKEN.set(token.to_owned());
+}
+
+/// Token for the GitHub API: an installation token when a GitHub App is configured, the first
+/// token found in `TOKEN_SOURCES` otherwise
+pub async fn token() -> Result<String> {
+    match env::var(APP_ID_VAR) {
+        Ok(app_id) => app_token(&app_id).await,
+        Err(_) => TOKEN.get_or_try_init(find_token).await.cloned(),
+    }
+}
+
+async fn find_token() -> Result<String> {
+    let mut tried = vec![];
+    for source in &TOKEN_SOURCES {
+        match source.lookup().await {
+            Ok(token) => {
+                debug!("using the GitHub token from {}", source);
+                return Ok(token);
+            }
+            Err(reason) => tried.push(format!("{} ({})", source, reason)),
+        }
+    }
+
+    Err(anyhow!(
+        "no GitHub token found, tried {}; set {} and {} to authenticate as a GitHub App instead",
+        tried.join(", "),
+        APP_ID_VAR,
+        APP_PRIVATE_KEY_VAR
+    ))
+}
+
+async fn app_token(app_id: &str) -> Result<String> {
+    let mut cached = INSTALLATION_TOKEN.lock().await;
+    if let Some(cached) = cached.as_ref() {
+        if cached.expires_at - OffsetDateTime::now_utc() > TOKEN_REFRESH_MARGIN {
+            return Ok(cached.token.clone());
+        }
+    }
+
+    let token = installation_token(app_id).await?;
+    let expires_at = OffsetDateTime::parse(&token.expires_at, &Rfc3339)
+        .with_context(|| format!("invalid token expiration {}", token.expires_at))?;
+    debug!(
+        "minted an installation token expiring at {}",
+        token.expires_at
+    );
+
+    *cached = Some(CachedToken {
+        token: token.token.clone(),
+        expires_at,
+    });
+
+    Ok(token.token)
+}
+
+async fn installation_token(app_id: &str) -> Result<InstallationToken> {
+    let jwt = app_jwt(app_id)?;
+
+    let installation_id = match env::var(APP_INSTALLATION_ID_VAR) {
+        Ok(id) => id,
+        Err(_) => installation_id(&jwt).await?.to_string(),
+    };
+
+    let client = github_client::instance();
+    let uri = format!(
+        "{}/app/installations/{}/access_tokens",
+        client.api_url(),
+        installation_id
+    );
+
+    client
+        .http()
+        .post(&uri)
+        .bearer_auth(&jwt)
+        .header(ACCEPT, "application/vnd.github+json")
+        .header(USER_AGENT, "rustreleaser")
+        .send_with_retry()
+        .await?
+        .error_for_status()
+        .with_context(|| {
+            format!(
+                "failed to create a token for installation {} of app {}",
+                installation_id, app_id
+            )
+        })?
+        .json::<InstallationToken>()
+        .await
+        .context("failed to read the installation token")
+}
+
+/// Id of the only installation of the app
+async fn installation_id(jwt: &str) -> Result<u64> {
+    let client = github_client::instance();
+    let installations = client
+        .http()
+        .get(format!("{}/app/installations", client.api_url()))
+        .bearer_auth(jwt)
+        .header(ACCEPT, "application/vnd.github+json")
+        .header(USER_AGENT, "rustreleaser")
+        .send_with_retry()
+        .await?
+        .error_for_status()
+        .context("failed to list the installations of the app")?
+        .json::<Vec<Installation>>()
+        .await?;
+
+    match installations.as_slice() {
+        [installation] => Ok(installation.id),
+        [] => bail!("the GitHub App is not installed on any account"),
+        _ => bail!(
+            "the GitHub App is installed on {}, set {} to pick one",
+            installations
+                .iter()
+                .map(|installation| format!("{} ({})", installation.account.login, installation.id))
+                .collect::<Vec<_>>()
+                .join(", "),
+            APP_INSTALLATION_ID_VAR
+        ),
+    }
+}
+
+/// JWT authenticating as the app itself, only used to mint installation tokens
+fn app_jwt(app_id: &str) -> Result<String> {
+    let key = env::var(APP_PRIVATE_KEY_VAR)
+        .with_context(|| format!("{} must be set with {}", APP_PRIVATE_KEY_VAR, APP_ID_VAR))?;
+    let pem = if key.trim_start().starts_with("-----BEGIN") {
+        key
+    } else {
+        fs::read_to_string(&key)
+            .with_context(|| format!("failed to read the app private key {}", key))?
+    };
+    let key = EncodingKey::from_rsa_pem(pem.as_bytes()).context("invalid app private key")?;
+
+    let now = OffsetDateTime::now_utc();
+    let claims = Claims {
+        iat: (now - JWT_BACKDATE).unix_timestamp(),
+        exp: (now + JWT_LIFETIME).unix_timestamp(),
+        iss: app_id.to_owned(),
+    };
+
+    jsonwebtoken::encode(&Header::new(Algorithm::RS256), &claims, &key)
+        .context("failed to sign the app JWT")
+}
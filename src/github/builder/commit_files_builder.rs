@@ -0,0 +1,71 @@
+use super::BuilderExecutor;
+use crate::{build::committer::Committer, github::github_client};
+
+/// Commits files on a branch through GraphQL, so GitHub signs the commit and shows it as verified
+pub struct CommitFilesBuilder {
+    owner: String,
+    repo: String,
+    branch: String,
+    message: String,
+    files: Vec<(String, Vec<u8>)>,
+    committer: Option<Committer>,
+}
+
+impl CommitFilesBuilder {
+    pub fn new<S, T>(owner: S, repo: T, branch: S) -> Self
+    where
+        S: Into<String>,
+        T: Into<String>,
+    {
+        CommitFilesBuilder {
+            owner: owner.into(),
+            repo: repo.into(),
+            branch: branch.into(),
+            message: String::new(),
+            files: vec![],
+            committer: None,
+        }
+    }
+
+    /// Add or replace the file at `path`
+    pub fn file<S, T>(mut self, path: S, content: T) -> Self
+    where
+        S: Into<String>,
+        T: Into<Vec<u8>>,
+    {
+        self.files.push((path.into(), content.into()));
+        self
+    }
+
+    pub fn message<S>(mut self, message: S) -> Self
+    where
+        S: Into<String>,
+    {
+        self.message = message.into();
+        self
+    }
+
+    /// GitHub authors the commit as the token owner, the committer is credited as co-author
+    pub fn committer(mut self, committer: &Committer) -> Self {
+        self.committer = Some(committer.to_owned());
+        self
+    }
+}
+
+impl BuilderExecutor for CommitFilesBuilder {
+    type Output = String;
+
+    async fn execute(self) -> anyhow::Result<Self::Output> {
+        let message = match &self.committer {
+            Some(committer) => format!(
+                "{}\n\nCo-authored-by: {} <{}>",
+                self.message, committer.author, committer.email
+            ),
+            None => self.message,
+        };
+
+        github_client::instance()
+            .create_commit(&self.owner, &self.repo, &self.branch, &message, self.files)
+            .await
+    }
+}
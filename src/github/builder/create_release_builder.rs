@@ -1,5 +1,8 @@
 use super::BuilderExecutor;
-use crate::github::{github_client, release::Release, tag::Tag};
+use crate::{
+    config::MakeLatest,
+    github::{github_client, release::Release, tag::Tag},
+};
 use anyhow::Result;
 
 pub struct CreateReleaseBuilder {
@@ -10,6 +13,8 @@ pub struct CreateReleaseBuilder {
     pub target_branch: String,
     pub draft: Option<bool>,
     pub prerelease: Option<bool>,
+    pub make_latest: Option<MakeLatest>,
+    pub discussion_category_name: Option<String>,
     pub body: Option<String>,
 }
 
@@ -23,6 +28,8 @@ impl CreateReleaseBuilder {
             target_branch: String::new(),
             draft: None,
             prerelease: None,
+            make_latest: None,
+            discussion_category_name: None,
             body: None,
         }
     }
@@ -58,6 +65,16 @@ impl CreateReleaseBuilder {
         self
     }
 
+    pub fn make_latest(mut self, make_latest: Option<MakeLatest>) -> Self {
+        self.make_latest = make_latest;
+        self
+    }
+
+    pub fn discussion_category_name(mut self, category: Option<String>) -> Self {
+        self.discussion_category_name = category;
+        self
+    }
+
     pub fn body<S>(mut self, body: S) -> Self
     where
         S: Into<String>,
@@ -80,6 +97,8 @@ impl BuilderExecutor for CreateReleaseBuilder {
                 &self.release_name,
                 self.draft.unwrap(),
                 self.prerelease.unwrap(),
+                self.make_latest,
+                self.discussion_category_name,
                 &self.body.unwrap_or_default(),
             )
             .await
@@ -1,7 +1,7 @@
+pub mod commit_files_builder;
 pub mod create_branch_builder;
 pub mod create_pull_request_builder;
 pub mod create_release_builder;
-pub mod upsert_file_builder;
 
 use anyhow::Result;
 
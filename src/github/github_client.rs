@@ -1,44 +1,117 @@
 use super::{
-    asset::{Asset, UploadedAsset},
+    asset::{download_url, Asset, UploadedAsset},
+    auth,
     handler::repository_handler::RepositoryHandler,
     request::{
-        branch_ref_request::BranchRefRequest, create_release_request::CreateReleaseRequest,
+        branch_ref_request::BranchRefRequest,
+        create_commit_request::{CreateCommitVariables, FileAddition, CREATE_COMMIT_MUTATION},
+        create_release_request::CreateReleaseRequest,
+        create_repository_request::CreateRepositoryRequest,
+        generate_notes_request::GenerateNotesRequest,
+        graphql_request::GraphqlRequest,
         pull_request_request::PullRquestRequest,
+        update_ref_request::UpdateRefRequest,
+        update_release_request::UpdateReleaseRequest,
     },
     response::{
-        assignees_request::AssigneesRequest, labels_request::LabelsRequest,
-        pull_request_response::PullRequest, release_response::ReleaseResponse, sha_response::Sha,
+        assignees_request::AssigneesRequest,
+        commit_response::CommitResponse,
+        generated_notes_response::GeneratedNotes,
+        graphql_response::{CreateCommitResponse, GraphqlResponse},
+        labels_request::LabelsRequest,
+        pull_request_response::PullRequest,
+        release_asset_response::ReleaseAsset,
+        release_response::ReleaseResponse,
+        repository_response::{AccountResponse, RepositoryAccess, RepositoryResponse},
+        sha_response::Sha,
     },
     tag::Tag,
 };
 use crate::{
-    build::committer::Committer,
-    get,
-    github::{macros::Headers, release::Release, request::upsert_file_request::UpsertFileRequest},
-    http::HttpClient,
-    post, put,
+    config::MakeLatest,
+    delete, get,
+    github::{macros::Headers, release::Release},
+    http::{self, cache::Cached, progress, HttpClient, RateLimited},
+    patch, post,
 };
-use anyhow::{Context, Result};
+use anyhow::{bail, Context, Result};
 use base64::{prelude::BASE64_STANDARD, Engine};
 use log::debug;
 use mime_guess::from_path;
 use once_cell::sync::Lazy;
-use reqwest::header::{CONTENT_LENGTH, CONTENT_TYPE};
-use std::{env, path::Path};
-use tokio::{fs::File, io::AsyncReadExt};
-
-pub static GITHUB_TOKEN: Lazy<String> =
-    Lazy::new(|| env::var("GITHUB_TOKEN").expect("GITHUB_TOKEN must be set"));
-
-static CLIENT: Lazy<GithubClient> = Lazy::new(|| GithubClient);
+use reqwest::{
+    header::{ACCEPT, CONTENT_LENGTH, CONTENT_TYPE, USER_AGENT},
+    Client, StatusCode,
+};
+use serde::{de::DeserializeOwned, Serialize};
+use std::path::Path;
+use tokio::fs::File;
+
+const API_URL: &str = "https://api.github.com";
+const UPLOADS_URL: &str = "https://uploads.github.com";
+const WEB_URL: &str = "https://github.com";
+/// Largest page size of the REST API
+const PER_PAGE: u32 = 100;
+
+static CLIENT: Lazy<GithubClient> = Lazy::new(GithubClient::default);
+
+#[cfg(test)]
+tokio::task_local! {
+    /// Client of the running test, see `github::tests`
+    pub(super) static TEST_CLIENT: &'static GithubClient;
+}
 
 pub fn instance() -> &'static GithubClient {
+    #[cfg(test)]
+    if let Ok(client) = TEST_CLIENT.try_with(|client| *client) {
+        return client;
+    }
+
     &CLIENT
 }
 
-pub struct GithubClient;
+pub struct GithubClient {
+    api_url: String,
+    uploads_url: String,
+    web_url: String,
+    http: Client,
+}
+
+impl Default for GithubClient {
+    fn default() -> Self {
+        GithubClient::new(API_URL, UPLOADS_URL, WEB_URL, (*HttpClient::new()).clone())
+    }
+}
 
 impl GithubClient {
+    /// Client of a GitHub instance, given the base URLs of its REST API, its asset uploads and
+    /// its website
+    pub fn new(
+        api_url: impl Into<String>,
+        uploads_url: impl Into<String>,
+        web_url: impl Into<String>,
+        http: Client,
+    ) -> Self {
+        GithubClient {
+            api_url: api_url.into(),
+            uploads_url: uploads_url.into(),
+            web_url: web_url.into(),
+            http,
+        }
+    }
+
+    pub(super) fn api_url(&self) -> &str {
+        &self.api_url
+    }
+
+    pub(super) fn web_url(&self) -> &str {
+        &self.web_url
+    }
+
+    pub(super) fn http(&self) -> &Client {
+        &self.http
+    }
+
     pub fn repo<S>(&self, owner: S, name: S) -> RepositoryHandler
     where
         S: Into<String>,
@@ -58,39 +131,33 @@ impl GithubClient {
         let repo = repo.into();
 
         let path = Path::new(&asset.path);
-        let mut file = File::open(&path).await?;
+        let file = File::open(&path).await?;
         let metadata = file.metadata().await?;
         let content_length = metadata.len();
-        let content_type = from_path(&path)
-            .first_or_octet_stream()
-            .as_ref()
-            .to_string();
+        let content_type = from_path(path).first_or_octet_stream().as_ref().to_string();
 
         let url = format!(
-            "https://uploads.github.com/repos/{}/{}/releases/{}/assets?name={}",
-            owner, repo, release_id, asset.name
+            "{}/repos/{}/{}/releases/{}/assets?name={}",
+            self.uploads_url, owner, repo, release_id, asset.name
         );
 
-        let mut buf: Vec<u8> = vec![];
-        file.read_to_end(&mut buf).await?;
-        let res = HttpClient::new()
+        let body = progress::body(&asset.name, file, content_length);
+        let res = self
+            .http
             .post(url)
             .default_headers()
+            .await?
             .header(CONTENT_LENGTH, content_length.to_string())
             .header(CONTENT_TYPE, content_type)
-            .body(buf)
-            .send()
-            .await?;
+            .timeout(http::transfer_timeout())
+            .body(body)
+            .send_with_retry()
+            .await?
+            .error_for_status()?;
 
         debug!("upload asset response: {:#?}", res);
 
-        let asset_url = format!(
-            "https://github.com/{}/{}/releases/download/{}/{}",
-            &owner,
-            &repo,
-            tag.strip_v_prefix(),
-            asset.name
-        );
+        let asset_url = download_url(&owner, &repo, tag, &asset.name);
         log::debug!("creating uploaded asset");
         let uploaded_asset = self.create_uploaded_asset(asset, asset_url);
 
@@ -120,11 +187,11 @@ impl GithubClient {
         let base = base.into();
 
         let uri = format!(
-            "https://api.github.com/repos/{}/{}/commits/{}",
-            &owner, &repo, &base
+            "{}/repos/{}/{}/commits/{}",
+            self.api_url, &owner, &repo, &base
         );
 
-        let response = get!(&uri)?;
+        let response = get!(self.http, &uri)?;
 
         let sha = Sha { sha: response };
 
@@ -138,69 +205,138 @@ impl GithubClient {
         branch: &str,
         sha: &str,
     ) -> Result<()> {
-        let uri = format!("https://api.github.com/repos/{}/{}/git/refs", owner, repo);
+        let uri = format!("{}/repos/{}/{}/git/refs", self.api_url, owner, repo);
 
         let request = BranchRefRequest::new(branch.to_string(), sha.to_string());
 
         let body: String = serde_json::to_string(&request)?;
 
-        post!(&uri, body)?;
+        post!(self.http, &uri, body)?;
 
         Ok(())
     }
 
-    pub(super) async fn upsert_file(
+    /// Point `branch` at `sha`, discarding the commits it had
+    pub(super) async fn reset_branch(
         &self,
         owner: &str,
         repo: &str,
-        path: &str,
-        content: &str,
-        commit_message: String,
-        committer: Committer,
-        head: String,
+        branch: &str,
+        sha: &str,
     ) -> Result<()> {
-        let content = BASE64_STANDARD.encode(content.as_bytes());
+        let uri = format!(
+            "{}/repos/{}/{}/git/refs/heads/{}",
+            self.api_url, owner, repo, branch
+        );
+
+        let request = UpdateRefRequest::new(sha.to_string(), true);
 
-        let uri = &format!(
-            "https://api.github.com/repos/{}/{}/contents/{}",
-            owner, repo, path
+        let body: String = serde_json::to_string(&request)?;
+
+        patch!(self.http, &uri, body)?;
+
+        Ok(())
+    }
+
+    /// Delete a branch or tag, e.g. `heads/bump` or `tags/v1.0.0`
+    pub(super) async fn delete_ref(&self, owner: &str, repo: &str, reference: &str) -> Result<()> {
+        let uri = format!(
+            "{}/repos/{}/{}/git/refs/{}",
+            self.api_url, owner, repo, reference
         );
 
-        let file_sha = get!(uri).context("failed to get Formula sha value")?;
+        delete!(self.http, &uri)?;
 
-        let sha = serde_json::from_str::<Sha>(&file_sha).unwrap_or_default();
+        Ok(())
+    }
 
-        let body = if sha.sha.is_empty() {
-            log::debug!("creating new file");
+    /// Commit `files`, as path and content, on top of `branch`, returning the commit SHA
+    pub(super) async fn create_commit(
+        &self,
+        owner: &str,
+        repo: &str,
+        branch: &str,
+        message: &str,
+        files: Vec<(String, Vec<u8>)>,
+    ) -> Result<String> {
+        let head = self.get_commit_sha(owner, repo, branch).await?;
+
+        let additions = files
+            .into_iter()
+            .map(|(path, content)| FileAddition {
+                path,
+                contents: BASE64_STANDARD.encode(content),
+            })
+            .collect();
+        let variables =
+            CreateCommitVariables::new(owner, repo, branch, message, head.sha, additions);
+
+        let response: CreateCommitResponse = self
+            .graphql(CREATE_COMMIT_MUTATION, variables)
+            .await
+            .with_context(|| format!("failed to commit on {}/{}@{}", owner, repo, branch))?;
+
+        let commit = response.create_commit_on_branch.commit;
+        debug!("created commit {}: {}", commit.oid, commit.url);
+
+        Ok(commit.oid)
+    }
 
-            let request =
-                UpsertFileRequest::new(commit_message, content, Some(head), None, committer.into());
+    /// Every item of a list endpoint, following the `Link` header from page to page
+    async fn get_all<T>(&self, uri: &str) -> Result<Vec<T>>
+    where
+        T: DeserializeOwned,
+    {
+        let separator = if uri.contains('?') { '&' } else { '?' };
+        let mut next = Some(format!("{}{}per_page={}", uri, separator, PER_PAGE));
+        let mut items = vec![];
+        while let Some(uri) = next {
+            let response = self
+                .http
+                .get(&uri)
+                .default_headers()
+                .await?
+                .send_with_retry()
+                .await?
+                .error_for_status()?;
+
+            next = http::next_page(response.headers());
+            items.extend(response.json::<Vec<T>>().await?);
+        }
 
-            serde_json::to_string(&request)?
-        } else {
-            log::debug!("updating file");
+        Ok(items)
+    }
 
-            let request = UpsertFileRequest::new(
-                commit_message,
-                content,
-                Some(head),
-                Some(sha.sha),
-                committer.into(),
-            );
+    async fn graphql<V, T>(&self, query: &str, variables: V) -> Result<T>
+    where
+        V: Serialize,
+        T: DeserializeOwned,
+    {
+        let uri = format!("{}/graphql", self.api_url);
 
-            serde_json::to_string(&request)?
-        };
+        let body = serde_json::to_string(&GraphqlRequest::new(query, variables))?;
 
-        let uri = format!(
-            "https://api.github.com/repos/{}/{}/contents/{}",
-            owner, repo, path
-        );
+        let response = post!(self.http, &uri, body)?;
 
-        put!(uri, body)?;
+        let response = serde_json::from_str::<GraphqlResponse<T>>(&response)
+            .with_context(|| format!("unexpected GraphQL response: {}", response))?;
 
-        Ok(())
+        if !response.errors.is_empty() {
+            bail!(
+                "{}",
+                response
+                    .errors
+                    .iter()
+                    .map(|error| error.message.as_str())
+                    .collect::<Vec<_>>()
+                    .join("; ")
+            );
+        }
+
+        response.data.context("empty GraphQL response")
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub(super) async fn create_pull_request(
         &self,
         owner: &str,
@@ -212,7 +348,7 @@ impl GithubClient {
         assignees: Vec<String>,
         labels: Vec<String>,
     ) -> Result<PullRequest> {
-        let uri = format!("https://api.github.com/repos/{}/{}/pulls", owner, repo);
+        let uri = format!("{}/repos/{}/{}/pulls", self.api_url, owner, repo);
 
         let request = PullRquestRequest::new(
             title.to_owned(),
@@ -222,23 +358,66 @@ impl GithubClient {
         );
         let body: String = serde_json::to_string(&request)?;
 
-        let response = post!(&uri, body)?;
-
-        let pr: PullRequest = serde_json::from_str(&response)?;
+        let pr: PullRequest = match post!(self.http, &uri, body) {
+            Ok(response) => serde_json::from_str(&response)?,
+            // the head branch of a previous release still has its pull request open
+            Err(err) if err.status == 422 => {
+                let Some(pr) = self.find_pull_request(owner, repo, head, base).await? else {
+                    return Err(err.into());
+                };
+                log::info!("Reusing the open pull request #{} of {}", pr.number, head);
+                pr
+            }
+            Err(err) => return Err(err.into()),
+        };
 
+        // the pull request is open by now, so a rejected assignee or label doesn't fail it
         if !assignees.is_empty() {
-            self.set_pr_assignees(owner, repo, pr.number, assignees)
-                .await?;
+            if let Err(err) = self
+                .set_pr_assignees(owner, repo, pr.number, assignees)
+                .await
+            {
+                log::warn!(
+                    "Failed to assign the pull request #{}: {:#}",
+                    pr.number,
+                    err
+                );
+            }
         }
 
         if !labels.is_empty() {
-            self.set_pr_labels(owner, repo, pr.number.to_string(), labels)
-                .await?;
+            if let Err(err) = self
+                .set_pr_labels(owner, repo, pr.number.to_string(), labels)
+                .await
+            {
+                log::warn!("Failed to label the pull request #{}: {:#}", pr.number, err);
+            }
         }
 
         Ok(pr)
     }
 
+    /// Open pull request from `head` to `base`, if any
+    async fn find_pull_request(
+        &self,
+        owner: &str,
+        repo: &str,
+        head: &str,
+        base: &str,
+    ) -> Result<Option<PullRequest>> {
+        let uri = format!(
+            "{}/repos/{}/{}/pulls?state=open&head={}:{}&base={}",
+            self.api_url, owner, repo, owner, head, base
+        );
+
+        let response = get!(self.http, &uri)?;
+
+        let pulls: Vec<PullRequest> = serde_json::from_str(&response)?;
+
+        Ok(pulls.into_iter().next())
+    }
+
+    #[allow(clippy::too_many_arguments)]
     pub(super) async fn create_release(
         &self,
         owner: &str,
@@ -248,9 +427,11 @@ impl GithubClient {
         release_name: &str,
         draft: bool,
         prerelease: bool,
+        make_latest: Option<MakeLatest>,
+        discussion_category_name: Option<String>,
         body: &str,
     ) -> Result<Release> {
-        let uri = format!("https://api.github.com/repos/{}/{}/releases", owner, repo);
+        let uri = format!("{}/repos/{}/{}/releases", self.api_url, owner, repo);
 
         let request = CreateReleaseRequest::new(
             tag.value().to_owned(),
@@ -259,17 +440,48 @@ impl GithubClient {
             body.to_owned(),
             draft,
             prerelease,
+            make_latest,
+            discussion_category_name,
         );
 
         let body: String = serde_json::to_string(&request)?;
 
-        let response = post!(&uri, body)?;
+        let response = post!(self.http, &uri, body)?;
 
         let release = serde_json::from_str::<ReleaseResponse>(&response)?;
 
         Ok(Release::new(release.id, owner, repo))
     }
 
+    pub(super) async fn generate_release_notes(
+        &self,
+        owner: &str,
+        repo: &str,
+        tag: &Tag,
+        target_branch: &str,
+        previous: Option<&Tag>,
+    ) -> Result<String> {
+        let uri = format!(
+            "{}/repos/{}/{}/releases/generate-notes",
+            self.api_url, owner, repo
+        );
+
+        let request = GenerateNotesRequest::new(
+            tag.value().to_owned(),
+            target_branch.to_owned(),
+            previous.map(|previous| previous.value().to_owned()),
+        );
+
+        let body: String = serde_json::to_string(&request)?;
+
+        let response = post!(self.http, &uri, body)?;
+
+        let notes = serde_json::from_str::<GeneratedNotes>(&response)
+            .context("failed to generate the release notes")?;
+
+        Ok(notes.body)
+    }
+
     pub(super) async fn get_release_by_tag(
         &self,
         owner: &str,
@@ -277,19 +489,336 @@ impl GithubClient {
         tag: &Tag,
     ) -> Result<Release> {
         let uri = format!(
-            "https://api.github.com/repos/{}/{}/releases/tags/{}",
+            "{}/repos/{}/{}/releases/tags/{}",
+            self.api_url,
             owner,
             repo,
             tag.value()
         );
 
-        let response = get!(&uri)?;
+        let response = get!(self.http, &uri)?;
 
         let release = serde_json::from_str::<ReleaseResponse>(&response)?;
         debug!("release: {:#?}", release);
         Ok(Release::new(release.id, owner, repo))
     }
 
+    pub(super) async fn list_releases(
+        &self,
+        owner: &str,
+        repo: &str,
+    ) -> Result<Vec<ReleaseResponse>> {
+        let uri = format!("{}/repos/{}/{}/releases", self.api_url, owner, repo);
+
+        self.get_all(&uri).await
+    }
+
+    pub(super) async fn delete_release(
+        &self,
+        owner: &str,
+        repo: &str,
+        release_id: u64,
+    ) -> Result<()> {
+        let uri = format!(
+            "{}/repos/{}/{}/releases/{}",
+            self.api_url, owner, repo, release_id
+        );
+
+        delete!(self.http, &uri)?;
+
+        Ok(())
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub(super) async fn update_release(
+        &self,
+        owner: &str,
+        repo: &str,
+        release_id: u64,
+        release_name: &str,
+        draft: bool,
+        prerelease: bool,
+        make_latest: Option<MakeLatest>,
+        body: &str,
+    ) -> Result<()> {
+        let uri = format!(
+            "{}/repos/{}/{}/releases/{}",
+            self.api_url, owner, repo, release_id
+        );
+
+        let request = UpdateReleaseRequest::new(
+            release_name.to_owned(),
+            body.to_owned(),
+            draft,
+            prerelease,
+            make_latest,
+        );
+
+        let body: String = serde_json::to_string(&request)?;
+
+        let response = patch!(self.http, &uri, body)?;
+
+        serde_json::from_str::<ReleaseResponse>(&response)
+            .with_context(|| format!("failed to update release {}", release_id))?;
+
+        Ok(())
+    }
+
+    pub(super) async fn delete_release_asset(
+        &self,
+        owner: &str,
+        repo: &str,
+        asset_id: u64,
+    ) -> Result<()> {
+        let uri = format!(
+            "{}/repos/{}/{}/releases/assets/{}",
+            self.api_url, owner, repo, asset_id
+        );
+
+        delete!(self.http, &uri)?;
+
+        Ok(())
+    }
+
+    pub(super) async fn list_release_assets(
+        &self,
+        owner: &str,
+        repo: &str,
+        release_id: u64,
+    ) -> Result<Vec<ReleaseAsset>> {
+        let uri = format!(
+            "{}/repos/{}/{}/releases/{}/assets",
+            self.api_url, owner, repo, release_id
+        );
+
+        self.get_all(&uri).await
+    }
+
+    pub(super) async fn download_asset(&self, asset: &ReleaseAsset) -> Result<Vec<u8>> {
+        debug!("downloading asset: {}", asset.name);
+
+        let bytes = self
+            .http
+            .get(&asset.url)
+            .bearer_auth(auth::token().await?)
+            .header(ACCEPT, "application/octet-stream")
+            .header(USER_AGENT, "rustreleaser")
+            .timeout(http::transfer_timeout())
+            .send_with_retry()
+            .await?
+            .error_for_status()?
+            .bytes()
+            .await?;
+
+        Ok(bytes.to_vec())
+    }
+
+    /// URL the repository is cloned from
+    pub(super) fn git_url(&self, owner: &str, repo: &str) -> String {
+        format!("{}/{}/{}.git", self.web_url, owner, repo)
+    }
+
+    /// Source archive GitHub generates for a tag
+    pub(super) fn archive_url(&self, owner: &str, repo: &str, tag: &Tag) -> String {
+        format!(
+            "{}/{}/{}/archive/refs/tags/{}.tar.gz",
+            self.web_url,
+            owner,
+            repo,
+            tag.value()
+        )
+    }
+
+    pub(super) async fn download(&self, url: &str) -> Result<Vec<u8>> {
+        let bytes = self
+            .http
+            .get(url)
+            .header(USER_AGENT, "rustreleaser")
+            .timeout(http::transfer_timeout())
+            .send_with_retry()
+            .await?
+            .error_for_status()
+            .with_context(|| format!("failed to download {}", url))?
+            .bytes()
+            .await?;
+
+        Ok(bytes.to_vec())
+    }
+
+    pub(super) async fn get_commit_author(
+        &self,
+        owner: &str,
+        repo: &str,
+        sha: &str,
+    ) -> Result<Option<String>> {
+        let uri = format!("{}/repos/{}/{}/commits/{}", self.api_url, owner, repo, sha);
+
+        let commit = self
+            .http
+            .get(&uri)
+            .bearer_auth(auth::token().await?)
+            .header(ACCEPT, "application/vnd.github+json")
+            .header(USER_AGENT, "rustreleaser")
+            .send_with_retry()
+            .await?
+            .error_for_status()?
+            .json::<CommitResponse>()
+            .await?;
+
+        Ok(commit.author.map(|author| author.login))
+    }
+
+    pub(super) async fn get_repository_access(
+        &self,
+        owner: &str,
+        repo: &str,
+    ) -> Result<RepositoryAccess> {
+        let uri = format!("{}/repos/{}/{}", self.api_url, owner, repo);
+
+        let response = self
+            .http
+            .get(&uri)
+            .bearer_auth(auth::token().await?)
+            .header(ACCEPT, "application/vnd.github+json")
+            .header(USER_AGENT, "rustreleaser")
+            .send_with_retry()
+            .await?
+            .error_for_status()?;
+
+        let scopes = response
+            .headers()
+            .get("x-oauth-scopes")
+            .and_then(|scopes| scopes.to_str().ok())
+            .map(|scopes| {
+                scopes
+                    .split(',')
+                    .map(|scope| scope.trim().to_string())
+                    .filter(|scope| !scope.is_empty())
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let repository = response.json::<RepositoryResponse>().await?;
+
+        Ok(RepositoryAccess {
+            push: repository.permissions.map(|permissions| permissions.push),
+            scopes,
+        })
+    }
+
+    /// Whether the repository exists, or is hidden from the token
+    pub(super) async fn repository_exists(&self, owner: &str, repo: &str) -> Result<bool> {
+        let uri = format!("{}/repos/{}/{}", self.api_url, owner, repo);
+
+        let response = self
+            .http
+            .get(&uri)
+            .default_headers()
+            .await?
+            .send_with_retry()
+            .await?;
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok(false);
+        }
+        response.error_for_status()?;
+
+        Ok(true)
+    }
+
+    /// Create a repository with a README on its default branch, returning the branch
+    pub(super) async fn create_repository(
+        &self,
+        owner: &str,
+        repo: &str,
+        description: &str,
+    ) -> Result<String> {
+        let account = get!(self.http, &format!("{}/users/{}", self.api_url, owner))
+            .with_context(|| format!("failed to get the GitHub account {}", owner))?;
+        let account: AccountResponse = serde_json::from_str(&account)?;
+
+        // a user's repositories can only be created for the user of the token
+        let uri = match account.account_type.as_str() {
+            "Organization" => format!("{}/orgs/{}/repos", self.api_url, owner),
+            _ => format!("{}/user/repos", self.api_url),
+        };
+        let request = CreateRepositoryRequest::new(repo.to_owned(), description.to_owned());
+        let body = serde_json::to_string(&request)?;
+
+        let response = post!(self.http, &uri, body)
+            .with_context(|| format!("failed to create {}/{}", owner, repo))?;
+        let repository: RepositoryResponse = serde_json::from_str(&response)?;
+        if !repository
+            .full_name
+            .eq_ignore_ascii_case(&format!("{}/{}", owner, repo))
+        {
+            bail!(
+                "created {} instead of {}/{}, the GitHub token belongs to another user",
+                repository.full_name,
+                owner,
+                repo
+            );
+        }
+
+        Ok(repository.default_branch)
+    }
+
+    pub(super) async fn get_file_content(
+        &self,
+        owner: &str,
+        repo: &str,
+        path: &str,
+        branch: &str,
+    ) -> Result<String> {
+        let uri = format!(
+            "{}/repos/{}/{}/contents/{}?ref={}",
+            self.api_url, owner, repo, path, branch
+        );
+
+        let content = self
+            .http
+            .get(&uri)
+            .bearer_auth(auth::token().await?)
+            .header(ACCEPT, "application/vnd.github.raw")
+            .header(USER_AGENT, "rustreleaser")
+            .send_cached()
+            .await?
+            .error_for_status()
+            .with_context(|| format!("failed to get {} from {}/{}", path, owner, repo))?
+            .text()
+            .await?;
+
+        Ok(content)
+    }
+
+    /// Raw content of a file, not cached since the cache only holds text
+    pub(super) async fn get_file_bytes(
+        &self,
+        owner: &str,
+        repo: &str,
+        path: &str,
+        branch: &str,
+    ) -> Result<Vec<u8>> {
+        let uri = format!(
+            "{}/repos/{}/{}/contents/{}?ref={}",
+            self.api_url, owner, repo, path, branch
+        );
+
+        let content = self
+            .http
+            .get(&uri)
+            .bearer_auth(auth::token().await?)
+            .header(ACCEPT, "application/vnd.github.raw")
+            .header(USER_AGENT, "rustreleaser")
+            .send_with_retry()
+            .await?
+            .error_for_status()
+            .with_context(|| format!("failed to get {} from {}/{}", path, owner, repo))?
+            .bytes()
+            .await?;
+
+        Ok(content.to_vec())
+    }
+
     async fn set_pr_assignees(
         &self,
         owner: &str,
@@ -298,15 +827,15 @@ impl GithubClient {
         assignees: Vec<String>,
     ) -> Result<()> {
         let uri = format!(
-            "https://api.github.com/repos/{}/{}/issues/{}/assignees",
-            owner, repo, pr_number
+            "{}/repos/{}/{}/issues/{}/assignees",
+            self.api_url, owner, repo, pr_number
         );
 
         let request = AssigneesRequest::new(assignees);
 
         let body: String = serde_json::to_string(&request)?;
 
-        post!(&uri, body)?;
+        post!(self.http, &uri, body)?;
 
         Ok(())
     }
@@ -319,15 +848,15 @@ impl GithubClient {
         labels: Vec<String>,
     ) -> Result<()> {
         let uri = format!(
-            "https://api.github.com/repos/{}/{}/issues/{}/labels",
-            owner, repo, pr_number
+            "{}/repos/{}/{}/issues/{}/labels",
+            self.api_url, owner, repo, pr_number
         );
 
         let request = LabelsRequest::new(labels);
 
         let body: String = serde_json::to_string(&request)?;
 
-        post!(&uri, body)?;
+        post!(self.http, &uri, body)?;
 
         Ok(())
     }
@@ -1,5 +1,5 @@
 use crate::github::{
-    builder::upsert_file_builder::UpsertFileBuilder, github_client, response::sha_response::Sha,
+    builder::commit_files_builder::CommitFilesBuilder, github_client, response::sha_response::Sha,
 };
 use anyhow::Result;
 
@@ -21,14 +21,39 @@ impl BranchHandler {
         }
     }
 
-    pub fn upsert_file(&self) -> UpsertFileBuilder {
-        UpsertFileBuilder::new(
+    pub fn commit_files(&self) -> CommitFilesBuilder {
+        CommitFilesBuilder::new(
             self.owner.to_owned(),
             self.repo.to_owned(),
             self.base.to_owned(),
         )
     }
 
+    pub async fn get_file_content(&self, path: &str) -> Result<String> {
+        github_client::instance()
+            .get_file_content(&self.owner, &self.repo, path, &self.base)
+            .await
+    }
+
+    pub async fn get_file_bytes(&self, path: &str) -> Result<Vec<u8>> {
+        github_client::instance()
+            .get_file_bytes(&self.owner, &self.repo, path, &self.base)
+            .await
+    }
+
+    /// Point the branch at `sha`, discarding the commits it had
+    pub async fn reset(&self, sha: &str) -> Result<()> {
+        github_client::instance()
+            .reset_branch(&self.owner, &self.repo, &self.base, sha)
+            .await
+    }
+
+    pub async fn delete(&self) -> Result<()> {
+        github_client::instance()
+            .delete_ref(&self.owner, &self.repo, &format!("heads/{}", self.base))
+            .await
+    }
+
     pub async fn get_commit_sha(&self) -> Result<Sha> {
         github_client::instance()
             .get_commit_sha(&self.owner, &self.repo, &self.base)
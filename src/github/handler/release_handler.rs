@@ -1,6 +1,6 @@
 use crate::github::{
     builder::create_release_builder::CreateReleaseBuilder, github_client, release::Release,
-    tag::Tag,
+    response::release_response::ReleaseResponse, tag::Tag,
 };
 use anyhow::Result;
 
@@ -24,6 +24,24 @@ impl ReleaseHandler {
         CreateReleaseBuilder::new(self.owner.to_owned(), self.repo.to_owned())
     }
 
+    /// Notes GitHub generates from the pull requests merged since `previous`
+    pub async fn generate_notes(
+        &self,
+        tag: &Tag,
+        target_branch: &str,
+        previous: Option<&Tag>,
+    ) -> Result<String> {
+        github_client::instance()
+            .generate_release_notes(&self.owner, &self.repo, tag, target_branch, previous)
+            .await
+    }
+
+    pub async fn list(&self) -> Result<Vec<ReleaseResponse>> {
+        github_client::instance()
+            .list_releases(&self.owner, &self.repo)
+            .await
+    }
+
     pub async fn get_by_tag(&self, tag: &Tag) -> Result<Release> {
         github_client::instance()
             .get_release_by_tag(&self.owner, &self.repo, tag)
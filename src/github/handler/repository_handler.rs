@@ -1,3 +1,6 @@
+use crate::github::{github_client, response::repository_response::RepositoryAccess, tag::Tag};
+use anyhow::Result;
+
 use super::{
     branch_handler::BranchHandler, branches_handler::BranchesHandler,
     pull_request_handler::PullRequestHandler, release_handler::ReleaseHandler,
@@ -35,6 +38,56 @@ impl RepositoryHandler {
         )
     }
 
+    /// Push permission and token scopes on the repository
+    pub async fn access(&self) -> Result<RepositoryAccess> {
+        github_client::instance()
+            .get_repository_access(&self.owner, &self.repo)
+            .await
+    }
+
+    pub async fn exists(&self) -> Result<bool> {
+        github_client::instance()
+            .repository_exists(&self.owner, &self.repo)
+            .await
+    }
+
+    /// Create the repository with a README, returning its default branch
+    pub async fn create(&self, description: &str) -> Result<String> {
+        github_client::instance()
+            .create_repository(&self.owner, &self.repo, description)
+            .await
+    }
+
+    pub async fn delete_tag(&self, tag: &str) -> Result<()> {
+        github_client::instance()
+            .delete_ref(&self.owner, &self.repo, &format!("tags/{}", tag))
+            .await
+    }
+
+    /// GitHub login of the author of a commit
+    pub async fn commit_author(&self, sha: &str) -> Result<Option<String>> {
+        github_client::instance()
+            .get_commit_author(&self.owner, &self.repo, sha)
+            .await
+    }
+
+    /// URL the repository is cloned from
+    pub fn git_url(&self) -> String {
+        github_client::instance().git_url(&self.owner, &self.repo)
+    }
+
+    /// URL of the source archive of a tag
+    pub fn archive_url(&self, tag: &Tag) -> String {
+        github_client::instance().archive_url(&self.owner, &self.repo, tag)
+    }
+
+    /// Source archive of a tag
+    pub async fn archive(&self, tag: &Tag) -> Result<Vec<u8>> {
+        github_client::instance()
+            .download(&self.archive_url(tag))
+            .await
+    }
+
     pub fn pull_request(&self) -> PullRequestHandler {
         PullRequestHandler::new(self.owner.to_owned(), self.repo.to_owned())
     }
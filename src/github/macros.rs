@@ -1,83 +1,162 @@
-use super::github_client::GITHUB_TOKEN;
+use super::auth;
+use crate::http::error_response::ErrorResponse;
 use reqwest::{
     header::{ACCEPT, USER_AGENT},
     RequestBuilder,
 };
 
 pub trait Headers {
-    fn default_headers(self) -> RequestBuilder;
+    async fn default_headers(self) -> Result<RequestBuilder, ErrorResponse>;
 }
 
 impl Headers for RequestBuilder {
-    fn default_headers(self) -> RequestBuilder {
-        self.bearer_auth(GITHUB_TOKEN.to_string())
+    async fn default_headers(self) -> Result<RequestBuilder, ErrorResponse> {
+        let token = auth::token()
+            .await
+            .map_err(|err| ErrorResponse::new(format!("{:#}", err), 401))?;
+
+        Ok(self
+            .bearer_auth(token)
             .header(ACCEPT, "application/vnd.github.VERSION.sha")
             .header("X-GitHub-Api-Version", "2022-11-28")
-            .header(USER_AGENT, "rustreleaser")
+            .header(USER_AGENT, "rustreleaser"))
     }
 }
 
 #[macro_export]
 macro_rules! put {
-    ($url:expr, $body:expr) => {{
-        use $crate::{github::macros::Headers, http::ResponseHandler};
+    ($client:expr, $url:expr, $body:expr) => {{
+        use $crate::{
+            github::macros::Headers,
+            http::{RateLimited, ResponseHandler},
+        };
 
-        $crate::http::HttpClient::new()
-            .put($url)
-            .default_headers()
-            .body($body)
-            .send()
-            .await
-            .handle()
-            .await
+        async {
+            $client
+                .put($url)
+                .default_headers()
+                .await?
+                .body($body)
+                .send_with_retry()
+                .await
+                .handle()
+                .await
+        }
+        .await
     }};
 }
 
 #[macro_export]
 macro_rules! get {
-    ($url:expr) => {{
-        use $crate::{github::macros::Headers, http::ResponseHandler};
+    ($client:expr, $url:expr) => {{
+        use $crate::{
+            github::macros::Headers,
+            http::{cache::Cached, ResponseHandler},
+        };
 
-        $crate::http::HttpClient::new()
-            .get($url)
-            .default_headers()
-            .send()
-            .await
-            .handle()
-            .await
+        async {
+            $client
+                .get($url)
+                .default_headers()
+                .await?
+                .send_cached()
+                .await
+                .handle()
+                .await
+        }
+        .await
     }};
 }
 
 #[macro_export]
 macro_rules! post {
-    ($url:expr, $body:expr) => {{
-        use $crate::{github::macros::Headers, http::ResponseHandler};
+    ($client:expr, $url:expr, $body:expr) => {{
+        use $crate::{
+            github::macros::Headers,
+            http::{RateLimited, ResponseHandler},
+        };
 
-        $crate::http::HttpClient::new()
-            .post($url)
-            .default_headers()
-            .body($body)
-            .send()
-            .await
-            .handle()
-            .await
+        async {
+            $client
+                .post($url)
+                .default_headers()
+                .await?
+                .body($body)
+                .send_with_retry()
+                .await
+                .handle()
+                .await
+        }
+        .await
     }};
 }
 
 #[macro_export]
 macro_rules! form {
-    ($url:expr, $form:expr) => {{
+    ($client:expr, $url:expr, $form:expr) => {{
         use reqwest::header::CONTENT_TYPE;
-        use $crate::{github::macros::Headers, http::ResponseHandler};
+        use $crate::{
+            github::macros::Headers,
+            http::{RateLimited, ResponseHandler},
+        };
 
-        $crate::http::HttpClient::new()
-            .post($url)
-            .default_headers()
-            .header(CONTENT_TYPE, "application/octet-stream")
-            .multipart($form)
-            .send()
-            .await
-            .handle()
-            .await
+        async {
+            $client
+                .post($url)
+                .default_headers()
+                .await?
+                .header(CONTENT_TYPE, "application/octet-stream")
+                .multipart($form)
+                .send_with_retry()
+                .await
+                .handle()
+                .await
+        }
+        .await
+    }};
+}
+
+#[macro_export]
+macro_rules! patch {
+    ($client:expr, $url:expr, $body:expr) => {{
+        use $crate::{
+            github::macros::Headers,
+            http::{RateLimited, ResponseHandler},
+        };
+
+        async {
+            $client
+                .patch($url)
+                .default_headers()
+                .await?
+                .body($body)
+                .send_with_retry()
+                .await
+                .handle()
+                .await
+        }
+        .await
+    }};
+}
+
+#[macro_export]
+macro_rules! delete {
+    ($client:expr, $url:expr) => {{
+        use $crate::{
+            github::macros::Headers,
+            http::{RateLimited, ResponseHandler},
+        };
+
+        async {
+            $client
+                .delete($url)
+                .default_headers()
+                .await?
+                .send_with_retry()
+                .await
+                .handle()
+                .await
+        }
+        .await
     }};
 }
@@ -1,51 +1,71 @@
 mod arch_os_matrix;
 pub mod asset;
+pub mod auth;
 pub mod builder;
 pub mod github_client;
 pub mod handler;
 pub mod macros;
+pub mod prune;
 pub mod release;
+pub mod release_template;
 pub mod request;
 pub mod response;
 pub mod tag;
 
+#[cfg(test)]
+pub(crate) mod tests;
+
 use self::{
-    arch_os_matrix::ArchOsMatrixEntry, asset::UploadedAsset, builder::BuilderExecutor,
-    release::Release, tag::Tag,
+    arch_os_matrix::ArchOsMatrixEntry,
+    asset::{download_url, UploadedAsset},
+    builder::BuilderExecutor,
+    release::Release,
+    release_template::Artifact,
+    request::create_release_request::CreateReleaseRequest,
+    tag::Tag,
 };
 use crate::{
-    brew::package::Package,
-    build::{arch::Arch, os::Os, Build},
+    archive::{self, ArchiveEntry},
+    build::{arch::Arch, committer::Committer, compression::Compression, os::Os, Build},
     checksum,
-    config::ReleaseConfig,
-    git,
+    config::{ExistingRelease, PullRequestConfig, ReleaseConfig},
+    dry_run, git,
     github::{arch_os_matrix::PushArchOsMatrix, asset::Asset},
+    http::error_response::ErrorResponse,
+    packager::package::Package,
+    pipeline::{self, CreatedBranch},
+    provenance,
 };
-use anyhow::{bail, Result};
-use flate2::{write::GzEncoder, Compression};
+use anyhow::{bail, Context, Result};
+use serde_json::json;
 use std::{
-    fs::{self, File},
-    future::Future,
-    io::Write,
+    fs, iter,
     path::{Path, PathBuf},
     vec,
 };
-use tar::Builder;
 
 const SINGLE_TARGET_DIR: &str = "target/release";
+pub const CHECKSUMS_FILE_NAME: &str = "checksums.txt";
 
+#[allow(clippy::too_many_arguments)]
 pub async fn release(
     build_info: &Build,
     release_info: &ReleaseConfig,
+    tag: &Tag,
+    changelog: Option<String>,
+    extra_assets: Vec<Asset>,
     base: PathBuf,
     dry_run: bool,
-    output_path: &PathBuf,
+    output_path: &Path,
 ) -> Result<Vec<Package>> {
     let packages = if build_info.is_multi_target() {
         log::debug!("Running multi target");
         multi(
             build_info.to_owned(),
             release_info.to_owned(),
+            tag.to_owned(),
+            changelog,
+            extra_assets,
             base,
             dry_run,
             output_path,
@@ -56,6 +76,9 @@ pub async fn release(
         single(
             build_info.to_owned(),
             release_info.to_owned(),
+            tag.to_owned(),
+            changelog,
+            extra_assets,
             base,
             dry_run,
             output_path,
@@ -66,37 +89,38 @@ pub async fn release(
     Ok(packages)
 }
 
+#[allow(clippy::too_many_arguments)]
 async fn single(
     build_info: Build,
     release_info: ReleaseConfig,
+    tag: Tag,
+    changelog: Option<String>,
+    extra_assets: Vec<Asset>,
     base: PathBuf,
     dry_run: bool,
-    output_path: &PathBuf,
+    output_path: &Path,
 ) -> Result<Vec<Package>> {
     // validate binary
-    check_binary(&build_info.binary.to_owned(), None, &base)?;
-
-    let tag = git::get_current_tag(&base)?;
+    let binary = Os::current().binary_name(&build_info.binary);
+    check_binary(&binary, None, &base)?;
 
     // calculate full binary name
-    let binary_name = format!(
-        "{}_{}.{}",
-        build_info.binary,
-        tag.value(),
-        build_info.compression.extension()
-    );
+    let compression = build_info.compression_for(&Arch::current(), &Os::current());
+    let binary_name = build_info.archive_name(&tag, &Arch::current(), &Os::current())?;
 
     log::debug!("binary name: {}", binary_name);
 
     // zip binary
     log::debug!("zipping binary");
-    zip_file(
-        &build_info.binary.to_owned(),
-        &output_path.join(binary_name.to_owned()),
-        base.join(format!("{}/{}", SINGLE_TARGET_DIR, build_info.binary)),
+    archive_file(
+        &compression,
+        &binary,
+        &output_path.join(&binary_name),
+        base.join(format!("{}/{}", SINGLE_TARGET_DIR, binary)),
+        &build_info.archive_files(&base, output_path)?,
     )?;
 
-    let path = output_path.join(binary_name.to_owned());
+    let path = output_path.join(&binary_name);
 
     // create an asset
     log::debug!("creating asset");
@@ -110,31 +134,74 @@ async fn single(
     log::debug!("adding checksum to asset");
     asset.add_checksum(checksum);
 
+    let provenance = create_provenance_asset(
+        &build_info,
+        &release_info,
+        &tag,
+        &[asset.to_owned()],
+        &base,
+        output_path,
+    )?;
+
+    let artifacts = vec![Artifact::new(
+        &release_info,
+        &tag,
+        &asset.name,
+        asset.checksum.as_deref().unwrap_or_default(),
+        None,
+    )];
+    let release_info =
+        release_template::render(release_info, &tag, artifacts, changelog.as_deref(), &base)?;
+
+    let extra_assets = add_checksums(extra_assets)?;
+    let checksums = create_checksums_asset(iter::once(&asset).chain(&extra_assets), output_path)?;
+
     // create release
     log::debug!("creating release");
 
     if dry_run {
+        record_release(
+            &release_info,
+            &tag,
+            iter::once(&asset)
+                .chain(&provenance)
+                .chain(&extra_assets)
+                .chain(iter::once(&checksums)),
+            output_path,
+        )?;
+
         let package = Package::new(
             asset.name.to_owned(),
             None,
             None,
-            None,
+            Some(download_url(
+                &release_info.owner,
+                &release_info.repo,
+                &tag,
+                &asset.name,
+            )),
             asset.checksum.to_owned().unwrap_or_default(),
         );
         Ok(vec![package])
     } else {
-        let release =
-            get_release(release_info, &tag, do_create_release, get_release_by_tag).await?;
+        let release = find_or_create_release(release_info, &tag).await?;
 
         // upload to release
         log::debug!("uploading asset");
-        let uploaded_assets = match release.upload_assets(vec![asset], &tag, output_path).await {
-            Ok(uploaded_assets) => uploaded_assets,
-            Err(e) => {
-                log::error!("Failed to upload asset {:#?}", e);
-                bail!(anyhow::anyhow!("Failed to upload asset"))
-            }
-        };
+        let uploaded_assets = release
+            .upload_assets(vec![asset], &tag, output_path)
+            .await?;
+
+        if let Some(provenance) = provenance {
+            log::debug!("uploading provenance");
+            release.upload_asset(&provenance, &tag).await?;
+        }
+
+        for extra_asset in &extra_assets {
+            release.upload_asset(extra_asset, &tag).await?;
+        }
+
+        release.upload_asset(&checksums, &tag).await?;
 
         // return a package with the asset url and checksum value
         let packages: Vec<Package> = uploaded_assets
@@ -145,22 +212,25 @@ async fn single(
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 async fn multi(
     build_info: Build,
     release_info: ReleaseConfig,
+    tag: Tag,
+    changelog: Option<String>,
+    extra_assets: Vec<Asset>,
     base: PathBuf,
     dry_run: bool,
-    output_path: &PathBuf,
+    output_path: &Path,
 ) -> Result<Vec<Package>> {
-    let tag = git::get_current_tag(&base)?;
-
-    let archs = build_info.arch.unwrap_or_default();
-    let os = build_info.os.unwrap_or_default();
+    let files = build_info.archive_files(&base, output_path)?;
+    let archs = build_info.arch.to_owned().unwrap_or_default();
+    let os = build_info.os.to_owned().unwrap_or_default();
     let mut matrix: Vec<ArchOsMatrixEntry> = Vec::new();
 
     for arch in &archs {
         for os in &os {
-            let binary = build_info.binary.to_owned();
+            let binary = os.binary_name(&build_info.binary);
             check_binary(
                 &binary,
                 Some(format!("{}-{}", &arch.to_string(), &os.to_string())),
@@ -168,7 +238,7 @@ async fn multi(
             )?;
 
             let mut entry =
-                ArchOsMatrixEntry::new(arch, os, binary, tag.value(), &build_info.compression);
+                ArchOsMatrixEntry::new(arch, os, build_info.archive_name(&tag, arch, os)?);
 
             let target = format!("{}-{}", &arch.to_string(), &os.to_string());
 
@@ -177,10 +247,12 @@ async fn multi(
             let entry_name = entry.name.to_owned();
 
             // zip binary
-            zip_file(
-                &build_info.binary.to_owned(),
-                &output_path.join(entry_name.to_owned()),
-                base.join(format!("target/{}/release/{}", target, build_info.binary)),
+            archive_file(
+                &build_info.compression_for(arch, os),
+                &binary,
+                &output_path.join(&entry_name),
+                base.join(format!("target/{}/release/{}", target, binary)),
+                &files,
             )?;
 
             // create an asset
@@ -202,7 +274,48 @@ async fn multi(
         .cloned()
         .filter_map(|entry| entry.asset)
         .collect();
+
+    let provenance = create_provenance_asset(
+        &build_info,
+        &release_info,
+        &tag,
+        &assets,
+        &base,
+        output_path,
+    )?;
+
+    let artifacts = matrix
+        .iter()
+        .filter_map(|entry| {
+            entry.asset.as_ref().map(|asset| {
+                Artifact::new(
+                    &release_info,
+                    &tag,
+                    &asset.name,
+                    asset.checksum.as_deref().unwrap_or_default(),
+                    Some((entry.os.to_string(), entry.arch.to_string())),
+                )
+            })
+        })
+        .collect();
+    let release_info =
+        release_template::render(release_info, &tag, artifacts, changelog.as_deref(), &base)?;
+
+    let extra_assets = add_checksums(extra_assets)?;
+    let checksums = create_checksums_asset(assets.iter().chain(&extra_assets), output_path)?;
+
     if dry_run {
+        record_release(
+            &release_info,
+            &tag,
+            assets
+                .iter()
+                .chain(&provenance)
+                .chain(&extra_assets)
+                .chain(iter::once(&checksums)),
+            output_path,
+        )?;
+
         let packages: Vec<Package> = matrix
             .into_iter()
             .map(|entry| {
@@ -216,19 +329,33 @@ async fn multi(
                     asset.name.to_owned(),
                     Some(entry.os.to_owned()),
                     Some(entry.arch.to_owned()),
-                    None,
+                    Some(download_url(
+                        &release_info.owner,
+                        &release_info.repo,
+                        &tag,
+                        &asset.name,
+                    )),
                     asset.checksum.to_owned().unwrap_or_default(),
                 )
             })
             .collect();
         Ok(packages)
     } else {
-        let release =
-            get_release(release_info, &tag, do_create_release, get_release_by_tag).await?;
+        let release = find_or_create_release(release_info, &tag).await?;
 
         // upload to release
         let uploaded_assets = release.upload_assets(assets, &tag, output_path).await?;
 
+        if let Some(provenance) = provenance {
+            release.upload_asset(&provenance, &tag).await?;
+        }
+
+        for extra_asset in &extra_assets {
+            release.upload_asset(extra_asset, &tag).await?;
+        }
+
+        release.upload_asset(&checksums, &tag).await?;
+
         let packages: Vec<Package> = matrix
             .into_iter()
             .map(|entry| {
@@ -245,22 +372,20 @@ async fn multi(
     }
 }
 
-fn zip_file(binary_name: &str, output_path: &PathBuf, binary_path: PathBuf) -> Result<()> {
-    let mut file = File::open(binary_path)?;
-    let mut archive = Builder::new(Vec::new());
-
-    archive.append_file(binary_name, &mut file)?;
-
-    let compressed_file = File::create(output_path)?;
-    let mut encoder = GzEncoder::new(compressed_file, Compression::default());
-    encoder.write_all(&archive.into_inner()?)?;
-
-    encoder.finish()?;
-
-    Ok(())
+fn archive_file(
+    compression: &Compression,
+    binary_name: &str,
+    output_path: &Path,
+    binary_path: PathBuf,
+    files: &[ArchiveEntry],
+) -> Result<()> {
+    let mut entries = vec![ArchiveEntry::new(binary_name, binary_path)];
+    entries.extend_from_slice(files);
+
+    compression.archiver().archive(&entries, output_path)
 }
 
-fn check_binary(name: &str, target: Option<String>, base: &PathBuf) -> Result<()> {
+fn check_binary(name: &str, target: Option<String>, base: &Path) -> Result<()> {
     log::debug!("checking binary: {} - {:#?}", name, target);
     let binary_path = base.join(if let Some(target) = target {
         format!("target/{}/release/{}", target, name)
@@ -270,7 +395,7 @@ fn check_binary(name: &str, target: Option<String>, base: &PathBuf) -> Result<()
 
     log::debug!("binary path: {:#?}", binary_path);
 
-    if !PathBuf::from(binary_path).exists() {
+    if !binary_path.exists() {
         bail!(anyhow::anyhow!(
             "no release folder found, please run `cargo build --release`"
         ));
@@ -285,14 +410,70 @@ async fn do_create_release(release_info: ReleaseConfig, tag: &Tag) -> Result<Rel
         .create()
         .tag(tag)
         .target_branch(&release_info.target_branch)
-        .name(&format!("v{}", tag.value()))
+        .name(release_name(&release_info, tag))
         .draft(release_info.draft)
-        .prerelease(release_info.prerelease)
+        .prerelease(is_prerelease(&release_info, tag))
+        .make_latest(release_info.make_latest)
+        .discussion_category_name(release_info.discussion_category_name)
         .body(release_info.body.unwrap_or_default())
         .execute()
         .await
 }
 
+/// Record the release a dry run would have created, with its assets
+fn record_release<'a>(
+    release_info: &ReleaseConfig,
+    tag: &Tag,
+    assets: impl Iterator<Item = &'a Asset>,
+    output_path: &Path,
+) -> Result<()> {
+    let request = CreateReleaseRequest::new(
+        tag.value().to_owned(),
+        release_info.target_branch.to_owned(),
+        release_name(release_info, tag),
+        release_info.body.to_owned().unwrap_or_default(),
+        release_info.draft,
+        is_prerelease(release_info, tag),
+        release_info.make_latest,
+        release_info.discussion_category_name.to_owned(),
+    );
+    let assets: Vec<_> = assets
+        .map(|asset| {
+            json!({
+                "name": asset.name,
+                "path": asset.path,
+                "sha256": asset.checksum,
+            })
+        })
+        .collect();
+
+    dry_run::record(
+        output_path,
+        "release",
+        &format!(
+            "create release {} on {}/{} with {} assets",
+            tag.value(),
+            release_info.owner,
+            release_info.repo,
+            assets.len()
+        ),
+        &json!({ "release": request, "assets": assets }),
+    )
+}
+
+fn release_name(release_info: &ReleaseConfig, tag: &Tag) -> String {
+    release_info
+        .name
+        .to_owned()
+        .unwrap_or_else(|| tag.value().to_owned())
+}
+
+fn is_prerelease(release_info: &ReleaseConfig, tag: &Tag) -> bool {
+    release_info
+        .prerelease
+        .unwrap_or_else(|| tag.is_prerelease())
+}
+
 async fn get_release_by_tag(release_info: ReleaseConfig, tag: &Tag) -> Result<Release> {
     github_client::instance()
         .repo(&release_info.owner, &release_info.repo)
@@ -301,27 +482,66 @@ async fn get_release_by_tag(release_info: ReleaseConfig, tag: &Tag) -> Result<Re
         .await
 }
 
-async fn get_release<'tag, F, C, FO, CO>(
-    release_info: ReleaseConfig,
-    tag: &'tag Tag,
-    function: F,
-    callback: C,
-) -> Result<Release>
-where
-    F: FnOnce(ReleaseConfig, &'tag Tag) -> FO,
-    C: FnOnce(ReleaseConfig, &'tag Tag) -> CO,
-    FO: Future<Output = Result<Release>>,
-    CO: Future<Output = Result<Release>>,
-{
-    let res = function(release_info.to_owned(), tag).await;
-    match res {
-        Ok(release) => Ok(release),
+/// Whether GitHub answered with `status`, e.g. 404 for a missing resource
+fn has_status(err: &anyhow::Error, status: u16) -> bool {
+    err.downcast_ref::<ErrorResponse>()
+        .is_some_and(|response| response.status == status)
+}
+
+/// Create the release, or reuse the existing one according to `release.existing`
+async fn find_or_create_release(release_info: ReleaseConfig, tag: &Tag) -> Result<Release> {
+    let release = match_release(release_info, tag).await?;
+    let id = release.id;
+    pipeline::record(|state| state.release_id = Some(id))?;
+    Ok(release)
+}
+
+/// The release of `tag`, created, reused or updated
+async fn match_release(release_info: ReleaseConfig, tag: &Tag) -> Result<Release> {
+    let upload = release_info.upload.to_owned();
+    let existing = match get_release_by_tag(release_info.to_owned(), tag).await {
+        Ok(release) => release.with_upload_config(upload),
+        Err(err) if has_status(&err, 404) => {
+            log::debug!("no existing release for {}", tag.value());
+            let release = do_create_release(release_info, tag).await?;
+            pipeline::record(|state| state.release_created = true)?;
+            return Ok(release.with_upload_config(upload));
+        }
         Err(err) => {
-            log::warn!(
-                "cannot create a release, trying to get the release by tag: {:?}",
-                err
-            );
-            callback(release_info, tag).await
+            return Err(err.context(format!("failed to look up the release of {}", tag.value())))
+        }
+    };
+
+    // created or updated by the unfinished run being resumed
+    if pipeline::state(|state| state.release_id) == Some(Some(existing.id)) {
+        log::info!("Resuming the release of {}", tag.value());
+        let replace = release_info.existing == ExistingRelease::Replace;
+        let uploaded = pipeline::state(|state| state.uploaded_assets.to_owned());
+        return existing
+            .replacing_assets(replace)
+            .resuming_uploads(uploaded.unwrap_or_default())
+            .with_existing_assets()
+            .await;
+    }
+
+    match release_info.existing {
+        ExistingRelease::Fail => bail!("a release already exists for {}", tag.value()),
+        ExistingRelease::Keep => {
+            log::info!("Reusing the existing release for {}", tag.value());
+            existing.with_existing_assets().await
+        }
+        ExistingRelease::Replace => {
+            log::info!("Updating the existing release for {}", tag.value());
+            existing
+                .update(
+                    &release_name(&release_info, tag),
+                    release_info.draft,
+                    is_prerelease(&release_info, tag),
+                    release_info.make_latest,
+                    release_info.body.as_deref().unwrap_or_default(),
+                )
+                .await?;
+            existing.replacing_assets(true).with_existing_assets().await
         }
     }
 }
@@ -339,7 +559,55 @@ fn generate_checksum(asset: &Asset) -> Result<String> {
     Ok(checksum)
 }
 
-fn generate_checksum_asset(asset: &Asset, output_path: &PathBuf) -> Result<Asset> {
+/// Resolve the `release.extra_assets` patterns into assets named after the file
+pub fn resolve_extra_assets(patterns: &[String], base: &Path) -> Result<Vec<Asset>> {
+    archive::resolve_files(base, patterns)?
+        .into_iter()
+        .map(|entry| {
+            let name = entry
+                .path
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or(entry.name);
+            Ok(create_asset(name, entry.path))
+        })
+        .collect()
+}
+
+fn add_checksums(assets: Vec<Asset>) -> Result<Vec<Asset>> {
+    assets
+        .into_iter()
+        .map(|mut asset| {
+            if asset.checksum.is_none() {
+                let checksum = generate_checksum(&asset)?;
+                asset.add_checksum(checksum);
+            }
+            Ok(asset)
+        })
+        .collect()
+}
+
+/// Write a single `sha256sum` compatible file covering every given asset
+fn create_checksums_asset<'a>(
+    assets: impl Iterator<Item = &'a Asset>,
+    output_path: &Path,
+) -> Result<Asset> {
+    let mut content = String::new();
+    for asset in assets {
+        let checksum = asset
+            .checksum
+            .as_ref()
+            .with_context(|| format!("checksum is not available for asset {}", asset.name))?;
+        content.push_str(&format!("{}  {}\n", checksum, asset.name));
+    }
+
+    let path = output_path.join(CHECKSUMS_FILE_NAME);
+    fs::write(&path, content)?;
+
+    Ok(create_asset(CHECKSUMS_FILE_NAME, path))
+}
+
+fn generate_checksum_asset(asset: &Asset, output_path: &Path) -> Result<Asset> {
     if let Some(checksum) = &asset.checksum {
         let sha256_file_name = format!("{}.sha256", asset.name);
 
@@ -356,6 +624,131 @@ fn generate_checksum_asset(asset: &Asset, output_path: &PathBuf) -> Result<Asset
     }
 }
 
+/// Release notes generated by GitHub for `tag`
+pub async fn generate_notes(
+    release_info: &ReleaseConfig,
+    tag: &Tag,
+    base: &PathBuf,
+) -> Result<String> {
+    let previous = git::get_previous_tag(base, tag)?;
+
+    github_client::instance()
+        .repo(&release_info.owner, &release_info.repo)
+        .releases()
+        .generate_notes(tag, &release_info.target_branch, previous.as_ref())
+        .await
+}
+
+/// Commit a file on a new branch created from the pull request base, then open the pull request,
+/// returning the SHA of the commit
+pub async fn push_with_pull_request(
+    owner: &str,
+    repo: &str,
+    path: &str,
+    content: &str,
+    message: &str,
+    committer: Option<&Committer>,
+    pull_request: PullRequestConfig,
+) -> Result<String> {
+    let repo_handler = github_client::instance().repo(owner, repo);
+
+    log::debug!("Creating branch");
+    let sha = repo_handler
+        .branch(&pull_request.base)
+        .get_commit_sha()
+        .await
+        .context("error getting the base branch commit sha")?;
+
+    let created = repo_handler
+        .branches()
+        .create()
+        .branch(&pull_request.head)
+        .sha(&sha.sha)
+        .execute()
+        .await;
+    match created {
+        Ok(()) => pipeline::record(|state| {
+            state.branches.push(CreatedBranch {
+                owner: owner.to_owned(),
+                repo: repo.to_owned(),
+                name: pull_request.head.to_owned(),
+            })
+        })?,
+        // left by a previous release, e.g. the default `bumps-formula-version`; it existed
+        // before the run, so a rollback keeps it
+        Err(err) if has_status(&err, 422) => {
+            log::info!(
+                "Resetting the existing branch {} to {}",
+                pull_request.head,
+                pull_request.base
+            );
+            repo_handler
+                .branch(&pull_request.head)
+                .reset(&sha.sha)
+                .await
+                .context("error resetting the existing branch")?;
+        }
+        Err(err) => return Err(err.context("error creating the branch")),
+    }
+
+    log::debug!("Updating {}", path);
+    let mut commit = repo_handler
+        .branch(&pull_request.head)
+        .commit_files()
+        .file(path, content)
+        .message(message);
+    if let Some(committer) = committer {
+        commit = commit.committer(committer);
+    }
+    let sha = commit
+        .execute()
+        .await
+        .context("error uploading file to head branch")?;
+
+    log::debug!("Creating pull request");
+    let mut create = repo_handler
+        .pull_request()
+        .create()
+        .assignees(pull_request.assignees.unwrap_or_default())
+        .base(pull_request.base)
+        .head(&pull_request.head)
+        .body(pull_request.body.unwrap_or_default())
+        .labels(pull_request.labels.unwrap_or_default())
+        .title(pull_request.title.unwrap_or_default());
+    if let Some(committer) = committer {
+        create = create.committer(committer);
+    }
+    create
+        .execute()
+        .await
+        .context("error creating pull request")?;
+
+    Ok(sha)
+}
+
+fn create_provenance_asset(
+    build_info: &Build,
+    release_info: &ReleaseConfig,
+    tag: &Tag,
+    assets: &[Asset],
+    base: &PathBuf,
+    output_path: &Path,
+) -> Result<Option<Asset>> {
+    let Some(config) = &release_info.provenance else {
+        return Ok(None);
+    };
+
+    let commit = git::get_head_commit(base)?;
+    let statement =
+        provenance::Statement::new(config, release_info, build_info, tag, &commit, assets)?;
+
+    let name = format!("{}_{}.intoto.jsonl", build_info.binary, tag.value());
+    let path = output_path.join(&name);
+    provenance::write(&statement, &path)?;
+
+    Ok(Some(create_asset(name, path)))
+}
+
 fn package_asset(asset: &UploadedAsset, os: Option<&Os>, arch: Option<&Arch>) -> Package {
     Package::new(
         asset.name.to_owned(),
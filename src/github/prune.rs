@@ -0,0 +1,92 @@
+use super::{
+    github_client, release::Release, response::release_response::ReleaseResponse, tag::Tag,
+};
+use crate::config::{PruneAction, PruneConfig, ReleaseConfig};
+use anyhow::{Context, Result};
+use semver::Version;
+
+/// Delete or mark the prereleases of the version line that `tag` just made stable
+pub async fn prune(
+    prune: &PruneConfig,
+    release_info: &ReleaseConfig,
+    tag: &Tag,
+    dry_run: bool,
+) -> Result<()> {
+    let version = match Version::parse(tag.version()) {
+        Ok(version) if version.pre.is_empty() => version,
+        _ => {
+            log::debug!("{} is not a stable release, nothing to prune", tag.value());
+            return Ok(());
+        }
+    };
+
+    if dry_run {
+        log::info!("Would prune the prereleases superseded by {}", tag.value());
+        return Ok(());
+    }
+
+    let releases = github_client::instance()
+        .repo(&release_info.owner, &release_info.repo)
+        .releases()
+        .list()
+        .await?;
+
+    for superseded in releases
+        .iter()
+        .filter(|release| is_superseded(release, tag, &version, prune.drafts))
+    {
+        let release = Release::new(superseded.id, &release_info.owner, &release_info.repo);
+        match prune.action {
+            PruneAction::Delete => {
+                release
+                    .delete()
+                    .await
+                    .with_context(|| format!("failed to delete {}", superseded.tag_name))?;
+                log::info!("Deleted superseded release {}", superseded.tag_name);
+            }
+            PruneAction::Mark => {
+                let notice = format!("> Superseded by {}", tag.value());
+                let body = superseded.body.as_deref().unwrap_or_default();
+                if body.starts_with(&notice) {
+                    continue;
+                }
+
+                log::info!("Marking {} as superseded", superseded.tag_name);
+                let name = superseded.name.as_deref().unwrap_or(&superseded.tag_name);
+                release
+                    .update(
+                        &format!("{} (superseded)", name),
+                        superseded.draft,
+                        superseded.prerelease,
+                        None,
+                        &format!("{}\n\n{}", notice, body),
+                    )
+                    .await
+                    .with_context(|| format!("failed to mark {}", superseded.tag_name))?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn is_superseded(release: &ReleaseResponse, tag: &Tag, version: &Version, drafts: bool) -> bool {
+    if release.tag_name == tag.value() {
+        return false;
+    }
+
+    // releases of other crates of a monorepo are named with another prefix
+    if !release.tag_name.starts_with(tag.scope()) {
+        return false;
+    }
+    let candidate = match Version::parse(Tag::with_scope(&release.tag_name, tag.scope()).version())
+    {
+        Ok(candidate) => candidate,
+        Err(_) => return false,
+    };
+    let same_line = candidate.major == version.major
+        && candidate.minor == version.minor
+        && candidate.patch == version.patch;
+
+    same_line && ((release.prerelease && !candidate.pre.is_empty()) || (drafts && release.draft))
+}
@@ -1,18 +1,38 @@
-use std::path::PathBuf;
+use std::{
+    fs,
+    path::Path,
+    time::{Duration, Instant},
+};
 
-use super::{asset::UploadedAsset, generate_checksum_asset, tag::Tag};
+use super::{
+    asset::{download_url, UploadedAsset},
+    generate_checksum_asset,
+    response::release_asset_response::ReleaseAsset,
+    tag::Tag,
+};
 use crate::{
-    brew::package::Package,
+    checksum,
+    config::{MakeLatest, UploadConfig},
+    error::Failure,
     github::{asset::Asset, github_client},
+    logger, pipeline, progress,
 };
-use anyhow::{bail, Result};
+use anyhow::{anyhow, Context, Result};
+use log::Level;
+use serde_json::json;
 
 #[derive(Debug)]
 pub struct Release {
     pub owner: String,
     pub repo: String,
     pub id: u64,
-    pub packages: Vec<Package>,
+    /// Delete assets with the same name before uploading
+    pub replace_assets: bool,
+    pub upload: UploadConfig,
+    /// Assets the release had before this run, `None` when they weren't listed
+    pub existing_assets: Option<Vec<ReleaseAsset>>,
+    /// Assets uploaded by the resumed run, replaced when they changed even if the others are kept
+    pub resumed_assets: Vec<String>,
 }
 
 impl Release {
@@ -24,43 +44,218 @@ impl Release {
             id,
             owner: owner.into(),
             repo: repo.into(),
-            packages: vec![],
+            replace_assets: false,
+            upload: UploadConfig::default(),
+            existing_assets: None,
+            resumed_assets: vec![],
         }
     }
 
+    pub fn with_upload_config(mut self, upload: UploadConfig) -> Self {
+        self.upload = upload;
+        self
+    }
+
+    pub fn replacing_assets(mut self, replace_assets: bool) -> Self {
+        self.replace_assets = replace_assets;
+        self
+    }
+
+    pub fn resuming_uploads(mut self, resumed_assets: Vec<String>) -> Self {
+        self.resumed_assets = resumed_assets;
+        self
+    }
+
+    /// List the assets of the release once, so the uploads skip the ones already there with the
+    /// same content and only replace the changed ones
+    pub async fn with_existing_assets(mut self) -> Result<Self> {
+        self.existing_assets = Some(self.assets().await?);
+        Ok(self)
+    }
+
+    pub async fn update(
+        &self,
+        name: &str,
+        draft: bool,
+        prerelease: bool,
+        make_latest: Option<MakeLatest>,
+        body: &str,
+    ) -> Result<()> {
+        github_client::instance()
+            .update_release(
+                &self.owner,
+                &self.repo,
+                self.id,
+                name,
+                draft,
+                prerelease,
+                make_latest,
+                body,
+            )
+            .await
+    }
+
     pub async fn upload_assets(
         &self,
         assets: Vec<Asset>,
         tag: &Tag,
-        output_path: &PathBuf,
+        output_path: &Path,
     ) -> Result<Vec<UploadedAsset>> {
         let mut uploaded = vec![];
         for asset in assets {
-            let uploaded_asset = github_client::instance()
-                .upload_asset(&asset, &self.owner, tag, &self.repo, self.id)
-                .await?;
-            log::debug!("Uploaded asset: {:#?}", uploaded_asset);
+            let uploaded_asset = self.upload_asset(&asset, tag).await?;
             uploaded.push(uploaded_asset);
 
-            if let Err(err) = self.upload_checksum_asset(&asset, tag, output_path).await {
-                bail!(err)
-            }
+            self.upload_checksum_asset(&asset, tag, output_path).await?;
         }
 
         Ok(uploaded)
     }
 
+    pub async fn delete(&self) -> Result<()> {
+        github_client::instance()
+            .delete_release(&self.owner, &self.repo, self.id)
+            .await
+    }
+
+    pub async fn assets(&self) -> Result<Vec<ReleaseAsset>> {
+        github_client::instance()
+            .list_release_assets(&self.owner, &self.repo, self.id)
+            .await
+    }
+
+    pub async fn download_asset(&self, asset: &ReleaseAsset) -> Result<Vec<u8>> {
+        github_client::instance().download_asset(asset).await
+    }
+
+    /// Upload a single asset without its checksum companion file
+    pub async fn upload_asset(&self, asset: &Asset, tag: &Tag) -> Result<UploadedAsset> {
+        match self
+            .existing_assets
+            .as_ref()
+            .map(|existing| existing.iter().find(|existing| existing.name == asset.name))
+        {
+            Some(Some(existing)) => {
+                if is_identical(existing, asset)? {
+                    log::info!(
+                        "Skipping {}, already uploaded with the same content",
+                        asset.name
+                    );
+                    return Ok(UploadedAsset::new(
+                        asset.name.to_owned(),
+                        download_url(&self.owner, &self.repo, tag, &asset.name),
+                        asset.checksum.to_owned().unwrap_or_default(),
+                    ));
+                }
+                if !self.replace_assets && !self.resumed_assets.contains(&asset.name) {
+                    return Err(anyhow!(
+                        "{} already exists on the release with different content, set \
+                         release.existing to \"replace\" to replace it",
+                        asset.name
+                    ))
+                    .context(Failure::Upload);
+                }
+                log::info!("Replacing changed asset {}", asset.name);
+                github_client::instance()
+                    .delete_release_asset(&self.owner, &self.repo, existing.id)
+                    .await?;
+            }
+            // listed without it, nothing to replace
+            Some(None) => {}
+            None if self.replace_assets => self.delete_asset(&asset.name).await?,
+            None => {}
+        }
+
+        let task = progress::task(format!("Uploading {}", asset.name));
+        let start = Instant::now();
+        let mut attempt = 0;
+        let mut delay = Duration::from_millis(self.upload.backoff);
+        loop {
+            match github_client::instance()
+                .upload_asset(asset, &self.owner, tag, &self.repo, self.id)
+                .await
+            {
+                Ok(uploaded_asset) => {
+                    log::debug!("Uploaded asset: {:#?}", uploaded_asset);
+                    let duration = start.elapsed();
+                    task.finish();
+                    logger::event(
+                        Level::Info,
+                        json!({ "asset": asset.name, "duration_ms": logger::millis(duration) }),
+                        &format!("Uploaded {} in {:.1?}", asset.name, duration),
+                    );
+                    pipeline::record(|state| state.uploaded_assets.push(asset.name.to_owned()))?;
+                    return Ok(uploaded_asset);
+                }
+                Err(err) if attempt < self.upload.retries => {
+                    attempt += 1;
+                    log::warn!(
+                        "Failed to upload {} ({:#}), retrying in {:?} ({}/{})",
+                        asset.name,
+                        err,
+                        delay,
+                        attempt,
+                        self.upload.retries
+                    );
+                    tokio::time::sleep(delay).await;
+                    delay = (delay * 2).min(Duration::from_millis(self.upload.max_backoff));
+
+                    // an interrupted upload can leave a broken asset behind that blocks the new one
+                    if let Err(err) = self.delete_asset(&asset.name).await {
+                        log::warn!("Failed to clean up {}: {:#}", asset.name, err);
+                    }
+                }
+                Err(err) => {
+                    return Err(err)
+                        .with_context(|| format!("failed to upload {}", asset.name))
+                        .context(Failure::Upload)
+                }
+            }
+        }
+    }
+
     async fn upload_checksum_asset(
         &self,
         asset: &Asset,
         tag: &Tag,
-        output_path: &PathBuf,
+        output_path: &Path,
     ) -> Result<()> {
         let checksum_asset = generate_checksum_asset(asset, output_path)?;
-        let ua = github_client::instance()
-            .upload_asset(&checksum_asset, &self.owner, tag, &self.repo, self.id)
-            .await?;
+        let ua = self.upload_asset(&checksum_asset, tag).await?;
         log::debug!("Uploaded checksum asset: {:#?}", ua);
         Ok(())
     }
+
+    async fn delete_asset(&self, name: &str) -> Result<()> {
+        for existing in self.assets().await?.iter().filter(|a| a.name == name) {
+            log::info!("Replacing existing asset {}", existing.name);
+            github_client::instance()
+                .delete_release_asset(&self.owner, &self.repo, existing.id)
+                .await?;
+        }
+        Ok(())
+    }
+}
+
+/// Whether `existing` has the content of `asset`: same size, and same SHA-256 when GitHub
+/// reports the digest
+fn is_identical(existing: &ReleaseAsset, asset: &Asset) -> Result<bool> {
+    let size = fs::metadata(&asset.path)
+        .with_context(|| format!("failed to read {}", asset.path.display()))?
+        .len();
+    if existing.size != size {
+        return Ok(false);
+    }
+    let Some(digest) = existing
+        .digest
+        .as_deref()
+        .and_then(|digest| digest.strip_prefix("sha256:"))
+    else {
+        return Ok(true);
+    };
+    let checksum = match &asset.checksum {
+        Some(checksum) => checksum.to_owned(),
+        None => checksum::create(&asset.name, &asset.path)?,
+    };
+    Ok(digest.eq_ignore_ascii_case(&checksum))
 }
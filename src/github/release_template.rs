@@ -0,0 +1,92 @@
+use super::{
+    asset::download_url,
+    tag::{Tag, VersionComponents},
+};
+use crate::{config::ReleaseConfig, git, template};
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::path::PathBuf;
+use time::OffsetDateTime;
+
+const DEFAULT_NAME_TEMPLATE: &str = "v{{ version }}";
+const DEFAULT_BODY_TEMPLATE: &str = "{{ changelog }}";
+
+/// Values available to the release name and body templates
+#[derive(Debug, Serialize)]
+pub struct ReleaseTemplateData<'data> {
+    pub version: &'data str,
+    #[serde(flatten)]
+    pub components: VersionComponents,
+    pub tag: &'data str,
+    pub date: String,
+    pub commit: String,
+    pub changelog: &'data str,
+    pub artifacts: Vec<Artifact>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Artifact {
+    pub name: String,
+    pub sha256: String,
+    pub url: String,
+    pub os: Option<String>,
+    pub arch: Option<String>,
+}
+
+impl Artifact {
+    pub fn new(
+        release_info: &ReleaseConfig,
+        tag: &Tag,
+        name: &str,
+        sha256: &str,
+        target: Option<(String, String)>,
+    ) -> Self {
+        let (os, arch) = target.unzip();
+        Artifact {
+            name: name.to_owned(),
+            sha256: sha256.to_owned(),
+            url: download_url(&release_info.owner, &release_info.repo, tag, name),
+            os,
+            arch,
+        }
+    }
+}
+
+/// Render the configured name and body templates into the release config
+pub fn render(
+    mut release_info: ReleaseConfig,
+    tag: &Tag,
+    artifacts: Vec<Artifact>,
+    changelog: Option<&str>,
+    base: &PathBuf,
+) -> Result<ReleaseConfig> {
+    let data = ReleaseTemplateData {
+        version: tag.version(),
+        components: tag.components(),
+        tag: tag.value(),
+        date: OffsetDateTime::now_utc().date().to_string(),
+        commit: git::get_head_commit(base)?,
+        changelog: changelog.unwrap_or_default(),
+        artifacts,
+    };
+
+    let name = release_info
+        .name
+        .as_deref()
+        .unwrap_or(DEFAULT_NAME_TEMPLATE);
+    let body = release_info
+        .body
+        .as_deref()
+        .unwrap_or(DEFAULT_BODY_TEMPLATE);
+
+    let name = template::render(name, &data).context("failed to render the release name")?;
+    let body = template::render(body, &data).context("failed to render the release body")?;
+
+    log::debug!("release name: {}", name);
+    log::debug!("release body:\n{}", body);
+
+    release_info.name = Some(name);
+    release_info.body = Some(body);
+
+    Ok(release_info)
+}
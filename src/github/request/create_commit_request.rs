@@ -0,0 +1,77 @@
+use serde::Serialize;
+
+pub const CREATE_COMMIT_MUTATION: &str = "mutation($input: CreateCommitOnBranchInput!) {
+  createCommitOnBranch(input: $input) { commit { oid url } }
+}";
+
+#[derive(Debug, Serialize)]
+pub struct CreateCommitVariables {
+    pub input: CreateCommitInput,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateCommitInput {
+    pub branch: CommittableBranch,
+    pub message: CommitMessage,
+    /// The commit fails if the branch moved since, instead of overwriting
+    pub expected_head_oid: String,
+    pub file_changes: FileChanges,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommittableBranch {
+    pub repository_name_with_owner: String,
+    pub branch_name: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CommitMessage {
+    pub headline: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub body: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FileChanges {
+    pub additions: Vec<FileAddition>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FileAddition {
+    pub path: String,
+    /// Base64 encoded
+    pub contents: String,
+}
+
+impl CreateCommitVariables {
+    pub fn new(
+        owner: &str,
+        repo: &str,
+        branch: &str,
+        message: &str,
+        head: String,
+        additions: Vec<FileAddition>,
+    ) -> Self {
+        let (headline, body) = match message.split_once('\n') {
+            Some((headline, body)) => (headline, Some(body.trim().to_owned())),
+            None => (message, None),
+        };
+
+        Self {
+            input: CreateCommitInput {
+                branch: CommittableBranch {
+                    repository_name_with_owner: format!("{}/{}", owner, repo),
+                    branch_name: branch.to_owned(),
+                },
+                message: CommitMessage {
+                    headline: headline.to_owned(),
+                    body: body.filter(|body| !body.is_empty()),
+                },
+                expected_head_oid: head,
+                file_changes: FileChanges { additions },
+            },
+        }
+    }
+}
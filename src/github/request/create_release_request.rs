@@ -1,3 +1,4 @@
+use crate::config::MakeLatest;
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -8,9 +9,14 @@ pub struct CreateReleaseRequest {
     pub body: String,
     pub draft: bool,
     pub prerelease: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub make_latest: Option<MakeLatest>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub discussion_category_name: Option<String>,
 }
 
 impl CreateReleaseRequest {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         tag_name: String,
         target_commitish: String,
@@ -18,6 +24,8 @@ impl CreateReleaseRequest {
         body: String,
         draft: bool,
         prerelease: bool,
+        make_latest: Option<MakeLatest>,
+        discussion_category_name: Option<String>,
     ) -> Self {
         Self {
             tag_name,
@@ -26,6 +34,8 @@ impl CreateReleaseRequest {
             body,
             draft,
             prerelease,
+            make_latest,
+            discussion_category_name,
         }
     }
 }
@@ -0,0 +1,19 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreateRepositoryRequest {
+    pub name: String,
+    pub description: String,
+    /// Create the default branch with a README, so files can be committed to it
+    pub auto_init: bool,
+}
+
+impl CreateRepositoryRequest {
+    pub fn new(name: String, description: String) -> Self {
+        Self {
+            name,
+            description,
+            auto_init: true,
+        }
+    }
+}
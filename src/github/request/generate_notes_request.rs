@@ -0,0 +1,23 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GenerateNotesRequest {
+    pub tag_name: String,
+    pub target_commitish: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub previous_tag_name: Option<String>,
+}
+
+impl GenerateNotesRequest {
+    pub fn new(
+        tag_name: String,
+        target_commitish: String,
+        previous_tag_name: Option<String>,
+    ) -> Self {
+        Self {
+            tag_name,
+            target_commitish,
+            previous_tag_name,
+        }
+    }
+}
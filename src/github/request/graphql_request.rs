@@ -0,0 +1,13 @@
+use serde::Serialize;
+
+#[derive(Debug, Serialize)]
+pub struct GraphqlRequest<'a, V> {
+    pub query: &'a str,
+    pub variables: V,
+}
+
+impl<'a, V> GraphqlRequest<'a, V> {
+    pub fn new(query: &'a str, variables: V) -> Self {
+        Self { query, variables }
+    }
+}
@@ -1,5 +1,9 @@
 pub mod branch_ref_request;
-pub mod committer_request;
+pub mod create_commit_request;
 pub mod create_release_request;
+pub mod create_repository_request;
+pub mod generate_notes_request;
+pub mod graphql_request;
 pub mod pull_request_request;
-pub mod upsert_file_request;
+pub mod update_ref_request;
+pub mod update_release_request;
@@ -0,0 +1,13 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UpdateRefRequest {
+    pub sha: String,
+    pub force: bool,
+}
+
+impl UpdateRefRequest {
+    pub fn new(sha: String, force: bool) -> Self {
+        Self { sha, force }
+    }
+}
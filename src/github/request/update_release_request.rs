@@ -0,0 +1,30 @@
+use crate::config::MakeLatest;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UpdateReleaseRequest {
+    pub name: String,
+    pub body: String,
+    pub draft: bool,
+    pub prerelease: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub make_latest: Option<MakeLatest>,
+}
+
+impl UpdateReleaseRequest {
+    pub fn new(
+        name: String,
+        body: String,
+        draft: bool,
+        prerelease: bool,
+        make_latest: Option<MakeLatest>,
+    ) -> Self {
+        Self {
+            name,
+            body,
+            draft,
+            prerelease,
+            make_latest,
+        }
+    }
+}
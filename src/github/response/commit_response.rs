@@ -0,0 +1,11 @@
+use serde::Deserialize;
+
+#[derive(Deserialize, Debug)]
+pub struct CommitResponse {
+    pub author: Option<CommitAuthor>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct CommitAuthor {
+    pub login: String,
+}
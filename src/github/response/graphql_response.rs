@@ -0,0 +1,30 @@
+use serde::Deserialize;
+
+#[derive(Deserialize, Debug)]
+pub struct GraphqlResponse<T> {
+    pub data: Option<T>,
+    #[serde(default)]
+    pub errors: Vec<GraphqlError>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct GraphqlError {
+    pub message: String,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateCommitResponse {
+    pub create_commit_on_branch: CreatedCommit,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct CreatedCommit {
+    pub commit: Commit,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct Commit {
+    pub oid: String,
+    pub url: String,
+}
@@ -0,0 +1,18 @@
+use serde::Deserialize;
+
+#[derive(Deserialize, Debug)]
+pub struct Installation {
+    pub id: u64,
+    pub account: InstallationAccount,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct InstallationAccount {
+    pub login: String,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct InstallationToken {
+    pub token: String,
+    pub expires_at: String,
+}
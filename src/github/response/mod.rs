@@ -1,5 +1,11 @@
 pub mod assignees_request;
+pub mod commit_response;
+pub mod generated_notes_response;
+pub mod graphql_response;
+pub mod installation_response;
 pub mod labels_request;
 pub mod pull_request_response;
+pub mod release_asset_response;
 pub mod release_response;
+pub mod repository_response;
 pub mod sha_response;
@@ -0,0 +1,13 @@
+use serde::Deserialize;
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct ReleaseAsset {
+    pub id: u64,
+    pub name: String,
+    pub url: String,
+    #[serde(default)]
+    pub size: u64,
+    /// Digest of the content, e.g. `sha256:<hex>`, missing on assets uploaded before GitHub
+    /// computed them
+    pub digest: Option<String>,
+}
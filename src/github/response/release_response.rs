@@ -3,4 +3,12 @@ use serde::Deserialize;
 #[derive(Deserialize, Debug)]
 pub struct ReleaseResponse {
     pub id: u64,
+    #[serde(default)]
+    pub tag_name: String,
+    pub name: Option<String>,
+    pub body: Option<String>,
+    #[serde(default)]
+    pub draft: bool,
+    #[serde(default)]
+    pub prerelease: bool,
 }
@@ -0,0 +1,34 @@
+use serde::Deserialize;
+
+#[derive(Deserialize, Debug)]
+pub struct RepositoryResponse {
+    /// Missing for GitHub App installation tokens
+    pub permissions: Option<Permissions>,
+    #[serde(default)]
+    pub full_name: String,
+    #[serde(default)]
+    pub default_branch: String,
+}
+
+/// User or organization owning repositories
+#[derive(Deserialize, Debug)]
+pub struct AccountResponse {
+    /// `User` or `Organization`
+    #[serde(rename = "type")]
+    pub account_type: String,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct Permissions {
+    #[serde(default)]
+    pub push: bool,
+}
+
+/// What the token is allowed to do on a repository
+#[derive(Debug)]
+pub struct RepositoryAccess {
+    /// Unknown for GitHub App installation tokens
+    pub push: Option<bool>,
+    /// OAuth scopes of a classic token, empty for fine-grained tokens
+    pub scopes: Vec<String>,
+}
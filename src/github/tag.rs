@@ -1,32 +1,114 @@
-use serde::{Deserialize, Serialize};
+use anyhow::{bail, Context, Result};
+use regex::Regex;
+use semver::Version;
+use serde::{ser::SerializeStruct, Deserialize, Serialize, Serializer};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone)]
 pub struct Tag {
-    #[serde(rename = "version")]
     pub name: String,
+    /// Part of the name before the version, e.g. `cli-` for `cli-v1.2.0`
+    scope: String,
+}
+
+/// Templates get the version, e.g. `1.2.0`, and the full name of the tag, e.g. `cli-v1.2.0`
+impl Serialize for Tag {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut tag = serializer.serialize_struct("Tag", 2)?;
+        tag.serialize_field("version", self.version())?;
+        tag.serialize_field("tag", self.value())?;
+        tag.end()
+    }
 }
 
 impl Tag {
     pub fn new(name: impl Into<String>) -> Self {
-        Tag { name: name.into() }
+        Tag {
+            name: name.into(),
+            scope: String::new(),
+        }
+    }
+
+    /// Tag named after the configured tag prefix, e.g. `cli-v1.2.0` for the prefix `cli-v`
+    pub fn with_prefix(name: impl Into<String>, prefix: &str) -> Self {
+        Tag::with_scope(name, scope(prefix))
+    }
+
+    /// Tag whose `scope` is already known, see [`scope`]
+    pub fn with_scope(name: impl Into<String>, scope: &str) -> Self {
+        Tag {
+            name: name.into(),
+            scope: scope.to_owned(),
+        }
     }
 
     pub fn value(&self) -> &str {
         &self.name
     }
 
-    /// Strip the leading 'v' from the tag name if it exists
-    pub fn strip_v_prefix(&self) -> &str {
-        if self.name.starts_with('v') {
-            self.name.strip_prefix('v').unwrap_or_default()
-        } else {
-            &self.name
+    pub fn scope(&self) -> &str {
+        &self.scope
+    }
+
+    /// The name without its scope and leading 'v'
+    pub fn version(&self) -> &str {
+        let name = self.unscoped();
+        name.strip_prefix('v').unwrap_or(name)
+    }
+
+    /// The name without its scope
+    fn unscoped(&self) -> &str {
+        self.name.strip_prefix(&self.scope).unwrap_or(&self.name)
+    }
+
+    /// Check that the tag, without its scope, matches `pattern` and parses as a semver version
+    pub fn validate(&self, pattern: &str) -> Result<Version> {
+        let regex =
+            Regex::new(pattern).with_context(|| format!("invalid tag pattern {}", pattern))?;
+        if !self.name.starts_with(&self.scope) {
+            bail!("tag {} doesn't start with {}", self.name, self.scope);
         }
+        if !regex.is_match(self.unscoped()) {
+            bail!("tag {} doesn't match the pattern {}", self.name, pattern);
+        }
+
+        Version::parse(self.version())
+            .with_context(|| format!("tag {} is not a semver version", self.name))
+    }
+
+    /// Components of the version, zero when the tag is not semver
+    pub fn components(&self) -> VersionComponents {
+        Version::parse(self.version())
+            .map(|version| VersionComponents {
+                major: version.major,
+                minor: version.minor,
+                patch: version.patch,
+                pre: version.pre.to_string(),
+            })
+            .unwrap_or_default()
+    }
+
+    /// Whether the tag is a semver prerelease, e.g. `v1.2.0-rc.1`
+    pub fn is_prerelease(&self) -> bool {
+        Version::parse(self.version())
+            .map(|version| !version.pre.is_empty())
+            .unwrap_or(false)
     }
 
     pub fn empty() -> Tag {
-        Tag {
-            name: "".to_string(),
-        }
+        Tag::new("")
     }
 }
+
+/// Scope of the tags named with `prefix`, the `v` before the version being optional
+pub fn scope(prefix: &str) -> &str {
+    prefix.strip_suffix('v').unwrap_or(prefix)
+}
+
+/// Version components available to the templates next to `version`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct VersionComponents {
+    pub major: u64,
+    pub minor: u64,
+    pub patch: u64,
+    pub pre: String,
+}
@@ -0,0 +1,725 @@
+use super::MockGithub;
+use crate::{
+    brew::{self, class_name, Brew},
+    build::{arch::Arch, completions::Completions, os::Os},
+    config::{BrewConfig, ReleaseConfig},
+    github::tag::Tag,
+    packager::{package::Package, Packager},
+};
+use base64::{prelude::BASE64_STANDARD, Engine};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use wiremock::{
+    matchers::{body_partial_json, method, path, query_param},
+    Mock, ResponseTemplate,
+};
+
+const ARCHIVE: &[u8] = b"source archive";
+
+fn release_config() -> ReleaseConfig {
+    serde_json::from_value(json!({
+        "owner": "owner",
+        "repo": "app",
+        "target_branch": "main",
+    }))
+    .unwrap()
+}
+
+fn brew_config(extra: Value) -> BrewConfig {
+    let mut config = json!({
+        "name": "app",
+        "description": "An app",
+        "install": "bin.install \"app\"",
+        "commit_message": "app {{version}}",
+        "repository": { "owner": "owner", "name": "homebrew-tap" },
+    });
+    config
+        .as_object_mut()
+        .unwrap()
+        .extend(extra.as_object().unwrap().to_owned());
+    serde_json::from_value(config).unwrap()
+}
+
+async fn mock_archive(github: &MockGithub) {
+    Mock::given(method("GET"))
+        .and(path("/web/owner/app/archive/refs/tags/v1.0.0.tar.gz"))
+        .respond_with(ResponseTemplate::new(200).set_body_bytes(ARCHIVE))
+        .expect(1)
+        .mount(&github.server)
+        .await;
+}
+
+async fn mock_commit(github: &MockGithub, branch: &str) {
+    Mock::given(method("GET"))
+        .and(path(format!(
+            "/repos/owner/homebrew-tap/commits/{}",
+            branch
+        )))
+        .respond_with(ResponseTemplate::new(200).set_body_string("base-sha"))
+        .mount(&github.server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/graphql"))
+        .and(body_partial_json(json!({
+            "variables": { "input": {
+                "branch": {
+                    "repositoryNameWithOwner": "owner/homebrew-tap",
+                    "branchName": branch,
+                },
+                "expectedHeadOid": "base-sha",
+            } }
+        })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "data": { "createCommitOnBranch": { "commit": {
+                "oid": "formula-sha",
+                "url": "https://github.com/owner/homebrew-tap/commit/formula-sha",
+            } } }
+        })))
+        .expect(1)
+        .mount(&github.server)
+        .await;
+}
+
+async fn release(github: &MockGithub, config: BrewConfig) -> String {
+    let output = tempfile::tempdir().unwrap();
+    let package = Package::new(
+        "app.tar.gz".to_owned(),
+        None,
+        None,
+        Some("https://github.com/owner/app/releases/download/v1.0.0/app.tar.gz".to_owned()),
+        "package-sha".to_owned(),
+    );
+
+    github
+        .run(brew::release(
+            config,
+            release_config(),
+            vec![package],
+            Tag::new("v1.0.0"),
+            false,
+            output.path(),
+            output.path(),
+        ))
+        .await
+        .unwrap()
+}
+
+/// Formula of `packages`, `(os, arch, name)`, without calling GitHub
+async fn render(
+    github: &MockGithub,
+    extra: Value,
+    packages: &[(Option<Os>, Option<Arch>, &str)],
+) -> String {
+    let packages = packages
+        .iter()
+        .map(|(os, arch, name)| {
+            Package::new(
+                name.to_string(),
+                os.to_owned(),
+                arch.to_owned(),
+                Some(format!("https://example.com/{}", name)),
+                format!("{}-sha", name),
+            )
+        })
+        .collect();
+
+    github
+        .run(async {
+            let brew = Brew::with_hash(
+                brew_config(extra),
+                &release_config(),
+                Tag::new("v1.0.0"),
+                packages,
+                "source-sha".to_owned(),
+            );
+            brew::serialize_brew(&brew).unwrap()
+        })
+        .await
+}
+
+/// The single commit sent through GraphQL, with its files decoded
+async fn committed(github: &MockGithub) -> (Value, Vec<(String, String)>) {
+    let mut commits = commits(github).await;
+    assert_eq!(commits.len(), 1);
+    commits.remove(0)
+}
+
+/// The commits sent through GraphQL, in order
+async fn commits(github: &MockGithub) -> Vec<(Value, Vec<(String, String)>)> {
+    github
+        .bodies("/graphql")
+        .await
+        .into_iter()
+        .map(|body| {
+            let input = body["variables"]["input"].to_owned();
+            let files = input["fileChanges"]["additions"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|file| {
+                    let contents = BASE64_STANDARD
+                        .decode(file["contents"].as_str().unwrap())
+                        .unwrap();
+                    (
+                        file["path"].as_str().unwrap().to_owned(),
+                        String::from_utf8(contents).unwrap(),
+                    )
+                })
+                .collect();
+            (input, files)
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn commits_the_formula_to_the_tap() {
+    let github = MockGithub::start().await;
+    mock_archive(&github).await;
+    mock_commit(&github, "main").await;
+
+    let formula = release(
+        &github,
+        brew_config(json!({
+            "path": "Formula",
+            "commit_author": { "name": "Releaser", "email": "releaser@example.com" },
+        })),
+    )
+    .await;
+
+    assert!(formula.contains("sha256 \"package-sha\""), "{}", formula);
+    assert!(formula.contains("version \"1.0.0\""), "{}", formula);
+
+    let (input, files) = committed(&github).await;
+    assert_eq!(files, [("Formula/app.rb".to_owned(), formula)]);
+    assert_eq!(input["message"]["headline"], "app 1.0.0");
+    assert_eq!(
+        input["message"]["body"],
+        "Co-authored-by: Releaser <releaser@example.com>"
+    );
+}
+
+#[tokio::test]
+async fn opens_a_pull_request_with_the_formula() {
+    let github = MockGithub::start().await;
+    mock_archive(&github).await;
+    Mock::given(method("GET"))
+        .and(path("/repos/owner/homebrew-tap/commits/main"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("base-sha"))
+        .expect(1)
+        .mount(&github.server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/repos/owner/homebrew-tap/git/refs"))
+        .and(body_partial_json(json!({
+            "ref": "refs/heads/app-1.0.0",
+            "sha": "base-sha",
+        })))
+        .respond_with(ResponseTemplate::new(201).set_body_json(json!({})))
+        .expect(1)
+        .mount(&github.server)
+        .await;
+    mock_commit(&github, "app-1.0.0").await;
+    Mock::given(method("POST"))
+        .and(path("/repos/owner/homebrew-tap/pulls"))
+        .and(body_partial_json(json!({
+            "title": "Update app",
+            "head": "app-1.0.0",
+            "base": "main",
+        })))
+        .respond_with(ResponseTemplate::new(201).set_body_json(json!({ "number": 3 })))
+        .expect(1)
+        .mount(&github.server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/repos/owner/homebrew-tap/issues/3/labels"))
+        .and(body_partial_json(json!({ "labels": ["release"] })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!([])))
+        .expect(1)
+        .mount(&github.server)
+        .await;
+
+    let formula = release(
+        &github,
+        brew_config(json!({
+            "formula_path": "Formula/app.rb",
+            "pull_request": {
+                "title": "Update app",
+                "head": "app-1.0.0",
+                "labels": ["release"],
+            },
+        })),
+    )
+    .await;
+
+    let (input, files) = committed(&github).await;
+    assert_eq!(files, [("Formula/app.rb".to_owned(), formula)]);
+    assert!(input["message"].get("body").is_none());
+}
+
+#[tokio::test]
+async fn reuses_the_head_branch_of_a_previous_release() {
+    let github = MockGithub::start().await;
+    mock_archive(&github).await;
+    Mock::given(method("GET"))
+        .and(path("/repos/owner/homebrew-tap/commits/main"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("base-sha"))
+        .mount(&github.server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/repos/owner/homebrew-tap/git/refs"))
+        .respond_with(
+            ResponseTemplate::new(422)
+                .set_body_json(json!({ "message": "Reference already exists" })),
+        )
+        .expect(1)
+        .mount(&github.server)
+        .await;
+    Mock::given(method("PATCH"))
+        .and(path(
+            "/repos/owner/homebrew-tap/git/refs/heads/bumps-formula-version",
+        ))
+        .and(body_partial_json(
+            json!({ "sha": "base-sha", "force": true }),
+        ))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({})))
+        .expect(1)
+        .mount(&github.server)
+        .await;
+    mock_commit(&github, "bumps-formula-version").await;
+    Mock::given(method("POST"))
+        .and(path("/repos/owner/homebrew-tap/pulls"))
+        .respond_with(ResponseTemplate::new(422).set_body_json(json!({
+            "message": "Validation Failed",
+            "errors": [{ "message": "A pull request already exists for owner:bumps-formula-version." }],
+        })))
+        .expect(1)
+        .mount(&github.server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/repos/owner/homebrew-tap/pulls"))
+        .and(query_param("head", "owner:bumps-formula-version"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!([{ "number": 5 }])))
+        .expect(1)
+        .mount(&github.server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/repos/owner/homebrew-tap/issues/5/labels"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!([])))
+        .expect(1)
+        .mount(&github.server)
+        .await;
+
+    let formula = release(
+        &github,
+        brew_config(json!({
+            "formula_path": "Formula/app.rb",
+            "pull_request": { "labels": ["release"] },
+        })),
+    )
+    .await;
+
+    let (_, files) = committed(&github).await;
+    assert_eq!(files, [("Formula/app.rb".to_owned(), formula)]);
+}
+
+#[tokio::test]
+async fn creates_a_missing_tap() {
+    let github = MockGithub::start().await;
+    mock_archive(&github).await;
+    Mock::given(method("GET"))
+        .and(path("/repos/owner/homebrew-tap"))
+        .respond_with(ResponseTemplate::new(404).set_body_json(json!({ "message": "Not Found" })))
+        .expect(1)
+        .mount(&github.server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/users/owner"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "type": "Organization" })))
+        .expect(1)
+        .mount(&github.server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/orgs/owner/repos"))
+        .and(body_partial_json(
+            json!({ "name": "homebrew-tap", "auto_init": true }),
+        ))
+        .respond_with(ResponseTemplate::new(201).set_body_json(json!({
+            "full_name": "owner/homebrew-tap",
+            "default_branch": "main",
+        })))
+        .expect(1)
+        .mount(&github.server)
+        .await;
+    mock_commit(&github, "main").await;
+    Mock::given(method("POST"))
+        .and(path("/repos/owner/homebrew-tap/git/refs"))
+        .and(body_partial_json(json!({
+            "ref": "refs/heads/formulas",
+            "sha": "base-sha",
+        })))
+        .respond_with(ResponseTemplate::new(201).set_body_json(json!({})))
+        .expect(1)
+        .mount(&github.server)
+        .await;
+    mock_commit(&github, "formulas").await;
+
+    let formula = release(
+        &github,
+        brew_config(json!({
+            "head": "formulas",
+            "path": "Formula",
+            "repository": { "owner": "owner", "name": "homebrew-tap", "create": true },
+        })),
+    )
+    .await;
+
+    let commits = commits(&github).await;
+    let paths: Vec<_> = commits[0].1.iter().map(|(path, _)| path.as_str()).collect();
+    assert_eq!(paths, ["README.md", "Formula/.gitkeep"]);
+    assert!(
+        commits[0].1[0].1.contains("brew install owner/tap/app"),
+        "{}",
+        commits[0].1[0].1
+    );
+    assert_eq!(commits[1].1, [("Formula/app.rb".to_owned(), formula)]);
+}
+
+#[tokio::test]
+async fn uploads_the_bottles_to_the_release() {
+    let github = MockGithub::start().await;
+    mock_archive(&github).await;
+    mock_commit(&github, "main").await;
+    Mock::given(method("GET"))
+        .and(path("/repos/owner/app/releases/tags/v1.0.0"))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_json(json!({ "id": 7, "tag_name": "v1.0.0" })),
+        )
+        .expect(1)
+        .mount(&github.server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/repos/owner/app/releases/7/assets"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!([])))
+        .mount(&github.server)
+        .await;
+    for name in [
+        "app-1.0.0.arm64_sonoma.bottle.1.tar.gz",
+        "app-1.0.0.x86_64_linux.bottle.1.tar.gz",
+    ] {
+        Mock::given(method("POST"))
+            .and(path("/uploads/repos/owner/app/releases/7/assets"))
+            .and(query_param("name", name))
+            .respond_with(ResponseTemplate::new(201).set_body_json(json!({})))
+            .expect(1)
+            .mount(&github.server)
+            .await;
+    }
+
+    let bottles = tempfile::tempdir().unwrap();
+    for tag in ["x86_64_linux", "arm64_sonoma"] {
+        std::fs::write(
+            bottles
+                .path()
+                .join(format!("app--1.0.0.{}.bottle.1.tar.gz", tag)),
+            tag,
+        )
+        .unwrap();
+    }
+    let formula = release(
+        &github,
+        brew_config(json!({
+            "bottles": {
+                "files": [bottles.path().join("*.bottle.*").to_string_lossy()],
+            },
+        })),
+    )
+    .await;
+
+    let sha256 = |content: &str| format!("{:x}", Sha256::digest(content));
+    let expected = format!(
+        r#"    bottle do
+        root_url "{}/owner/app/releases/download/v1.0.0"
+        rebuild 1
+        sha256 cellar: :any_skip_relocation, arm64_sonoma: "{}"
+        sha256 cellar: :any_skip_relocation, x86_64_linux: "{}"
+    end
+"#,
+        github.web_url(),
+        sha256("arm64_sonoma"),
+        sha256("x86_64_linux"),
+    );
+    assert!(formula.contains(&expected), "{}", formula);
+}
+
+#[tokio::test]
+async fn renders_a_custom_formula_template() {
+    let github = MockGithub::start().await;
+    mock_archive(&github).await;
+    mock_commit(&github, "main").await;
+
+    let template = tempfile::NamedTempFile::new().unwrap();
+    std::fs::write(
+        template.path(),
+        "# {{name}} {{version}}, built from {{hash}}\n{{> single_target}}",
+    )
+    .unwrap();
+
+    let formula = release(
+        &github,
+        brew_config(json!({ "template_path": template.path() })),
+    )
+    .await;
+
+    let hash = format!("{:x}", Sha256::digest(ARCHIVE));
+    assert!(
+        formula.starts_with(&format!("# App 1.0.0, built from {}\n", hash)),
+        "{}",
+        formula
+    );
+    assert!(formula.contains("sha256 \"package-sha\""), "{}", formula);
+
+    let (_, files) = committed(&github).await;
+    assert_eq!(files, [("app.rb".to_owned(), formula)]);
+}
+
+#[tokio::test]
+async fn renders_a_single_target_formula() {
+    let github = MockGithub::start().await;
+
+    let formula = render(&github, json!({}), &[(None, None, "app.tar.gz")]).await;
+
+    assert!(
+        formula.contains("url \"https://example.com/app.tar.gz\"\n    sha256 \"app.tar.gz-sha\""),
+        "{}",
+        formula
+    );
+    assert!(!formula.contains("on_linux"), "{}", formula);
+}
+
+#[tokio::test]
+async fn installs_the_bundled_completions() {
+    let github = MockGithub::start().await;
+    let completions: Completions = serde_json::from_value(json!({ "dir": "complete" })).unwrap();
+    let mut brew = brew_config(json!({
+        "install": { "script": "bin.install \"app\"", "zsh_completion": "zsh/_app" },
+    }));
+    completions.install(&mut brew.install, "app");
+
+    let formula = github
+        .run(async {
+            let brew = Brew::with_hash(
+                brew,
+                &release_config(),
+                Tag::new("v1.0.0"),
+                vec![],
+                "source-sha".to_owned(),
+            );
+            brew::serialize_brew(&brew).unwrap()
+        })
+        .await;
+
+    assert!(
+        formula.contains(
+            "        bash_completion.install \"complete/app.bash\"\n        \
+             zsh_completion.install \"zsh/_app\"\n        \
+             fish_completion.install \"complete/app.fish\"\n"
+        ),
+        "{}",
+        formula
+    );
+    assert!(!formula.contains(".ps1"), "{}", formula);
+}
+
+#[tokio::test]
+async fn renders_the_version_of_a_scoped_tag() {
+    let github = MockGithub::start().await;
+
+    let brew = github
+        .run(async {
+            Brew::with_hash(
+                brew_config(json!({})),
+                &release_config(),
+                Tag::with_prefix("cli-v1.2.0", "cli-v"),
+                vec![],
+                "source-sha".to_owned(),
+            )
+        })
+        .await;
+    let formula = github
+        .run(async { brew::serialize_brew(&brew).unwrap() })
+        .await;
+
+    assert!(formula.contains("version \"1.2.0\""), "{}", formula);
+    assert_eq!(brew.commit_message(), "app 1.2.0");
+}
+
+#[tokio::test]
+async fn renders_a_multi_arch_formula() {
+    let github = MockGithub::start().await;
+
+    let formula = render(
+        &github,
+        json!({}),
+        &[
+            (
+                Some(Os::UnknownLinuxGnu),
+                Some(Arch::Amd64),
+                "app-amd64.tar.gz",
+            ),
+            (
+                Some(Os::UnknownLinuxGnu),
+                Some(Arch::Arm64),
+                "app-arm64.tar.gz",
+            ),
+        ],
+    )
+    .await;
+
+    assert_eq!(formula.matches("on_linux do").count(), 1, "{}", formula);
+    assert!(!formula.contains("on_macos"), "{}", formula);
+    assert!(
+        formula.contains("on_intel do\n            url \"https://example.com/app-amd64.tar.gz\""),
+        "{}",
+        formula
+    );
+    assert!(
+        formula.contains("on_arm do\n            url \"https://example.com/app-arm64.tar.gz\""),
+        "{}",
+        formula
+    );
+}
+
+#[tokio::test]
+async fn renders_a_multi_os_formula() {
+    let github = MockGithub::start().await;
+
+    // the packages of an OS are grouped even when they are not listed together
+    let formula = render(
+        &github,
+        json!({}),
+        &[
+            (
+                Some(Os::UnknownLinuxGnu),
+                Some(Arch::Amd64),
+                "app-linux-amd64.tar.gz",
+            ),
+            (
+                Some(Os::AppleDarwin),
+                Some(Arch::Arm64),
+                "app-darwin-arm64.tar.gz",
+            ),
+            (
+                Some(Os::UnknownLinuxGnu),
+                Some(Arch::Arm64),
+                "app-linux-arm64.tar.gz",
+            ),
+            (
+                Some(Os::PcWindowsMsvc),
+                Some(Arch::Amd64),
+                "app-windows.zip",
+            ),
+        ],
+    )
+    .await;
+
+    assert_eq!(formula.matches("on_linux do").count(), 1, "{}", formula);
+    assert_eq!(formula.matches("on_macos do").count(), 1, "{}", formula);
+    for name in [
+        "app-linux-amd64.tar.gz",
+        "app-linux-arm64.tar.gz",
+        "app-darwin-arm64.tar.gz",
+    ] {
+        assert!(
+            formula.contains(&format!("sha256 \"{}-sha\"", name)),
+            "{}",
+            formula
+        );
+    }
+    assert!(!formula.contains("app-windows.zip"), "{}", formula);
+}
+
+#[test]
+fn derives_the_class_name_like_homebrew() {
+    for (name, class) in [
+        ("app", "App"),
+        ("my-cool-tool", "MyCoolTool"),
+        ("my_cool.tool", "MyCoolTool"),
+        ("MyApp", "Myapp"),
+        ("openssl@3", "OpensslAT3"),
+        ("foo++", "Fooxx"),
+        ("tool-2", "Tool2"),
+    ] {
+        assert_eq!(class_name(name), class, "{}", name);
+    }
+}
+
+#[tokio::test]
+async fn names_the_file_after_the_formula() {
+    let github = MockGithub::start().await;
+    mock_archive(&github).await;
+    mock_commit(&github, "main").await;
+
+    let formula = release(&github, brew_config(json!({ "name": "my-cool-tool" }))).await;
+
+    assert!(
+        formula.contains("class MyCoolTool < Formula"),
+        "{}",
+        formula
+    );
+    let (_, files) = committed(&github).await;
+    assert_eq!(files, [("my-cool-tool.rb".to_owned(), formula)]);
+}
+
+#[tokio::test]
+async fn renders_a_head_spec_and_a_source_fallback() {
+    let github = MockGithub::start().await;
+
+    let formula = render(
+        &github,
+        json!({ "source": { "fallback": true } }),
+        &[
+            (
+                Some(Os::AppleDarwin),
+                Some(Arch::Amd64),
+                "app-darwin-amd64.tar.gz",
+            ),
+            (
+                Some(Os::AppleDarwin),
+                Some(Arch::Arm64),
+                "app-darwin-arm64.tar.gz",
+            ),
+            (
+                Some(Os::UnknownLinuxGnu),
+                Some(Arch::Amd64),
+                "app-linux-amd64.tar.gz",
+            ),
+        ],
+    )
+    .await;
+
+    assert!(
+        formula.contains(&format!(
+            "head do\n        url \"{}/owner/app.git\", branch: \"main\"",
+            github.web_url()
+        )),
+        "{}",
+        formula
+    );
+    // only linux on arm has no binary, and builds the source archive
+    assert_eq!(
+        formula.matches("depends_on \"rust\" => :build").count(),
+        2,
+        "{}",
+        formula
+    );
+    assert!(
+        formula.contains("on_linux do\n        on_arm do\n            depends_on \"rust\""),
+        "{}",
+        formula
+    );
+    assert!(
+        formula.contains("if build.head? || File.exist?(\"Cargo.toml\")"),
+        "{}",
+        formula
+    );
+}
@@ -0,0 +1,65 @@
+//! End-to-end tests of the GitHub flows, run against a local mock of the API
+
+mod formula;
+mod release;
+mod verify;
+
+use super::{
+    auth,
+    github_client::{GithubClient, TEST_CLIENT},
+};
+use reqwest::Client;
+use serde_json::Value;
+use std::future::Future;
+use wiremock::{MockServer, Request};
+
+pub const TOKEN: &str = "test-token";
+
+/// Mock of the REST and GraphQL APIs, the asset uploads and the website of GitHub, served under
+/// `/`, `/uploads` and `/web`
+pub struct MockGithub {
+    pub server: MockServer,
+    client: &'static GithubClient,
+}
+
+impl MockGithub {
+    pub async fn start() -> Self {
+        auth::set_test_token(TOKEN);
+
+        let server = MockServer::start().await;
+        let client = GithubClient::new(
+            server.uri(),
+            format!("{}/uploads", server.uri()),
+            format!("{}/web", server.uri()),
+            Client::new(),
+        );
+
+        MockGithub {
+            server,
+            // a task local needs a static reference, the few bytes leaked per test don't matter
+            client: Box::leak(Box::new(client)),
+        }
+    }
+
+    pub fn web_url(&self) -> String {
+        format!("{}/web", self.server.uri())
+    }
+
+    /// Run `future` with `github_client::instance()` talking to this mock
+    pub async fn run<F: Future>(&self, future: F) -> F::Output {
+        TEST_CLIENT.scope(self.client, future).await
+    }
+
+    /// Bodies of the requests received on `path`, in order
+    pub async fn bodies(&self, path: &str) -> Vec<Value> {
+        self.server
+            .received_requests()
+            .await
+            .unwrap_or_default()
+            .iter()
+            .filter(|request| request.url.path() == path)
+            .map(Request::body_json)
+            .collect::<Result<_, _>>()
+            .expect("JSON body")
+    }
+}
@@ -0,0 +1,539 @@
+use super::{MockGithub, TOKEN};
+use crate::{
+    build::Build,
+    config::{PruneConfig, ReleaseConfig, Rollback},
+    error::Failure,
+    github,
+    github::{asset::Asset, release::Release, tag::Tag},
+    packager::package::Package,
+    pipeline::{self, CreatedBranch, State},
+};
+use git2::{Repository, Signature};
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use std::{fs, path::Path};
+use tempfile::TempDir;
+use wiremock::{
+    matchers::{body_partial_json, header, method, path, query_param},
+    Mock, ResponseTemplate,
+};
+
+/// Git repository with a built `app` binary
+fn project() -> TempDir {
+    let dir = tempfile::tempdir().unwrap();
+    fs::create_dir_all(dir.path().join("target/release")).unwrap();
+    fs::write(dir.path().join("target/release/app"), "binary").unwrap();
+
+    let repo = Repository::init(dir.path()).unwrap();
+    let tree = repo
+        .find_tree(repo.index().unwrap().write_tree().unwrap())
+        .unwrap();
+    let signature = Signature::now("Test", "test@example.com").unwrap();
+    repo.commit(Some("HEAD"), &signature, &signature, "init", &tree, &[])
+        .unwrap();
+
+    dir
+}
+
+fn build() -> Build {
+    serde_json::from_value(json!({ "binary": "app" })).unwrap()
+}
+
+fn release_config(existing: &str) -> ReleaseConfig {
+    serde_json::from_value(json!({
+        "owner": "owner",
+        "repo": "app",
+        "target_branch": "main",
+        "existing": existing,
+        "upload": { "retries": 0 },
+    }))
+    .unwrap()
+}
+
+async fn try_release(
+    github: &MockGithub,
+    config: &ReleaseConfig,
+    base: &Path,
+    extra_assets: Vec<Asset>,
+) -> anyhow::Result<Vec<Package>> {
+    let output = tempfile::tempdir().unwrap();
+    github
+        .run(github::release(
+            &build(),
+            config,
+            &Tag::new("v1.0.0"),
+            None,
+            extra_assets,
+            base.to_path_buf(),
+            false,
+            output.path(),
+        ))
+        .await
+}
+
+async fn release(
+    github: &MockGithub,
+    config: &ReleaseConfig,
+    base: &Path,
+    extra_assets: Vec<Asset>,
+) -> Vec<String> {
+    try_release(github, config, base, extra_assets)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|package| package.url.unwrap_or_default())
+        .collect()
+}
+
+#[tokio::test]
+async fn creates_the_release_and_uploads_the_assets() {
+    let github = MockGithub::start().await;
+    let project = project();
+
+    Mock::given(method("GET"))
+        .and(path("/repos/owner/app/releases/tags/v1.0.0"))
+        .respond_with(ResponseTemplate::new(404).set_body_json(json!({ "message": "Not Found" })))
+        .expect(1)
+        .mount(&github.server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/repos/owner/app/releases"))
+        .and(header(
+            "authorization",
+            format!("Bearer {}", TOKEN).as_str(),
+        ))
+        .and(body_partial_json(json!({
+            "tag_name": "v1.0.0",
+            "target_commitish": "main",
+            "draft": false,
+            "prerelease": false,
+        })))
+        .respond_with(ResponseTemplate::new(201).set_body_json(json!({ "id": 1 })))
+        .expect(1)
+        .mount(&github.server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/uploads/repos/owner/app/releases/1/assets"))
+        .respond_with(ResponseTemplate::new(201).set_body_json(json!({})))
+        .expect(3)
+        .mount(&github.server)
+        .await;
+
+    let urls = release(&github, &release_config("keep"), project.path(), vec![]).await;
+
+    assert_eq!(urls.len(), 1);
+    assert!(urls[0].starts_with(&format!(
+        "{}/owner/app/releases/download/v1.0.0/",
+        github.web_url()
+    )));
+
+    let uploaded: Vec<_> = github
+        .server
+        .received_requests()
+        .await
+        .unwrap()
+        .into_iter()
+        .filter(|request| request.url.path().starts_with("/uploads/"))
+        .filter_map(|request| {
+            request
+                .url
+                .query_pairs()
+                .find(|(key, _)| key == "name")
+                .map(|(_, name)| name.into_owned())
+        })
+        .collect();
+    let archive = urls[0].rsplit('/').next().unwrap();
+    assert_eq!(
+        uploaded,
+        [
+            archive.to_owned(),
+            format!("{}.sha256", archive),
+            "checksums.txt".to_owned()
+        ]
+    );
+}
+
+#[tokio::test]
+async fn fails_with_the_forge_exit_code_when_github_rejects_the_release() {
+    let github = MockGithub::start().await;
+    let project = project();
+
+    Mock::given(method("GET"))
+        .and(path("/repos/owner/app/releases/tags/v1.0.0"))
+        .respond_with(ResponseTemplate::new(404).set_body_json(json!({ "message": "Not Found" })))
+        .mount(&github.server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/repos/owner/app/releases"))
+        .respond_with(
+            ResponseTemplate::new(422).set_body_json(json!({ "message": "Validation Failed" })),
+        )
+        .expect(1)
+        .mount(&github.server)
+        .await;
+
+    let err = try_release(&github, &release_config("keep"), project.path(), vec![])
+        .await
+        .unwrap_err();
+
+    assert_eq!(Failure::of(&err), Some(Failure::Forge), "{:?}", err);
+    assert_eq!(Failure::Forge.exit_code(), 5);
+}
+
+#[tokio::test]
+async fn fails_with_the_upload_exit_code_when_an_upload_fails() {
+    let github = MockGithub::start().await;
+    let project = project();
+
+    Mock::given(method("GET"))
+        .and(path("/repos/owner/app/releases/tags/v1.0.0"))
+        .respond_with(ResponseTemplate::new(404).set_body_json(json!({ "message": "Not Found" })))
+        .mount(&github.server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/repos/owner/app/releases"))
+        .respond_with(ResponseTemplate::new(201).set_body_json(json!({ "id": 1 })))
+        .mount(&github.server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/uploads/repos/owner/app/releases/1/assets"))
+        .respond_with(ResponseTemplate::new(502))
+        .expect(1)
+        .mount(&github.server)
+        .await;
+
+    let err = try_release(&github, &release_config("keep"), project.path(), vec![])
+        .await
+        .unwrap_err();
+
+    assert_eq!(Failure::of(&err), Some(Failure::Upload), "{:?}", err);
+    assert!(format!("{:#}", err).contains("502"), "{:#}", err);
+}
+
+#[tokio::test]
+async fn replaces_the_assets_of_an_existing_release() {
+    let github = MockGithub::start().await;
+    let project = project();
+
+    Mock::given(method("GET"))
+        .and(path("/repos/owner/app/releases/tags/v1.0.0"))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_json(json!({ "id": 7, "tag_name": "v1.0.0" })),
+        )
+        .mount(&github.server)
+        .await;
+    Mock::given(method("PATCH"))
+        .and(path("/repos/owner/app/releases/7"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "id": 7 })))
+        .expect(1)
+        .mount(&github.server)
+        .await;
+
+    // the existing assets span two pages
+    let next = format!(
+        "<{}/repos/owner/app/releases/7/assets?per_page=100&page=2>; rel=\"next\"",
+        github.server.uri()
+    );
+    Mock::given(method("GET"))
+        .and(path("/repos/owner/app/releases/7/assets"))
+        .and(query_param("page", "2"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+            { "id": 42, "name": "checksums.txt", "url": "" },
+        ])))
+        .with_priority(1)
+        .mount(&github.server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/repos/owner/app/releases/7/assets"))
+        .and(query_param("per_page", "100"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("link", next.as_str())
+                .set_body_json(json!([{ "id": 41, "name": "notes.txt", "url": "" }])),
+        )
+        .with_priority(2)
+        .mount(&github.server)
+        .await;
+    Mock::given(method("DELETE"))
+        .and(path("/repos/owner/app/releases/assets/42"))
+        .respond_with(ResponseTemplate::new(204))
+        .expect(1)
+        .mount(&github.server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/uploads/repos/owner/app/releases/7/assets"))
+        .respond_with(ResponseTemplate::new(201).set_body_json(json!({})))
+        .expect(3)
+        .mount(&github.server)
+        .await;
+
+    release(&github, &release_config("replace"), project.path(), vec![]).await;
+
+    let update = github.bodies("/repos/owner/app/releases/7").await;
+    assert_eq!(update.len(), 1);
+    assert_eq!(update[0]["name"], "v1.0.0");
+}
+
+#[tokio::test]
+async fn skips_the_assets_already_uploaded_with_the_same_content() {
+    let github = MockGithub::start().await;
+    let project = project();
+    let notes = project.path().join("notes.txt");
+    fs::write(&notes, "release notes").unwrap();
+
+    Mock::given(method("GET"))
+        .and(path("/repos/owner/app/releases/tags/v1.0.0"))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_json(json!({ "id": 7, "tag_name": "v1.0.0" })),
+        )
+        .mount(&github.server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/repos/owner/app/releases/7/assets"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+            {
+                "id": 41,
+                "name": "notes.txt",
+                "url": "",
+                "size": 13,
+                "digest": format!("sha256:{}", hex::encode(Sha256::digest("release notes"))),
+            },
+        ])))
+        .expect(1)
+        .mount(&github.server)
+        .await;
+    Mock::given(method("DELETE"))
+        .respond_with(ResponseTemplate::new(204))
+        .expect(0)
+        .mount(&github.server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/uploads/repos/owner/app/releases/7/assets"))
+        .respond_with(ResponseTemplate::new(201).set_body_json(json!({})))
+        .expect(3)
+        .mount(&github.server)
+        .await;
+
+    release(
+        &github,
+        &release_config("keep"),
+        project.path(),
+        vec![Asset::new("notes.txt".to_owned(), notes)],
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn replaces_the_changed_assets_the_resumed_run_uploaded() {
+    let github = MockGithub::start().await;
+    let project = project();
+    let notes = project.path().join("notes.txt");
+    fs::write(&notes, "rebuilt release notes").unwrap();
+
+    // uploaded by the resumed run, then rebuilt with another content
+    Mock::given(method("GET"))
+        .and(path("/repos/owner/app/releases/7/assets"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+            { "id": 41, "name": "notes.txt", "url": "", "size": 13 },
+        ])))
+        .mount(&github.server)
+        .await;
+    Mock::given(method("DELETE"))
+        .and(path("/repos/owner/app/releases/assets/41"))
+        .respond_with(ResponseTemplate::new(204))
+        .expect(1)
+        .mount(&github.server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/uploads/repos/owner/app/releases/7/assets"))
+        .respond_with(ResponseTemplate::new(201).set_body_json(json!({})))
+        .expect(1)
+        .mount(&github.server)
+        .await;
+
+    github
+        .run(async {
+            Release::new(7, "owner", "app")
+                .resuming_uploads(vec!["notes.txt".to_owned()])
+                .with_existing_assets()
+                .await?
+                .upload_asset(
+                    &Asset::new("notes.txt".to_owned(), notes),
+                    &Tag::new("v1.0.0"),
+                )
+                .await
+        })
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn rolls_back_what_the_failed_run_created() {
+    let github = MockGithub::start().await;
+
+    for deleted in [
+        "/repos/owner/app/releases/7",
+        "/repos/owner/app/git/refs/tags/v1.0.0",
+        "/repos/owner/homebrew-tap/git/refs/heads/app-1.0.0",
+    ] {
+        Mock::given(method("DELETE"))
+            .and(path(deleted))
+            .respond_with(ResponseTemplate::new(204))
+            .expect(1)
+            .mount(&github.server)
+            .await;
+    }
+
+    let state = State {
+        tag: "v1.0.0".to_owned(),
+        release_id: Some(7),
+        release_created: true,
+        tag_created: true,
+        branches: vec![CreatedBranch {
+            owner: "owner".to_owned(),
+            repo: "homebrew-tap".to_owned(),
+            name: "app-1.0.0".to_owned(),
+        }],
+        ..State::default()
+    };
+    github
+        .run(pipeline::rollback(
+            Rollback::All,
+            &state,
+            &release_config("keep"),
+        ))
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn keeps_the_release_it_did_not_create() {
+    let github = MockGithub::start().await;
+
+    Mock::given(method("DELETE"))
+        .respond_with(ResponseTemplate::new(204))
+        .expect(0)
+        .mount(&github.server)
+        .await;
+
+    let state = State {
+        tag: "v1.0.0".to_owned(),
+        release_id: Some(7),
+        ..State::default()
+    };
+    github
+        .run(pipeline::rollback(
+            Rollback::Release,
+            &state,
+            &release_config("keep"),
+        ))
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn reports_what_the_rollback_could_not_delete() {
+    let github = MockGithub::start().await;
+
+    Mock::given(method("DELETE"))
+        .and(path("/repos/owner/app/releases/7"))
+        .respond_with(
+            ResponseTemplate::new(403)
+                .set_body_json(json!({ "message": "Resource not accessible by integration" })),
+        )
+        .expect(1)
+        .mount(&github.server)
+        .await;
+    Mock::given(method("DELETE"))
+        .and(path("/repos/owner/app/git/refs/tags/v1.0.0"))
+        .respond_with(ResponseTemplate::new(204))
+        .expect(1)
+        .mount(&github.server)
+        .await;
+
+    let state = State {
+        tag: "v1.0.0".to_owned(),
+        release_id: Some(7),
+        release_created: true,
+        tag_created: true,
+        ..State::default()
+    };
+    let err = github
+        .run(pipeline::rollback(
+            Rollback::All,
+            &state,
+            &release_config("keep"),
+        ))
+        .await
+        .unwrap_err()
+        .to_string();
+
+    assert!(err.contains("the release of v1.0.0"), "{}", err);
+    assert!(err.contains("403"), "{}", err);
+    assert!(!err.contains("the tag"), "{}", err);
+}
+
+#[tokio::test]
+async fn fails_to_prune_a_release_github_refuses_to_delete() {
+    let github = MockGithub::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/repos/owner/app/releases"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+            { "id": 7, "tag_name": "v1.0.0-rc.1", "prerelease": true },
+            { "id": 8, "tag_name": "v0.9.0" },
+        ])))
+        .mount(&github.server)
+        .await;
+    Mock::given(method("DELETE"))
+        .and(path("/repos/owner/app/releases/7"))
+        .respond_with(ResponseTemplate::new(403).set_body_json(json!({ "message": "Forbidden" })))
+        .expect(1)
+        .mount(&github.server)
+        .await;
+
+    let prune: PruneConfig = serde_json::from_value(json!({})).unwrap();
+    let err = github
+        .run(github::prune::prune(
+            &prune,
+            &release_config("keep"),
+            &Tag::new("v1.0.0"),
+            false,
+        ))
+        .await
+        .unwrap_err();
+
+    assert_eq!(format!("{}", err), "failed to delete v1.0.0-rc.1");
+    assert_eq!(Failure::of(&err), Some(Failure::Forge), "{:?}", err);
+}
+
+#[tokio::test]
+async fn does_not_create_a_release_when_the_lookup_fails() {
+    let github = MockGithub::start().await;
+    let project = project();
+
+    Mock::given(method("GET"))
+        .and(path("/repos/owner/app/releases/tags/v1.0.0"))
+        .respond_with(
+            ResponseTemplate::new(401).set_body_json(json!({ "message": "Bad credentials" })),
+        )
+        .mount(&github.server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/repos/owner/app/releases"))
+        .respond_with(ResponseTemplate::new(201).set_body_json(json!({ "id": 1 })))
+        .expect(0)
+        .mount(&github.server)
+        .await;
+
+    let err = try_release(&github, &release_config("keep"), project.path(), vec![])
+        .await
+        .unwrap_err();
+
+    assert!(
+        format!("{:#}", err).contains("Bad credentials"),
+        "{:#}",
+        err
+    );
+    assert_eq!(Failure::of(&err), Some(Failure::Forge), "{:?}", err);
+}
@@ -0,0 +1,106 @@
+use super::MockGithub;
+use crate::{config::ReleaserConfig, github::tag::Tag, verify};
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use wiremock::{
+    matchers::{method, path},
+    Mock, ResponseTemplate,
+};
+
+const ARCHIVE: &[u8] = b"archive";
+
+fn config() -> ReleaserConfig {
+    serde_json::from_value(json!({
+        "build": { "binary": "app" },
+        "release": { "owner": "owner", "repo": "app", "target_branch": "main" },
+        "brew": {
+            "name": "app",
+            "description": "An app",
+            "install": "bin.install \"app\"",
+            "formula_path": "Formula/app.rb",
+            "repository": { "owner": "owner", "name": "homebrew-tap" },
+        },
+    }))
+    .unwrap()
+}
+
+/// Release of v1.0.0 with the `app.tar.gz` asset, and `formula` in the tap
+async fn publish(github: &MockGithub, formula: String) {
+    Mock::given(method("GET"))
+        .and(path("/repos/owner/app/releases/tags/v1.0.0"))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_json(json!({ "id": 1, "tag_name": "v1.0.0" })),
+        )
+        .mount(&github.server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/repos/owner/app/releases/1/assets"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!([{
+            "id": 11,
+            "name": "app.tar.gz",
+            "url": format!("{}/assets/11", github.server.uri()),
+        }])))
+        .mount(&github.server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/assets/11"))
+        .respond_with(ResponseTemplate::new(200).set_body_bytes(ARCHIVE))
+        .mount(&github.server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/repos/owner/homebrew-tap/contents/Formula/app.rb"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(formula))
+        .mount(&github.server)
+        .await;
+}
+
+async fn verify(github: &MockGithub) -> anyhow::Result<()> {
+    let output = tempfile::tempdir().unwrap();
+    github
+        .run(verify::verify(
+            &config(),
+            &Tag::new("v1.0.0"),
+            output.path(),
+        ))
+        .await
+}
+
+/// Private formula downloading the `(name, sha256)` assets of v1.0.0, next to the source archive
+fn formula(assets: &[(&str, &str)]) -> String {
+    let mut formula = "class App < Formula\n    url \"https://github.com/owner/app/archive/refs/tags/v1.0.0.tar.gz\"\n    sha256 \"source-sha\"\n".to_owned();
+    for (name, hash) in assets {
+        formula.push_str(&format!(
+            "    resource \"{}\" do\n        url \"https://github.com/owner/app/releases/download/v1.0.0/{}\", using: GitHubPrivateRepositoryReleaseDownloadStrategy\n        sha256 \"{}\"\n    end\n",
+            name, name, hash
+        ));
+    }
+    formula.push_str("end\n");
+    formula
+}
+
+#[tokio::test]
+async fn checks_the_assets_of_a_private_formula() {
+    let github = MockGithub::start().await;
+    publish(
+        &github,
+        formula(&[
+            ("app.tar.gz", "stale-sha"),
+            ("app-linux.tar.gz", "linux-sha"),
+        ]),
+    )
+    .await;
+
+    let err = verify(&github).await.unwrap_err();
+
+    // the changed archive and the missing linux one
+    assert_eq!(err.to_string(), "release v1.0.0 has 2 problems");
+}
+
+#[tokio::test]
+async fn verifies_a_formula_matching_the_release() {
+    let github = MockGithub::start().await;
+    let hash = hex::encode(Sha256::digest(ARCHIVE));
+    publish(&github, formula(&[("app.tar.gz", &hash)])).await;
+
+    verify(&github).await.unwrap();
+}
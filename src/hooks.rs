@@ -0,0 +1,129 @@
+use crate::{
+    cli::{Opts, Stage},
+    config::{HooksConfig, ReleaseConfig, StageHooks},
+    github::{asset::release_url, tag::Tag},
+    packager::package::Package,
+    template,
+};
+use anyhow::{bail, Context, Result};
+use serde::Serialize;
+use std::path::Path;
+use tokio::process::Command;
+
+/// Runs the shell commands configured around the stages
+pub struct Hooks<'a> {
+    config: &'a HooksConfig,
+    tag: &'a Tag,
+    release_url: String,
+    dry_run: bool,
+    path: &'a Path,
+}
+
+/// Values of the commands, also set as the `VERSION`, `TAG`, `RELEASE_URL` and `ASSETS`
+/// environment variables
+#[derive(Debug, Serialize)]
+struct HookValues<'a> {
+    version: &'a str,
+    tag: &'a str,
+    release_url: &'a str,
+    /// Names of the archives of the release, empty until it is created
+    assets: Vec<&'a str>,
+}
+
+impl<'a> Hooks<'a> {
+    pub fn new(
+        config: &'a HooksConfig,
+        release_config: &ReleaseConfig,
+        tag: &'a Tag,
+        opts: &'a Opts,
+    ) -> Self {
+        Hooks {
+            config,
+            tag,
+            release_url: release_url(&release_config.owner, &release_config.repo, tag),
+            dry_run: opts.dry_run,
+            path: &opts.path,
+        }
+    }
+
+    pub async fn before(&self, stage: Stage, packages: &[Package]) -> Result<()> {
+        self.run("before", stage, &self.config.before, packages)
+            .await
+    }
+
+    pub async fn after(&self, stage: Stage, packages: &[Package]) -> Result<()> {
+        self.run("after", stage, &self.config.after, packages).await
+    }
+
+    async fn run(
+        &self,
+        when: &str,
+        stage: Stage,
+        hooks: &StageHooks,
+        packages: &[Package],
+    ) -> Result<()> {
+        let values = HookValues {
+            version: self.tag.version(),
+            tag: self.tag.value(),
+            release_url: &self.release_url,
+            assets: packages
+                .iter()
+                .map(|package| package.name.as_str())
+                .collect(),
+        };
+        for command in commands(hooks, stage) {
+            let command = template::render(command, &values)
+                .with_context(|| format!("invalid hook {}", command))?;
+            log::info!("Running `{}` {} the {} stage", command, when, stage.name());
+
+            let mut cmd = match cfg!(windows) {
+                true => {
+                    let mut cmd = Command::new("cmd");
+                    cmd.arg("/C");
+                    cmd
+                }
+                false => {
+                    let mut cmd = Command::new("sh");
+                    cmd.arg("-c");
+                    cmd
+                }
+            };
+            let status = cmd
+                .arg(&command)
+                .current_dir(self.path)
+                .env("VERSION", values.version)
+                .env("TAG", values.tag)
+                .env("RELEASE_URL", values.release_url)
+                .env("ASSETS", values.assets.join(" "))
+                .env("DRY_RUN", self.dry_run.to_string())
+                .status()
+                .await
+                .with_context(|| format!("failed to run `{}`", command))?;
+            if !status.success() {
+                bail!(
+                    "`{}` {} the {} stage failed ({})",
+                    command,
+                    when,
+                    stage.name(),
+                    status
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
+fn commands(hooks: &StageHooks, stage: Stage) -> &[String] {
+    match stage {
+        Stage::Build => &hooks.build,
+        Stage::Sign => &hooks.sign,
+        Stage::Sbom => &hooks.sbom,
+        Stage::Changelog => &hooks.changelog,
+        Stage::Release => &hooks.release,
+        Stage::Upload => &hooks.upload,
+        Stage::Prune => &hooks.prune,
+        Stage::Brew => &hooks.brew,
+        Stage::CratesIo => &hooks.crates_io,
+        Stage::Announce => &hooks.announce,
+    }
+}
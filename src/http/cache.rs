@@ -0,0 +1,180 @@
+use super::RateLimited;
+use log::debug;
+use once_cell::sync::OnceCell;
+use reqwest::{
+    header::{HeaderMap, HeaderName, HeaderValue, ACCEPT, AUTHORIZATION, ETAG, IF_NONE_MATCH},
+    Method, RequestBuilder, Response, ResponseBuilderExt, StatusCode, Url,
+};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::{
+    env,
+    fs::{self, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+static CACHE_DIR: OnceCell<PathBuf> = OnceCell::new();
+
+/// Cache GET responses in `dir`, nothing is cached until then
+pub fn init(dir: PathBuf) {
+    debug!("Caching responses in {}", dir.display());
+    let _ = CACHE_DIR.set(dir);
+}
+
+/// Per-user cache directory of the platform
+pub fn default_dir() -> Option<PathBuf> {
+    let base = env::var_os("XDG_CACHE_HOME")
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("LOCALAPPDATA").map(PathBuf::from))
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".cache")))?;
+    Some(base.join("rustreleaser").join("http"))
+}
+
+#[derive(Serialize, Deserialize)]
+struct Entry {
+    etag: String,
+    status: u16,
+    headers: Vec<(String, String)>,
+    body: String,
+}
+
+impl Entry {
+    /// The cached response, as if it was received from `url`
+    fn response(self, url: Url) -> Response {
+        let mut builder = ::http::Response::builder().status(self.status).url(url);
+        if let Some(headers) = builder.headers_mut() {
+            for (name, value) in &self.headers {
+                if let (Ok(name), Ok(value)) = (
+                    HeaderName::from_bytes(name.as_bytes()),
+                    HeaderValue::from_str(value),
+                ) {
+                    headers.append(name, value);
+                }
+            }
+        }
+        Response::from(
+            builder
+                .body(self.body)
+                .expect("the cached status and headers come from a valid response"),
+        )
+    }
+}
+
+pub trait Cached {
+    /// Sends the request like `send_with_retry`, revalidating the cached response of a GET with
+    /// its ETag. GitHub answers 304 Not Modified for unchanged data, which doesn't count against
+    /// the rate limit, and the cached body is returned instead.
+    async fn send_cached(self) -> Result<Response, reqwest::Error>;
+}
+
+impl Cached for RequestBuilder {
+    async fn send_cached(self) -> Result<Response, reqwest::Error> {
+        match CACHE_DIR.get() {
+            Some(dir) => send_in(self, dir).await,
+            None => self.send_with_retry().await,
+        }
+    }
+}
+
+/// `send_cached` with the responses cached in `dir`
+pub(crate) async fn send_in(
+    builder: RequestBuilder,
+    dir: &Path,
+) -> Result<Response, reqwest::Error> {
+    let (client, request) = builder.build_split();
+    let request = request?;
+
+    if request.method() != Method::GET {
+        return RequestBuilder::from_parts(client, request)
+            .send_with_retry()
+            .await;
+    }
+
+    // the same URL has other representations with other media types, and other data for
+    // other credentials, which are hashed into the key rather than stored
+    let header = |name| {
+        request
+            .headers()
+            .get(name)
+            .and_then(|value: &HeaderValue| value.to_str().ok())
+            .unwrap_or_default()
+    };
+    let path = dir.join(format!(
+        "{:x}.json",
+        Sha256::digest(format!(
+            "{} {} {}",
+            header(AUTHORIZATION),
+            header(ACCEPT),
+            request.url()
+        ))
+    ));
+    let url = request.url().to_owned();
+
+    let cached = fs::read(&path)
+        .ok()
+        .and_then(|entry| serde_json::from_slice::<Entry>(&entry).ok());
+    let mut builder = RequestBuilder::from_parts(client, request);
+    if let Some(entry) = &cached {
+        builder = builder.header(IF_NONE_MATCH, &entry.etag);
+    }
+
+    let response = builder.send_with_retry().await?;
+
+    if response.status() == StatusCode::NOT_MODIFIED {
+        if let Some(entry) = cached {
+            debug!("Not modified, using the cached response of {}", url);
+            return Ok(entry.response(url));
+        }
+    }
+
+    let etag = response
+        .headers()
+        .get(ETAG)
+        .and_then(|etag| etag.to_str().ok())
+        .map(str::to_owned);
+    let Some(etag) = etag.filter(|_| response.status() == StatusCode::OK) else {
+        return Ok(response);
+    };
+
+    // the body is read to be stored, the response is rebuilt around it
+    let status = response.status().as_u16();
+    let headers = stored_headers(response.headers());
+    let body = response.text().await?;
+
+    let entry = Entry {
+        etag,
+        status,
+        headers,
+        body,
+    };
+    if let Err(err) = store(dir, &path, &serde_json::to_vec(&entry).unwrap_or_default()) {
+        debug!("Failed to cache the response of {}: {}", url, err);
+    }
+
+    Ok(entry.response(url))
+}
+
+fn stored_headers(headers: &HeaderMap) -> Vec<(String, String)> {
+    headers
+        .iter()
+        .filter_map(|(name, value)| Some((name.to_string(), value.to_str().ok()?.to_owned())))
+        .collect()
+}
+
+/// Write an entry readable by the user only, the bodies can come from private repositories
+fn store(dir: &Path, path: &Path, content: &[u8]) -> io::Result<()> {
+    fs::create_dir_all(dir)?;
+    let mut file = OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .open(path)?;
+    // also restricts the entries written before
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        file.set_permissions(fs::Permissions::from_mode(0o600))?;
+    }
+    file.write_all(content)
+}
@@ -1,22 +1,120 @@
+pub mod cache;
 pub mod error_response;
+pub mod progress;
 
-use reqwest::Client;
-use std::ops::{Deref, DerefMut};
+use anyhow::Context;
+use log::{debug, warn};
+use once_cell::sync::OnceCell;
+use reqwest::{
+    header::{HeaderMap, LINK},
+    Certificate, Client, NoProxy, Proxy, RequestBuilder, Response, StatusCode,
+};
+use std::{
+    env, fs,
+    ops::{Deref, DerefMut},
+    path::PathBuf,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
 
 use self::error_response::ErrorResponse;
+use crate::config::HttpConfig;
+
+const CA_BUNDLE_VAR: &str = "SSL_CERT_FILE";
+const PEM_BEGIN: &str = "-----BEGIN CERTIFICATE-----";
+const PEM_END: &str = "-----END CERTIFICATE-----";
+
+/// How many times a rate limited request is retried before giving up.
+const RATE_LIMIT_RETRIES: u32 = 5;
+/// Wait used for secondary rate limits that don't say how long to wait.
+const RATE_LIMIT_DEFAULT_WAIT: u64 = 60;
+/// Longer waits, e.g. for an exhausted hourly quota, fail instead.
+const RATE_LIMIT_MAX_WAIT: u64 = 15 * 60;
+
+static CLIENT: OnceCell<Client> = OnceCell::new();
+static TRANSFER_TIMEOUT: OnceCell<Duration> = OnceCell::new();
 
 pub struct HttpClient {
     client: Client,
 }
 
 impl HttpClient {
+    /// Handle on the shared client, whose connections are pooled across all requests
     pub fn new() -> Self {
         HttpClient {
-            client: Client::new(),
+            client: CLIENT.get_or_init(Client::new).clone(),
         }
     }
 }
 
+/// Build the client shared by all requests, going through the configured proxy, trusting the
+/// configured certificates and keeping connections open for reuse
+pub fn init(config: &HttpConfig) -> anyhow::Result<()> {
+    let mut builder = Client::builder()
+        .connect_timeout(Duration::from_secs(config.connect_timeout))
+        .timeout(Duration::from_secs(config.timeout))
+        .pool_idle_timeout(Duration::from_secs(config.pool_idle_timeout));
+
+    if config.tcp_keepalive > 0 {
+        builder = builder.tcp_keepalive(Duration::from_secs(config.tcp_keepalive));
+    }
+    if let Some(max) = config.pool_max_idle_per_host {
+        builder = builder.pool_max_idle_per_host(max);
+    }
+    builder = if config.http2 {
+        builder.http2_adaptive_window(true)
+    } else {
+        builder.http1_only()
+    };
+
+    // without an explicit proxy, reqwest picks up the proxy variables itself
+    if let Some(proxy) = &config.proxy {
+        let proxy = Proxy::all(proxy)
+            .with_context(|| format!("invalid proxy {}", proxy))?
+            .no_proxy(NoProxy::from_env());
+        builder = builder.proxy(proxy);
+    }
+
+    let ca_bundle = config
+        .ca_bundle
+        .to_owned()
+        .or_else(|| env::var(CA_BUNDLE_VAR).ok());
+    if let Some(path) = ca_bundle {
+        let pem = fs::read_to_string(&path).with_context(|| format!("failed to read {}", path))?;
+        let certificates =
+            certificates(&pem).with_context(|| format!("invalid certificates in {}", path))?;
+        debug!(
+            "Trusting {} certificate(s) from {}",
+            certificates.len(),
+            path
+        );
+        for certificate in certificates {
+            builder = builder.add_root_certificate(certificate);
+        }
+    }
+
+    if CLIENT.set(builder.build()?).is_err() {
+        warn!("HTTP client already in use, ignoring the [http] config");
+    }
+    let _ = TRANSFER_TIMEOUT.set(Duration::from_secs(config.transfer_timeout));
+
+    if config.cache {
+        if let Some(dir) = config
+            .cache_dir
+            .as_ref()
+            .map(PathBuf::from)
+            .or_else(cache::default_dir)
+        {
+            cache::init(dir);
+        }
+    }
+    Ok(())
+}
+
+/// Timeout of asset uploads and downloads, which outlast the timeout of API requests
+pub fn transfer_timeout() -> Duration {
+    *TRANSFER_TIMEOUT.get_or_init(|| Duration::from_secs(HttpConfig::default().transfer_timeout))
+}
+
 impl Deref for HttpClient {
     type Target = Client;
 
@@ -31,6 +129,43 @@ impl DerefMut for HttpClient {
     }
 }
 
+/// Certificates of a PEM bundle, parsed one by one since reqwest hands DER to native-tls when
+/// parsing a whole bundle
+fn certificates(pem: &str) -> anyhow::Result<Vec<Certificate>> {
+    let certificates = pem
+        .split_inclusive(PEM_END)
+        .filter_map(|block| block.find(PEM_BEGIN).map(|start| &block[start..]))
+        .filter(|block| block.ends_with(PEM_END))
+        .map(|block| Certificate::from_pem(block.as_bytes()))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    if certificates.is_empty() {
+        anyhow::bail!("no PEM certificate found");
+    }
+    Ok(certificates)
+}
+
+/// URL of the next page of a paginated response, from its `Link` header
+pub fn next_page(headers: &HeaderMap) -> Option<String> {
+    headers
+        .get(LINK)?
+        .to_str()
+        .ok()?
+        .split(',')
+        .find_map(|link| {
+            let (url, params) = link.split_once(';')?;
+            params
+                .split(';')
+                .any(|param| param.trim() == r#"rel="next""#)
+                .then(|| {
+                    url.trim()
+                        .trim_start_matches('<')
+                        .trim_end_matches('>')
+                        .to_owned()
+                })
+        })
+}
+
 pub trait ResponseHandler {
     async fn handle(self) -> anyhow::Result<String, ErrorResponse>;
 }
@@ -49,8 +184,8 @@ impl ResponseHandler for Result<reqwest::Response, reqwest::Error> {
                 if status.is_success() {
                     Ok(message)
                 } else {
-                    log::warn!("Response message: {}", message);
-                    Ok(message)
+                    log::debug!("Response message: {}", message);
+                    Err(ErrorResponse::new(message, status.as_u16()))
                 }
             }
             Err(error) => Err(ErrorResponse::internal_server_error(
@@ -63,3 +198,87 @@ impl ResponseHandler for Result<reqwest::Response, reqwest::Error> {
         }
     }
 }
+
+pub trait RateLimited {
+    /// Sends the request, sleeping and retrying while GitHub rate limits it.
+    async fn send_with_retry(self) -> Result<Response, reqwest::Error>;
+}
+
+impl RateLimited for RequestBuilder {
+    async fn send_with_retry(self) -> Result<Response, reqwest::Error> {
+        let mut attempt = 0;
+        loop {
+            // streamed bodies can't be replayed, send them once
+            let Some(request) = self.try_clone() else {
+                return self.send().await;
+            };
+
+            let response = request.send().await?;
+            log_quota(response.headers());
+
+            let Some(wait) = rate_limit_wait(&response, attempt) else {
+                return Ok(response);
+            };
+
+            if attempt >= RATE_LIMIT_RETRIES || wait > RATE_LIMIT_MAX_WAIT {
+                warn!(
+                    "Rate limited by GitHub, giving up after {} retries (the limit resets in {}s)",
+                    attempt, wait
+                );
+                return Ok(response);
+            }
+
+            attempt += 1;
+            warn!(
+                "Rate limited by GitHub, retrying in {}s ({}/{})",
+                wait, attempt, RATE_LIMIT_RETRIES
+            );
+            tokio::time::sleep(Duration::from_secs(wait)).await;
+        }
+    }
+}
+
+fn log_quota(headers: &HeaderMap) {
+    if let (Some(remaining), Some(limit)) = (
+        header(headers, "x-ratelimit-remaining"),
+        header(headers, "x-ratelimit-limit"),
+    ) {
+        debug!(
+            "GitHub rate limit: {} of {} requests remaining",
+            remaining, limit
+        );
+    }
+}
+
+/// Seconds to wait before retrying, when the response is a rate limit.
+fn rate_limit_wait(response: &Response, attempt: u32) -> Option<u64> {
+    let status = response.status();
+    if status != StatusCode::FORBIDDEN && status != StatusCode::TOO_MANY_REQUESTS {
+        return None;
+    }
+
+    let headers = response.headers();
+    if let Some(retry_after) = header(headers, "retry-after") {
+        return Some(retry_after);
+    }
+
+    if header(headers, "x-ratelimit-remaining") == Some(0) {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|now| now.as_secs())
+            .unwrap_or_default();
+        let reset = header(headers, "x-ratelimit-reset").unwrap_or(now);
+        return Some(reset.saturating_sub(now) + 1);
+    }
+
+    // a 403 without any rate limit header is a permission error
+    if status == StatusCode::FORBIDDEN {
+        return None;
+    }
+
+    Some(RATE_LIMIT_DEFAULT_WAIT << attempt.min(4))
+}
+
+fn header(headers: &HeaderMap, name: &str) -> Option<u64> {
+    headers.get(name)?.to_str().ok()?.trim().parse().ok()
+}
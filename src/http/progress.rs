@@ -0,0 +1,113 @@
+use log::{debug, info};
+use reqwest::Body;
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+    time::{Duration, Instant},
+};
+use tokio::io::{AsyncRead, ReadBuf};
+use tokio_util::io::ReaderStream;
+
+/// How often the progress of a transfer is logged, quick transfers log nothing
+const REPORT_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Body streaming `reader`, logging how much of its `total` bytes was sent
+pub fn body<R>(name: impl Into<String>, reader: R, total: u64) -> Body
+where
+    R: AsyncRead + Send + Sync + Unpin + 'static,
+{
+    Body::wrap_stream(ReaderStream::new(ProgressReader {
+        inner: reader,
+        progress: Progress::new(name.into(), total),
+    }))
+}
+
+struct ProgressReader<R> {
+    inner: R,
+    progress: Progress,
+}
+
+impl<R: AsyncRead + Unpin> AsyncRead for ProgressReader<R> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        let before = buf.filled().len();
+        let poll = Pin::new(&mut self.inner).poll_read(cx, buf);
+        if let Poll::Ready(Ok(())) = poll {
+            let read = buf.filled().len() - before;
+            self.progress.advance(read as u64);
+        }
+        poll
+    }
+}
+
+struct Progress {
+    name: String,
+    total: u64,
+    done: u64,
+    started: Instant,
+    reported: Instant,
+}
+
+impl Progress {
+    fn new(name: String, total: u64) -> Self {
+        let now = Instant::now();
+        Progress {
+            name,
+            total,
+            done: 0,
+            started: now,
+            reported: now,
+        }
+    }
+
+    fn advance(&mut self, read: u64) {
+        self.done += read;
+
+        if read == 0 || self.done >= self.total {
+            debug!(
+                "Sent {} ({}) in {:.1?} at {}/s",
+                self.name,
+                human_size(self.done),
+                self.started.elapsed(),
+                human_size(self.throughput())
+            );
+        } else if self.reported.elapsed() >= REPORT_INTERVAL {
+            self.reported = Instant::now();
+            info!(
+                "Uploading {}: {}% of {} at {}/s",
+                self.name,
+                self.done * 100 / self.total,
+                human_size(self.total),
+                human_size(self.throughput())
+            );
+        }
+    }
+
+    /// Bytes per second since the start
+    fn throughput(&self) -> u64 {
+        let elapsed = self.started.elapsed().as_secs_f64();
+        if elapsed > 0.0 {
+            (self.done as f64 / elapsed) as u64
+        } else {
+            self.done
+        }
+    }
+}
+
+fn human_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} {}", bytes, UNITS[0])
+    } else {
+        format!("{:.1} {}", size, UNITS[unit])
+    }
+}
@@ -0,0 +1,156 @@
+use crate::template;
+use anyhow::{bail, Context, Result};
+use git2::Repository;
+use serde::Serialize;
+use std::{
+    fs,
+    io::{self, BufRead, Write},
+    path::Path,
+};
+use toml::Value;
+
+const CONFIG_TEMPLATE: &str = include_str!("./template/init.hbs");
+
+/// Values of the starter config, already quoted as TOML strings
+#[derive(Debug, Serialize)]
+struct InitData {
+    binary: String,
+    multi_target: bool,
+    owner: String,
+    repo: String,
+    target_branch: String,
+    name: String,
+    description: String,
+    homepage: String,
+    license: String,
+    install: String,
+    test: String,
+    tap: Option<Tap>,
+}
+
+#[derive(Debug, Serialize)]
+struct Tap {
+    owner: String,
+    name: String,
+}
+
+/// Write a starter config from Cargo.toml and the git remote
+pub fn init(base: &Path, config_path: &str, yes: bool, force: bool) -> Result<()> {
+    if Path::new(config_path).exists() && !force {
+        bail!(
+            "{} already exists, use --force to overwrite it",
+            config_path
+        );
+    }
+
+    let manifest_path = base.join("Cargo.toml");
+    let manifest: Value = fs::read_to_string(&manifest_path)
+        .with_context(|| format!("failed to read {}", manifest_path.display()))?
+        .parse()
+        .with_context(|| format!("failed to parse {}", manifest_path.display()))?;
+
+    let package = |key: &str| {
+        manifest
+            .get("package")
+            .and_then(|package| package.get(key))
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_string()
+    };
+    let name = package("name");
+    let binary = manifest
+        .get("bin")
+        .and_then(Value::as_array)
+        .and_then(|bins| bins.first())
+        .and_then(|bin| bin.get("name"))
+        .and_then(Value::as_str)
+        .map(str::to_string)
+        .unwrap_or_else(|| name.to_owned());
+
+    let (default_owner, default_repo) = github_remote(base)
+        .or_else(|| parse_github_url(&package("repository")))
+        .unwrap_or_default();
+
+    let ask = |question: &str, default: &str| -> Result<String> {
+        if yes {
+            return Ok(default.to_string());
+        }
+        prompt(question, default)
+    };
+
+    let owner = ask("GitHub owner", &default_owner)?;
+    let repo = ask("GitHub repository", &default_repo)?;
+    let target_branch = ask("Release branch", "main")?;
+    let multi_target = ask("Build for multiple targets? (y/n)", "n")?.starts_with('y');
+    let tap = ask("Homebrew tap as owner/name (empty to skip)", "")?;
+
+    let homepage = match package("homepage") {
+        homepage if homepage.is_empty() => format!("https://github.com/{}/{}", owner, repo),
+        homepage => homepage,
+    };
+
+    let data = InitData {
+        binary: quote(&binary),
+        multi_target,
+        owner: quote(&owner),
+        repo: quote(&repo),
+        target_branch: quote(&target_branch),
+        name: quote(&name),
+        description: quote(&package("description")),
+        homepage: quote(&homepage),
+        license: quote(&package("license")),
+        install: quote(&format!("bin.install \"{}\"", binary)),
+        test: quote(&format!("system \"#{{bin}}/{}\", \"--version\"", binary)),
+        tap: tap.split_once('/').map(|(owner, name)| Tap {
+            owner: quote(owner),
+            name: quote(name),
+        }),
+    };
+
+    let config = template::render(CONFIG_TEMPLATE, &data)?;
+    fs::write(config_path, config).with_context(|| format!("failed to write {}", config_path))?;
+
+    log::info!("Wrote {}", config_path);
+
+    Ok(())
+}
+
+fn prompt(question: &str, default: &str) -> Result<String> {
+    if default.is_empty() {
+        print!("{}: ", question);
+    } else {
+        print!("{} [{}]: ", question, default);
+    }
+    io::stdout().flush()?;
+
+    let mut answer = String::new();
+    io::stdin().lock().read_line(&mut answer)?;
+
+    let answer = answer.trim();
+    Ok(if answer.is_empty() { default } else { answer }.to_string())
+}
+
+fn quote(value: &str) -> String {
+    Value::String(value.to_string()).to_string()
+}
+
+/// Owner and name of the `origin` remote, if it points to GitHub
+fn github_remote(base: &Path) -> Option<(String, String)> {
+    let repo = Repository::open(base).ok()?;
+    let remote = repo.find_remote("origin").ok()?;
+    parse_github_url(remote.url()?)
+}
+
+/// Parse `https://github.com/owner/repo(.git)` and `git@github.com:owner/repo.git`
+fn parse_github_url(url: &str) -> Option<(String, String)> {
+    let path = url
+        .strip_prefix("https://github.com/")
+        .or_else(|| url.strip_prefix("http://github.com/"))
+        .or_else(|| url.strip_prefix("git@github.com:"))
+        .or_else(|| url.strip_prefix("ssh://git@github.com/"))?;
+
+    let path = path.trim_end_matches('/').trim_end_matches(".git");
+    let (owner, repo) = path.split_once('/')?;
+
+    Some((owner.to_string(), repo.to_string()))
+}
@@ -1,7 +1,224 @@
+use crate::{
+    actions,
+    cli::{LogFormat, Stage},
+    error::Failure,
+    progress,
+};
 use anyhow::Result;
-use simple_logger::init_with_env;
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use once_cell::sync::OnceCell;
+use serde_json::{json, Map, Value};
+use simple_logger::SimpleLogger;
+use std::{
+    io::{IsTerminal, Write},
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+use time::{format_description::well_known::Rfc3339, macros::format_description, OffsetDateTime};
 
-pub fn init() -> Result<()> {
-    init_with_env()?;
+static FORMAT: OnceCell<LogFormat> = OnceCell::new();
+/// Stage running, added to the JSON events
+static STAGE: Mutex<Option<Stage>> = Mutex::new(None);
+
+/// Log at info level, debug with `--verbose` or warn when `quiet`; `RUST_LOG` takes precedence.
+/// In an interactive terminal, the text logs are printed above the spinners of [`progress`]
+pub fn init(verbose: bool, quiet: bool, format: LogFormat) -> Result<()> {
+    let level = if verbose {
+        LevelFilter::Debug
+    } else if quiet {
+        LevelFilter::Warn
+    } else {
+        LevelFilter::Info
+    };
+    FORMAT.get_or_init(|| format);
+    progress::init(
+        format == LogFormat::Text
+            && !quiet
+            && std::io::stdout().is_terminal()
+            && !actions::enabled(),
+    );
+    let env_level = std::env::var("RUST_LOG")
+        .ok()
+        .and_then(|level| level.parse().ok())
+        .unwrap_or(level);
+    match format {
+        LogFormat::Text if !progress::enabled() => {
+            SimpleLogger::new().with_level(level).env().init()?
+        }
+        LogFormat::Text => {
+            log::set_boxed_logger(Box::new(ProgressLogger { level: env_level }))?;
+            log::set_max_level(env_level);
+        }
+        LogFormat::Json => {
+            log::set_boxed_logger(Box::new(JsonLogger { level: env_level }))?;
+            log::set_max_level(env_level);
+        }
+    }
     Ok(())
 }
+
+/// Log `message` along with `fields`, e.g. `{ "asset": "app.tar.gz" }`, which become keys of the
+/// event in the JSON format and are left out of the text one. The spinners of [`progress`] already
+/// show these events, so they aren't logged along them
+pub fn event(level: Level, fields: Value, message: &str) {
+    if !log::log_enabled!(level) {
+        return;
+    }
+    match FORMAT.get() {
+        Some(LogFormat::Json) => {
+            let Value::Object(fields) = fields else {
+                return;
+            };
+            write_event(level, "rr", message, fields);
+        }
+        _ if progress::enabled() => {}
+        _ => log::log!(level, "{}", message),
+    }
+}
+
+/// Log the error ending the run
+pub fn error(err: &anyhow::Error) {
+    actions::error(err);
+    if FORMAT.get() == Some(&LogFormat::Json) {
+        let message = err.to_string();
+        let kind = Failure::of(err).map_or("other", |failure| failure.name());
+        event(
+            Level::Error,
+            json!({ "error": format!("{:#}", err), "kind": kind }),
+            &message,
+        );
+    }
+}
+
+/// Start timing `stage`, tagging the events logged until it finishes
+pub fn stage(stage: Stage) -> StageTimer {
+    *STAGE.lock().unwrap() = Some(stage);
+    actions::group(&stage.name());
+    StageTimer {
+        stage,
+        start: Instant::now(),
+        task: progress::task(format!("{} stage", stage.name())),
+    }
+}
+
+/// Stage running, or the one that failed when the run ended with an error
+pub fn running() -> Option<Stage> {
+    *STAGE.lock().unwrap()
+}
+
+/// Running stage, see [`stage`]
+pub struct StageTimer {
+    stage: Stage,
+    start: Instant,
+    task: progress::Task,
+}
+
+impl StageTimer {
+    pub fn finish(self) {
+        self.task.finish();
+        let duration = self.start.elapsed();
+        event(
+            Level::Info,
+            json!({ "duration_ms": millis(duration) }),
+            &format!(
+                "Finished the {} stage in {:.1?}",
+                self.stage.name(),
+                duration
+            ),
+        );
+        *STAGE.lock().unwrap() = None;
+        actions::end_group();
+    }
+}
+
+/// Milliseconds of `duration`, as logged in the `duration_ms` fields
+pub fn millis(duration: Duration) -> u64 {
+    duration.as_millis().try_into().unwrap_or(u64::MAX)
+}
+
+/// Text logs in the format of [`SimpleLogger`], printed above the spinners
+struct ProgressLogger {
+    level: LevelFilter,
+}
+
+impl Log for ProgressLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= self.level
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        let color = match record.level() {
+            Level::Error => "31",
+            Level::Warn => "33",
+            Level::Info => "36",
+            Level::Debug => "35",
+            Level::Trace => "0",
+        };
+        let timestamp = OffsetDateTime::now_utc()
+            .format(format_description!(
+                "[year]-[month]-[day]T[hour]:[minute]:[second].[subsecond digits:3]Z"
+            ))
+            .unwrap_or_default();
+        progress::println(&format!(
+            "{} \x1b[{}m{:<5}\x1b[0m [{}] {}",
+            timestamp,
+            color,
+            record.level(),
+            record.target(),
+            record.args()
+        ));
+    }
+
+    fn flush(&self) {
+        let _ = std::io::stdout().flush();
+    }
+}
+
+struct JsonLogger {
+    level: LevelFilter,
+}
+
+impl Log for JsonLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= self.level
+    }
+
+    fn log(&self, record: &Record) {
+        if self.enabled(record.metadata()) {
+            write_event(
+                record.level(),
+                record.target(),
+                &record.args().to_string(),
+                Map::new(),
+            );
+        }
+    }
+
+    fn flush(&self) {
+        let _ = std::io::stderr().flush();
+    }
+}
+
+/// Write one event per line to stderr, keeping stdout for the output of the commands
+fn write_event(level: Level, module: &str, message: &str, fields: Map<String, Value>) {
+    let mut event = Map::new();
+    event.insert(
+        "timestamp".into(),
+        OffsetDateTime::now_utc()
+            .format(&Rfc3339)
+            .unwrap_or_default()
+            .into(),
+    );
+    event.insert("level".into(), level.as_str().into());
+    event.insert("module".into(), module.into());
+    event.insert("message".into(), message.into());
+    if let Some(stage) = *STAGE.lock().unwrap() {
+        event.insert("stage".into(), stage.name().into());
+    }
+    event.extend(fields);
+
+    let _ = writeln!(std::io::stderr(), "{}", Value::Object(event));
+}
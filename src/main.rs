@@ -1,88 +1,538 @@
+mod actions;
+mod announce;
+mod archive;
 mod brew;
 mod build;
+mod bump;
+mod changelog;
+mod check;
 mod checksum;
 mod cli;
 mod config;
+mod crates_io;
+mod doctor;
+mod dry_run;
+mod error;
 mod git;
 mod github;
+mod hooks;
 mod http;
+mod init;
 mod logger;
+mod packager;
+mod pipeline;
+mod plugin;
+mod progress;
+mod provenance;
+mod render;
+mod sbom;
+mod sign;
 mod template;
+mod upload;
+mod verify;
 
-use tokio::process::Command;
+#[cfg(test)]
+mod tests;
 
-use crate::{cli::Opts, template::Template};
-use anyhow::Result;
+use crate::{
+    build::{arch::Arch, os::Os},
+    cli::{Commands, Opts, Stage},
+    config::{BodySource, PluginHook, Rollback, WebhookEvent},
+    error::Failure,
+    github::tag::Tag,
+    hooks::Hooks,
+    pipeline::{Released, State},
+};
+use anyhow::{bail, Context, Result};
 use clap::Parser;
 use config::ReleaserConfig;
+use std::process::ExitCode;
 
 #[tokio::main]
-async fn main() -> Result<()> {
-    logger::init()?;
+async fn main() -> ExitCode {
     let opts = Opts::parse();
+    let error_format = opts.error_format;
+    // the output of `render`, `changelog` and `doctor` is printed along the logs
+    let init = logger::init(
+        opts.verbose,
+        matches!(
+            opts.command,
+            Some(Commands::Render { .. } | Commands::Changelog { .. } | Commands::Doctor { .. })
+        ),
+        opts.log_format,
+    );
 
+    let result = match init {
+        Ok(()) => run(opts).await,
+        Err(err) => Err(err),
+    };
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            logger::error(&err);
+            error::report(&err, error_format)
+        }
+    }
+}
+
+async fn run(mut opts: Opts) -> Result<()> {
+    // packaging only leaves the archives in the output directory
+    if opts.snapshot || matches!(opts.command, Some(Commands::Package)) {
+        opts.dry_run = true;
+    }
+
+    match &opts.command {
+        Some(Commands::Init { yes, force }) => {
+            let path = opts
+                .config
+                .as_deref()
+                .unwrap_or(config::DEFAULT_CONFIG_FILE);
+            return init::init(&opts.path, path, *yes, *force);
+        }
+        Some(Commands::Schema) => {
+            println!("{}", config::schema()?);
+            return Ok(());
+        }
+        _ => {}
+    }
+
+    opts.validate().context(Failure::Config)?;
     log::info!("Starting");
-    let config = ReleaserConfig::load(opts.config).await?;
+    let path = opts.config.to_owned().or_else(|| config::find(&opts.path));
+    let mut config = ReleaserConfig::load(&opts.path, path, opts.profile.as_deref())
+        .await
+        .context(Failure::Config)?;
+    if opts.draft {
+        config.release.draft = true;
+    }
+    github::auth::init(&config.github);
+    http::init(&config.http)?;
+    template::init(&opts.path);
 
-    if !std::fs::metadata(&opts.output).is_ok() {
+    if std::fs::metadata(&opts.output).is_err() {
         log::info!("Creating directory: {:?}", &opts.output);
         std::fs::create_dir_all(&opts.output)?;
     }
 
+    match &opts.command {
+        Some(Commands::Verify { tag }) => {
+            log::info!("Verifying release {}", tag);
+            let tag = Tag::with_prefix(tag, &config.tag.prefix);
+            verify::verify(&config, &tag, &opts.output).await
+        }
+        Some(Commands::Check { offline }) => check::check(&config, *offline)
+            .await
+            .context(Failure::Config),
+        Some(Commands::Doctor { offline }) => doctor::doctor(&config, &opts, *offline).await,
+        Some(Commands::Bump {
+            level,
+            changelog,
+            no_push,
+        }) => bump::bump(&config, &opts, *level, *changelog, !*no_push).await,
+        Some(Commands::Render { target, data, file }) => {
+            render::render(&config, &opts, *target, data.as_deref(), file.as_deref())
+        }
+        Some(Commands::Changelog { from, to, file }) => changelog::print(
+            &config,
+            &opts,
+            from.as_deref(),
+            to.as_deref(),
+            file.as_deref(),
+        ),
+        Some(Commands::Init { .. } | Commands::Schema) => {
+            unreachable!("handled before loading the config")
+        }
+        Some(Commands::Build { os, arch }) => {
+            // a job of a CI matrix only builds its share of the targets
+            if let Some(oses) = config.build.os.as_mut().filter(|_| !os.is_empty()) {
+                oses.retain(|o| os.iter().any(|name| Os::from(name.to_owned()) == *o));
+            }
+            if let Some(archs) = config.build.arch.as_mut().filter(|_| !arch.is_empty()) {
+                archs.retain(|a| arch.iter().any(|name| Arch::from(name.to_owned()) == *a));
+            }
+            release(config, &opts).await
+        }
+        Some(Commands::Package | Commands::Release | Commands::Publish) | None => {
+            release(config, &opts).await
+        }
+    }
+}
+
+/// Run the stages, rolling back what the run published when the release, the uploads or the
+/// formulas fail, according to `release.rollback`
+async fn release(config: ReleaserConfig, opts: &Opts) -> Result<()> {
+    let policy = config.release.rollback;
+    let release_config = config.release.clone();
+    let result = run_stages(config, opts).await;
+
+    let rolls_back = matches!(
+        logger::running(),
+        Some(Stage::Release | Stage::Upload | Stage::Brew)
+    );
+    if result.is_err() && rolls_back && policy != Rollback::None {
+        if let Some(state) = pipeline::state(State::clone) {
+            // the error of the run is the one reported, a partial rollback keeps the state so
+            // the next run resumes what is left
+            match pipeline::rollback(policy, &state, &release_config).await {
+                Ok(()) => pipeline::finish()?,
+                Err(err) => log::error!("{:#}", err),
+            }
+        }
+    }
+    result
+}
+
+async fn run_stages(config: ReleaserConfig, opts: &Opts) -> Result<()> {
+    let problems = git::unpublished_changes(&opts.path, &config.tag.remote)?;
+    if !problems.is_empty() {
+        // dry runs publish nothing, the artifacts can't mismatch a release
+        if !opts.allow_dirty && !opts.dry_run {
+            bail!(
+                "refusing to release with {}; use --allow-dirty to release anyway",
+                problems.join("; ")
+            );
+        }
+        for problem in &problems {
+            log::warn!("Releasing with {}", problem);
+        }
+    }
+
     let build_info = config.build;
+    let mut tag_created = false;
+    let tag = match &opts.tag {
+        Some(tag) => Tag::with_prefix(tag, &config.tag.prefix),
+        None if opts.snapshot => {
+            let tag = git::get_snapshot_tag(&opts.path, &config.tag.prefix)?;
+            log::info!(
+                "Building snapshot {}, nothing will be published",
+                tag.value()
+            );
+            tag
+        }
+        None => match &opts.create_tag {
+            Some(version) => {
+                let (tag, created) = git::create_tag(
+                    &opts.path,
+                    version.as_deref(),
+                    &config.tag,
+                    true,
+                    opts.dry_run,
+                )
+                .await?;
+                tag_created = created;
+                tag
+            }
+            None => git::get_current_tag(&opts.path, &config.tag.prefix)?,
+        },
+    };
+    // snapshot versions are synthesized, only actual tags must follow the pattern
+    if !opts.snapshot {
+        tag.validate(&config.tag.pattern).context(Failure::Config)?;
+    }
+    // tags created above are signed by us, snapshots have none
+    if config.tag.verify && opts.create_tag.is_none() && !opts.snapshot {
+        git::verify_tag(&opts.path, &tag).await?;
+    }
 
-    log::info!("Building with {:?}", build_info.tool);
-
-    build::build(&build_info, opts.path.clone(), opts.dry_run).await?;
-
-    log::info!("Creating release");
-    let packages = github::release(
-        &build_info,
-        &config.release.clone(),
-        opts.path.clone(),
-        opts.dry_run,
-        &opts.output,
-    )
-    .await?;
-
-    if config.brew.is_some() {
-        log::info!("Creating brew formula");
-        brew::release(
-            config.brew.unwrap(),
-            config.release.clone(),
-            packages,
-            Template::from(build_info),
+    pipeline::init(&opts.output, &tag, opts.dry_run)?;
+    if tag_created {
+        pipeline::record(|state| state.tag_created = true)?;
+    }
+    let hooks = Hooks::new(&config.hooks, &config.release, &tag, opts);
+
+    if opts.runs(Stage::Build) {
+        let stage = logger::stage(Stage::Build);
+        hooks.before(Stage::Build, &[]).await?;
+        log::info!("Building with {:?}", build_info.tool);
+        build::build(&build_info, opts.path.clone())
+            .await
+            .context(Failure::Build)?;
+        build::completions::generate(&build_info, &opts.path, &opts.output)
+            .await
+            .context(Failure::Build)?;
+        hooks.after(Stage::Build, &[]).await?;
+        stage.finish();
+    }
+
+    if opts.runs(Stage::Sign) {
+        let stage = logger::stage(Stage::Sign);
+        hooks.before(Stage::Sign, &[]).await?;
+        if let Some(sign) = config.windows.as_ref().and_then(|w| w.sign.as_ref()) {
+            log::info!("Signing Windows binaries");
+            sign::windows::sign(sign, &build_info, &opts.path, opts.dry_run).await?;
+        }
+
+        if let Some(macos) = &config.macos {
+            log::info!("Signing macOS binaries");
+            sign::macos::sign(macos, &build_info, &opts.path, opts.dry_run).await?;
+        }
+        hooks.after(Stage::Sign, &[]).await?;
+        stage.finish();
+    }
+
+    let mut extra_assets = match &config.sbom {
+        Some(sbom) if opts.runs(Stage::Sbom) => {
+            let stage = logger::stage(Stage::Sbom);
+            hooks.before(Stage::Sbom, &[]).await?;
+            log::info!("Generating SBOM");
+            let assets = sbom::generate(sbom, &build_info, &tag, &opts.path, &opts.output)?;
+            hooks.after(Stage::Sbom, &[]).await?;
+            stage.finish();
+            assets
+        }
+        _ => vec![],
+    };
+    extra_assets.extend(github::resolve_extra_assets(
+        &config.release.extra_assets,
+        &opts.path,
+    )?);
+
+    // the stages after the release pick up the one made by an earlier run, e.g. by `release`
+    let released = if opts.reuses_release() {
+        match Released::load(&opts.output, &tag) {
+            Ok(released) => Some(released),
+            Err(err) if matches!(opts.command, Some(Commands::Publish)) => return Err(err),
+            Err(err) => {
+                log::warn!("{:#}", err);
+                None
+            }
+        }
+    } else {
+        None
+    };
+
+    let release_config = config.release.clone();
+    let changelog = match &release_config.changelog {
+        Some(changelog_config) if opts.runs(Stage::Changelog) => {
+            let stage = logger::stage(Stage::Changelog);
+            hooks.before(Stage::Changelog, &[]).await?;
+            log::info!("Generating changelog");
+            let mut changelog = changelog::generate(changelog_config, &tag, &opts.path)?;
+            if changelog_config.contributors && !opts.dry_run {
+                changelog::contributors::resolve_logins(&mut changelog, &release_config).await?;
+            }
+            hooks.after(Stage::Changelog, &[]).await?;
+            stage.finish();
+            Some(changelog)
+        }
+        _ => None,
+    };
+    let notes = match (
+        &released,
+        &release_config.body_source,
+        &changelog,
+        &release_config.changelog,
+    ) {
+        (Some(released), _, _, _) => released.notes.to_owned(),
+        (_, BodySource::Github, _, _) if opts.dry_run => {
+            log::info!("Would generate the release notes with GitHub");
+            None
+        }
+        (_, BodySource::Github, _, _) => {
+            log::info!("Generating release notes with GitHub");
+            Some(github::generate_notes(&release_config, &tag, &opts.path).await?)
+        }
+        (_, BodySource::Changelog, Some(changelog), Some(changelog_config)) => {
+            Some(changelog.render(changelog_config)?)
+        }
+        _ => None,
+    };
+
+    let packages = if opts.runs(Stage::Release) {
+        let context = plugin::Context::new(
+            PluginHook::PreRelease,
+            &release_config,
+            &tag,
+            opts,
+            &[],
+            &extra_assets,
+        );
+        let plugin_assets = plugin::run(&config.plugins, PluginHook::PreRelease, &context).await?;
+        extra_assets.extend(plugin_assets);
+
+        let stage = logger::stage(Stage::Release);
+        hooks.before(Stage::Release, &[]).await?;
+        log::info!("Creating release");
+        let packages = github::release(
+            &build_info,
+            &release_config,
+            &tag,
+            notes.clone(),
+            extra_assets.clone(),
             opts.path.clone(),
             opts.dry_run,
             &opts.output,
         )
         .await?;
+        Released {
+            tag: tag.value().to_owned(),
+            notes: notes.clone(),
+            packages: packages.clone(),
+        }
+        .save(&opts.output)?;
+        hooks.after(Stage::Release, &packages).await?;
+        stage.finish();
+        packages
+    } else if let Some(released) = released {
+        released.packages
+    } else {
+        vec![]
+    };
+
+    if let Some(storage) = config.storage.as_ref().filter(|_| opts.runs(Stage::Upload)) {
+        if packages.is_empty() {
+            log::warn!("Skipping the upload, the release stage did not run");
+        } else {
+            let stage = logger::stage(Stage::Upload);
+            hooks.before(Stage::Upload, &packages).await?;
+            log::info!("Mirroring the release");
+            upload::upload(
+                storage,
+                &release_config,
+                &tag,
+                &packages,
+                &opts.path,
+                opts.dry_run,
+                &opts.output,
+            )
+            .await
+            .context(Failure::Upload)?;
+            hooks.after(Stage::Upload, &packages).await?;
+            stage.finish();
+        }
+    }
+    if !packages.is_empty() {
+        let context = plugin::Context::new(
+            PluginHook::PostUpload,
+            &release_config,
+            &tag,
+            opts,
+            &packages,
+            &extra_assets,
+        );
+        plugin::run(&config.plugins, PluginHook::PostUpload, &context).await?;
     }
 
-    if config.crates_io.is_some() && !opts.dry_run {
-        let crates_io = config.crates_io.unwrap();
-        for package in &crates_io.packages {
-            log::info!("Publishing {} to crates.io", package);
-            let mut cmd = Command::new("cargo");
-            cmd.arg("publish").current_dir(opts.path.clone());
-            if crates_io.allow_dirty.unwrap_or(false) {
-                cmd.arg("--allow-dirty");
-            }
-            if crates_io.no_verify.unwrap_or(false) {
-                cmd.arg("--no-verify");
-            }
-            if crates_io.clone().registry.is_some() {
-                cmd.arg("--registry")
-                    .arg(crates_io.clone().registry.unwrap());
+    // values of the announcements and the webhooks, once the release is out
+    let announcement = (opts.runs(Stage::Announce) && !packages.is_empty()).then(|| {
+        announce::Announcement::new(
+            &release_config,
+            &tag,
+            notes.as_deref(),
+            changelog.as_ref(),
+            &packages,
+        )
+    });
+    let webhooks = config
+        .announce
+        .as_ref()
+        .map(|announce| announce.webhooks.clone())
+        .unwrap_or_default();
+    if let Some(announcement) = &announcement {
+        for event in [WebhookEvent::ReleaseCreated, WebhookEvent::AssetsUploaded] {
+            announce::webhook::notify(&webhooks, event, announcement, opts.dry_run, &opts.output)
+                .await?;
+        }
+    }
+
+    if let Some(prune) = release_config
+        .prune
+        .as_ref()
+        .filter(|_| opts.runs(Stage::Prune))
+    {
+        let stage = logger::stage(Stage::Prune);
+        hooks.before(Stage::Prune, &packages).await?;
+        github::prune::prune(prune, &release_config, &tag, opts.dry_run).await?;
+        hooks.after(Stage::Prune, &packages).await?;
+        stage.finish();
+    }
+
+    if let (Some(changelog), Some(changelog_config)) = (&changelog, &release_config.changelog) {
+        if let Some(file) = &changelog_config.file {
+            log::info!("Updating {}", file.path);
+            changelog::file::update(
+                changelog,
+                changelog_config,
+                file,
+                &release_config,
+                &opts.path,
+                opts.dry_run,
+                &opts.output,
+            )
+            .await?;
+        }
+    }
+
+    if !config.brew.is_empty() && opts.runs(Stage::Brew) {
+        if packages.is_empty() {
+            log::warn!("Skipping the brew formulas, the release stage did not run");
+        } else {
+            let stage = logger::stage(Stage::Brew);
+            hooks.before(Stage::Brew, &packages).await?;
+            for brew in config.brew {
+                log::info!("Creating brew formula {}", brew.name);
+                brew::release(
+                    brew,
+                    config.release.clone(),
+                    packages.clone(),
+                    tag.to_owned(),
+                    opts.dry_run,
+                    &opts.path,
+                    &opts.output,
+                )
+                .await?;
             }
-            if crates_io.clone().index.is_some() {
-                cmd.arg("--index").arg(crates_io.clone().index.unwrap());
+            let context = plugin::Context::new(
+                PluginHook::PostBrew,
+                &release_config,
+                &tag,
+                opts,
+                &packages,
+                &extra_assets,
+            );
+            plugin::run(&config.plugins, PluginHook::PostBrew, &context).await?;
+
+            if let Some(announcement) = &announcement {
+                announce::webhook::notify(
+                    &webhooks,
+                    WebhookEvent::FormulaUpdated,
+                    announcement,
+                    opts.dry_run,
+                    &opts.output,
+                )
+                .await?;
             }
-            cmd.arg("--package").arg(package);
+            hooks.after(Stage::Brew, &packages).await?;
+            stage.finish();
+        }
+    }
 
-            cmd.status().await?;
+    if let Some(crates_io) = config.crates_io.filter(|_| opts.runs(Stage::CratesIo)) {
+        let stage = logger::stage(Stage::CratesIo);
+        hooks.before(Stage::CratesIo, &packages).await?;
+        crates_io::publish(&crates_io, &tag, &opts.path, opts.dry_run).await?;
+        hooks.after(Stage::CratesIo, &packages).await?;
+        stage.finish();
+    }
+
+    if let Some(announce) = config.announce.filter(|_| opts.runs(Stage::Announce)) {
+        match &announcement {
+            Some(announcement) => {
+                let stage = logger::stage(Stage::Announce);
+                hooks.before(Stage::Announce, &packages).await?;
+                log::info!("Announcing the release");
+                announce::announce(&announce, announcement, opts.dry_run, &opts.output).await?;
+                hooks.after(Stage::Announce, &packages).await?;
+                stage.finish();
+            }
+            None => log::warn!("Skipping the announcement, the release stage did not run"),
         }
     }
 
-    Ok(())
+    if !packages.is_empty() {
+        actions::report(&release_config, &tag, &packages, opts.dry_run)?;
+        progress::summary(&packages, &opts.output);
+    }
+
+    pipeline::finish()
 }
@@ -0,0 +1,128 @@
+pub mod package;
+
+use crate::{
+    build::committer::Committer,
+    config::PullRequestConfig,
+    dry_run::{self, FileChange},
+    github::{self, builder::BuilderExecutor, github_client},
+    pipeline,
+};
+use anyhow::{Context, Result};
+use std::{fs, path::Path};
+
+/// A package manager whose manifest is rendered from the packages of a release and committed to
+/// a repository, e.g. a formula in a Homebrew tap
+///
+/// Implementors only describe the manifest and where it goes, [`publish`] writes, checks and
+/// commits it, directly or through a pull request.
+pub trait Packager {
+    fn render(&self) -> Result<String>;
+
+    /// Repository the manifest is committed to, as owner and name
+    fn repository(&self) -> (&str, &str);
+
+    /// Branch the manifest is committed to without a pull request
+    fn branch(&self) -> &str;
+
+    /// Path of the manifest in the repository
+    fn path(&self) -> String;
+
+    fn commit_message(&self) -> String;
+
+    fn committer(&self) -> Option<Committer>;
+
+    fn pull_request(&self) -> Option<&PullRequestConfig>;
+
+    /// Check the manifest written at `path` before it's committed, returning it as it should be
+    /// committed
+    async fn check(&self, _path: &Path, manifest: String) -> Result<String> {
+        Ok(manifest)
+    }
+
+    /// Get the repository ready before the manifest is committed to it
+    async fn prepare(&self) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Render the manifest of `packager` to the output path and commit it, or record the commit in
+/// a dry run, returning the manifest
+pub async fn publish<P: Packager>(
+    packager: &P,
+    dry_run: bool,
+    output_path: &Path,
+) -> Result<String> {
+    let manifest = packager.render()?;
+    let path = packager.path();
+    let (owner, repo) = packager.repository();
+
+    let file = output_path.join(
+        Path::new(&path)
+            .file_name()
+            .context("empty manifest path")?,
+    );
+    fs::write(&file, &manifest)?;
+    let manifest = packager.check(&file, manifest).await?;
+
+    if dry_run {
+        dry_run::record_file_change(
+            output_path,
+            &FileChange {
+                owner,
+                repo,
+                branch: packager.branch(),
+                path: &path,
+                message: &packager.commit_message(),
+                content: &manifest,
+                pull_request: packager.pull_request(),
+            },
+        )?;
+        return Ok(manifest);
+    }
+
+    // committed by the unfinished run being resumed
+    let key = format!("{}/{}/{}", owner, repo, path);
+    if let Some(Some(sha)) = pipeline::state(|state| state.commits.get(&key).cloned()) {
+        log::info!("Skipping {}, committed as {} by the resumed run", path, sha);
+        return Ok(manifest);
+    }
+
+    packager.prepare().await?;
+    let committer = packager.committer();
+    let sha = match packager.pull_request() {
+        Some(pull_request) => {
+            log::debug!("Creating pull request");
+            github::push_with_pull_request(
+                owner,
+                repo,
+                &path,
+                &manifest,
+                &packager.commit_message(),
+                committer.as_ref(),
+                pull_request.to_owned(),
+            )
+            .await?
+        }
+        None => {
+            log::debug!("Committing file to head branch");
+            let mut commit = github_client::instance()
+                .repo(owner, repo)
+                .branch(packager.branch())
+                .commit_files()
+                .file(path, manifest.as_str())
+                .message(packager.commit_message());
+            if let Some(committer) = &committer {
+                commit = commit.committer(committer);
+            }
+            commit
+                .execute()
+                .await
+                .context("error uploading file to main branch")?
+        }
+    };
+    pipeline::record(|state| {
+        state.commits.insert(key, sha);
+    })?;
+
+    Ok(manifest)
+}
@@ -0,0 +1,209 @@
+use crate::{
+    config::{ReleaseConfig, Rollback},
+    github::{github_client, release::Release, tag::Tag},
+    packager::package::Package,
+};
+use anyhow::{bail, Context, Result};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+/// File of the output directory the `release` subcommand leaves for `publish`
+const RELEASED_FILE: &str = "released.json";
+/// File of the output directory recording the steps of an unfinished run
+const STATE_FILE: &str = ".rustreleaser-state.json";
+
+static STATE: Lazy<Mutex<Option<StateFile>>> = Lazy::new(|| Mutex::new(None));
+
+/// Steps of a release already done, so a run resumed after a failure skips them
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct State {
+    pub tag: String,
+    pub release_id: Option<u64>,
+    /// Whether the run created the release rather than reusing an existing one
+    #[serde(default)]
+    pub release_created: bool,
+    /// Whether the run created and pushed the tag
+    #[serde(default)]
+    pub tag_created: bool,
+    /// Branches of the pull requests opened by the run
+    #[serde(default)]
+    pub branches: Vec<CreatedBranch>,
+    #[serde(default)]
+    pub uploaded_assets: Vec<String>,
+    /// SHA of the commits of the manifests, by `owner/repo/path`
+    #[serde(default)]
+    pub commits: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CreatedBranch {
+    pub owner: String,
+    pub repo: String,
+    pub name: String,
+}
+
+#[derive(Debug)]
+struct StateFile {
+    path: PathBuf,
+    state: State,
+}
+
+/// Resume the unfinished run of `tag` recorded in `output_path`, if any. Dry runs publish
+/// nothing, so they neither resume nor record anything
+pub fn init(output_path: &Path, tag: &Tag, dry_run: bool) -> Result<()> {
+    if dry_run {
+        return Ok(());
+    }
+
+    let path = output_path.join(STATE_FILE);
+    let state = match fs::read_to_string(&path) {
+        Ok(content) => serde_json::from_str::<State>(&content)
+            .with_context(|| format!("invalid state in {}", path.display()))?,
+        Err(_) => State::default(),
+    };
+    let state = if state.tag == tag.value() {
+        log::info!(
+            "Resuming the release of {} recorded in {}",
+            state.tag,
+            path.display()
+        );
+        state
+    } else {
+        State {
+            tag: tag.value().to_owned(),
+            ..State::default()
+        }
+    };
+
+    *STATE.lock().unwrap() = Some(StateFile { path, state });
+    Ok(())
+}
+
+/// Read the state of the run, `None` outside of a run that records one
+pub fn state<T>(read: impl FnOnce(&State) -> T) -> Option<T> {
+    STATE.lock().unwrap().as_ref().map(|file| read(&file.state))
+}
+
+/// Record a step done and write the state file
+pub fn record(update: impl FnOnce(&mut State)) -> Result<()> {
+    let mut guard = STATE.lock().unwrap();
+    let Some(file) = guard.as_mut() else {
+        return Ok(());
+    };
+    update(&mut file.state);
+    fs::write(&file.path, serde_json::to_string_pretty(&file.state)?)
+        .with_context(|| format!("failed to write {}", file.path.display()))
+}
+
+/// Remove the state file once the run succeeded
+pub fn finish() -> Result<()> {
+    if let Some(file) = STATE.lock().unwrap().take() {
+        if file.path.exists() {
+            fs::remove_file(&file.path)?;
+        }
+    }
+    Ok(())
+}
+
+/// Delete what the failed run recorded in `state` created, according to `policy`. Every step is
+/// tried, and the ones that failed are returned as an error listing what is left behind
+pub async fn rollback(
+    policy: Rollback,
+    state: &State,
+    release_config: &ReleaseConfig,
+) -> Result<()> {
+    if policy == Rollback::None {
+        return Ok(());
+    }
+
+    let mut failures = vec![];
+    if let Some(id) = state.release_id.filter(|_| state.release_created) {
+        log::warn!("Rolling back: deleting the release of {}", state.tag);
+        let release = Release::new(id, &release_config.owner, &release_config.repo);
+        if let Err(err) = release.delete().await {
+            failures.push(format!("the release of {} ({:#})", state.tag, err));
+        }
+    }
+
+    if policy == Rollback::All {
+        if state.tag_created {
+            log::warn!("Rolling back: deleting the tag {}", state.tag);
+            let deleted = github_client::instance()
+                .repo(&release_config.owner, &release_config.repo)
+                .delete_tag(&state.tag)
+                .await;
+            if let Err(err) = deleted {
+                failures.push(format!("the tag {} ({:#})", state.tag, err));
+            }
+        }
+        for branch in &state.branches {
+            log::warn!(
+                "Rolling back: deleting the branch {} of {}/{}",
+                branch.name,
+                branch.owner,
+                branch.repo
+            );
+            let deleted = github_client::instance()
+                .repo(&branch.owner, &branch.repo)
+                .branch(&branch.name)
+                .delete()
+                .await;
+            if let Err(err) = deleted {
+                failures.push(format!(
+                    "the branch {} of {}/{} ({:#})",
+                    branch.name, branch.owner, branch.repo, err
+                ));
+            }
+        }
+    }
+
+    if !failures.is_empty() {
+        bail!("failed to roll back {}", failures.join(", "));
+    }
+    Ok(())
+}
+
+/// What a release made, for the formulas and announcements published by a later run
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Released {
+    pub tag: String,
+    pub notes: Option<String>,
+    pub packages: Vec<Package>,
+}
+
+impl Released {
+    pub fn save(&self, output_path: &Path) -> Result<()> {
+        let path = output_path.join(RELEASED_FILE);
+        fs::write(&path, serde_json::to_string_pretty(self)?)?;
+        log::debug!("Wrote the released packages to {}", path.display());
+        Ok(())
+    }
+
+    /// The release of `tag` saved in `output_path` by an earlier run
+    pub fn load(output_path: &Path, tag: &Tag) -> Result<Released> {
+        let path = output_path.join(RELEASED_FILE);
+        let content = fs::read_to_string(&path).with_context(|| {
+            format!(
+                "failed to read {}, run `release` with the same output directory first",
+                path.display()
+            )
+        })?;
+        let released: Released = serde_json::from_str(&content)
+            .with_context(|| format!("invalid release in {}", path.display()))?;
+        if released.tag != tag.value() {
+            bail!(
+                "{} holds the release of {}, not {}",
+                path.display(),
+                released.tag,
+                tag.value()
+            );
+        }
+        Ok(released)
+    }
+}
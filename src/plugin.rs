@@ -0,0 +1,137 @@
+use crate::{
+    cli::Opts,
+    config::{PluginConfig, PluginHook, ReleaseConfig},
+    github::{
+        asset::{release_url, Asset},
+        tag::Tag,
+    },
+    packager::package::Package,
+};
+use anyhow::{bail, Context as _, Result};
+use serde::{Deserialize, Serialize};
+use std::{io::ErrorKind, path::Path, process::Stdio};
+use tokio::{io::AsyncWriteExt, process::Command};
+
+/// State of the release written as JSON to the stdin of the plugins
+#[derive(Debug, Serialize)]
+pub struct Context<'a> {
+    pub hook: &'static str,
+    pub tag: &'a str,
+    pub version: &'a str,
+    pub owner: &'a str,
+    pub repo: &'a str,
+    pub release_url: String,
+    pub dry_run: bool,
+    pub path: &'a Path,
+    pub output: &'a Path,
+    /// Archives of the release, empty before it is created
+    pub packages: &'a [Package],
+    /// Names of the extra assets of the release
+    pub assets: Vec<&'a str>,
+}
+
+impl<'a> Context<'a> {
+    pub fn new(
+        hook: PluginHook,
+        release_config: &'a ReleaseConfig,
+        tag: &'a Tag,
+        opts: &'a Opts,
+        packages: &'a [Package],
+        assets: &'a [Asset],
+    ) -> Self {
+        Context {
+            hook: hook.as_str(),
+            tag: tag.value(),
+            version: tag.version(),
+            owner: &release_config.owner,
+            repo: &release_config.repo,
+            release_url: release_url(&release_config.owner, &release_config.repo, tag),
+            dry_run: opts.dry_run,
+            path: &opts.path,
+            output: &opts.output,
+            packages,
+            assets: assets.iter().map(|asset| asset.name.as_str()).collect(),
+        }
+    }
+}
+
+/// What a plugin prints to stdout, nothing meaning it has nothing to add
+#[derive(Debug, Default, Deserialize)]
+struct Response {
+    /// Files to add to the release, relative to the project
+    #[serde(default)]
+    assets: Vec<String>,
+    /// Reason to stop the release
+    abort: Option<String>,
+}
+
+/// Call the plugins subscribed to `hook`, failing when one exits with an error or aborts, and
+/// return the assets they contributed
+pub async fn run(
+    plugins: &[PluginConfig],
+    hook: PluginHook,
+    context: &Context<'_>,
+) -> Result<Vec<Asset>> {
+    let mut assets = vec![];
+    for plugin in plugins.iter().filter(|plugin| plugin.hooks.contains(&hook)) {
+        log::info!("Running plugin {} at {}", plugin.name, hook.as_str());
+        let response = call(plugin, context)
+            .await
+            .with_context(|| format!("plugin {} failed at {}", plugin.name, hook.as_str()))?;
+
+        if let Some(reason) = response.abort {
+            bail!("plugin {} aborted the release: {}", plugin.name, reason);
+        }
+        if !response.assets.is_empty() && hook != PluginHook::PreRelease {
+            log::warn!(
+                "Ignoring the assets of plugin {}, they are only added at pre-release",
+                plugin.name
+            );
+            continue;
+        }
+        for file in response.assets {
+            let path = context.path.join(&file);
+            if !path.is_file() {
+                bail!("plugin {} added {}, which is not a file", plugin.name, file);
+            }
+            let name = path
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or(file);
+            assets.push(Asset::new(name, path));
+        }
+    }
+    Ok(assets)
+}
+
+async fn call(plugin: &PluginConfig, context: &Context<'_>) -> Result<Response> {
+    let Some((program, args)) = plugin.command.split_first() else {
+        bail!("the command is empty");
+    };
+    let mut child = Command::new(program)
+        .args(args)
+        .current_dir(context.path)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::inherit())
+        .spawn()
+        .with_context(|| format!("failed to run {}", program))?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        // plugins that don't need the context may exit without reading it
+        match stdin.write_all(&serde_json::to_vec(context)?).await {
+            Err(err) if err.kind() != ErrorKind::BrokenPipe => return Err(err.into()),
+            _ => {}
+        }
+    }
+    let output = child.wait_with_output().await?;
+    if !output.status.success() {
+        bail!("{} exited with {}", program, output.status);
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    if stdout.trim().is_empty() {
+        return Ok(Response::default());
+    }
+    serde_json::from_str(&stdout).context("invalid JSON response")
+}
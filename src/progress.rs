@@ -0,0 +1,218 @@
+use crate::packager::package::Package;
+use once_cell::sync::{Lazy, OnceCell};
+use std::{
+    fmt::Write as _,
+    io::Write,
+    path::Path,
+    sync::Mutex,
+    thread,
+    time::{Duration, Instant},
+};
+
+const SPINNER: [char; 10] = ['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
+const TICK: Duration = Duration::from_millis(80);
+
+static ENABLED: OnceCell<bool> = OnceCell::new();
+static PROGRESS: Lazy<Mutex<Progress>> = Lazy::new(|| Mutex::new(Progress::default()));
+
+/// Tasks running, drawn below the logs and redrawn on each tick
+#[derive(Default)]
+struct Progress {
+    tasks: Vec<(u64, String, Instant)>,
+    next_id: u64,
+    /// Lines of the last drawing, cleared before printing over it
+    drawn: usize,
+    frame: usize,
+    ticking: bool,
+}
+
+impl Progress {
+    fn clear(&mut self, out: &mut String) {
+        if self.drawn > 0 {
+            let _ = write!(out, "\x1b[{}A\x1b[J", self.drawn);
+            self.drawn = 0;
+        }
+    }
+
+    fn draw(&mut self, out: &mut String) {
+        let spinner = SPINNER[self.frame % SPINNER.len()];
+        for (_, label, start) in &self.tasks {
+            let _ = writeln!(
+                out,
+                "\x1b[36m{}\x1b[0m {} \x1b[2m{:.1?}\x1b[0m",
+                spinner,
+                label,
+                start.elapsed()
+            );
+        }
+        self.drawn = self.tasks.len();
+    }
+}
+
+/// Show the tasks as spinners when the output is an interactive terminal, leaving the plain logs
+/// otherwise
+pub fn init(enabled: bool) {
+    ENABLED.get_or_init(|| enabled);
+}
+
+pub fn enabled() -> bool {
+    ENABLED.get().copied().unwrap_or(false)
+}
+
+/// Print `line` above the spinners
+pub fn println(line: &str) {
+    let mut progress = PROGRESS.lock().unwrap();
+    let mut out = String::new();
+    progress.clear(&mut out);
+    let _ = writeln!(out, "{}", line);
+    progress.draw(&mut out);
+    print(&out);
+}
+
+/// Show a spinner for `label` until the task finishes, e.g. `Uploading app.tar.gz`
+pub fn task(label: impl Into<String>) -> Task {
+    if !enabled() {
+        return Task { id: None };
+    }
+
+    let mut progress = PROGRESS.lock().unwrap();
+    let id = progress.next_id;
+    progress.next_id += 1;
+    progress.tasks.push((id, label.into(), Instant::now()));
+    if !progress.ticking {
+        progress.ticking = true;
+        thread::spawn(tick);
+    }
+    Task { id: Some(id) }
+}
+
+/// Spinner of a running task, marked as failed if dropped before [`Task::finish`]
+pub struct Task {
+    id: Option<u64>,
+}
+
+impl Task {
+    pub fn finish(mut self) {
+        self.end(true);
+    }
+
+    fn end(&mut self, success: bool) {
+        let Some(id) = self.id.take() else {
+            return;
+        };
+        let mut progress = PROGRESS.lock().unwrap();
+        let Some(index) = progress.tasks.iter().position(|(task, ..)| *task == id) else {
+            return;
+        };
+        let (_, label, start) = progress.tasks.remove(index);
+        let mut out = String::new();
+        progress.clear(&mut out);
+        let _ = match success {
+            true => writeln!(
+                out,
+                "\x1b[32m✔\x1b[0m {} \x1b[2m{:.1?}\x1b[0m",
+                label,
+                start.elapsed()
+            ),
+            false => writeln!(out, "\x1b[31m✘\x1b[0m {}", label),
+        };
+        progress.draw(&mut out);
+        print(&out);
+    }
+}
+
+impl Drop for Task {
+    fn drop(&mut self) {
+        self.end(false);
+    }
+}
+
+/// Print the packages released, with the size of their archive in `output_path`
+pub fn summary(packages: &[Package], output_path: &Path) {
+    if !enabled() || packages.is_empty() {
+        return;
+    }
+
+    let rows: Vec<[String; 4]> = packages
+        .iter()
+        .map(|package| {
+            let size = std::fs::metadata(output_path.join(&package.name))
+                .map(|metadata| size(metadata.len()))
+                .unwrap_or_default();
+            [
+                package.name.to_owned(),
+                size,
+                package.sha256.to_owned(),
+                package.url.to_owned().unwrap_or_default(),
+            ]
+        })
+        .collect();
+    let header = ["Artifact", "Size", "SHA-256", "URL"].map(String::from);
+    let widths: Vec<usize> = (0..header.len())
+        .map(|column| {
+            std::iter::once(&header)
+                .chain(&rows)
+                .map(|row| row[column].chars().count())
+                .max()
+                .unwrap_or_default()
+        })
+        .collect();
+
+    let mut table = String::new();
+    for (index, row) in std::iter::once(&header).chain(&rows).enumerate() {
+        let line = row
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+            .collect::<Vec<_>>()
+            .join("  ");
+        let line = line.trim_end();
+        match index {
+            0 => {
+                let _ = writeln!(table, "\x1b[1m{}\x1b[0m", line);
+            }
+            _ => {
+                let _ = writeln!(table, "{}", line);
+            }
+        }
+    }
+    println(table.trim_end());
+}
+
+/// Redraw the spinners until no task is left
+fn tick() {
+    loop {
+        thread::sleep(TICK);
+        let mut progress = PROGRESS.lock().unwrap();
+        if progress.tasks.is_empty() {
+            progress.ticking = false;
+            return;
+        }
+        progress.frame += 1;
+        let mut out = String::new();
+        progress.clear(&mut out);
+        progress.draw(&mut out);
+        print(&out);
+    }
+}
+
+fn print(out: &str) {
+    let mut stdout = std::io::stdout().lock();
+    let _ = stdout.write_all(out.as_bytes());
+    let _ = stdout.flush();
+}
+
+/// Human size of `bytes`, e.g. `1.2 MiB`
+fn size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    match unit {
+        0 => format!("{} B", bytes),
+        _ => format!("{:.1} {}", size, UNITS[unit]),
+    }
+}
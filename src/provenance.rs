@@ -0,0 +1,157 @@
+use crate::{
+    build::Build,
+    config::{ProvenanceConfig, ReleaseConfig},
+    github::{asset::Asset, tag::Tag},
+};
+use anyhow::{bail, Result};
+use serde::Serialize;
+use std::{collections::BTreeMap, env, fs, path::Path};
+
+const STATEMENT_TYPE: &str = "https://in-toto.io/Statement/v1";
+const PREDICATE_TYPE: &str = "https://slsa.dev/provenance/v1";
+const BUILD_TYPE: &str = "https://github.com/cestef/rustreleaser/buildtypes/cargo/v1";
+
+#[derive(Debug, Serialize)]
+pub struct Statement {
+    #[serde(rename = "_type")]
+    pub statement_type: String,
+    pub subject: Vec<Subject>,
+    #[serde(rename = "predicateType")]
+    pub predicate_type: String,
+    pub predicate: Predicate,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Subject {
+    pub name: String,
+    pub digest: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Predicate {
+    pub build_definition: BuildDefinition,
+    pub run_details: RunDetails,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BuildDefinition {
+    pub build_type: String,
+    pub external_parameters: ExternalParameters,
+    pub resolved_dependencies: Vec<ResourceDescriptor>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ExternalParameters {
+    pub repository: String,
+    #[serde(rename = "ref")]
+    pub git_ref: String,
+    pub build: Build,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ResourceDescriptor {
+    pub uri: String,
+    pub digest: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RunDetails {
+    pub builder: Builder,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<Metadata>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Builder {
+    pub id: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Metadata {
+    pub invocation_id: String,
+}
+
+impl Statement {
+    pub fn new(
+        config: &ProvenanceConfig,
+        release_info: &ReleaseConfig,
+        build_info: &Build,
+        tag: &Tag,
+        commit: &str,
+        assets: &[Asset],
+    ) -> Result<Self> {
+        let mut subject = vec![];
+        for asset in assets {
+            let Some(checksum) = &asset.checksum else {
+                bail!("checksum is not available for asset {}", asset.name)
+            };
+            subject.push(Subject {
+                name: asset.name.to_owned(),
+                digest: BTreeMap::from([("sha256".to_string(), checksum.to_owned())]),
+            });
+        }
+
+        let repository = format!(
+            "https://github.com/{}/{}",
+            release_info.owner, release_info.repo
+        );
+        let git_ref = format!("refs/tags/{}", tag.value());
+
+        Ok(Statement {
+            statement_type: STATEMENT_TYPE.to_string(),
+            subject,
+            predicate_type: PREDICATE_TYPE.to_string(),
+            predicate: Predicate {
+                build_definition: BuildDefinition {
+                    build_type: BUILD_TYPE.to_string(),
+                    external_parameters: ExternalParameters {
+                        repository: repository.to_owned(),
+                        git_ref: git_ref.to_owned(),
+                        build: build_info.to_owned(),
+                    },
+                    resolved_dependencies: vec![ResourceDescriptor {
+                        uri: format!("git+{}@{}", repository, git_ref),
+                        digest: BTreeMap::from([("gitCommit".to_string(), commit.to_string())]),
+                    }],
+                },
+                run_details: RunDetails {
+                    builder: Builder {
+                        id: config.builder_id.to_owned(),
+                    },
+                    metadata: invocation_id().map(|invocation_id| Metadata { invocation_id }),
+                },
+            },
+        })
+    }
+}
+
+/// Write the statement as a single-line in-toto bundle (`.intoto.jsonl`)
+pub fn write<P>(statement: &Statement, path: P) -> Result<()>
+where
+    P: AsRef<Path>,
+{
+    let path = path.as_ref();
+    log::info!("writing provenance statement: {}", path.display());
+
+    let mut line = serde_json::to_string(statement)?;
+    line.push('\n');
+    fs::write(path, line)?;
+
+    Ok(())
+}
+
+/// Link the statement to the GitHub Actions run when available
+fn invocation_id() -> Option<String> {
+    let server = env::var("GITHUB_SERVER_URL").ok()?;
+    let repository = env::var("GITHUB_REPOSITORY").ok()?;
+    let run_id = env::var("GITHUB_RUN_ID").ok()?;
+    let attempt = env::var("GITHUB_RUN_ATTEMPT").unwrap_or_else(|_| "1".to_string());
+
+    Some(format!(
+        "{}/{}/actions/runs/{}/attempts/{}",
+        server, repository, run_id, attempt
+    ))
+}
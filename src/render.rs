@@ -0,0 +1,179 @@
+use crate::{
+    brew::{bottle::Bottles, Brew},
+    changelog, checksum,
+    cli::{Opts, RenderTarget},
+    config::{BodySource, ChangelogConfig, ReleaserConfig},
+    git,
+    github::{
+        asset::download_url,
+        release_template::{self, Artifact},
+        tag::Tag,
+    },
+    packager::{package::Package, Packager},
+};
+use anyhow::{bail, Context, Result};
+use std::{fs, path::Path};
+
+/// Checksum of the files that aren't available offline
+pub const PLACEHOLDER_SHA256: &str =
+    "0000000000000000000000000000000000000000000000000000000000000000";
+
+/// Render `target` like a release of the current tag would, without building or calling GitHub,
+/// and print it or write it to `file`
+pub fn render(
+    config: &ReleaserConfig,
+    opts: &Opts,
+    target: RenderTarget,
+    data: Option<&Path>,
+    file: Option<&Path>,
+) -> Result<()> {
+    let rendered = rendered(config, opts, target, data)?;
+    match file {
+        Some(file) => fs::write(file, rendered)
+            .with_context(|| format!("failed to write {}", file.display()))?,
+        None => println!("{}", rendered),
+    }
+    Ok(())
+}
+
+/// Output of `target` for the current tag, see [`render`]
+pub fn rendered(
+    config: &ReleaserConfig,
+    opts: &Opts,
+    target: RenderTarget,
+    data: Option<&Path>,
+) -> Result<String> {
+    let tag = match &opts.tag {
+        Some(tag) => Tag::with_prefix(tag, &config.tag.prefix),
+        None => git::get_current_tag(&opts.path, &config.tag.prefix)
+            .or_else(|_| git::get_snapshot_tag(&opts.path, &config.tag.prefix))?,
+    };
+    log::debug!("Rendering {:?} for {}", target, tag.value());
+
+    Ok(match target {
+        RenderTarget::Brew => {
+            if config.brew.is_empty() {
+                bail!("no [brew] section in the config");
+            }
+            let packages = packages(config, &tag, data, &opts.output)?;
+            let mut formulas = vec![];
+            for brew in &config.brew {
+                // the source archive only exists once the tag is pushed
+                let formula = Brew::with_hash(
+                    brew.to_owned(),
+                    &config.release,
+                    tag.to_owned(),
+                    packages.to_owned(),
+                    PLACEHOLDER_SHA256.to_owned(),
+                )
+                .with_bottles(
+                    brew.bottles
+                        .to_owned()
+                        .map(|bottles| {
+                            Bottles::resolve(bottles, &opts.path, &brew.name, &config.release, &tag)
+                        })
+                        .transpose()?,
+                );
+                formulas.push(formula.render()?);
+            }
+            formulas.join("\n")
+        }
+        RenderTarget::Notes => {
+            let changelog_config = changelog_config(config)?;
+            changelog::generate(changelog_config, &tag, &opts.path)?.render(changelog_config)?
+        }
+        RenderTarget::ChangelogFile => {
+            let changelog_config = changelog_config(config)?;
+            changelog::generate(changelog_config, &tag, &opts.path)?
+                .render_file(changelog_config)?
+        }
+        RenderTarget::Release => {
+            if config.release.body_source == BodySource::Github {
+                log::warn!("The notes generated by GitHub are left out of the release body");
+            }
+            let notes = match &config.release.changelog {
+                Some(changelog_config) if config.release.body_source == BodySource::Changelog => {
+                    let changelog = changelog::generate(changelog_config, &tag, &opts.path)?;
+                    Some(changelog.render(changelog_config)?)
+                }
+                _ => None,
+            };
+            let artifacts = packages(config, &tag, data, &opts.output)?
+                .into_iter()
+                .map(|package| {
+                    let target = package
+                        .os
+                        .zip(package.arch)
+                        .map(|(os, arch)| (os.to_string(), arch.to_string()));
+                    Artifact::new(
+                        &config.release,
+                        &tag,
+                        &package.name,
+                        &package.sha256,
+                        target,
+                    )
+                })
+                .collect();
+            let release = release_template::render(
+                config.release.to_owned(),
+                &tag,
+                artifacts,
+                notes.as_deref(),
+                &opts.path,
+            )?;
+            format!(
+                "{}\n\n{}",
+                release.name.unwrap_or_default(),
+                release.body.unwrap_or_default()
+            )
+        }
+    })
+}
+
+fn changelog_config(config: &ReleaserConfig) -> Result<&ChangelogConfig> {
+    config
+        .release
+        .changelog
+        .as_ref()
+        .context("no [release.changelog] section in the config")
+}
+
+/// Packages read from `data`, or else those the build would produce, with the checksums of the
+/// archives already in `output_path`
+fn packages(
+    config: &ReleaserConfig,
+    tag: &Tag,
+    data: Option<&Path>,
+    output_path: &Path,
+) -> Result<Vec<Package>> {
+    if let Some(data) = data {
+        let content = fs::read_to_string(data)
+            .with_context(|| format!("failed to read {}", data.display()))?;
+        return serde_json::from_str(&content)
+            .with_context(|| format!("invalid packages in {}", data.display()));
+    }
+
+    let build = &config.build;
+    build
+        .targets()
+        .into_iter()
+        .map(|(arch, os)| {
+            let name = build.archive_name(tag, &arch, &os)?;
+            let archive = output_path.join(&name);
+            let sha256 = if archive.is_file() {
+                checksum::create(&name, &archive)?
+            } else {
+                PLACEHOLDER_SHA256.to_owned()
+            };
+            let url = download_url(&config.release.owner, &config.release.repo, tag, &name);
+
+            // single target formulas don't tell the platforms apart
+            let package = if build.is_multi_target() {
+                Package::new(name, Some(os), Some(arch), Some(url), sha256)
+            } else {
+                Package::new(name, None, None, Some(url), sha256)
+            };
+            Ok(package)
+        })
+        .collect()
+}
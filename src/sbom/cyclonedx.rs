@@ -0,0 +1,88 @@
+use super::{LockedPackage, Lockfile};
+use crate::{build::Build, github::tag::Tag};
+use serde::Serialize;
+
+const SPEC_VERSION: &str = "1.5";
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Bom {
+    pub bom_format: String,
+    pub spec_version: String,
+    pub version: u32,
+    pub metadata: Metadata,
+    pub components: Vec<Component>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Metadata {
+    pub component: Component,
+    pub tools: Vec<Tool>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Tool {
+    pub name: String,
+    pub version: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Component {
+    #[serde(rename = "type")]
+    pub component_type: String,
+    pub name: String,
+    pub version: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub purl: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub hashes: Vec<Hash>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Hash {
+    pub alg: String,
+    pub content: String,
+}
+
+impl Bom {
+    pub fn new(build_info: &Build, tag: &Tag, lockfile: &Lockfile) -> Self {
+        Bom {
+            bom_format: "CycloneDX".to_string(),
+            spec_version: SPEC_VERSION.to_string(),
+            version: 1,
+            metadata: Metadata {
+                component: Component {
+                    component_type: "application".to_string(),
+                    name: build_info.binary.to_owned(),
+                    version: tag.version().to_string(),
+                    purl: None,
+                    hashes: vec![],
+                },
+                tools: vec![Tool {
+                    name: env!("CARGO_PKG_NAME").to_string(),
+                    version: env!("CARGO_PKG_VERSION").to_string(),
+                }],
+            },
+            components: lockfile.package.iter().map(Component::from).collect(),
+        }
+    }
+}
+
+impl From<&LockedPackage> for Component {
+    fn from(package: &LockedPackage) -> Self {
+        Component {
+            component_type: "library".to_string(),
+            name: package.name.to_owned(),
+            version: package.version.to_owned(),
+            purl: Some(package.purl()),
+            hashes: package
+                .checksum
+                .iter()
+                .map(|checksum| Hash {
+                    alg: "SHA-256".to_string(),
+                    content: checksum.to_owned(),
+                })
+                .collect(),
+        }
+    }
+}
@@ -0,0 +1,104 @@
+pub mod cyclonedx;
+pub mod spdx;
+
+use crate::{
+    build::Build,
+    config::SbomConfig,
+    github::{asset::Asset, tag::Tag},
+};
+use anyhow::{Context, Result};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::{fs, path::Path};
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum SbomFormat {
+    CycloneDx,
+    Spdx,
+}
+
+impl SbomFormat {
+    pub fn extension(&self) -> &str {
+        match self {
+            SbomFormat::CycloneDx => "cdx.json",
+            SbomFormat::Spdx => "spdx.json",
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Lockfile {
+    #[serde(default)]
+    pub package: Vec<LockedPackage>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct LockedPackage {
+    pub name: String,
+    pub version: String,
+    pub source: Option<String>,
+    pub checksum: Option<String>,
+}
+
+impl LockedPackage {
+    pub fn purl(&self) -> String {
+        format!("pkg:cargo/{}@{}", self.name, self.version)
+    }
+
+    pub fn is_registry(&self) -> bool {
+        self.source
+            .as_ref()
+            .is_some_and(|source| source.starts_with("registry+"))
+    }
+}
+
+impl Lockfile {
+    pub fn load<P>(path: P) -> Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("failed to read lockfile {}", path.display()))?;
+        let lockfile = toml::from_str(&content)?;
+        Ok(lockfile)
+    }
+}
+
+/// Render every configured SBOM format into the output directory
+pub fn generate(
+    config: &SbomConfig,
+    build_info: &Build,
+    tag: &Tag,
+    base: &Path,
+    output_path: &Path,
+) -> Result<Vec<Asset>> {
+    let lockfile = Lockfile::load(base.join(&config.lockfile))?;
+
+    let mut assets = vec![];
+    for format in &config.formats {
+        let content = match format {
+            SbomFormat::CycloneDx => {
+                serde_json::to_string_pretty(&cyclonedx::Bom::new(build_info, tag, &lockfile))?
+            }
+            SbomFormat::Spdx => {
+                serde_json::to_string_pretty(&spdx::Document::new(build_info, tag, &lockfile)?)?
+            }
+        };
+
+        let name = format!(
+            "{}_{}.{}",
+            build_info.binary,
+            tag.value(),
+            format.extension()
+        );
+        let path = output_path.join(&name);
+        log::info!("writing sbom: {}", path.display());
+        fs::write(&path, content)?;
+
+        assets.push(Asset::new(name, path));
+    }
+
+    Ok(assets)
+}
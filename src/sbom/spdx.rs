@@ -0,0 +1,126 @@
+use super::{LockedPackage, Lockfile};
+use crate::{build::Build, github::tag::Tag};
+use anyhow::Result;
+use serde::Serialize;
+use time::{format_description::well_known::Rfc3339, OffsetDateTime};
+
+const SPDX_VERSION: &str = "SPDX-2.3";
+const NO_ASSERTION: &str = "NOASSERTION";
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Document {
+    pub spdx_version: String,
+    pub data_license: String,
+    #[serde(rename = "SPDXID")]
+    pub spdx_id: String,
+    pub name: String,
+    pub document_namespace: String,
+    pub creation_info: CreationInfo,
+    pub packages: Vec<Package>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CreationInfo {
+    pub created: String,
+    pub creators: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Package {
+    pub name: String,
+    #[serde(rename = "SPDXID")]
+    pub spdx_id: String,
+    pub version_info: String,
+    pub download_location: String,
+    pub external_refs: Vec<ExternalRef>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub checksums: Vec<Checksum>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExternalRef {
+    pub reference_category: String,
+    pub reference_type: String,
+    pub reference_locator: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Checksum {
+    pub algorithm: String,
+    pub checksum_value: String,
+}
+
+impl Document {
+    pub fn new(build_info: &Build, tag: &Tag, lockfile: &Lockfile) -> Result<Self> {
+        let name = format!("{}-{}", build_info.binary, tag.version());
+
+        Ok(Document {
+            spdx_version: SPDX_VERSION.to_string(),
+            data_license: "CC0-1.0".to_string(),
+            spdx_id: "SPDXRef-DOCUMENT".to_string(),
+            document_namespace: format!("https://spdx.org/spdxdocs/{}", name),
+            name,
+            creation_info: CreationInfo {
+                created: OffsetDateTime::now_utc().format(&Rfc3339)?,
+                creators: vec![format!(
+                    "Tool: {}-{}",
+                    env!("CARGO_PKG_NAME"),
+                    env!("CARGO_PKG_VERSION")
+                )],
+            },
+            packages: lockfile.package.iter().map(Package::from).collect(),
+        })
+    }
+}
+
+impl From<&LockedPackage> for Package {
+    fn from(package: &LockedPackage) -> Self {
+        let download_location = if package.is_registry() {
+            format!(
+                "https://crates.io/api/v1/crates/{}/{}/download",
+                package.name, package.version
+            )
+        } else {
+            NO_ASSERTION.to_string()
+        };
+
+        Package {
+            name: package.name.to_owned(),
+            spdx_id: spdx_id(package),
+            version_info: package.version.to_owned(),
+            download_location,
+            external_refs: vec![ExternalRef {
+                reference_category: "PACKAGE-MANAGER".to_string(),
+                reference_type: "purl".to_string(),
+                reference_locator: package.purl(),
+            }],
+            checksums: package
+                .checksum
+                .iter()
+                .map(|checksum| Checksum {
+                    algorithm: "SHA256".to_string(),
+                    checksum_value: checksum.to_owned(),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// SPDX identifiers only allow letters, numbers, `.` and `-`
+fn spdx_id(package: &LockedPackage) -> String {
+    let id: String = format!("{}-{}", package.name, package.version)
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '.' || c == '-' {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect();
+    format!("SPDXRef-Package-{}", id)
+}
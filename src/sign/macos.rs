@@ -0,0 +1,105 @@
+use super::{run, secret};
+use crate::{
+    build::{os::Os, Build},
+    config::{MacosConfig, MacosSignConfig, NotarizeConfig},
+};
+use anyhow::{bail, Result};
+use std::path::Path;
+use tokio::process::Command;
+
+/// Sign, notarize and staple every macOS binary produced by the build
+pub async fn sign(
+    config: &MacosConfig,
+    build_info: &Build,
+    base: &Path,
+    dry_run: bool,
+) -> Result<()> {
+    for (arch, os) in build_info.targets() {
+        if os != Os::AppleDarwin {
+            continue;
+        }
+
+        let binary = build_info.binary_path(base, &arch, &os);
+
+        if let Some(sign) = &config.sign {
+            log::info!("Signing {}", binary.display());
+            run(codesign(sign, &binary), dry_run).await?;
+        }
+
+        if let Some(notarize) = &config.notarize {
+            // notarytool only accepts zip, dmg and pkg uploads
+            let archive = binary.with_extension("zip");
+            let mut ditto = Command::new("ditto");
+            ditto
+                .arg("-c")
+                .arg("-k")
+                .arg("--keepParent")
+                .arg(&binary)
+                .arg(&archive);
+            run(ditto, dry_run).await?;
+
+            log::info!("Notarizing {}", binary.display());
+            run(notarytool(notarize, &archive)?, dry_run).await?;
+
+            if notarize.staple {
+                let mut stapler = Command::new("xcrun");
+                stapler.arg("stapler").arg("staple").arg(&binary);
+                run(stapler, dry_run).await?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn codesign(config: &MacosSignConfig, binary: &Path) -> Command {
+    let mut cmd = Command::new("codesign");
+    cmd.arg("--force")
+        .arg("--timestamp")
+        .arg("--options")
+        .arg("runtime")
+        .arg("--sign")
+        .arg(&config.identity);
+    if let Some(entitlements) = &config.entitlements {
+        cmd.arg("--entitlements").arg(entitlements);
+    }
+    cmd.arg(binary);
+    cmd
+}
+
+fn notarytool(config: &NotarizeConfig, archive: &Path) -> Result<Command> {
+    let mut cmd = Command::new("xcrun");
+    cmd.arg("notarytool")
+        .arg("submit")
+        .arg(archive)
+        .arg("--wait");
+
+    match (&config.key, &config.apple_id) {
+        (Some(key), _) => {
+            let (Some(key_id), Some(issuer)) = (&config.key_id, &config.issuer) else {
+                bail!("key_id and issuer must be configured to notarize with an API key")
+            };
+            cmd.arg("--key")
+                .arg(key)
+                .arg("--key-id")
+                .arg(key_id)
+                .arg("--issuer")
+                .arg(issuer);
+        }
+        (None, Some(apple_id)) => {
+            let (Some(team_id), Some(password_env)) = (&config.team_id, &config.password_env)
+            else {
+                bail!("team_id and password_env must be configured to notarize with an Apple ID")
+            };
+            cmd.arg("--apple-id")
+                .arg(apple_id)
+                .arg("--team-id")
+                .arg(team_id)
+                .arg("--password")
+                .arg(secret(password_env)?);
+        }
+        (None, None) => bail!("either an API key or an Apple ID must be configured to notarize"),
+    }
+
+    Ok(cmd)
+}
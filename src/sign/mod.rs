@@ -0,0 +1,30 @@
+pub mod macos;
+pub mod windows;
+
+use anyhow::{bail, Result};
+use std::env;
+use tokio::process::Command;
+
+/// Run a signing command, or only log it on dry runs
+async fn run(mut cmd: Command, dry_run: bool) -> Result<()> {
+    // only the program is logged, arguments may contain secrets
+    if dry_run {
+        log::info!("Would run: {:?}", cmd.as_std().get_program());
+        return Ok(());
+    }
+
+    let status = cmd.status().await?;
+    if !status.success() {
+        bail!("{:?} exited with {}", cmd.as_std().get_program(), status);
+    }
+
+    Ok(())
+}
+
+/// Read a secret from the environment variable named in the config
+fn secret(name: &str) -> Result<String> {
+    match env::var(name) {
+        Ok(value) => Ok(value),
+        Err(_) => bail!("environment variable {} must be set", name),
+    }
+}
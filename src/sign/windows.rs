@@ -0,0 +1,126 @@
+use super::{run, secret};
+use crate::{
+    build::{os::Os, Build},
+    config::{WindowsSignConfig, WindowsSignTool},
+};
+use anyhow::{bail, Result};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+use tokio::process::Command;
+
+/// Sign every Windows binary produced by the build in place
+pub async fn sign(
+    config: &WindowsSignConfig,
+    build_info: &Build,
+    base: &Path,
+    dry_run: bool,
+) -> Result<()> {
+    for (arch, os) in build_info.targets() {
+        if os != Os::PcWindowsMsvc {
+            continue;
+        }
+
+        let binary = build_info.binary_path(base, &arch, &os);
+        log::info!("Signing {}", binary.display());
+        run(command(config, &binary)?, dry_run).await?;
+
+        // osslsigncode cannot sign in place, replace the binary with the signed copy
+        if matches!(config.tool, WindowsSignTool::Osslsigncode) && !dry_run {
+            fs::rename(signed_path(&binary), &binary)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn command(config: &WindowsSignConfig, binary: &Path) -> Result<Command> {
+    let cmd = match config.tool {
+        WindowsSignTool::Signtool => {
+            let mut cmd = Command::new("signtool");
+            cmd.arg("sign")
+                .arg("/f")
+                .arg(certificate(config)?)
+                .arg("/fd")
+                .arg("SHA256")
+                .arg("/tr")
+                .arg(&config.timestamp_url)
+                .arg("/td")
+                .arg("SHA256");
+            if let Some(password_env) = &config.password_env {
+                cmd.arg("/p").arg(secret(password_env)?);
+            }
+            if let Some(description) = &config.description {
+                cmd.arg("/d").arg(description);
+            }
+            cmd.arg(binary);
+            cmd
+        }
+        WindowsSignTool::Osslsigncode => {
+            let mut cmd = Command::new("osslsigncode");
+            cmd.arg("sign")
+                .arg("-pkcs12")
+                .arg(certificate(config)?)
+                .arg("-h")
+                .arg("sha256")
+                .arg("-ts")
+                .arg(&config.timestamp_url);
+            if let Some(password_env) = &config.password_env {
+                cmd.arg("-pass").arg(secret(password_env)?);
+            }
+            if let Some(description) = &config.description {
+                cmd.arg("-n").arg(description);
+            }
+            cmd.arg("-in")
+                .arg(binary)
+                .arg("-out")
+                .arg(signed_path(binary));
+            cmd
+        }
+        WindowsSignTool::AzureSignTool => {
+            let Some(key_vault) = &config.key_vault else {
+                bail!("key_vault must be configured to sign with azuresigntool")
+            };
+            let mut cmd = Command::new("azuresigntool");
+            cmd.arg("sign")
+                .arg("-kvu")
+                .arg(&key_vault.url)
+                .arg("-kvc")
+                .arg(&key_vault.certificate)
+                .arg("-kvi")
+                .arg(&key_vault.client_id)
+                .arg("-kvt")
+                .arg(&key_vault.tenant_id)
+                .arg("-kvs")
+                .arg(secret(&key_vault.client_secret_env)?)
+                .arg("-fd")
+                .arg("sha256")
+                .arg("-tr")
+                .arg(&config.timestamp_url)
+                .arg("-td")
+                .arg("sha256");
+            if let Some(description) = &config.description {
+                cmd.arg("-d").arg(description);
+            }
+            cmd.arg(binary);
+            cmd
+        }
+    };
+
+    Ok(cmd)
+}
+
+fn signed_path(binary: &Path) -> PathBuf {
+    binary.with_extension("signed.exe")
+}
+
+fn certificate(config: &WindowsSignConfig) -> Result<&str> {
+    match &config.certificate {
+        Some(certificate) => Ok(certificate),
+        None => bail!(
+            "certificate must be configured to sign with {:?}",
+            config.tool
+        ),
+    }
+}
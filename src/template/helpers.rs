@@ -0,0 +1,114 @@
+use handlebars::{handlebars_helper, Handlebars, RenderError, RenderErrorReason};
+use semver::Version;
+use sha2::{Digest, Sha256};
+use std::{fs::File, io};
+use time::{format_description, format_description::well_known::Rfc3339, OffsetDateTime};
+
+/// Register the helpers available to every template
+pub fn register(hb: &mut Handlebars) {
+    handlebars_helper!(eq: |this: str, other: str| this.eq(other));
+
+    handlebars_helper!(upper: |value: str| value.to_uppercase());
+    handlebars_helper!(lower: |value: str| value.to_lowercase());
+    handlebars_helper!(snake_case: |value: str| words(value).join("_"));
+    handlebars_helper!(kebab_case: |value: str| words(value).join("-"));
+    handlebars_helper!(camel_case: |value: str| {
+        let pascal = pascal(value);
+        let mut chars = pascal.chars();
+        chars
+            .next()
+            .map(|first| first.to_lowercase().chain(chars).collect::<String>())
+            .unwrap_or_default()
+    });
+    handlebars_helper!(pascal_case: |value: str| pascal(value));
+
+    handlebars_helper!(semver_major: |version: str| parse_version(version)?.major);
+    handlebars_helper!(semver_minor: |version: str| parse_version(version)?.minor);
+    handlebars_helper!(semver_patch: |version: str| parse_version(version)?.patch);
+    handlebars_helper!(semver_pre: |version: str| parse_version(version)?.pre.to_string());
+
+    handlebars_helper!(now: |*args| {
+        let date = OffsetDateTime::now_utc();
+        match args.first().and_then(|format| format.as_str()) {
+            Some(format) => format_description::parse_borrowed::<2>(format)
+                .map_err(nested)
+                .and_then(|format| date.format(&format).map_err(nested))?,
+            None => date.format(&Rfc3339).map_err(nested)?,
+        }
+    });
+
+    handlebars_helper!(sha256_of: |path: str| {
+        let mut file = File::open(path).map_err(RenderErrorReason::IOError)?;
+        let mut hasher = Sha256::new();
+        io::copy(&mut file, &mut hasher).map_err(RenderErrorReason::IOError)?;
+        hex::encode(hasher.finalize())
+    });
+
+    hb.register_helper("eq", Box::new(eq));
+    hb.register_helper("upper", Box::new(upper));
+    hb.register_helper("lower", Box::new(lower));
+    hb.register_helper("snake_case", Box::new(snake_case));
+    hb.register_helper("kebab_case", Box::new(kebab_case));
+    hb.register_helper("camel_case", Box::new(camel_case));
+    hb.register_helper("pascal_case", Box::new(pascal_case));
+    hb.register_helper("semver_major", Box::new(semver_major));
+    hb.register_helper("semver_minor", Box::new(semver_minor));
+    hb.register_helper("semver_patch", Box::new(semver_patch));
+    hb.register_helper("semver_pre", Box::new(semver_pre));
+    hb.register_helper("now", Box::new(now));
+    hb.register_helper("sha256_of", Box::new(sha256_of));
+}
+
+/// Lowercase words of `value`, split on separators and before capitals starting a word, e.g.
+/// `HTTPServer` is `http` and `server`
+fn words(value: &str) -> Vec<String> {
+    let chars: Vec<char> = value.chars().collect();
+    let mut words = vec![];
+    let mut word = String::new();
+
+    for (i, &c) in chars.iter().enumerate() {
+        if !c.is_alphanumeric() {
+            if !word.is_empty() {
+                words.push(std::mem::take(&mut word));
+            }
+            continue;
+        }
+        if c.is_uppercase() && !word.is_empty() {
+            let previous = chars[i - 1];
+            let next_lower = chars.get(i + 1).is_some_and(|next| next.is_lowercase());
+            if !previous.is_uppercase() || next_lower {
+                words.push(std::mem::take(&mut word));
+            }
+        }
+        word.extend(c.to_lowercase());
+    }
+    if !word.is_empty() {
+        words.push(word);
+    }
+    words
+}
+
+fn pascal(value: &str) -> String {
+    words(value)
+        .iter()
+        .map(|word| {
+            let mut chars = word.chars();
+            chars
+                .next()
+                .map(|first| first.to_uppercase().chain(chars).collect::<String>())
+                .unwrap_or_default()
+        })
+        .collect()
+}
+
+/// Parse a version, with or without the `v` of its tag
+fn parse_version(version: &str) -> Result<Version, RenderError> {
+    Version::parse(version.trim_start_matches('v')).map_err(nested)
+}
+
+fn nested<E>(err: E) -> RenderError
+where
+    E: std::error::Error + Send + Sync + 'static,
+{
+    RenderErrorReason::NestedError(Box::new(err)).into()
+}
@@ -1,21 +1,97 @@
-use crate::build::Build;
-use anyhow::Result;
-use handlebars::{handlebars_helper, Handlebars};
+mod helpers;
+
+use crate::brew::target::Targets;
+use anyhow::{Context, Result};
+use handlebars::Handlebars;
+use once_cell::sync::OnceCell;
+use serde::Serialize;
+use std::{
+    fmt::Display,
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// Directory of the project whose templates override the built-in ones with the same name
+pub const OVERRIDES_DIR: &str = ".rustreleaser/templates";
+
+const BUILT_IN: [(&str, &str); 13] = [
+    ("multi_target", include_str!("./multi_target.hbs")),
+    ("single_target", include_str!("./single_target.hbs")),
+    (
+        "formula_dependencies",
+        include_str!("./formula_dependencies.hbs"),
+    ),
+    (
+        "formula_download_strategy",
+        include_str!("./formula_download_strategy.hbs"),
+    ),
+    ("formula_head", include_str!("./formula_head.hbs")),
+    ("formula_livecheck", include_str!("./formula_livecheck.hbs")),
+    ("formula_bottle", include_str!("./formula_bottle.hbs")),
+    ("formula_install", include_str!("./formula_install.hbs")),
+    ("formula_caveats", include_str!("./formula_caveats.hbs")),
+    ("formula_service", include_str!("./formula_service.hbs")),
+    ("formula_test", include_str!("./formula_test.hbs")),
+    ("changelog", include_str!("./changelog.hbs")),
+    ("changelog_file", include_str!("./changelog_file.hbs")),
+];
+
+static OVERRIDES: OnceCell<PathBuf> = OnceCell::new();
+
+/// Use the template overrides of the project at `base`
+pub fn init(base: &Path) {
+    let _ = OVERRIDES.set(base.join(OVERRIDES_DIR));
+}
 
 pub fn handlebars<'hb>() -> Result<Handlebars<'hb>> {
     let mut hb = Handlebars::new();
 
-    let multi_target = include_str!("./multi_target.hbs");
-    let single_target = include_str!("./single_target.hbs");
+    for (name, template) in BUILT_IN {
+        hb.register_template_string(name, template)?;
+    }
+    register_overrides(&mut hb)?;
 
-    hb.register_template_string("multi_target", multi_target)?;
-    hb.register_template_string("single_target", single_target)?;
+    helpers::register(&mut hb);
 
-    handlebars_helper!(eq: |this: str, other: str| this.eq(other));
+    Ok(hb)
+}
 
-    hb.register_helper("eq", Box::new(eq));
+/// Register the `.hbs` files of the overrides directory by file name, replacing the built-in
+/// templates and partials they are named after
+fn register_overrides(hb: &mut Handlebars) -> Result<()> {
+    let Some(dir) = OVERRIDES.get().filter(|dir| dir.is_dir()) else {
+        return Ok(());
+    };
 
-    Ok(hb)
+    let entries = fs::read_dir(dir).with_context(|| format!("failed to read {}", dir.display()))?;
+    for entry in entries {
+        let path = entry?.path();
+        if path.extension().is_some_and(|extension| extension == "hbs") {
+            let Some(name) = path.file_stem().and_then(|name| name.to_str()) else {
+                continue;
+            };
+            let content = fs::read_to_string(&path)
+                .with_context(|| format!("failed to read template {}", path.display()))?;
+            hb.register_template_string(name, content)
+                .with_context(|| format!("invalid template {}", path.display()))?;
+            log::debug!("Using template {} from {}", name, path.display());
+        }
+    }
+    Ok(())
+}
+
+/// Render an inline template string, without HTML escaping
+pub fn render<T>(template: &str, data: &T) -> Result<String>
+where
+    T: Serialize,
+{
+    let mut hb = Handlebars::new();
+    hb.register_escape_fn(handlebars::no_escape);
+    hb.set_strict_mode(true);
+    helpers::register(&mut hb);
+    register_overrides(&mut hb)?;
+    let rendered = hb.render_template(template, data)?;
+    Ok(rendered)
 }
 
 pub enum Template {
@@ -23,18 +99,18 @@ pub enum Template {
     SingleTarget,
 }
 
-impl ToString for Template {
-    fn to_string(&self) -> String {
+impl Display for Template {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            Template::MultiTarget => "multi_target".to_string(),
-            Template::SingleTarget => "single_target".to_string(),
+            Template::MultiTarget => write!(f, "multi_target"),
+            Template::SingleTarget => write!(f, "single_target"),
         }
     }
 }
 
-impl From<Build> for Template {
-    fn from(build: Build) -> Self {
-        match build.is_multi_target() {
+impl From<&Targets> for Template {
+    fn from(targets: &Targets) -> Self {
+        match targets.is_multi_target() {
             true => Template::MultiTarget,
             false => Template::SingleTarget,
         }
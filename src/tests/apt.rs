@@ -0,0 +1,232 @@
+use crate::{
+    config::{AptConfig, ReleaseConfig},
+    github::tests::MockGithub,
+    upload::apt,
+};
+use flate2::{write::GzEncoder, Compression};
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use std::{fs, path::Path};
+use tempfile::TempDir;
+use wiremock::{
+    matchers::{method, path, query_param},
+    Mock, ResponseTemplate,
+};
+
+const CONTROL: &str = "Package: app\nVersion: 1.0.0\nArchitecture: amd64\nMaintainer: Test <test@example.com>\nDescription: An app\n";
+
+/// Member of an ar archive, padded to an even size
+fn ar_member(name: &str, data: &[u8]) -> Vec<u8> {
+    let mut member = format!(
+        "{:<16}{:<12}{:<6}{:<6}{:<8}{:<10}`\n",
+        name,
+        0,
+        0,
+        0,
+        100644,
+        data.len()
+    )
+    .into_bytes();
+    member.extend_from_slice(data);
+    if data.len() % 2 == 1 {
+        member.push(b'\n');
+    }
+    member
+}
+
+/// Deb package holding `control` in its `control.tar.gz`
+fn deb(control: &str) -> Vec<u8> {
+    let mut tar = tar::Builder::new(GzEncoder::new(vec![], Compression::default()));
+    let mut header = tar::Header::new_gnu();
+    header.set_size(control.len() as u64);
+    header.set_mode(0o644);
+    header.set_cksum();
+    tar.append_data(&mut header, "./control", control.as_bytes())
+        .unwrap();
+    let control = tar.into_inner().unwrap().finish().unwrap();
+
+    let mut deb = b"!<arch>\n".to_vec();
+    deb.extend(ar_member("debian-binary", b"2.0\n"));
+    deb.extend(ar_member("control.tar.gz", &control));
+    deb
+}
+
+fn release_config() -> ReleaseConfig {
+    serde_json::from_value(json!({ "owner": "owner", "repo": "app", "target_branch": "main" }))
+        .unwrap()
+}
+
+/// Project with the deb package of `CONTROL`, returned along with its content
+fn project() -> (TempDir, Vec<u8>) {
+    let project = tempfile::tempdir().unwrap();
+    let package = deb(CONTROL);
+    fs::write(project.path().join("app_1.0.0_amd64.deb"), &package).unwrap();
+    (project, package)
+}
+
+/// Serve `content` as the file at `key` of the published repository, on the `gh-pages` branch
+async fn publish(github: &MockGithub, key: &str, status: u16, content: &str) {
+    Mock::given(method("GET"))
+        .and(path(format!("/repos/owner/app/contents/{}", key)))
+        .and(query_param("ref", "gh-pages"))
+        .respond_with(ResponseTemplate::new(status).set_body_string(content))
+        .mount(&github.server)
+        .await;
+}
+
+fn read(dir: &Path, key: &str) -> String {
+    fs::read_to_string(dir.join(key)).unwrap()
+}
+
+#[tokio::test]
+async fn writes_the_indexes_of_a_package() {
+    let (project, package) = project();
+    let output = tempfile::tempdir().unwrap();
+    let config: AptConfig = serde_json::from_value(json!({ "files": ["*.deb"] })).unwrap();
+
+    let objects = apt::build(
+        &config,
+        &release_config(),
+        project.path(),
+        output.path(),
+        false,
+    )
+    .await
+    .unwrap();
+
+    let keys = objects
+        .iter()
+        .map(|object| object.key.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        keys,
+        [
+            "pool/main/a/app/app_1.0.0_amd64.deb",
+            "dists/stable/main/binary-amd64/Packages",
+            "dists/stable/main/binary-amd64/Packages.gz",
+            "dists/stable/Release",
+        ]
+    );
+
+    let packages = read(output.path(), "dists/stable/main/binary-amd64/Packages");
+    assert_eq!(
+        packages,
+        format!(
+            "{}Filename: pool/main/a/app/app_1.0.0_amd64.deb\nSize: {}\nSHA256: {}\n",
+            CONTROL,
+            package.len(),
+            hex::encode(Sha256::digest(&package))
+        )
+    );
+
+    let release = read(output.path(), "dists/stable/Release");
+    for line in [
+        "Origin: owner",
+        "Label: owner",
+        "Suite: stable",
+        "Codename: stable",
+        "Architectures: amd64",
+        "Components: main",
+        &format!(
+            " {} {} main/binary-amd64/Packages",
+            hex::encode(Sha256::digest(&packages)),
+            packages.len()
+        ),
+    ] {
+        assert!(
+            release.lines().any(|candidate| candidate == line),
+            "{} in\n{}",
+            line,
+            release
+        );
+    }
+    assert!(release.contains("main/binary-amd64/Packages.gz"));
+}
+
+#[tokio::test]
+async fn keeps_the_published_packages() {
+    let github = MockGithub::start().await;
+    let (project, package) = project();
+    let output = tempfile::tempdir().unwrap();
+    publish(
+        &github,
+        "dists/stable/Release",
+        200,
+        "Suite: stable\nArchitectures: amd64 arm64\nComponents: main\n",
+    )
+    .await;
+    publish(
+        &github,
+        "dists/stable/main/binary-amd64/Packages",
+        200,
+        "Package: app\nVersion: 0.9.0\nArchitecture: amd64\nFilename: pool/main/a/app/app_0.9.0_amd64.deb\n\n\
+         Package: app\nVersion: 1.0.0\nArchitecture: amd64\nFilename: pool/main/a/app/app_1.0.0_amd64.deb\nSize: 1\n",
+    )
+    .await;
+    publish(
+        &github,
+        "dists/stable/main/binary-arm64/Packages",
+        200,
+        "Package: app\nVersion: 0.9.0\nArchitecture: arm64\nFilename: pool/main/a/app/app_0.9.0_arm64.deb\n",
+    )
+    .await;
+    let config: AptConfig =
+        serde_json::from_value(json!({ "files": ["*.deb"], "branch": "gh-pages" })).unwrap();
+
+    github
+        .run(apt::build(
+            &config,
+            &release_config(),
+            project.path(),
+            output.path(),
+            false,
+        ))
+        .await
+        .unwrap();
+
+    // the republished 1.0.0 replaces the published one
+    assert_eq!(
+        read(output.path(), "dists/stable/main/binary-amd64/Packages"),
+        format!(
+            "Package: app\nVersion: 0.9.0\nArchitecture: amd64\nFilename: pool/main/a/app/app_0.9.0_amd64.deb\n\n\
+             {}Filename: pool/main/a/app/app_1.0.0_amd64.deb\nSize: {}\nSHA256: {}\n",
+            CONTROL,
+            package.len(),
+            hex::encode(Sha256::digest(&package))
+        )
+    );
+    assert_eq!(
+        read(output.path(), "dists/stable/main/binary-arm64/Packages"),
+        "Package: app\nVersion: 0.9.0\nArchitecture: arm64\nFilename: pool/main/a/app/app_0.9.0_arm64.deb\n"
+    );
+    let release = read(output.path(), "dists/stable/Release");
+    assert!(
+        release.contains("\nArchitectures: amd64 arm64\n"),
+        "{}",
+        release
+    );
+}
+
+#[tokio::test]
+async fn fails_when_the_published_repository_cannot_be_read() {
+    let github = MockGithub::start().await;
+    let (project, _) = project();
+    let output = tempfile::tempdir().unwrap();
+    publish(&github, "dists/stable/Release", 500, "").await;
+    let config: AptConfig =
+        serde_json::from_value(json!({ "files": ["*.deb"], "branch": "gh-pages" })).unwrap();
+
+    let err = github
+        .run(apt::build(
+            &config,
+            &release_config(),
+            project.path(),
+            output.path(),
+            false,
+        ))
+        .await
+        .unwrap_err();
+
+    assert!(format!("{:#}", err).contains("500"), "{:#}", err);
+    assert!(!output.path().join("dists/stable/Release").exists());
+}
@@ -0,0 +1,62 @@
+use crate::http::{cache, HttpClient};
+use reqwest::header::AUTHORIZATION;
+use wiremock::{
+    matchers::{header, method, path},
+    Mock, MockServer, ResponseTemplate,
+};
+
+#[tokio::test]
+async fn revalidates_the_responses_of_each_credential() {
+    let server = MockServer::start().await;
+    let dir = tempfile::tempdir().unwrap();
+    Mock::given(method("GET"))
+        .and(path("/data"))
+        .and(header("if-none-match", "\"v1\""))
+        .respond_with(ResponseTemplate::new(304))
+        .with_priority(1)
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/data"))
+        .and(header(AUTHORIZATION.as_str(), "Bearer one"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("etag", "\"v1\"")
+                .insert_header("link", "<next>; rel=\"next\"")
+                .set_body_string("data of one"),
+        )
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/data"))
+        .and(header(AUTHORIZATION.as_str(), "Bearer two"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("data of two"))
+        .expect(1)
+        .mount(&server)
+        .await;
+    let url = format!("{}/data", server.uri());
+    let get = |token: &'static str| {
+        cache::send_in(HttpClient::new().get(&url).bearer_auth(token), dir.path())
+    };
+
+    get("one").await.unwrap();
+    let response = get("one").await.unwrap();
+
+    // the 304 is answered with the cached response, as it was received
+    assert_eq!(response.status(), 200);
+    assert_eq!(response.url().as_str(), url);
+    assert_eq!(response.headers()["link"], "<next>; rel=\"next\"");
+    assert_eq!(response.text().await.unwrap(), "data of one");
+
+    // not revalidated with the ETag cached for another token
+    let response = get("two").await.unwrap();
+    assert_eq!(response.text().await.unwrap(), "data of two");
+
+    #[cfg(unix)]
+    for entry in std::fs::read_dir(dir.path()).unwrap() {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = entry.unwrap().metadata().unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+}
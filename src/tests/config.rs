@@ -0,0 +1,138 @@
+use crate::config::ReleaserConfig;
+use std::{env, fs};
+use tempfile::TempDir;
+use tokio::sync::Mutex;
+
+const CONFIG: &str = r#"
+[build]
+binary = "app"
+
+[release]
+owner = "owner"
+repo = "app"
+target_branch = "main"
+"#;
+
+fn project(config: &str) -> TempDir {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("releaser.toml"), config).unwrap();
+    dir
+}
+
+/// Held while loading, so the tests don't see the variables of each other
+static ENV: Mutex<()> = Mutex::const_new(());
+
+async fn load(project: &TempDir) -> anyhow::Result<ReleaserConfig> {
+    load_with(project, &[]).await
+}
+
+/// Load the config of `project` with the environment variables `vars`, removed afterwards
+async fn load_with(project: &TempDir, vars: &[(&str, &str)]) -> anyhow::Result<ReleaserConfig> {
+    let _env = ENV.lock().await;
+    for (name, value) in vars {
+        env::set_var(name, value);
+    }
+
+    let path = project.path().join("releaser.toml");
+    let config = ReleaserConfig::load(project.path(), Some(path.display().to_string()), None).await;
+
+    for (name, _) in vars {
+        env::remove_var(name);
+    }
+    config
+}
+
+#[tokio::test]
+async fn leaves_the_cli_variables_to_the_cli() {
+    let project = project(CONFIG);
+    let vars = [
+        ("RELEASER_TAG", "v0.1.0"),
+        ("RELEASER__RELEASE__DRAFT", "true"),
+    ];
+
+    let config = load_with(&project, &vars).await.unwrap();
+
+    assert_eq!(config.tag.prefix, "v");
+    assert!(config.release.draft);
+}
+
+#[tokio::test]
+async fn interpolates_the_environment_in_the_values() {
+    let project = project(
+        r#"
+# ${RELEASER_TEST_UNSET} in a comment is left alone
+[build]
+binary = "${RELEASER_TEST_BINARY}"
+
+[release]
+owner = "${RELEASER_TEST_OWNER:-owner}"
+repo = "app"
+target_branch = "main"
+draft = "${RELEASER_TEST_DRAFT:-true}"
+body = "$${NOT_INTERPOLATED}"
+"#,
+    );
+    let vars = [(
+        "RELEASER_TEST_BINARY",
+        "app\" # not a comment\nname = \"quoted",
+    )];
+
+    let config = load_with(&project, &vars).await.unwrap();
+
+    assert_eq!(
+        config.build.binary,
+        "app\" # not a comment\nname = \"quoted"
+    );
+    assert_eq!(config.release.owner, "owner");
+    assert!(config.release.draft);
+    assert_eq!(config.release.body.as_deref(), Some("${NOT_INTERPOLATED}"));
+}
+
+#[tokio::test]
+async fn fails_on_a_missing_variable() {
+    let project = project(&CONFIG.replace("\"owner\"", "\"${RELEASER_TEST_MISSING}\""));
+
+    let err = load(&project).await.unwrap_err();
+
+    assert!(
+        format!("{:#}", err).contains("environment variable RELEASER_TEST_MISSING is not set"),
+        "{:#}",
+        err
+    );
+}
+
+#[tokio::test]
+async fn reads_the_storages_apart_from_the_release_uploads() {
+    let project = project(&format!(
+        "{}{}",
+        CONFIG,
+        r#"
+[release.upload]
+retries = 1
+
+[[storage.s3]]
+bucket = "releases"
+"#
+    ));
+
+    let config = load(&project).await.unwrap();
+
+    assert_eq!(config.release.upload.retries, 1);
+    assert_eq!(config.storage.unwrap().s3[0].bucket, "releases");
+}
+
+#[tokio::test]
+async fn leaves_the_hook_variables_to_the_shell() {
+    let project = project(&format!(
+        "{}{}",
+        CONFIG,
+        r#"
+[hooks.after]
+release = ["echo ${VERSION}"]
+"#
+    ));
+
+    let config = load(&project).await.unwrap();
+
+    assert_eq!(config.hooks.after.release, ["echo ${VERSION}"]);
+}
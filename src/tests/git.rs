@@ -0,0 +1,88 @@
+use crate::{git, github::tag::Tag};
+use git2::{Oid, Repository, Signature};
+use std::{fs, path::PathBuf};
+use tempfile::TempDir;
+
+/// Commit `path` of the repository in `dir`, changed with `message` as its content
+fn commit(repo: &Repository, dir: &TempDir, path: &str, message: &str) -> Oid {
+    let file = dir.path().join(path);
+    fs::create_dir_all(file.parent().unwrap()).unwrap();
+    fs::write(&file, message).unwrap();
+
+    let mut index = repo.index().unwrap();
+    index.add_path(path.as_ref()).unwrap();
+    index.write().unwrap();
+    let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+    let parent = repo.head().ok().map(|head| head.peel_to_commit().unwrap());
+    let signature = Signature::now("Test", "test@example.com").unwrap();
+    repo.commit(
+        Some("HEAD"),
+        &signature,
+        &signature,
+        message,
+        &tree,
+        &parent.iter().collect::<Vec<_>>(),
+    )
+    .unwrap()
+}
+
+fn tag(repo: &Repository, name: &str, oid: Oid) {
+    repo.tag_lightweight(name, &repo.find_object(oid, None).unwrap(), false)
+        .unwrap();
+}
+
+/// Workspace whose `cli` crate is tagged `cli-v*`, next to the `v*` tags of the project
+fn workspace() -> TempDir {
+    let dir = tempfile::tempdir().unwrap();
+    let repo = Repository::init(dir.path()).unwrap();
+
+    let init = commit(&repo, &dir, "README.md", "init");
+    tag(&repo, "v2.0.0", init);
+    let cli = commit(&repo, &dir, "cli/src/main.rs", "add the cli");
+    tag(&repo, "cli-v1.0.0", cli);
+    commit(&repo, &dir, "lib/src/lib.rs", "change the lib");
+    let fix = commit(&repo, &dir, "cli/src/main.rs", "fix the cli");
+    tag(&repo, "cli-v1.1.0", fix);
+    tag(&repo, "v3.0.0", fix);
+
+    dir
+}
+
+fn messages(commits: Vec<git::Commit>) -> Vec<String> {
+    commits.into_iter().map(|commit| commit.message).collect()
+}
+
+#[test]
+fn selects_the_tags_of_the_scope() {
+    let dir = workspace();
+    let base = PathBuf::from(dir.path());
+
+    let tag = git::get_current_tag(&base, "cli-v").unwrap();
+    assert_eq!(tag.value(), "cli-v1.1.0");
+    assert_eq!(tag.version(), "1.1.0");
+
+    let previous = git::get_previous_tag(&base, &tag).unwrap().unwrap();
+    assert_eq!(previous.value(), "cli-v1.0.0");
+
+    let tag = git::get_current_tag(&base, "v").unwrap();
+    assert_eq!(tag.value(), "v3.0.0");
+    let previous = git::get_previous_tag(&base, &tag).unwrap().unwrap();
+    assert_eq!(previous.value(), "v2.0.0");
+}
+
+#[test]
+fn leaves_out_the_commits_outside_the_paths() {
+    let dir = workspace();
+    let base = PathBuf::from(dir.path());
+    let from = Tag::with_prefix("cli-v1.0.0", "cli-v");
+    let to = Tag::with_prefix("cli-v1.1.0", "cli-v");
+
+    let commits = git::commits_between(&base, Some(&from), &to, &["cli".to_owned()]).unwrap();
+    assert_eq!(messages(commits), ["fix the cli"]);
+
+    let commits = git::commits_between(&base, Some(&from), &to, &[]).unwrap();
+    assert_eq!(messages(commits), ["fix the cli", "change the lib"]);
+
+    let commits = git::commits_between(&base, None, &to, &["lib".to_owned()]).unwrap();
+    assert_eq!(messages(commits), ["change the lib"]);
+}
@@ -0,0 +1,7 @@
+//! Tests of the flows that run locally, from the config to the git history
+
+mod apt;
+mod cache;
+mod config;
+mod git;
+mod yum;
@@ -0,0 +1,256 @@
+use crate::{
+    config::{ReleaseConfig, YumConfig},
+    github::tests::MockGithub,
+    upload::yum,
+};
+use flate2::read::GzDecoder;
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use std::{fs, io::Read, path::Path};
+use wiremock::{
+    matchers::{method, path, query_param},
+    Mock, ResponseTemplate,
+};
+
+const STRING: u32 = 6;
+const STRING_ARRAY: u32 = 8;
+const I18N_STRING: u32 = 9;
+const INT16: u32 = 3;
+const INT32: u32 = 4;
+
+/// Entry of an rpm header: tag, type, count and data
+type Entry = (u32, u32, u32, Vec<u8>);
+
+fn strings(tag: u32, kind: u32, values: &[&str]) -> Entry {
+    let data = values
+        .iter()
+        .flat_map(|value| value.bytes().chain([0]))
+        .collect();
+    (tag, kind, values.len() as u32, data)
+}
+
+fn header(entries: &[Entry]) -> Vec<u8> {
+    let mut index = vec![];
+    let mut store: Vec<u8> = vec![];
+    for (tag, kind, count, data) in entries {
+        for value in [*tag, *kind, store.len() as u32, *count] {
+            index.extend(value.to_be_bytes());
+        }
+        store.extend(data);
+    }
+    let mut header = vec![0x8e, 0xad, 0xe8, 0x01, 0, 0, 0, 0];
+    header.extend((entries.len() as u32).to_be_bytes());
+    header.extend((store.len() as u32).to_be_bytes());
+    header.extend(index);
+    header.extend(store);
+    header
+}
+
+/// Rpm package of `app` installing `/usr/bin/app`
+fn rpm() -> Vec<u8> {
+    let mut rpm = vec![0xed, 0xab, 0xee, 0xdb];
+    rpm.resize(96, 0);
+    // an empty signature header, 16 bytes long so the main header is already aligned
+    rpm.extend(header(&[]));
+    rpm.extend(header(&[
+        strings(1000, STRING, &["app"]),
+        strings(1001, STRING, &["1.0.0"]),
+        strings(1002, STRING, &["1"]),
+        strings(1004, I18N_STRING, &["An <app>"]),
+        strings(1022, STRING, &["x86_64"]),
+        (1030, INT16, 1, 0o100755u16.to_be_bytes().to_vec()),
+        strings(
+            1049,
+            STRING_ARRAY,
+            &["libc.so.6", "rpmlib(CompressedFileNames)"],
+        ),
+        (1116, INT32, 1, 0u32.to_be_bytes().to_vec()),
+        strings(1117, STRING_ARRAY, &["app"]),
+        strings(1118, STRING_ARRAY, &["/usr/bin/"]),
+    ]));
+    rpm
+}
+
+fn release_config() -> ReleaseConfig {
+    serde_json::from_value(json!({ "owner": "owner", "repo": "app", "target_branch": "main" }))
+        .unwrap()
+}
+
+fn gunzip(path: &Path) -> String {
+    let mut xml = String::new();
+    GzDecoder::new(fs::File::open(path).unwrap())
+        .read_to_string(&mut xml)
+        .unwrap();
+    xml
+}
+
+#[tokio::test]
+async fn writes_the_metadata_of_a_package() {
+    let project = tempfile::tempdir().unwrap();
+    let output = tempfile::tempdir().unwrap();
+    let package = rpm();
+    fs::write(project.path().join("app-1.0.0-1.x86_64.rpm"), &package).unwrap();
+    let config: YumConfig = serde_json::from_value(json!({ "files": ["*.rpm"] })).unwrap();
+
+    let objects = yum::build(
+        &config,
+        &release_config(),
+        project.path(),
+        output.path(),
+        false,
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(objects[0].key, "Packages/a/app-1.0.0-1.x86_64.rpm");
+    assert_eq!(objects.last().unwrap().key, "repodata/repomd.xml");
+    let repomd = fs::read_to_string(output.path().join("repodata/repomd.xml")).unwrap();
+    let pkgid = hex::encode(Sha256::digest(&package));
+
+    for (kind, expected) in [
+        (
+            "primary",
+            vec![
+                r#"<metadata xmlns="http://linux.duke.edu/metadata/common" xmlns:rpm="http://linux.duke.edu/metadata/rpm" packages="1">"#.to_owned(),
+                "<name>app</name>".to_owned(),
+                "<arch>x86_64</arch>".to_owned(),
+                r#"<version epoch="0" ver="1.0.0" rel="1"/>"#.to_owned(),
+                format!(r#"<checksum type="sha256" pkgid="YES">{}</checksum>"#, pkgid),
+                "<summary>An &lt;app&gt;</summary>".to_owned(),
+                r#"<location href="Packages/a/app-1.0.0-1.x86_64.rpm"/>"#.to_owned(),
+                r#"<rpm:entry name="libc.so.6"/>"#.to_owned(),
+                "<file>/usr/bin/app</file>".to_owned(),
+            ],
+        ),
+        (
+            "filelists",
+            vec![
+                format!(r#"<package pkgid="{}" name="app" arch="x86_64">"#, pkgid),
+                "<file>/usr/bin/app</file>".to_owned(),
+            ],
+        ),
+        (
+            "other",
+            vec![format!(
+                r#"<package pkgid="{}" name="app" arch="x86_64">"#,
+                pkgid
+            )],
+        ),
+    ] {
+        let object = objects
+            .iter()
+            .find(|object| object.key.ends_with(&format!("-{}.xml.gz", kind)))
+            .unwrap();
+        assert!(
+            repomd.contains(&format!(
+                "<data type=\"{}\">\n    <checksum type=\"sha256\">{}</checksum>",
+                kind,
+                hex::encode(Sha256::digest(fs::read(&object.path).unwrap()))
+            )),
+            "{}",
+            repomd
+        );
+        assert!(repomd.contains(&format!("<location href=\"{}\"/>", object.key)));
+
+        let xml = gunzip(&object.path);
+        for expected in expected {
+            assert!(xml.contains(&expected), "{} in\n{}", expected, xml);
+        }
+        assert!(!xml.contains("rpmlib("), "{}", xml);
+    }
+}
+
+/// Primary block of a published package
+fn primary(pkgid: &str, file_name: &str) -> String {
+    format!(
+        "<package type=\"rpm\">\n  <name>app</name>\n  <checksum type=\"sha256\" pkgid=\"YES\">{}</checksum>\n  <location href=\"Packages/a/{}\"/>\n</package>",
+        pkgid, file_name
+    )
+}
+
+/// Filelists or other block of a published package
+fn listed(pkgid: &str) -> String {
+    format!(
+        "<package pkgid=\"{}\" name=\"app\" arch=\"x86_64\">\n</package>",
+        pkgid
+    )
+}
+
+#[tokio::test]
+async fn keeps_the_published_packages() {
+    let github = MockGithub::start().await;
+    let project = tempfile::tempdir().unwrap();
+    let output = tempfile::tempdir().unwrap();
+    let package = rpm();
+    fs::write(project.path().join("app-1.0.0-1.x86_64.rpm"), &package).unwrap();
+    let pkgid = hex::encode(Sha256::digest(&package));
+
+    // 0.9.0 is kept, the previous upload of 1.0.0 is replaced, and the blocks of a package
+    // missing from primary are dropped
+    let published = [
+        (
+            "repodata/repomd.xml",
+            "<repomd>\n  <data type=\"primary\">\n    <location href=\"repodata/primary.xml\"/>\n  </data>\n  <data type=\"filelists\">\n    <location href=\"repodata/filelists.xml\"/>\n  </data>\n  <data type=\"other\">\n    <location href=\"repodata/other.xml\"/>\n  </data>\n</repomd>".to_owned(),
+        ),
+        (
+            "repodata/primary.xml",
+            format!(
+                "<metadata packages=\"2\">\n{}\n{}\n</metadata>",
+                primary("old-id", "app-0.9.0-1.x86_64.rpm"),
+                primary("stale-id", "app-1.0.0-1.x86_64.rpm")
+            ),
+        ),
+        (
+            "repodata/filelists.xml",
+            format!(
+                "<filelists>\n{}\n{}\n{}\n</filelists>",
+                listed("old-id"),
+                listed("stale-id"),
+                listed("orphan-id")
+            ),
+        ),
+        (
+            "repodata/other.xml",
+            format!(
+                "<otherdata>\n{}\n{}\n</otherdata>",
+                listed("old-id"),
+                listed("stale-id")
+            ),
+        ),
+    ];
+    for (key, content) in published {
+        Mock::given(method("GET"))
+            .and(path(format!("/repos/owner/app/contents/{}", key)))
+            .and(query_param("ref", "gh-pages"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(content))
+            .mount(&github.server)
+            .await;
+    }
+    let config: YumConfig =
+        serde_json::from_value(json!({ "files": ["*.rpm"], "branch": "gh-pages" })).unwrap();
+
+    let objects = github
+        .run(yum::build(
+            &config,
+            &release_config(),
+            project.path(),
+            output.path(),
+            false,
+        ))
+        .await
+        .unwrap();
+
+    for kind in ["primary", "filelists", "other"] {
+        let object = objects
+            .iter()
+            .find(|object| object.key.ends_with(&format!("-{}.xml.gz", kind)))
+            .unwrap();
+        let xml = gunzip(&object.path);
+
+        assert!(xml.contains("packages=\"2\""), "{}", xml);
+        assert!(xml.contains("old-id"), "{}", xml);
+        assert!(xml.contains(&pkgid), "{}", xml);
+        assert!(!xml.contains("stale-id"), "{}", xml);
+        assert!(!xml.contains("orphan-id"), "{}", xml);
+    }
+}
@@ -0,0 +1,273 @@
+use super::{repository, Object};
+use crate::{
+    archive,
+    config::{AptConfig, ReleaseConfig},
+};
+use anyhow::{bail, Context, Result};
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use itertools::Itertools;
+use sha2::{Digest, Sha256};
+use std::{
+    fs,
+    io::{Read, Write},
+    path::Path,
+};
+use time::{format_description::FormatItem, macros::format_description, OffsetDateTime};
+use xz2::read::XzDecoder;
+
+/// Directory of the output the repository is written to
+pub const DIRECTORY: &str = "apt";
+const AR_MAGIC: &[u8] = b"!<arch>\n";
+const AR_HEADER_LENGTH: usize = 60;
+/// RFC 2822 date of the Release file
+const DATE_FORMAT: &[FormatItem] = format_description!(
+    "[weekday repr:short], [day] [month repr:short] [year] [hour]:[minute]:[second] UTC"
+);
+
+/// Paragraph of a Packages index
+#[derive(Debug, Clone)]
+struct Stanza {
+    package: String,
+    version: String,
+    architecture: String,
+    text: String,
+}
+
+impl Stanza {
+    fn parse(text: &str) -> Option<Stanza> {
+        Some(Stanza {
+            package: field(text, "Package")?.to_owned(),
+            version: field(text, "Version")?.to_owned(),
+            architecture: field(text, "Architecture")?.to_owned(),
+            text: text.trim().to_owned(),
+        })
+    }
+
+    fn same_package(&self, other: &Stanza) -> bool {
+        self.package == other.package
+            && self.version == other.version
+            && self.architecture == other.architecture
+    }
+}
+
+/// Write the repository to `dir`: the packages of `config.files` in the pool, and the indexes
+/// listing them along with the packages already published
+pub async fn build(
+    config: &AptConfig,
+    release_info: &ReleaseConfig,
+    base: &Path,
+    dir: &Path,
+    sign: bool,
+) -> Result<Vec<Object>> {
+    if dir.exists() {
+        fs::remove_dir_all(dir)?;
+    }
+    let dist = format!("dists/{}", config.suite);
+    let mut objects = vec![];
+
+    let mut packages = vec![];
+    for entry in archive::resolve_files(base, &config.files)? {
+        let control = control(&entry.path)
+            .with_context(|| format!("failed to read {}", entry.path.display()))?;
+        let stanza = Stanza::parse(&control).with_context(|| {
+            format!(
+                "{} has no Package, Version or Architecture",
+                entry.path.display()
+            )
+        })?;
+        let file_name = entry
+            .path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or(entry.name);
+        let source = field(&control, "Source")
+            .and_then(|source| source.split_whitespace().next())
+            .unwrap_or(&stanza.package);
+        let key = pool_path(&config.component, source, &file_name);
+
+        let content = fs::read(&entry.path)?;
+        let text = format!(
+            "{}\nFilename: {}\nSize: {}\nSHA256: {}",
+            stanza.text,
+            key,
+            content.len(),
+            hex::encode(Sha256::digest(&content))
+        );
+        objects.push(repository::write(dir, &key, &content)?);
+        packages.push(Stanza { text, ..stanza });
+    }
+    if packages.is_empty() {
+        bail!("no deb packages matched {}", config.files.join(", "));
+    }
+
+    // the packages of the previous releases, listed in every index of the Release file
+    let mut published: Vec<Stanza> = vec![];
+    let mut architectures = vec![];
+    let release = repository::existing(
+        &config.repository,
+        release_info,
+        &format!("{}/Release", dist),
+    )
+    .await?;
+    if let Some(release) = release.map(|release| String::from_utf8_lossy(&release).into_owned()) {
+        architectures.extend(
+            field(&release, "Architectures")
+                .unwrap_or_default()
+                .split_whitespace()
+                .map(str::to_owned),
+        );
+        for architecture in &architectures {
+            let path = format!(
+                "{}/{}/binary-{}/Packages",
+                dist, config.component, architecture
+            );
+            let Some(index) = repository::existing(&config.repository, release_info, &path).await?
+            else {
+                continue;
+            };
+            let index = String::from_utf8_lossy(&index);
+            for stanza in index.split("\n\n").filter_map(Stanza::parse) {
+                if !published.iter().any(|other| other.same_package(&stanza)) {
+                    published.push(stanza);
+                }
+            }
+        }
+    }
+    published.retain(|stanza| !packages.iter().any(|new| new.same_package(stanza)));
+    published.extend(packages);
+
+    architectures.extend(
+        published
+            .iter()
+            .map(|stanza| stanza.architecture.to_owned())
+            .filter(|architecture| architecture != "all"),
+    );
+    let mut architectures = architectures.into_iter().unique().sorted().collect_vec();
+    if architectures.is_empty() {
+        architectures.push("all".to_owned());
+    }
+
+    let mut checksums = vec![];
+    for architecture in &architectures {
+        let index = published
+            .iter()
+            .filter(|stanza| stanza.architecture == *architecture || stanza.architecture == "all")
+            .map(|stanza| format!("{}\n", stanza.text))
+            .join("\n");
+        let mut gzip = GzEncoder::new(vec![], Compression::best());
+        gzip.write_all(index.as_bytes())?;
+        let gzip = gzip.finish()?;
+
+        for (name, content) in [("Packages", index.into_bytes()), ("Packages.gz", gzip)] {
+            let path = format!("{}/binary-{}/{}", config.component, architecture, name);
+            checksums.push(format!(
+                " {} {} {}",
+                hex::encode(Sha256::digest(&content)),
+                content.len(),
+                path
+            ));
+            let key = format!("{}/{}", dist, path);
+            objects.push(repository::write(dir, &key, &content)?);
+        }
+    }
+
+    let origin = config.origin.as_deref().unwrap_or(&release_info.owner);
+    let release = format!(
+        "Origin: {}\nLabel: {}\nSuite: {}\nCodename: {}\nDate: {}\nArchitectures: {}\nComponents: {}\nSHA256:\n{}\n",
+        origin,
+        config.label.as_deref().unwrap_or(origin),
+        config.suite,
+        config.suite,
+        OffsetDateTime::now_utc().format(DATE_FORMAT)?,
+        architectures.join(" "),
+        config.component,
+        checksums.join("\n")
+    );
+    let key = format!("{}/Release", dist);
+    objects.push(repository::write(dir, &key, release.as_bytes())?);
+
+    if sign {
+        log::info!("Signing {}/Release", dist);
+        let release = dir.join(&dist).join("Release");
+        for (name, args) in [
+            ("InRelease", ["--clearsign"].as_slice()),
+            ("Release.gpg", ["--armor", "--detach-sign"].as_slice()),
+        ] {
+            let output = dir.join(&dist).join(name);
+            repository::sign(&config.repository, args, &output, &release).await?;
+            objects.push(Object {
+                key: format!("{}/{}", dist, name),
+                path: output,
+            });
+        }
+        objects.extend(repository::export_key(&config.repository, dir).await?);
+    }
+
+    Ok(objects)
+}
+
+/// Control file of a deb package, an ar archive holding a `control.tar` archive
+fn control(path: &Path) -> Result<String> {
+    let content = fs::read(path)?;
+    let mut rest = content
+        .strip_prefix(AR_MAGIC)
+        .context("not a deb package")?;
+
+    while rest.len() >= AR_HEADER_LENGTH {
+        let (header, data) = rest.split_at(AR_HEADER_LENGTH);
+        let name = String::from_utf8_lossy(&header[..16]);
+        let name = name.trim_end().trim_end_matches('/');
+        let size = std::str::from_utf8(&header[48..58])?
+            .trim()
+            .parse::<usize>()
+            .context("invalid ar header")?;
+        let member = data.get(..size).context("truncated ar archive")?;
+
+        if let Some(compression) = name.strip_prefix("control.tar") {
+            let reader: Box<dyn Read> = match compression {
+                "" => Box::new(member),
+                ".gz" => Box::new(GzDecoder::new(member)),
+                ".xz" => Box::new(XzDecoder::new(member)),
+                ".zst" => Box::new(zstd::Decoder::new(member)?),
+                _ => bail!("unsupported control archive {}", name),
+            };
+            let mut archive = tar::Archive::new(reader);
+            for entry in archive.entries()? {
+                let mut entry = entry?;
+                if entry
+                    .path()?
+                    .as_os_str()
+                    .to_string_lossy()
+                    .trim_start_matches("./")
+                    == "control"
+                {
+                    let mut control = String::new();
+                    entry.read_to_string(&mut control)?;
+                    return Ok(control);
+                }
+            }
+            bail!("no control file in {}", name);
+        }
+
+        // members are aligned on even offsets
+        rest = data.get(size + size % 2..).unwrap_or_default();
+    }
+    bail!("no control archive")
+}
+
+/// Value of the first line of `name` in a control paragraph
+fn field<'a>(text: &'a str, name: &str) -> Option<&'a str> {
+    text.lines().find_map(|line| {
+        line.strip_prefix(name)
+            .and_then(|rest| rest.strip_prefix(':'))
+            .map(str::trim)
+    })
+}
+
+/// Pool path of a package, grouped by source like reprepro and aptly, e.g.
+/// `pool/main/libf/libfoo/libfoo_1.0_amd64.deb`
+fn pool_path(component: &str, source: &str, file_name: &str) -> String {
+    let length = if source.starts_with("lib") { 4 } else { 1 };
+    let group = source.get(..length).unwrap_or(source);
+    format!("pool/{}/{}/{}/{}", component, group, source, file_name)
+}
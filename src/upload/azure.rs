@@ -0,0 +1,125 @@
+use super::Object;
+use crate::http::{self, progress, HttpClient};
+use anyhow::{bail, Context, Result};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use hmac::{Hmac, Mac};
+use reqwest::{
+    header::{AUTHORIZATION, CONTENT_LENGTH, CONTENT_TYPE},
+    Url,
+};
+use sha2::Sha256;
+use std::env;
+use time::{format_description::FormatItem, macros::format_description, OffsetDateTime};
+use tokio::fs::File;
+
+const SAS_TOKEN_VAR: &str = "AZURE_STORAGE_SAS_TOKEN";
+const ACCOUNT_KEY_VAR: &str = "AZURE_STORAGE_KEY";
+const API_VERSION: &str = "2021-08-06";
+/// RFC 1123 date of the `x-ms-date` header
+const DATE_FORMAT: &[FormatItem] = format_description!(
+    "[weekday repr:short], [day] [month repr:short] [year] [hour]:[minute]:[second] GMT"
+);
+
+enum Auth {
+    Sas(String),
+    SharedKey(Vec<u8>),
+}
+
+/// Upload `objects` as block blobs of the container, authorized by a SAS token or the account key
+pub async fn upload(account: &str, container: &str, objects: &[Object]) -> Result<()> {
+    let auth = if let Ok(token) = env::var(SAS_TOKEN_VAR) {
+        Auth::Sas(token.trim_start_matches('?').to_owned())
+    } else if let Ok(key) = env::var(ACCOUNT_KEY_VAR) {
+        let key = STANDARD
+            .decode(key.trim())
+            .with_context(|| format!("{} is not base64", ACCOUNT_KEY_VAR))?;
+        Auth::SharedKey(key)
+    } else {
+        bail!(
+            "set {} or {} to upload to Azure",
+            SAS_TOKEN_VAR,
+            ACCOUNT_KEY_VAR
+        );
+    };
+
+    for object in objects {
+        log::debug!("Uploading {} to {}", object.path.display(), object.key);
+        let file = File::open(&object.path)
+            .await
+            .with_context(|| format!("failed to read {}", object.path.display()))?;
+        let length = file.metadata().await?.len();
+        let content_type = object.content_type();
+
+        let mut url = Url::parse(&format!("https://{}.blob.core.windows.net", account))?;
+        url.path_segments_mut()
+            .map_err(|_| anyhow::anyhow!("invalid account {}", account))?
+            .push(container)
+            .extend(object.key.split('/'));
+        let date = OffsetDateTime::now_utc().format(DATE_FORMAT)?;
+
+        let authorization = match &auth {
+            Auth::Sas(token) => {
+                url.set_query(Some(token));
+                None
+            }
+            Auth::SharedKey(key) => {
+                let signature = sign(key, account, &url, length, &content_type, &date)?;
+                Some(format!("SharedKey {}:{}", account, signature))
+            }
+        };
+        let mut request = HttpClient::new()
+            .put(url)
+            .header("x-ms-blob-type", "BlockBlob")
+            .header("x-ms-date", &date)
+            .header("x-ms-version", API_VERSION)
+            .header(CONTENT_LENGTH, length)
+            .header(CONTENT_TYPE, &content_type)
+            .timeout(http::transfer_timeout());
+        if let Some(authorization) = authorization {
+            request = request.header(AUTHORIZATION, authorization);
+        }
+
+        let response = request
+            .body(progress::body(&object.key, file, length))
+            .send()
+            .await?;
+        let status = response.status();
+        if !status.is_success() {
+            bail!(
+                "failed to upload {}: {} {}",
+                object.key,
+                status,
+                response.text().await?
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Shared Key signature of a Put Blob request
+fn sign(
+    key: &[u8],
+    account: &str,
+    url: &Url,
+    length: u64,
+    content_type: &str,
+    date: &str,
+) -> Result<String> {
+    // since 2015-02-21, a zero Content-Length is signed as an empty string
+    let length = match length {
+        0 => String::new(),
+        length => length.to_string(),
+    };
+    let string_to_sign = format!(
+        "PUT\n\n\n{}\n\n{}\n\n\n\n\n\n\nx-ms-blob-type:BlockBlob\nx-ms-date:{}\nx-ms-version:{}\n/{}{}",
+        length,
+        content_type,
+        date,
+        API_VERSION,
+        account,
+        url.path()
+    );
+    let mut mac = Hmac::<Sha256>::new_from_slice(key)?;
+    mac.update(string_to_sign.as_bytes());
+    Ok(STANDARD.encode(mac.finalize().into_bytes()))
+}
@@ -0,0 +1,120 @@
+use super::Object;
+use crate::{
+    checksum,
+    config::{CloudsmithConfig, CloudsmithFormat},
+    http::{self, progress, HttpClient},
+};
+use anyhow::{bail, Context, Result};
+use reqwest::header::{CONTENT_LENGTH, CONTENT_TYPE};
+use serde::{Deserialize, Serialize};
+use std::env;
+use tokio::fs::File;
+
+const API_KEY_VAR: &str = "CLOUDSMITH_API_KEY";
+const UPLOAD_URL: &str = "https://upload.cloudsmith.io";
+const API_URL: &str = "https://api.cloudsmith.io/v1";
+
+#[derive(Deserialize)]
+struct UploadedFile {
+    identifier: String,
+}
+
+/// Package created from an uploaded file
+#[derive(Serialize)]
+struct NewPackage<'a> {
+    package_file: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    distribution: Option<&'a str>,
+    /// Only read for raw packages
+    #[serde(skip_serializing_if = "Option::is_none")]
+    name: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    version: Option<&'a str>,
+    republish: bool,
+}
+
+/// Upload every object as a package of the repository, raw packages being named after the
+/// project and versioned after the tag
+pub async fn upload(
+    config: &CloudsmithConfig,
+    name: &str,
+    version: &str,
+    objects: &[Object],
+) -> Result<()> {
+    let api_key = env::var(API_KEY_VAR)
+        .with_context(|| format!("{} is required to upload to Cloudsmith", API_KEY_VAR))?;
+
+    for object in objects {
+        log::debug!(
+            "Uploading {} to {}/{}",
+            object.path.display(),
+            config.owner,
+            config.repository
+        );
+        let identifier = upload_file(config, &api_key, object).await?;
+
+        let raw = config.format == CloudsmithFormat::Raw;
+        let package = NewPackage {
+            package_file: &identifier,
+            distribution: config.distribution.as_deref().filter(|_| !raw),
+            name: Some(name).filter(|_| raw),
+            version: Some(version).filter(|_| raw),
+            republish: config.republish,
+        };
+        let response = HttpClient::new()
+            .post(format!(
+                "{}/packages/{}/{}/upload/{}/",
+                API_URL,
+                config.owner,
+                config.repository,
+                config.format.as_str()
+            ))
+            .header("X-Api-Key", &api_key)
+            .json(&package)
+            .send()
+            .await?;
+        let status = response.status();
+        if !status.is_success() {
+            bail!(
+                "failed to create the package of {}: {} {}",
+                object.key,
+                status,
+                response.text().await?
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Upload the file of a package, returning the identifier it is created from
+async fn upload_file(config: &CloudsmithConfig, api_key: &str, object: &Object) -> Result<String> {
+    let sha256 = checksum::create(&object.key, &object.path)?;
+    let file = File::open(&object.path)
+        .await
+        .with_context(|| format!("failed to read {}", object.path.display()))?;
+    let length = file.metadata().await?.len();
+
+    let response = HttpClient::new()
+        .put(format!(
+            "{}/{}/{}/{}",
+            UPLOAD_URL, config.owner, config.repository, object.key
+        ))
+        .header("X-Api-Key", api_key)
+        .header("Content-Sha256", sha256)
+        .header(CONTENT_LENGTH, length)
+        .header(CONTENT_TYPE, object.content_type())
+        .timeout(http::transfer_timeout())
+        .body(progress::body(&object.key, file, length))
+        .send()
+        .await?;
+    let status = response.status();
+    if !status.is_success() {
+        bail!(
+            "failed to upload {}: {} {}",
+            object.key,
+            status,
+            response.text().await?
+        );
+    }
+    Ok(response.json::<UploadedFile>().await?.identifier)
+}
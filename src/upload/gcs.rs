@@ -0,0 +1,178 @@
+use super::Object;
+use crate::http::{self, progress, HttpClient};
+use anyhow::{bail, Context, Result};
+use jsonwebtoken::{Algorithm, EncodingKey, Header};
+use reqwest::header::{CONTENT_LENGTH, CONTENT_TYPE};
+use serde::{Deserialize, Serialize};
+use std::{env, fs, path::PathBuf, time::SystemTime};
+use tokio::fs::File;
+
+const ACCESS_TOKEN_VAR: &str = "GOOGLE_OAUTH_ACCESS_TOKEN";
+const CREDENTIALS_VAR: &str = "GOOGLE_APPLICATION_CREDENTIALS";
+const UPLOAD_URL: &str = "https://storage.googleapis.com/upload/storage/v1/b";
+const TOKEN_URL: &str = "https://oauth2.googleapis.com/token";
+const METADATA_TOKEN_URL: &str =
+    "http://metadata.google.internal/computeMetadata/v1/instance/service-accounts/default/token";
+const SCOPE: &str = "https://www.googleapis.com/auth/devstorage.read_write";
+
+/// Application default credentials file
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum Credentials {
+    ServiceAccount {
+        client_email: String,
+        private_key: String,
+        token_uri: Option<String>,
+    },
+    /// Written by `gcloud auth application-default login`
+    AuthorizedUser {
+        client_id: String,
+        client_secret: String,
+        refresh_token: String,
+    },
+}
+
+#[derive(Serialize)]
+struct Claims<'a> {
+    iss: &'a str,
+    scope: &'a str,
+    aud: &'a str,
+    iat: u64,
+    exp: u64,
+}
+
+#[derive(Deserialize)]
+struct Token {
+    access_token: String,
+}
+
+/// Upload `objects` to the bucket, with `acl` as their predefined ACL
+pub async fn upload(bucket: &str, acl: Option<&str>, objects: &[Object]) -> Result<()> {
+    let token = access_token().await?;
+
+    for object in objects {
+        log::debug!("Uploading {} to {}", object.path.display(), object.key);
+        let file = File::open(&object.path)
+            .await
+            .with_context(|| format!("failed to read {}", object.path.display()))?;
+        let length = file.metadata().await?.len();
+
+        let mut query = vec![("uploadType", "media"), ("name", object.key.as_str())];
+        if let Some(acl) = acl {
+            query.push(("predefinedAcl", acl));
+        }
+        let response = HttpClient::new()
+            .post(format!("{}/{}/o", UPLOAD_URL, bucket))
+            .query(&query)
+            .bearer_auth(&token)
+            .header(CONTENT_LENGTH, length)
+            .header(CONTENT_TYPE, object.content_type())
+            .timeout(http::transfer_timeout())
+            .body(progress::body(&object.key, file, length))
+            .send()
+            .await?;
+
+        let status = response.status();
+        if !status.is_success() {
+            bail!(
+                "failed to upload {}: {} {}",
+                object.key,
+                status,
+                response.text().await?
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Token from `GOOGLE_OAUTH_ACCESS_TOKEN`, the application default credentials, or else the
+/// metadata server of the instance
+async fn access_token() -> Result<String> {
+    if let Ok(token) = env::var(ACCESS_TOKEN_VAR) {
+        return Ok(token);
+    }
+
+    let Some(path) = credentials_path() else {
+        log::debug!("No application default credentials, asking the metadata server");
+        let token = HttpClient::new()
+            .get(METADATA_TOKEN_URL)
+            .header("Metadata-Flavor", "Google")
+            .send()
+            .await
+            .with_context(|| {
+                format!(
+                    "no Google credentials, set {} or {}",
+                    ACCESS_TOKEN_VAR, CREDENTIALS_VAR
+                )
+            })?
+            .error_for_status()?
+            .json::<Token>()
+            .await?;
+        return Ok(token.access_token);
+    };
+
+    let content =
+        fs::read_to_string(&path).with_context(|| format!("failed to read {}", path.display()))?;
+    let credentials = serde_json::from_str::<Credentials>(&content)
+        .with_context(|| format!("unsupported credentials in {}", path.display()))?;
+
+    let request = match &credentials {
+        Credentials::ServiceAccount {
+            client_email,
+            private_key,
+            token_uri,
+        } => {
+            let token_uri = token_uri.as_deref().unwrap_or(TOKEN_URL);
+            let iat = SystemTime::now()
+                .duration_since(SystemTime::UNIX_EPOCH)?
+                .as_secs();
+            let claims = Claims {
+                iss: client_email,
+                scope: SCOPE,
+                aud: token_uri,
+                iat,
+                exp: iat + 3600,
+            };
+            let key = EncodingKey::from_rsa_pem(private_key.as_bytes())
+                .context("invalid service account private key")?;
+            let assertion = jsonwebtoken::encode(&Header::new(Algorithm::RS256), &claims, &key)?;
+            HttpClient::new().post(token_uri).form(&[
+                ("grant_type", "urn:ietf:params:oauth:grant-type:jwt-bearer"),
+                ("assertion", &assertion),
+            ])
+        }
+        Credentials::AuthorizedUser {
+            client_id,
+            client_secret,
+            refresh_token,
+        } => HttpClient::new().post(TOKEN_URL).form(&[
+            ("grant_type", "refresh_token"),
+            ("client_id", client_id),
+            ("client_secret", client_secret),
+            ("refresh_token", refresh_token),
+        ]),
+    };
+
+    let response = request.send().await?;
+    let status = response.status();
+    if !status.is_success() {
+        bail!(
+            "failed to get an access token: {} {}",
+            status,
+            response.text().await?
+        );
+    }
+    Ok(response.json::<Token>().await?.access_token)
+}
+
+/// `GOOGLE_APPLICATION_CREDENTIALS`, or else the file written by gcloud if it exists
+fn credentials_path() -> Option<PathBuf> {
+    if let Some(path) = env::var_os(CREDENTIALS_VAR) {
+        return Some(PathBuf::from(path));
+    }
+    let config_dir = env::var_os("CLOUDSDK_CONFIG")
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("APPDATA").map(|dir| PathBuf::from(dir).join("gcloud")))
+        .or_else(|| env::var_os("HOME").map(|dir| PathBuf::from(dir).join(".config/gcloud")))?;
+    Some(config_dir.join("application_default_credentials.json")).filter(|path| path.is_file())
+}
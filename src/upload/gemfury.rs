@@ -0,0 +1,44 @@
+use super::Object;
+use crate::http::{self, progress, HttpClient};
+use anyhow::{bail, Context, Result};
+use reqwest::multipart::{Form, Part};
+use std::env;
+use tokio::fs::File;
+
+const PUSH_TOKEN_VAR: &str = "FURY_PUSH_TOKEN";
+const PUSH_URL: &str = "https://push.fury.io";
+
+/// Push every object as a package of the account
+pub async fn upload(account: &str, objects: &[Object]) -> Result<()> {
+    let token = env::var(PUSH_TOKEN_VAR)
+        .with_context(|| format!("{} is required to push to Gemfury", PUSH_TOKEN_VAR))?;
+
+    for object in objects {
+        log::debug!("Pushing {} to {}", object.path.display(), account);
+        let file = File::open(&object.path)
+            .await
+            .with_context(|| format!("failed to read {}", object.path.display()))?;
+        let length = file.metadata().await?.len();
+
+        let part = Part::stream_with_length(progress::body(&object.key, file, length), length)
+            .file_name(object.key.to_owned())
+            .mime_str(&object.content_type())?;
+        let response = HttpClient::new()
+            .post(format!("{}/{}/", PUSH_URL, account))
+            .basic_auth(&token, None::<&str>)
+            .multipart(Form::new().part("package", part))
+            .timeout(http::transfer_timeout())
+            .send()
+            .await?;
+        let status = response.status();
+        if !status.is_success() {
+            bail!(
+                "failed to push {}: {} {}",
+                object.key,
+                status,
+                response.text().await?
+            );
+        }
+    }
+    Ok(())
+}
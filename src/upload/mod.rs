@@ -0,0 +1,405 @@
+pub mod apt;
+pub mod azure;
+pub mod cloudsmith;
+pub mod gcs;
+pub mod gemfury;
+pub mod put;
+pub mod repository;
+pub mod s3;
+pub mod yum;
+
+use crate::{
+    archive,
+    config::{
+        PackageRepositoryConfig, ReleaseConfig, S3Config, StorageConfig, StorageTargetConfig,
+    },
+    dry_run,
+    github::{
+        tag::{Tag, VersionComponents},
+        CHECKSUMS_FILE_NAME,
+    },
+    packager::package::Package,
+    template,
+};
+use anyhow::{bail, Context, Result};
+use mime_guess::from_path;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+const SCHEMES: &str = "s3://, gs://, az://, http:// or https://";
+
+/// Values available to the prefix templates
+#[derive(Debug, Serialize)]
+struct Location<'a> {
+    name: &'a str,
+    version: &'a str,
+    tag: &'a str,
+    #[serde(flatten)]
+    components: VersionComponents,
+}
+
+/// File of the release and the key it is stored at
+#[derive(Debug, Serialize)]
+pub struct Object {
+    pub key: String,
+    #[serde(skip)]
+    pub path: PathBuf,
+}
+
+impl Object {
+    fn content_type(&self) -> String {
+        from_path(&self.path).first_or_octet_stream().to_string()
+    }
+}
+
+/// Storage of a target, picked by the scheme of its URL
+#[derive(Debug, PartialEq)]
+enum Target {
+    S3 {
+        bucket: String,
+    },
+    Gcs {
+        bucket: String,
+    },
+    Azure {
+        account: String,
+        container: String,
+    },
+    /// Base URL the files are PUT under
+    Http {
+        url: String,
+    },
+}
+
+impl Target {
+    /// The target of `url` and the prefix of its keys
+    fn parse(url: &str) -> Result<(Target, String)> {
+        let (scheme, rest) = url
+            .split_once("://")
+            .with_context(|| format!("{} has no scheme, expected {}", url, SCHEMES))?;
+        let (root, path) = rest.split_once('/').unwrap_or((rest, ""));
+        if root.is_empty() {
+            bail!("{} has no bucket or account", url);
+        }
+
+        match scheme {
+            "s3" => Ok((
+                Target::S3 {
+                    bucket: root.to_owned(),
+                },
+                path.to_owned(),
+            )),
+            "gs" => Ok((
+                Target::Gcs {
+                    bucket: root.to_owned(),
+                },
+                path.to_owned(),
+            )),
+            "az" => {
+                let (container, prefix) = path.split_once('/').unwrap_or((path, ""));
+                if container.is_empty() {
+                    bail!(
+                        "{} has no container, expected az://<account>/<container>",
+                        url
+                    );
+                }
+                let target = Target::Azure {
+                    account: root.to_owned(),
+                    container: container.to_owned(),
+                };
+                Ok((target, prefix.to_owned()))
+            }
+            "http" | "https" => {
+                let target = Target::Http {
+                    url: url.trim_end_matches('/').to_owned(),
+                };
+                Ok((target, String::new()))
+            }
+            _ => bail!("unsupported scheme {}://, expected {}", scheme, SCHEMES),
+        }
+    }
+}
+
+/// Check the scheme and the parts of a target URL before rendering it
+pub fn check_url(url: &str) -> Result<()> {
+    Target::parse(url).map(|_| ())
+}
+
+/// Mirror the archives of the release and their checksums to every configured storage, and push
+/// the packages matched under `base` to the package services
+pub async fn upload(
+    config: &StorageConfig,
+    release_info: &ReleaseConfig,
+    tag: &Tag,
+    packages: &[Package],
+    base: &Path,
+    dry_run: bool,
+    output_path: &Path,
+) -> Result<()> {
+    let location = Location {
+        name: &release_info.repo,
+        version: tag.version(),
+        tag: tag.value(),
+        components: tag.components(),
+    };
+    let mut files = packages
+        .iter()
+        .map(|package| package.name.to_owned())
+        .collect::<Vec<_>>();
+    if output_path.join(CHECKSUMS_FILE_NAME).is_file() {
+        files.push(CHECKSUMS_FILE_NAME.to_owned());
+    }
+
+    for (i, s3) in config.s3.iter().enumerate() {
+        let prefix = template::render(&s3.prefix, &location)
+            .with_context(|| format!("failed to render storage.s3[{}].prefix", i))?;
+        let objects = objects(&prefix, &files, output_path);
+        if dry_run {
+            dry_run::record(
+                output_path,
+                &format!("upload-s3-{}", i),
+                &format!("upload {} files to s3://{}", objects.len(), s3.bucket),
+                &objects,
+            )?;
+            continue;
+        }
+        log::info!("Uploading {} files to s3://{}", objects.len(), s3.bucket);
+        s3::upload(s3, &objects)
+            .await
+            .with_context(|| format!("failed to upload to s3://{}", s3.bucket))?;
+    }
+
+    for (i, target) in config.targets.iter().enumerate() {
+        let url = template::render(&target.url, &location)
+            .with_context(|| format!("failed to render storage.targets[{}].url", i))?;
+        let (storage, prefix) = Target::parse(&url)?;
+        let objects = objects(&prefix, &files, output_path);
+        if dry_run {
+            dry_run::record(
+                output_path,
+                &format!("upload-target-{}", i),
+                &format!("upload {} files to {}", objects.len(), url),
+                &objects,
+            )?;
+            continue;
+        }
+        log::info!("Uploading {} files to {}", objects.len(), url);
+        upload_target(storage, target, &objects)
+            .await
+            .with_context(|| format!("failed to upload to {}", url))?;
+    }
+
+    for (i, cloudsmith) in config.cloudsmith.iter().enumerate() {
+        let repository = format!("{}/{}", cloudsmith.owner, cloudsmith.repository);
+        let objects = if cloudsmith.files.is_empty() {
+            objects("", &files, output_path)
+        } else {
+            packages_matching(base, &cloudsmith.files)?
+        };
+        if dry_run {
+            dry_run::record(
+                output_path,
+                &format!("upload-cloudsmith-{}", i),
+                &format!(
+                    "upload {} {} packages to Cloudsmith {}",
+                    objects.len(),
+                    cloudsmith.format.as_str(),
+                    repository
+                ),
+                &objects,
+            )?;
+            continue;
+        }
+        log::info!(
+            "Uploading {} packages to Cloudsmith {}",
+            objects.len(),
+            repository
+        );
+        cloudsmith::upload(cloudsmith, location.name, location.version, &objects)
+            .await
+            .with_context(|| format!("failed to upload to Cloudsmith {}", repository))?;
+    }
+
+    for (i, gemfury) in config.gemfury.iter().enumerate() {
+        let objects = packages_matching(base, &gemfury.files)?;
+        if dry_run {
+            dry_run::record(
+                output_path,
+                &format!("upload-gemfury-{}", i),
+                &format!(
+                    "push {} packages to Gemfury {}",
+                    objects.len(),
+                    gemfury.account
+                ),
+                &objects,
+            )?;
+            continue;
+        }
+        log::info!(
+            "Pushing {} packages to Gemfury {}",
+            objects.len(),
+            gemfury.account
+        );
+        gemfury::upload(&gemfury.account, &objects)
+            .await
+            .with_context(|| format!("failed to push to Gemfury {}", gemfury.account))?;
+    }
+
+    if let Some(apt) = &config.apt {
+        let dir = output_path.join(apt::DIRECTORY);
+        let sign = apt.repository.sign && !dry_run;
+        let objects = apt::build(apt, release_info, base, &dir, sign)
+            .await
+            .context("failed to build the apt repository")?;
+        publish_repository(
+            "apt",
+            &apt.repository,
+            release_info,
+            &location,
+            &dir,
+            objects,
+            dry_run,
+            output_path,
+        )
+        .await?;
+    }
+
+    if let Some(yum) = &config.yum {
+        let dir = output_path.join(yum::DIRECTORY);
+        let sign = yum.repository.sign && !dry_run;
+        let objects = yum::build(yum, release_info, base, &dir, sign)
+            .await
+            .context("failed to build the yum repository")?;
+        publish_repository(
+            "yum",
+            &yum.repository,
+            release_info,
+            &location,
+            &dir,
+            objects,
+            dry_run,
+            output_path,
+        )
+        .await?;
+    }
+    Ok(())
+}
+
+/// Commit the `kind` package repository built in `dir` to its branch, or upload it to its target
+#[allow(clippy::too_many_arguments)]
+async fn publish_repository(
+    kind: &str,
+    config: &PackageRepositoryConfig,
+    release_info: &ReleaseConfig,
+    location: &Location<'_>,
+    dir: &Path,
+    objects: Vec<Object>,
+    dry_run: bool,
+    output_path: &Path,
+) -> Result<()> {
+    let destination = match (&config.branch, &config.target) {
+        (Some(branch), _) => format!("branch {}", branch),
+        (None, Some(target)) => template::render(&target.url, location)
+            .with_context(|| format!("failed to render storage.{}.target.url", kind))?,
+        (None, None) => bail!("storage.{} needs a branch or a target", kind),
+    };
+    if dry_run {
+        return dry_run::record(
+            output_path,
+            &format!("upload-{}", kind),
+            &format!(
+                "publish the {} repository built in {} to {}",
+                kind,
+                dir.display(),
+                destination
+            ),
+            &objects,
+        );
+    }
+
+    if let Some(branch) = &config.branch {
+        log::info!("Committing the {} repository to {}", kind, branch);
+        let message = format!(
+            "Publish {} {} to the {} repository",
+            location.name, location.version, kind
+        );
+        repository::commit(release_info, branch, &message, &objects)
+            .await
+            .with_context(|| format!("failed to commit the {} repository to {}", kind, branch))?;
+    } else if let Some(target) = &config.target {
+        log::info!("Uploading the {} repository to {}", kind, destination);
+        let (storage, prefix) = Target::parse(&destination)?;
+        let objects = objects
+            .into_iter()
+            .map(|object| Object {
+                key: key(&prefix, &object.key),
+                path: object.path,
+            })
+            .collect::<Vec<_>>();
+        upload_target(storage, target, &objects)
+            .await
+            .with_context(|| format!("failed to upload to {}", destination))?;
+    }
+    Ok(())
+}
+
+/// Files matched by `patterns` under `base`, stored under their file name
+fn packages_matching(base: &Path, patterns: &[String]) -> Result<Vec<Object>> {
+    Ok(archive::resolve_files(base, patterns)?
+        .into_iter()
+        .map(|entry| Object {
+            key: entry
+                .path
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or(entry.name),
+            path: entry.path,
+        })
+        .collect())
+}
+
+async fn upload_target(
+    storage: Target,
+    config: &StorageTargetConfig,
+    objects: &[Object],
+) -> Result<()> {
+    match storage {
+        Target::S3 { bucket } => {
+            // the region, endpoint and credentials come from the AWS environment
+            let s3 = S3Config {
+                bucket,
+                prefix: String::new(),
+                region: None,
+                endpoint: None,
+                force_path_style: false,
+                acl: config.acl.to_owned(),
+                profile: None,
+            };
+            s3::upload(&s3, objects).await
+        }
+        Target::Gcs { bucket } => gcs::upload(&bucket, config.acl.as_deref(), objects).await,
+        Target::Azure { account, container } => azure::upload(&account, &container, objects).await,
+        Target::Http { url } => put::upload(&url, config, objects).await,
+    }
+}
+
+/// `files` of `output_path` stored under `prefix`
+fn objects(prefix: &str, files: &[String], output_path: &Path) -> Vec<Object> {
+    files
+        .iter()
+        .map(|file| Object {
+            key: key(prefix, file),
+            path: output_path.join(file),
+        })
+        .collect()
+}
+
+/// `file` under `prefix`
+fn key(prefix: &str, file: &str) -> String {
+    let prefix = prefix.trim_matches('/');
+    if prefix.is_empty() {
+        file.to_owned()
+    } else {
+        format!("{}/{}", prefix, file)
+    }
+}
@@ -0,0 +1,74 @@
+use super::Object;
+use crate::{
+    checksum,
+    config::StorageTargetConfig,
+    http::{self, progress, HttpClient},
+};
+use anyhow::{bail, Context, Result};
+use reqwest::header::{CONTENT_LENGTH, CONTENT_TYPE};
+use std::env;
+use tokio::fs::File;
+
+/// Header of the SHA-256 of the file, verified by Artifactory and ignored by most other servers
+const CHECKSUM_HEADER: &str = "X-Checksum-Sha256";
+
+/// PUT `objects` under `url`, e.g. a generic Artifactory or Nexus raw repository
+pub async fn upload(url: &str, config: &StorageTargetConfig, objects: &[Object]) -> Result<()> {
+    let password = match &config.password_env {
+        Some(var) => Some(
+            env::var(var).with_context(|| format!("{} is required to upload to {}", var, url))?,
+        ),
+        None => None,
+    };
+    let token = match &config.token_env {
+        Some(var) => Some(
+            env::var(var).with_context(|| format!("{} is required to upload to {}", var, url))?,
+        ),
+        None => None,
+    };
+
+    for object in objects {
+        log::debug!(
+            "Uploading {} to {}/{}",
+            object.path.display(),
+            url,
+            object.key
+        );
+        let sha256 = checksum::create(&object.key, &object.path)?;
+        let file = File::open(&object.path)
+            .await
+            .with_context(|| format!("failed to read {}", object.path.display()))?;
+        let length = file.metadata().await?.len();
+
+        let mut request = HttpClient::new()
+            .put(format!("{}/{}", url, object.key))
+            .header(CONTENT_LENGTH, length)
+            .header(CONTENT_TYPE, object.content_type())
+            .header(CHECKSUM_HEADER, sha256)
+            .timeout(http::transfer_timeout());
+        if let Some(username) = &config.username {
+            request = request.basic_auth(username, password.as_ref());
+        }
+        if let Some(token) = &token {
+            request = request.bearer_auth(token);
+        }
+        for (name, value) in &config.headers {
+            request = request.header(name, value);
+        }
+
+        let response = request
+            .body(progress::body(&object.key, file, length))
+            .send()
+            .await?;
+        let status = response.status();
+        if !status.is_success() {
+            bail!(
+                "failed to upload {}: {} {}",
+                object.key,
+                status,
+                response.text().await?
+            );
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,143 @@
+use super::Object;
+use crate::{
+    config::{PackageRepositoryConfig, ReleaseConfig},
+    github::{builder::BuilderExecutor, github_client},
+    http::HttpClient,
+};
+use anyhow::{bail, Context, Result};
+use reqwest::StatusCode;
+use std::{fs, path::Path};
+use tokio::process::Command;
+
+/// Public key of `signing_key`, for the users to check the signed metadata
+const KEY_FILE_NAME: &str = "key.asc";
+
+/// Content of a file of the published repository, if any
+pub async fn existing(
+    config: &PackageRepositoryConfig,
+    release_info: &ReleaseConfig,
+    path: &str,
+) -> Result<Option<Vec<u8>>> {
+    if let Some(branch) = &config.branch {
+        let content = github_client::instance()
+            .repo(&release_info.owner, &release_info.repo)
+            .branch(branch)
+            .get_file_bytes(path)
+            .await;
+        return match content {
+            Ok(content) => Ok(Some(content)),
+            // any other failure would drop the published packages from the metadata
+            Err(err) if is_not_found(&err) => {
+                log::debug!("No {} on {}", path, branch);
+                Ok(None)
+            }
+            Err(err) => Err(err),
+        };
+    }
+
+    let Some(url) = &config.url else {
+        return Ok(None);
+    };
+    let url = format!("{}/{}", url.trim_end_matches('/'), path);
+    let response = HttpClient::new().get(&url).send().await?;
+    match response.status() {
+        StatusCode::NOT_FOUND | StatusCode::FORBIDDEN => {
+            log::debug!("No {}", url);
+            Ok(None)
+        }
+        status if status.is_success() => Ok(Some(response.bytes().await?.to_vec())),
+        status => bail!("failed to get {}: {}", url, status),
+    }
+}
+
+fn is_not_found(err: &anyhow::Error) -> bool {
+    err.downcast_ref::<reqwest::Error>()
+        .and_then(reqwest::Error::status)
+        == Some(StatusCode::NOT_FOUND)
+}
+
+/// Commit the repository on `branch` of the release repository
+pub async fn commit(
+    release_info: &ReleaseConfig,
+    branch: &str,
+    message: &str,
+    objects: &[Object],
+) -> Result<()> {
+    let mut commit = github_client::instance()
+        .repo(&release_info.owner, &release_info.repo)
+        .branch(branch)
+        .commit_files()
+        .message(message);
+    for object in objects {
+        let content = fs::read(&object.path)
+            .with_context(|| format!("failed to read {}", object.path.display()))?;
+        commit = commit.file(&object.key, content);
+    }
+    commit.execute().await?;
+    Ok(())
+}
+
+/// Sign `input` into `output` with the gpg signing `args`, e.g. `--clearsign`
+pub async fn sign(
+    config: &PackageRepositoryConfig,
+    args: &[&str],
+    output: &Path,
+    input: &Path,
+) -> Result<()> {
+    let mut command = Command::new("gpg");
+    command.args(["--batch", "--yes"]);
+    if let Some(key) = &config.signing_key {
+        command.arg("--local-user").arg(key);
+    }
+    let output = command
+        .args(args)
+        .arg("--output")
+        .arg(output)
+        .arg(input)
+        .output()
+        .await
+        .context("failed to run gpg")?;
+    if !output.status.success() {
+        bail!(
+            "failed to sign {}: {}",
+            input.display(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(())
+}
+
+/// Export the public key of `signing_key` at the root of the repository
+pub async fn export_key(config: &PackageRepositoryConfig, dir: &Path) -> Result<Option<Object>> {
+    let Some(key) = &config.signing_key else {
+        return Ok(None);
+    };
+    let output = dir.join(KEY_FILE_NAME);
+    let status = Command::new("gpg")
+        .args(["--batch", "--yes", "--armor", "--output"])
+        .arg(&output)
+        .args(["--export", key])
+        .status()
+        .await
+        .context("failed to run gpg")?;
+    if !status.success() {
+        bail!("failed to export the key {}", key);
+    }
+    Ok(Some(Object {
+        key: KEY_FILE_NAME.to_owned(),
+        path: output,
+    }))
+}
+
+/// Write the file at `key` of the repository in `dir`
+pub fn write(dir: &Path, key: &str, content: &[u8]) -> Result<Object> {
+    let path = dir.join(key);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(&path, content).with_context(|| format!("failed to write {}", path.display()))?;
+    Ok(Object {
+        key: key.to_owned(),
+        path,
+    })
+}
@@ -0,0 +1,57 @@
+use super::Object;
+use crate::config::S3Config;
+use anyhow::{Context, Result};
+use aws_config::{BehaviorVersion, Region};
+use aws_sdk_s3::{
+    config::RequestChecksumCalculation, primitives::ByteStream, types::ObjectCannedAcl, Client,
+};
+
+/// Put `objects` in the bucket with the credentials of the AWS environment, config files or
+/// instance role
+pub async fn upload(config: &S3Config, objects: &[Object]) -> Result<()> {
+    let client = client(config).await;
+
+    for object in objects {
+        log::debug!("Uploading {} to {}", object.path.display(), object.key);
+        let body = ByteStream::from_path(&object.path)
+            .await
+            .with_context(|| format!("failed to read {}", object.path.display()))?;
+
+        let mut request = client
+            .put_object()
+            .bucket(&config.bucket)
+            .key(&object.key)
+            .body(body)
+            .content_type(object.content_type());
+        if let Some(acl) = &config.acl {
+            request = request.acl(ObjectCannedAcl::from(acl.as_str()));
+        }
+        request
+            .send()
+            .await
+            .with_context(|| format!("failed to upload {}", object.key))?;
+    }
+    Ok(())
+}
+
+async fn client(config: &S3Config) -> Client {
+    let mut loader = aws_config::defaults(BehaviorVersion::latest());
+    if let Some(profile) = &config.profile {
+        loader = loader.profile_name(profile);
+    }
+    if let Some(region) = &config.region {
+        loader = loader.region(Region::new(region.to_owned()));
+    }
+    if let Some(endpoint) = &config.endpoint {
+        loader = loader.endpoint_url(endpoint);
+    }
+    let shared = loader.load().await;
+
+    let mut s3 =
+        aws_sdk_s3::config::Builder::from(&shared).force_path_style(config.force_path_style);
+    if config.endpoint.is_some() {
+        // not every S3-compatible storage accepts the checksums sent by default
+        s3 = s3.request_checksum_calculation(RequestChecksumCalculation::WhenRequired);
+    }
+    Client::from_conf(s3.build())
+}
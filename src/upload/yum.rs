@@ -0,0 +1,605 @@
+use super::{repository, Object};
+use crate::{
+    archive,
+    config::{ReleaseConfig, YumConfig},
+};
+use anyhow::{bail, Context, Result};
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use itertools::Itertools;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use sha2::{Digest, Sha256};
+use std::{
+    collections::HashSet,
+    fmt::Write as _,
+    fs,
+    io::{Read, Write},
+    path::Path,
+    time::UNIX_EPOCH,
+};
+use time::OffsetDateTime;
+use xz2::read::XzDecoder;
+
+/// Directory of the output the repository is written to
+pub const DIRECTORY: &str = "yum";
+const REPOMD_PATH: &str = "repodata/repomd.xml";
+const LEAD_LENGTH: usize = 96;
+const HEADER_MAGIC: &[u8] = &[0x8e, 0xad, 0xe8, 0x01];
+const COMMON_NAMESPACE: &str = "http://linux.duke.edu/metadata/common";
+const RPM_NAMESPACE: &str = "http://linux.duke.edu/metadata/rpm";
+
+// header tags
+const NAME: u32 = 1000;
+const VERSION: u32 = 1001;
+const RELEASE: u32 = 1002;
+const EPOCH: u32 = 1003;
+const SUMMARY: u32 = 1004;
+const DESCRIPTION: u32 = 1005;
+const BUILD_TIME: u32 = 1006;
+const BUILD_HOST: u32 = 1007;
+const SIZE: u32 = 1009;
+const VENDOR: u32 = 1011;
+const LICENSE: u32 = 1014;
+const PACKAGER: u32 = 1015;
+const GROUP: u32 = 1016;
+const URL: u32 = 1020;
+const ARCH: u32 = 1022;
+const FILE_MODES: u32 = 1030;
+const SOURCE_RPM: u32 = 1044;
+const ARCHIVE_SIZE: u32 = 1046;
+const PROVIDE_NAME: u32 = 1047;
+const REQUIRE_FLAGS: u32 = 1048;
+const REQUIRE_NAME: u32 = 1049;
+const REQUIRE_VERSION: u32 = 1050;
+const CONFLICT_FLAGS: u32 = 1053;
+const CONFLICT_NAME: u32 = 1054;
+const CONFLICT_VERSION: u32 = 1055;
+const OBSOLETE_NAME: u32 = 1090;
+const PROVIDE_FLAGS: u32 = 1112;
+const PROVIDE_VERSION: u32 = 1113;
+const OBSOLETE_FLAGS: u32 = 1114;
+const OBSOLETE_VERSION: u32 = 1115;
+const DIR_INDEXES: u32 = 1116;
+const BASE_NAMES: u32 = 1117;
+const DIR_NAMES: u32 = 1118;
+const LONG_SIZE: u32 = 5009;
+/// Payload size in the signature header
+const SIGNATURE_PAYLOAD_SIZE: u32 = 1007;
+
+static PACKAGE: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?s)<package\b.*?</package>").unwrap());
+static PKGID: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"<checksum type="[^"]*" pkgid="YES">([^<]+)</checksum>|pkgid="([^"]+)""#).unwrap()
+});
+static LOCATION: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"<location [^>]*href="([^"]+)""#).unwrap());
+static DATA: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"(?s)<data type="(\w+)">.*?<location [^>]*href="([^"]+)""#).unwrap());
+
+/// Index and data store of an rpm header
+struct Header<'a> {
+    index: &'a [u8],
+    store: &'a [u8],
+}
+
+impl<'a> Header<'a> {
+    /// The header at `offset` and the offset it ends at
+    fn parse(data: &'a [u8], offset: usize) -> Result<(Header<'a>, usize)> {
+        let intro = data
+            .get(offset..offset + 16)
+            .context("truncated rpm header")?;
+        if &intro[..4] != HEADER_MAGIC {
+            bail!("invalid rpm header");
+        }
+        let count = u32::from_be_bytes(intro[8..12].try_into()?) as usize;
+        let size = u32::from_be_bytes(intro[12..16].try_into()?) as usize;
+        let store = offset + 16 + count * 16;
+        let header = Header {
+            index: data
+                .get(offset + 16..store)
+                .context("truncated rpm header")?,
+            store: data
+                .get(store..store + size)
+                .context("truncated rpm header")?,
+        };
+        Ok((header, store + size))
+    }
+
+    /// Type, offset and count of `tag`
+    fn entry(&self, tag: u32) -> Option<(u32, usize, usize)> {
+        self.index.chunks_exact(16).find_map(|entry| {
+            let value = |i: usize| u32::from_be_bytes(entry[i..i + 4].try_into().unwrap());
+            (value(0) == tag).then(|| (value(4), value(8) as usize, value(12) as usize))
+        })
+    }
+
+    fn strings(&self, tag: u32) -> Vec<String> {
+        let Some((kind, offset, count)) = self.entry(tag) else {
+            return vec![];
+        };
+        // a single string has a count of 1, i18n strings hold a translation per locale
+        let count = match kind {
+            6 => 1,
+            8 | 9 => count,
+            _ => return vec![],
+        };
+        self.store
+            .get(offset..)
+            .unwrap_or_default()
+            .split(|byte| *byte == 0)
+            .take(count)
+            .map(|string| String::from_utf8_lossy(string).into_owned())
+            .collect()
+    }
+
+    fn string(&self, tag: u32) -> String {
+        self.strings(tag).into_iter().next().unwrap_or_default()
+    }
+
+    fn integers(&self, tag: u32) -> Vec<u64> {
+        let Some((kind, offset, count)) = self.entry(tag) else {
+            return vec![];
+        };
+        let width = match kind {
+            3 => 2,
+            4 => 4,
+            5 => 8,
+            _ => return vec![],
+        };
+        self.store
+            .get(offset..offset + width * count)
+            .unwrap_or_default()
+            .chunks_exact(width)
+            .map(|bytes| {
+                bytes
+                    .iter()
+                    .fold(0u64, |value, byte| value << 8 | u64::from(*byte))
+            })
+            .collect()
+    }
+
+    fn integer(&self, tag: u32) -> Option<u64> {
+        self.integers(tag).into_iter().next()
+    }
+}
+
+/// `epoch:version-release` of a package or a dependency
+#[derive(Debug, Default)]
+struct Evr {
+    epoch: String,
+    version: String,
+    release: String,
+}
+
+impl Evr {
+    fn parse(evr: &str) -> Evr {
+        let (epoch, rest) = evr.split_once(':').unwrap_or(("0", evr));
+        let (version, release) = rest.rsplit_once('-').unwrap_or((rest, ""));
+        Evr {
+            epoch: epoch.to_owned(),
+            version: version.to_owned(),
+            release: release.to_owned(),
+        }
+    }
+
+    fn xml(&self) -> String {
+        let mut xml = format!(
+            r#"epoch="{}" ver="{}""#,
+            escape(&self.epoch),
+            escape(&self.version)
+        );
+        if !self.release.is_empty() {
+            let _ = write!(xml, r#" rel="{}""#, escape(&self.release));
+        }
+        xml
+    }
+}
+
+#[derive(Debug)]
+struct Dependency {
+    name: String,
+    flags: Option<&'static str>,
+    evr: Option<Evr>,
+}
+
+/// A package of the repository and its metadata
+#[derive(Debug)]
+struct Package {
+    pkgid: String,
+    name: String,
+    arch: String,
+    evr: Evr,
+    location: String,
+    primary: String,
+    filelists: String,
+    other: String,
+}
+
+/// Write the repository to `dir`: the packages of `config.files`, and the createrepo metadata
+/// listing them along with the packages already published
+pub async fn build(
+    config: &YumConfig,
+    release_info: &ReleaseConfig,
+    base: &Path,
+    dir: &Path,
+    sign: bool,
+) -> Result<Vec<Object>> {
+    if dir.exists() {
+        fs::remove_dir_all(dir)?;
+    }
+    let mut objects = vec![];
+
+    let mut packages = vec![];
+    for entry in archive::resolve_files(base, &config.files)? {
+        let file_name = entry
+            .path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or(entry.name);
+        let group = file_name.chars().next().unwrap_or('_').to_ascii_lowercase();
+        let location = format!("Packages/{}/{}", group, file_name);
+        let package = package(&entry.path, &location)
+            .with_context(|| format!("failed to read {}", entry.path.display()))?;
+        objects.push(repository::write(dir, &location, &fs::read(&entry.path)?)?);
+        packages.push(package);
+    }
+    if packages.is_empty() {
+        bail!("no rpm packages matched {}", config.files.join(", "));
+    }
+
+    let locations = packages
+        .iter()
+        .map(|package| package.location.as_str())
+        .collect::<HashSet<_>>();
+    let mut primary = vec![];
+    let mut filelists = vec![];
+    let mut other = vec![];
+    for (kind, data) in published(config, release_info).await? {
+        let blocks = PACKAGE
+            .find_iter(&data)
+            .map(|block| block.as_str().to_owned());
+        match kind.as_str() {
+            "primary" => primary.extend(blocks),
+            "filelists" => filelists.extend(blocks),
+            "other" => other.extend(blocks),
+            _ => {}
+        }
+    }
+    // the previous packages replaced by a package of the same file name are dropped
+    primary.retain(|block| {
+        LOCATION
+            .captures(block)
+            .is_some_and(|location| !locations.contains(&location[1]))
+    });
+    let kept = primary
+        .iter()
+        .filter_map(|block| pkgid(block))
+        .collect::<HashSet<_>>();
+    filelists.retain(|block| pkgid(block).is_some_and(|pkgid| kept.contains(&pkgid)));
+    other.retain(|block| pkgid(block).is_some_and(|pkgid| kept.contains(&pkgid)));
+    for package in &packages {
+        log::debug!(
+            "Adding {} {}:{}-{} {} ({})",
+            package.name,
+            package.evr.epoch,
+            package.evr.version,
+            package.evr.release,
+            package.arch,
+            package.pkgid
+        );
+        primary.push(package.primary.to_owned());
+        filelists.push(package.filelists.to_owned());
+        other.push(package.other.to_owned());
+    }
+
+    let timestamp = OffsetDateTime::now_utc().unix_timestamp();
+    let mut repomd = format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<repomd xmlns=\"http://linux.duke.edu/metadata/repo\" xmlns:rpm=\"{}\">\n  <revision>{}</revision>\n",
+        RPM_NAMESPACE, timestamp
+    );
+    for (kind, root, namespaces, blocks) in [
+        (
+            "primary",
+            "metadata",
+            format!(
+                r#"xmlns="{}" xmlns:rpm="{}""#,
+                COMMON_NAMESPACE, RPM_NAMESPACE
+            ),
+            &primary,
+        ),
+        (
+            "filelists",
+            "filelists",
+            r#"xmlns="http://linux.duke.edu/metadata/filelists""#.to_owned(),
+            &filelists,
+        ),
+        (
+            "other",
+            "otherdata",
+            r#"xmlns="http://linux.duke.edu/metadata/other""#.to_owned(),
+            &other,
+        ),
+    ] {
+        let xml = format!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<{} {} packages=\"{}\">\n{}\n</{}>\n",
+            root,
+            namespaces,
+            blocks.len(),
+            blocks.join("\n"),
+            root
+        );
+        let mut gzip = GzEncoder::new(vec![], Compression::best());
+        gzip.write_all(xml.as_bytes())?;
+        let gzip = gzip.finish()?;
+
+        let checksum = hex::encode(Sha256::digest(&gzip));
+        let location = format!("repodata/{}-{}.xml.gz", checksum, kind);
+        let _ = write!(
+            repomd,
+            "  <data type=\"{}\">\n    <checksum type=\"sha256\">{}</checksum>\n    <open-checksum type=\"sha256\">{}</open-checksum>\n    <location href=\"{}\"/>\n    <timestamp>{}</timestamp>\n    <size>{}</size>\n    <open-size>{}</open-size>\n  </data>\n",
+            kind,
+            checksum,
+            hex::encode(Sha256::digest(xml.as_bytes())),
+            location,
+            timestamp,
+            gzip.len(),
+            xml.len()
+        );
+        objects.push(repository::write(dir, &location, &gzip)?);
+    }
+    repomd.push_str("</repomd>\n");
+    objects.push(repository::write(dir, REPOMD_PATH, repomd.as_bytes())?);
+
+    if sign {
+        log::info!("Signing {}", REPOMD_PATH);
+        let key = format!("{}.asc", REPOMD_PATH);
+        let output = dir.join(&key);
+        repository::sign(
+            &config.repository,
+            &["--armor", "--detach-sign"],
+            &output,
+            &dir.join(REPOMD_PATH),
+        )
+        .await?;
+        objects.push(Object { key, path: output });
+        objects.extend(repository::export_key(&config.repository, dir).await?);
+    }
+
+    Ok(objects)
+}
+
+/// Uncompressed metadata of the published repository, by type
+async fn published(
+    config: &YumConfig,
+    release_info: &ReleaseConfig,
+) -> Result<Vec<(String, String)>> {
+    let Some(repomd) = repository::existing(&config.repository, release_info, REPOMD_PATH).await?
+    else {
+        return Ok(vec![]);
+    };
+    let repomd = String::from_utf8_lossy(&repomd);
+
+    let mut metadata = vec![];
+    for data in DATA.captures_iter(&repomd) {
+        let (kind, location) = (&data[1], &data[2]);
+        if !matches!(kind, "primary" | "filelists" | "other") {
+            continue;
+        }
+        let Some(content) =
+            repository::existing(&config.repository, release_info, location).await?
+        else {
+            bail!("{} lists {}, which is missing", REPOMD_PATH, location);
+        };
+        let mut reader: Box<dyn Read> = match location.rsplit('.').next() {
+            Some("gz") => Box::new(GzDecoder::new(content.as_slice())),
+            Some("xz") => Box::new(XzDecoder::new(content.as_slice())),
+            Some("zst") => Box::new(zstd::Decoder::new(content.as_slice())?),
+            Some("xml") => Box::new(content.as_slice()),
+            _ => bail!("unsupported compression of {}", location),
+        };
+        let mut xml = String::new();
+        reader
+            .read_to_string(&mut xml)
+            .with_context(|| format!("failed to decompress {}", location))?;
+        metadata.push((kind.to_owned(), xml));
+    }
+    Ok(metadata)
+}
+
+/// Read the headers of an rpm package into its metadata
+fn package(path: &Path, location: &str) -> Result<Package> {
+    let content = fs::read(path)?;
+    if !content.starts_with(&[0xed, 0xab, 0xee, 0xdb]) {
+        bail!("not an rpm package");
+    }
+    let (signature, end) = Header::parse(&content, LEAD_LENGTH)?;
+    // the main header is aligned on 8 bytes after the signature
+    let start = end.next_multiple_of(8);
+    let (header, end) = Header::parse(&content, start)?;
+
+    let pkgid = hex::encode(Sha256::digest(&content));
+    let name = header.string(NAME);
+    let arch = header.string(ARCH);
+    let evr = Evr {
+        epoch: header.integer(EPOCH).unwrap_or_default().to_string(),
+        version: header.string(VERSION),
+        release: header.string(RELEASE),
+    };
+    let file_time = fs::metadata(path)?
+        .modified()?
+        .duration_since(UNIX_EPOCH)?
+        .as_secs();
+
+    let dirs = header.strings(DIR_NAMES);
+    let modes = header.integers(FILE_MODES);
+    let files = header
+        .strings(BASE_NAMES)
+        .into_iter()
+        .zip(header.integers(DIR_INDEXES))
+        .enumerate()
+        .map(|(i, (base, dir))| {
+            let dir = dirs.get(dir as usize).map(String::as_str).unwrap_or("/");
+            let is_dir = modes.get(i).is_some_and(|mode| mode & 0o170000 == 0o040000);
+            (format!("{}{}", dir, base), is_dir)
+        })
+        .collect::<Vec<_>>();
+    let file_xml = |indent: &str, (path, is_dir): &(String, bool)| {
+        if *is_dir {
+            format!("{}<file type=\"dir\">{}</file>\n", indent, escape(path))
+        } else {
+            format!("{}<file>{}</file>\n", indent, escape(path))
+        }
+    };
+
+    let mut format = String::new();
+    for (element, tag) in [
+        ("license", LICENSE),
+        ("vendor", VENDOR),
+        ("group", GROUP),
+        ("buildhost", BUILD_HOST),
+        ("sourcerpm", SOURCE_RPM),
+    ] {
+        let _ = writeln!(
+            format,
+            "    <rpm:{}>{}</rpm:{}>",
+            element,
+            escape(&header.string(tag)),
+            element
+        );
+    }
+    let _ = writeln!(
+        format,
+        "    <rpm:header-range start=\"{}\" end=\"{}\"/>",
+        start, end
+    );
+    for (element, names, flags, versions) in [
+        ("provides", PROVIDE_NAME, PROVIDE_FLAGS, PROVIDE_VERSION),
+        ("requires", REQUIRE_NAME, REQUIRE_FLAGS, REQUIRE_VERSION),
+        ("conflicts", CONFLICT_NAME, CONFLICT_FLAGS, CONFLICT_VERSION),
+        ("obsoletes", OBSOLETE_NAME, OBSOLETE_FLAGS, OBSOLETE_VERSION),
+    ] {
+        let entries = dependencies(&header, names, flags, versions)
+            .into_iter()
+            // resolved by rpm itself
+            .filter(|dependency| !dependency.name.starts_with("rpmlib("))
+            .map(|dependency| match (dependency.flags, &dependency.evr) {
+                (Some(flags), Some(evr)) => format!(
+                    "      <rpm:entry name=\"{}\" flags=\"{}\" {}/>\n",
+                    escape(&dependency.name),
+                    flags,
+                    evr.xml()
+                ),
+                _ => format!("      <rpm:entry name=\"{}\"/>\n", escape(&dependency.name)),
+            })
+            .unique()
+            .collect::<String>();
+        if entries.is_empty() {
+            continue;
+        }
+        let _ = writeln!(format, "    <rpm:{}>", element);
+        format.push_str(&entries);
+        let _ = writeln!(format, "    </rpm:{}>", element);
+    }
+    // like createrepo, primary only lists the files dependencies usually point to
+    for file in files.iter().filter(|(path, _)| {
+        path.starts_with("/etc/") || path.contains("/bin/") || path == "/usr/lib/sendmail"
+    }) {
+        format.push_str(&file_xml("    ", file));
+    }
+
+    let installed_size = header
+        .integer(LONG_SIZE)
+        .or_else(|| header.integer(SIZE))
+        .unwrap_or_default();
+    let archive_size = header
+        .integer(ARCHIVE_SIZE)
+        .or_else(|| signature.integer(SIGNATURE_PAYLOAD_SIZE))
+        .unwrap_or_default();
+    let primary = format!(
+        "<package type=\"rpm\">\n  <name>{name}</name>\n  <arch>{arch}</arch>\n  <version {evr}/>\n  <checksum type=\"sha256\" pkgid=\"YES\">{pkgid}</checksum>\n  <summary>{summary}</summary>\n  <description>{description}</description>\n  <packager>{packager}</packager>\n  <url>{url}</url>\n  <time file=\"{file_time}\" build=\"{build_time}\"/>\n  <size package=\"{package_size}\" installed=\"{installed_size}\" archive=\"{archive_size}\"/>\n  <location href=\"{location}\"/>\n  <format>\n{format}  </format>\n</package>",
+        name = escape(&name),
+        arch = escape(&arch),
+        evr = evr.xml(),
+        pkgid = pkgid,
+        summary = escape(&header.string(SUMMARY)),
+        description = escape(&header.string(DESCRIPTION)),
+        packager = escape(&header.string(PACKAGER)),
+        url = escape(&header.string(URL)),
+        file_time = file_time,
+        build_time = header.integer(BUILD_TIME).unwrap_or_default(),
+        package_size = content.len(),
+        installed_size = installed_size,
+        archive_size = archive_size,
+        location = escape(location),
+        format = format,
+    );
+    let package_element = format!(
+        "<package pkgid=\"{}\" name=\"{}\" arch=\"{}\">\n  <version {}/>\n",
+        pkgid,
+        escape(&name),
+        escape(&arch),
+        evr.xml()
+    );
+    let filelists = format!(
+        "{}{}</package>",
+        package_element,
+        files
+            .iter()
+            .map(|file| file_xml("  ", file))
+            .collect::<String>()
+    );
+    let other = format!("{}</package>", package_element);
+
+    Ok(Package {
+        pkgid,
+        name,
+        arch,
+        evr,
+        location: location.to_owned(),
+        primary,
+        filelists,
+        other,
+    })
+}
+
+fn dependencies(header: &Header, names: u32, flags: u32, versions: u32) -> Vec<Dependency> {
+    let flags = header.integers(flags);
+    let versions = header.strings(versions);
+    header
+        .strings(names)
+        .into_iter()
+        .enumerate()
+        .map(|(i, name)| {
+            let version = versions.get(i).filter(|version| !version.is_empty());
+            // RPMSENSE_LESS, RPMSENSE_GREATER and RPMSENSE_EQUAL
+            let flags = match flags.get(i).map(|flags| flags & 0x0e) {
+                Some(0x02) => Some("LT"),
+                Some(0x04) => Some("GT"),
+                Some(0x08) => Some("EQ"),
+                Some(0x0a) => Some("LE"),
+                Some(0x0c) => Some("GE"),
+                _ => None,
+            };
+            Dependency {
+                name,
+                flags: flags.filter(|_| version.is_some()),
+                evr: version.map(|version| Evr::parse(version)),
+            }
+        })
+        .collect()
+}
+
+/// `pkgid` of a package of the primary, filelists or other metadata
+fn pkgid(block: &str) -> Option<String> {
+    let captures = PKGID.captures(block)?;
+    captures
+        .get(1)
+        .or_else(|| captures.get(2))
+        .map(|pkgid| pkgid.as_str().to_owned())
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
@@ -0,0 +1,187 @@
+use crate::{
+    brew::formula_path,
+    checksum,
+    config::ReleaserConfig,
+    github::{github_client, response::release_asset_response::ReleaseAsset, tag::Tag},
+};
+use anyhow::{bail, Result};
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+};
+use tokio::process::Command;
+
+const CHECKSUM_SUFFIX: &str = ".sha256";
+const PROVENANCE_SUFFIX: &str = ".intoto.jsonl";
+/// Path of the asset URLs of a release, followed by the tag and the asset name
+const RELEASE_DOWNLOAD_PATH: &str = "/releases/download/";
+const SIGNATURE_SUFFIXES: [&str; 2] = [".asc", ".sig"];
+
+/// Download the assets of a published release and check them against
+/// their checksum files, provenance, signatures and the brew formula
+pub async fn verify(config: &ReleaserConfig, tag: &Tag, output_path: &Path) -> Result<()> {
+    let release = github_client::instance()
+        .repo(&config.release.owner, &config.release.repo)
+        .releases()
+        .get_by_tag(tag)
+        .await?;
+
+    let dir = output_path.join(format!("verify-{}", tag.value()));
+    fs::create_dir_all(&dir)?;
+
+    let assets = release.assets().await?;
+    let mut files: HashMap<String, PathBuf> = HashMap::new();
+    let mut digests: HashMap<String, String> = HashMap::new();
+
+    for asset in &assets {
+        log::info!("Downloading {}", asset.name);
+        let path = download(&release, asset, &dir).await?;
+        digests.insert(asset.name.to_owned(), checksum::create(&asset.name, &path)?);
+        files.insert(asset.name.to_owned(), path);
+    }
+
+    let mut drift = vec![];
+
+    for (name, path) in &files {
+        if let Some(target) = name.strip_suffix(CHECKSUM_SUFFIX) {
+            let content = fs::read_to_string(path)?;
+            let expected = content.split_whitespace().next().unwrap_or_default();
+            check(&mut drift, name, target, expected, &digests);
+        } else if name.ends_with(PROVENANCE_SUFFIX) {
+            for line in fs::read_to_string(path)?.lines() {
+                let statement: serde_json::Value = serde_json::from_str(line)?;
+                for subject in statement["subject"].as_array().into_iter().flatten() {
+                    let target = subject["name"].as_str().unwrap_or_default();
+                    let expected = subject["digest"]["sha256"].as_str().unwrap_or_default();
+                    check(&mut drift, name, target, expected, &digests);
+                }
+            }
+        } else if let Some(target) = SIGNATURE_SUFFIXES
+            .iter()
+            .find_map(|suffix| name.strip_suffix(suffix))
+        {
+            match files.get(target) {
+                Some(file) => verify_signature(&mut drift, path, file).await,
+                None => drift.push(format!("{}: signed asset {} is missing", name, target)),
+            }
+        }
+    }
+
+    for brew in &config.brew {
+        let path = formula_path(
+            &brew.name,
+            brew.path.as_deref(),
+            brew.formula_path.as_deref(),
+        );
+        log::info!("Checking formula {}", path);
+
+        let formula = github_client::instance()
+            .repo(&brew.repository.owner, &brew.repository.name)
+            .branch(&brew.head)
+            .get_file_content(&path)
+            .await?;
+
+        let download = format!("{}{}/", RELEASE_DOWNLOAD_PATH, tag.value());
+        for (url, expected) in formula_hashes(&formula) {
+            match url.split_once(&download) {
+                Some((_, target)) => check(&mut drift, &path, target, &expected, &digests),
+                None if url.contains(RELEASE_DOWNLOAD_PATH) => drift.push(format!(
+                    "{}: {} is not an asset of {}",
+                    path,
+                    url,
+                    tag.value()
+                )),
+                // source archives and resources, not assets of the release
+                None => {}
+            }
+        }
+    }
+
+    if drift.is_empty() {
+        log::info!("Release {} verified, {} assets", tag.value(), assets.len());
+        Ok(())
+    } else {
+        for message in &drift {
+            log::error!("{}", message);
+        }
+        bail!("release {} has {} problems", tag.value(), drift.len())
+    }
+}
+
+async fn download(
+    release: &crate::github::release::Release,
+    asset: &ReleaseAsset,
+    dir: &Path,
+) -> Result<PathBuf> {
+    let bytes = release.download_asset(asset).await?;
+    let path = dir.join(&asset.name);
+    fs::write(&path, bytes)?;
+    Ok(path)
+}
+
+fn check(
+    drift: &mut Vec<String>,
+    source: &str,
+    target: &str,
+    expected: &str,
+    digests: &HashMap<String, String>,
+) {
+    match digests.get(target) {
+        Some(actual) if actual == expected => {
+            log::info!("{}: {} matches", source, target);
+        }
+        Some(actual) => drift.push(format!(
+            "{}: {} expected {} but got {}",
+            source, target, expected, actual
+        )),
+        None => drift.push(format!("{}: asset {} is missing", source, target)),
+    }
+}
+
+async fn verify_signature(drift: &mut Vec<String>, signature: &Path, file: &Path) {
+    let status = Command::new("gpg")
+        .arg("--verify")
+        .arg(signature)
+        .arg(file)
+        .status()
+        .await;
+
+    match status {
+        Ok(status) if status.success() => {
+            log::info!("{}: signature is valid", signature.display());
+        }
+        Ok(_) => drift.push(format!("{}: invalid signature", signature.display())),
+        Err(err) => log::warn!("cannot run gpg, skipping {}: {}", signature.display(), err),
+    }
+}
+
+/// Extract the `url`/`sha256` pairs declared in a formula
+fn formula_hashes(formula: &str) -> Vec<(String, String)> {
+    let mut hashes = vec![];
+    let mut url = None;
+
+    for line in formula.lines().map(str::trim) {
+        if let Some(value) = quoted(line, "url") {
+            url = Some(value);
+        } else if let Some(hash) = quoted(line, "sha256") {
+            if let Some(url) = url.take() {
+                hashes.push((url, hash));
+            }
+        }
+    }
+
+    hashes
+}
+
+/// First quoted string of a `key "value"` line, which can be followed by options, e.g.
+/// `url "...", using: GitHubPrivateRepositoryReleaseDownloadStrategy`
+fn quoted(line: &str, key: &str) -> Option<String> {
+    let value = line
+        .strip_prefix(key)?
+        .strip_prefix(char::is_whitespace)?
+        .trim_start()
+        .strip_prefix('"')?;
+    let end = value.find('"')?;
+    Some(value[..end].to_string())
+}